@@ -0,0 +1,83 @@
+/*
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::{Ok, Result};
+use clap::Parser;
+use dsi_progress_logger::*;
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand::SeedableRng;
+use std::hint::black_box;
+use sux::utils::word_ops::*;
+
+#[derive(Parser, Debug)]
+#[command(about = "Benchmarks selection in a word", long_about = None)]
+struct Args {
+    /// The number of words to test
+    t: usize,
+
+    /// The probability that a bit is one
+    #[arg(short, long, default_value = "0.5")]
+    density: f64,
+
+    /// The number of test repetitions
+    #[arg(short, long, default_value = "10")]
+    repeats: usize,
+}
+
+fn main() -> Result<()> {
+    env_logger::builder()
+        .filter_level(log::LevelFilter::Info)
+        .try_init()?;
+
+    let args = Args::parse();
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut words = Vec::with_capacity(args.t);
+    while words.len() < args.t {
+        let word = (0..usize::BITS).fold(0, |word, bit| {
+            word | (rng.random_bool(args.density) as usize) << bit
+        });
+        if word != 0 {
+            let rank = rng.random_range(0..word.count_ones() as usize);
+            words.push((word, rank));
+        }
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    let bmi2 = std::arch::is_x86_feature_detected!("bmi2");
+    #[cfg(not(target_arch = "x86_64"))]
+    let bmi2 = false;
+    if !bmi2 {
+        log::info!("BMI2 is not available on this CPU");
+    }
+
+    for _ in 0..args.repeats {
+        let mut pl = ProgressLogger::default();
+
+        pl.start("Benchmarking select_in_word_broadword...");
+        for &(word, rank) in &words {
+            black_box(select_in_word_broadword(black_box(word), rank));
+        }
+        pl.done_with_count(args.t);
+
+        #[cfg(target_arch = "x86_64")]
+        if bmi2 {
+            pl.start("Benchmarking select_in_word_bmi2...");
+            for &(word, rank) in &words {
+                black_box(unsafe { select_in_word_bmi2(black_box(word), rank) });
+            }
+            pl.done_with_count(args.t);
+        }
+
+        pl.start("Benchmarking select_in_word...");
+        for &(word, rank) in &words {
+            black_box(select_in_word(black_box(word), rank));
+        }
+        pl.done_with_count(args.t);
+    }
+
+    Ok(())
+}
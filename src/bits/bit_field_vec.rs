@@ -356,7 +356,8 @@ impl<W: Word> BitFieldVec<W, Vec<W>> {
     /// Adds a value at the end of the vector.
     pub fn push(&mut self, value: W) {
         panic_if_value!(value, self.mask, self.bit_width);
-        if (self.len + 1) * self.bit_width > self.bits.len() * W::BITS {
+        // We need at least one word to handle the case of bit width zero.
+        if self.bits.is_empty() || (self.len + 1) * self.bit_width > self.bits.len() * W::BITS {
             self.bits.push(W::ZERO);
         }
         unsafe {
@@ -407,6 +408,38 @@ impl<W: Word, T> BitFieldSliceCore<W> for BitFieldVec<W, T> {
     }
 }
 
+impl<W: Word, B: AsRef<[W]>> Verify for BitFieldVec<W, B> {
+    fn verify(&self, _mode: VerifyMode) -> Result<(), VerifyError> {
+        if self.bit_width > W::BITS {
+            return Err(VerifyError::Param {
+                what: "bit width",
+                expected: W::BITS,
+                found: self.bit_width,
+            });
+        }
+        if self.mask != mask(self.bit_width) {
+            return Err(VerifyError::Param {
+                what: "mask width",
+                expected: self.bit_width,
+                found: self.mask.count_ones() as usize,
+            });
+        }
+        let num_words = self
+            .len
+            .checked_mul(self.bit_width)
+            .map(|bits| bits.div_ceil(W::BITS))
+            .unwrap_or(usize::MAX);
+        if self.bits.as_ref().len() < num_words {
+            return Err(VerifyError::Param {
+                what: "number of words",
+                expected: num_words,
+                found: self.bits.as_ref().len(),
+            });
+        }
+        Ok(())
+    }
+}
+
 impl<W: Word, B: AsRef<[W]>> BitFieldSlice<W> for BitFieldVec<W, B> {
     #[inline]
     unsafe fn get_unchecked(&self, index: usize) -> W {
@@ -496,7 +529,7 @@ impl<W: Word, B: AsRef<[W]> + AsMut<[W]>> BitFieldSliceMut<W> for BitFieldVec<W,
         );
         // Reduce len to the elements available in both vectors
         let len = Ord::min(Ord::min(len, dst.len - to), self.len - from);
-        if len == 0 {
+        if len == 0 || self.bit_width == 0 {
             return;
         }
         let bit_width = Ord::min(self.bit_width, dst.bit_width);
@@ -561,11 +594,15 @@ impl<W: Word, B: AsRef<[W]> + AsMut<[W]>> BitFieldSliceMut<W> for BitFieldVec<W,
                 dest[dst_first_word + i] = word | source[src_first_word + i] << shift;
                 word = source[src_first_word + i] >> (W::BITS - shift);
             }
+            // The source might end one word before the destination
+            if src_first_word + (dst_last_word - dst_first_word) == src_last_word {
+                word |= source[src_last_word] << shift;
+            }
             let residual =
                 bit_len - (W::BITS - dst_bit) - (dst_last_word - dst_first_word - 1) * W::BITS;
             let mask = W::MAX >> (W::BITS - residual);
             dest[dst_last_word] &= !mask;
-            dest[dst_last_word] |= source[src_last_word] & mask;
+            dest[dst_last_word] |= word & mask;
         } else {
             // src_first_word != src_last_word && dst_first_word !=
             // dst_last_word && src_bit > dst_bit
@@ -640,8 +677,8 @@ impl<W: Word, B: AsRef<[W]> + AsMut<[W]>> BitFieldSliceMut<W> for BitFieldVec<W,
                     }
 
                     let value = read_buffer & mask;
-                    // throw away the bits we just read
-                    read_buffer >>= bit_width;
+                    // throw away the bits we just read (bit_width might be W::BITS)
+                    read_buffer = read_buffer.checked_shr(bit_width as u32).unwrap_or(W::ZERO);
                     // apply user func
                     let new_value = f(value);
                     // put the new value in the write buffer
@@ -660,8 +697,8 @@ impl<W: Word, B: AsRef<[W]> + AsMut<[W]>> BitFieldSliceMut<W> for BitFieldVec<W,
             // write the last word if we have some bits left
             while bits_in_buffer < buffer_limit {
                 let value = read_buffer & mask;
-                // throw away the bits we just read
-                read_buffer >>= bit_width;
+                // throw away the bits we just read (bit_width might be W::BITS)
+                read_buffer = read_buffer.checked_shr(bit_width as u32).unwrap_or(W::ZERO);
                 // apply user func
                 let new_value = f(value);
                 // put the new value in the write buffer
@@ -837,7 +874,8 @@ impl<W: Word, B: AsRef<[W]>> crate::traits::UncheckedIterator
         if self.fill >= bit_width {
             self.fill -= bit_width;
             let res = self.window & self.vec.mask;
-            self.window >>= bit_width;
+            // bit_width might be W::BITS
+            self.window = self.window.checked_shr(bit_width as u32).unwrap_or(W::ZERO);
             return res;
         }
 
@@ -846,7 +884,7 @@ impl<W: Word, B: AsRef<[W]>> crate::traits::UncheckedIterator
         self.window = *self.vec.bits.as_ref().get_unchecked(self.word_index);
         let res = (res | (self.window << self.fill)) & self.vec.mask;
         let used = bit_width - self.fill;
-        self.window >>= used;
+        self.window = self.window.checked_shr(used as u32).unwrap_or(W::ZERO);
         self.fill = W::BITS - used;
         res
     }
@@ -918,8 +956,10 @@ impl<W: Word, B: AsRef<[W]>> crate::traits::UncheckedIterator
         self.word_index -= 1;
         self.window = *self.vec.bits.as_ref().get_unchecked(self.word_index);
         let used = bit_width - self.fill;
-        res = ((res << used) | (self.window >> (W::BITS - used))) & self.vec.mask;
-        self.window <<= used;
+        // used might be W::BITS
+        res = (res.checked_shl(used as u32).unwrap_or(W::ZERO) | (self.window >> (W::BITS - used)))
+            & self.vec.mask;
+        self.window = self.window.checked_shl(used as u32).unwrap_or(W::ZERO);
         self.fill = W::BITS - used;
         res
     }
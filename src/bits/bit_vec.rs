@@ -68,7 +68,7 @@
 //! assert_eq!(unsafe { BitVec::from_raw_parts(ones, 1) }.count_ones(), 1);
 //! ```
 
-use common_traits::{IntoAtomic, SelectInWord};
+use common_traits::IntoAtomic;
 #[allow(unused_imports)] // this is in the std prelude but not in no_std!
 use core::borrow::BorrowMut;
 use core::fmt;
@@ -82,7 +82,11 @@ use std::{
 
 use crate::{
     traits::rank_sel::*,
-    utils::{transmute_boxed_slice, transmute_vec},
+    traits::{Verify, VerifyError, VerifyMode},
+    utils::{
+        transmute_boxed_slice, transmute_vec,
+        word_ops::{self, select_in_word},
+    },
 };
 
 const BITS: usize = usize::BITS as usize;
@@ -388,8 +392,35 @@ impl<B> BitLength for BitVec<B> {
     }
 }
 
+impl<B: AsRef<[usize]>> BitWords for BitVec<B> {
+    #[inline(always)]
+    fn num_words(&self) -> usize {
+        self.bits.as_ref().len()
+    }
+
+    #[inline(always)]
+    fn word(&self, index: usize) -> usize {
+        self.bits.as_ref()[index]
+    }
+}
+
+impl<B: AsRef<[usize]>> Verify for BitVec<B> {
+    fn verify(&self, _mode: VerifyMode) -> Result<(), VerifyError> {
+        let num_words = self.len.div_ceil(BITS);
+        if self.bits.as_ref().len() < num_words {
+            return Err(VerifyError::Param {
+                what: "number of words",
+                expected: num_words,
+                found: self.bits.as_ref().len(),
+            });
+        }
+        Ok(())
+    }
+}
+
 /// If the feature "rayon" is enabled, [`count_ones`](BitCount::count_ones) is
-/// computed in parallel.
+/// computed in parallel. Ones are counted using
+/// [`word_ops::count_ones`], which uses AVX-512 instructions, if available.
 impl<B: AsRef<[usize]>> BitCount for BitVec<B> {
     fn count_ones(&self) -> usize {
         let full_words = self.len() / BITS;
@@ -400,17 +431,14 @@ impl<B: AsRef<[usize]>> BitCount for BitVec<B> {
         #[cfg(feature = "rayon")]
         {
             num_ones = bits[..full_words]
-                .par_iter()
-                .map(|x| x.count_ones() as usize)
+                .par_chunks(1 << 12)
+                .map(word_ops::count_ones)
                 .sum();
         }
 
         #[cfg(not(feature = "rayon"))]
         {
-            num_ones = bits[..full_words]
-                .iter()
-                .map(|x| x.count_ones() as usize)
-                .sum()
+            num_ones = word_ops::count_ones(&bits[..full_words]);
         }
 
         if residual != 0 {
@@ -483,7 +511,7 @@ impl<B: AsRef<[usize]>> SelectHinted for BitVec<B> {
         loop {
             let bit_count = word.count_ones() as usize;
             if residual < bit_count {
-                return word_index * BITS + word.select_in_word(residual);
+                return word_index * BITS + select_in_word(word, residual);
             }
             word_index += 1;
             word = *self.as_ref().get_unchecked(word_index);
@@ -501,7 +529,7 @@ impl<B: AsRef<[usize]>> SelectZeroHinted for BitVec<B> {
         loop {
             let bit_count = word.count_ones() as usize;
             if residual < bit_count {
-                return word_index * BITS + word.select_in_word(residual);
+                return word_index * BITS + select_in_word(word, residual);
             }
             word_index += 1;
             word = !self.as_ref().get_unchecked(word_index);
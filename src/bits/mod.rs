@@ -5,8 +5,9 @@
  * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
  */
 
-//! Structures for [bit vectors](`mod@bit_vec`) and
-//! [vectors of values of bounded bit width](`mod@bit_field_vec`).
+//! Structures for [bit vectors](`mod@bit_vec`),
+//! [vectors of values of bounded bit width](`mod@bit_field_vec`), and
+//! [vectors of signed values of bounded bit width](`mod@signed_bit_field_vec`).
 
 pub mod bit_field_vec;
 pub use bit_field_vec::*;
@@ -14,5 +15,8 @@ pub use bit_field_vec::*;
 pub mod bit_vec;
 pub use bit_vec::*;
 
+pub mod signed_bit_field_vec;
+pub use signed_bit_field_vec::*;
+
 pub use crate::bit_field_vec;
 pub use crate::bit_vec;
@@ -0,0 +1,291 @@
+/*
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Vectors of signed values of fixed bit width.
+//!
+//! A [`SignedBitFieldVec`] is a thin adapter over a [`BitFieldVec`] that
+//! stores signed values using the [zig-zag
+//! encoding](https://en.wikipedia.org/wiki/Variable-length_quantity#Zigzag_encoding),
+//! which maps 0, −1, 1, −2, 2, … to 0, 1, 2, 3, 4, …, so that values of
+//! small absolute value use few bits. The signed type is the signed
+//! counterpart of the word type `W` (e.g., `i64` for `u64`, `i32` for `u32`).
+//!
+//! A vector of bit width *w* can store values in the range
+//! [−2<sup>*w* − 1</sup> . . 2<sup>*w* − 1</sup>).
+//!
+//! The functions [`zig_zag_encode`] and [`zig_zag_decode`] can be used to
+//! perform the mapping directly.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use sux::bits::SignedBitFieldVec;
+//!
+//! let mut v = SignedBitFieldVec::<u64>::new(4, 3);
+//! v.set(0, -8);
+//! v.set(1, 7);
+//! v.set(2, -1);
+//! assert_eq!(v.get(0), -8);
+//! assert_eq!(v.get(1), 7);
+//! assert_eq!(v.iter().collect::<Vec<_>>(), vec![-8, 7, -1]);
+//!
+//! // The minimum bit width is computed automatically
+//! let v = SignedBitFieldVec::<u32>::from_slice(&[-3, 0, 2]);
+//! assert_eq!(v.bit_width(), 3);
+//! assert_eq!(v.get(0), -3_i32);
+//! ```
+
+use crate::prelude::*;
+use common_traits::*;
+use epserde::*;
+use mem_dbg::*;
+
+/// Maps a signed value to an unsigned value using the zig-zag encoding.
+#[inline(always)]
+pub fn zig_zag_encode<W: Word>(value: W::SignedInt) -> W {
+    (value.to_unsigned() << 1_usize) ^ (value >> (W::BITS - 1)).to_unsigned()
+}
+
+/// Maps an unsigned value to a signed value using the zig-zag encoding.
+#[inline(always)]
+pub fn zig_zag_decode<W: Word>(value: W) -> W::SignedInt {
+    ((value >> 1_usize) ^ (-(value & W::ONE).to_signed()).to_unsigned()).to_signed()
+}
+
+/// A vector of signed values of fixed bit width, stored in a [`BitFieldVec`]
+/// using the zig-zag encoding.
+#[derive(Epserde, Debug, Clone, Hash, MemDbg, MemSize)]
+pub struct SignedBitFieldVec<W: Word = usize, B = Vec<W>> {
+    /// The underlying bit-field vector, containing zig-zag encoded values.
+    bit_field_vec: BitFieldVec<W, B>,
+}
+
+impl<W: Word, B> SignedBitFieldVec<W, B> {
+    /// Returns the underlying bit-field vector, containing zig-zag encoded
+    /// values.
+    pub fn into_inner(self) -> BitFieldVec<W, B> {
+        self.bit_field_vec
+    }
+
+    /// Returns a reference to the underlying bit-field vector, containing
+    /// zig-zag encoded values.
+    pub fn as_bit_field_vec(&self) -> &BitFieldVec<W, B> {
+        &self.bit_field_vec
+    }
+}
+
+/// Wraps a bit-field vector, interpreting its content as zig-zag encoded
+/// values.
+impl<W: Word, B> From<BitFieldVec<W, B>> for SignedBitFieldVec<W, B> {
+    fn from(bit_field_vec: BitFieldVec<W, B>) -> Self {
+        Self { bit_field_vec }
+    }
+}
+
+/// Converts the backend, as in the case of [`BitFieldVec`].
+impl<W: Word> From<SignedBitFieldVec<W, Vec<W>>> for SignedBitFieldVec<W, Box<[W]>> {
+    fn from(value: SignedBitFieldVec<W, Vec<W>>) -> Self {
+        Self {
+            bit_field_vec: value.bit_field_vec.into(),
+        }
+    }
+}
+
+impl<W: Word> SignedBitFieldVec<W, Vec<W>> {
+    /// Creates a new zero-initialized vector of given bit width and length.
+    pub fn new(bit_width: usize, len: usize) -> Self {
+        Self {
+            bit_field_vec: BitFieldVec::new(bit_width, len),
+        }
+    }
+
+    /// Creates an empty vector that doesn't need to reallocate for up to
+    /// `capacity` elements.
+    pub fn with_capacity(bit_width: usize, capacity: usize) -> Self {
+        Self {
+            bit_field_vec: BitFieldVec::with_capacity(bit_width, capacity),
+        }
+    }
+
+    /// Creates a new vector by copying a slice; the bit width will be the
+    /// minimum width sufficient to hold all values in the slice.
+    pub fn from_slice(slice: &[W::SignedInt]) -> Self {
+        let bit_width = slice
+            .iter()
+            .map(|&value| W::BITS - zig_zag_encode::<W>(value).leading_zeros() as usize)
+            .max()
+            .unwrap_or(0);
+        let mut result = Self::with_capacity(bit_width, slice.len());
+        for &value in slice {
+            result.bit_field_vec.push(zig_zag_encode(value));
+        }
+        result
+    }
+
+    /// Adds a value at the end of the vector.
+    pub fn push(&mut self, value: W::SignedInt) {
+        let encoded = zig_zag_encode::<W>(value);
+        panic_if_signed_value(value, encoded, self.bit_field_vec.mask(), self.bit_width());
+        self.bit_field_vec.push(encoded);
+    }
+
+    /// Removes and returns a value from the end of the vector.
+    ///
+    /// Returns None if the vector is empty.
+    pub fn pop(&mut self) -> Option<W::SignedInt> {
+        self.bit_field_vec.pop().map(zig_zag_decode)
+    }
+
+    /// Sets len to 0.
+    pub fn clear(&mut self) {
+        self.bit_field_vec.clear();
+    }
+}
+
+#[inline(always)]
+fn panic_if_signed_value<W: Word>(value: W::SignedInt, encoded: W, mask: W, bit_width: usize) {
+    if encoded & mask != encoded {
+        panic!("Value {} does not fit in {} bits", value, bit_width);
+    }
+}
+
+impl<W: Word, B: AsRef<[W]>> SignedBitFieldVec<W, B> {
+    /// Returns the bit width of the values inside the vector.
+    #[inline(always)]
+    pub fn bit_width(&self) -> usize {
+        self.bit_field_vec.bit_width()
+    }
+
+    /// Returns the length of the vector.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.bit_field_vec.len()
+    }
+
+    /// Returns true if the vector is empty.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the value at the specified index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    #[inline]
+    pub fn get(&self, index: usize) -> W::SignedInt {
+        zig_zag_decode(self.bit_field_vec.get(index))
+    }
+
+    /// Returns the value at the specified index.
+    ///
+    /// # Safety
+    ///
+    /// `index` must be in [0..[len](`SignedBitFieldVec::len`)).
+    #[inline]
+    pub unsafe fn get_unchecked(&self, index: usize) -> W::SignedInt {
+        zig_zag_decode(self.bit_field_vec.get_unchecked(index))
+    }
+
+    /// Returns an iterator over the values of the vector starting at the given
+    /// position.
+    pub fn iter_from(&self, from: usize) -> SignedBitFieldVecIterator<'_, W, B> {
+        SignedBitFieldVecIterator(self.bit_field_vec.iter_from(from))
+    }
+
+    /// Returns an iterator over the values of the vector.
+    pub fn iter(&self) -> SignedBitFieldVecIterator<'_, W, B> {
+        self.iter_from(0)
+    }
+}
+
+impl<W: Word, B: AsRef<[W]> + AsMut<[W]>> SignedBitFieldVec<W, B> {
+    /// Sets the value at the specified index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds or if the value does not fit in
+    /// [`bit_width`](SignedBitFieldVec::bit_width) bits.
+    #[inline]
+    pub fn set(&mut self, index: usize, value: W::SignedInt) {
+        let encoded = zig_zag_encode::<W>(value);
+        panic_if_signed_value(value, encoded, self.bit_field_vec.mask(), self.bit_width());
+        self.bit_field_vec.set(index, encoded);
+    }
+
+    /// Sets the value at the specified index.
+    ///
+    /// # Safety
+    ///
+    /// `index` must be in [0..[len](`SignedBitFieldVec::len`)) and the value
+    /// must fit in [`bit_width`](SignedBitFieldVec::bit_width) bits.
+    #[inline]
+    pub unsafe fn set_unchecked(&mut self, index: usize, value: W::SignedInt) {
+        self.bit_field_vec
+            .set_unchecked(index, zig_zag_encode(value));
+    }
+}
+
+impl<W: Word, B: AsRef<[W]>, C: AsRef<[W]>> PartialEq<SignedBitFieldVec<W, C>>
+    for SignedBitFieldVec<W, B>
+{
+    fn eq(&self, other: &SignedBitFieldVec<W, C>) -> bool {
+        self.bit_field_vec == other.bit_field_vec
+    }
+}
+
+impl Eq for SignedBitFieldVec {}
+
+/// An [`Iterator`] over the values of a [`SignedBitFieldVec`].
+#[derive(Debug, Clone, MemDbg, MemSize)]
+pub struct SignedBitFieldVecIterator<'a, W: Word, B>(BitFieldVecIterator<'a, W, B>);
+
+impl<W: Word, B: AsRef<[W]>> Iterator for SignedBitFieldVecIterator<'_, W, B> {
+    type Item = W::SignedInt;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(zig_zag_decode)
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<W: Word, B: AsRef<[W]>> ExactSizeIterator for SignedBitFieldVecIterator<'_, W, B> {
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl<'a, W: Word, B: AsRef<[W]>> IntoIterator for &'a SignedBitFieldVec<W, B> {
+    type Item = W::SignedInt;
+    type IntoIter = SignedBitFieldVecIterator<'a, W, B>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, W: Word, B: AsRef<[W]>> IntoIteratorFrom for &'a SignedBitFieldVec<W, B> {
+    type IntoIterFrom = SignedBitFieldVecIterator<'a, W, B>;
+
+    fn into_iter_from(self, from: usize) -> Self::IntoIterFrom {
+        self.iter_from(from)
+    }
+}
+
+impl<W: Word> Extend<W::SignedInt> for SignedBitFieldVec<W, Vec<W>> {
+    fn extend<T: IntoIterator<Item = W::SignedInt>>(&mut self, iter: T) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
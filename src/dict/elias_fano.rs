@@ -50,27 +50,35 @@
 //! subtracting *i* (note that this will work because the upper bits are
 //! nondecreasing).
 //!
+//! The structure is generic in the type `W` of the values, which defaults to
+//! `usize`, but it can be any unsigned integer type, including `u128`; the
+//! lower bits are stored in a [`BitFieldVec`] with words of type `W`, whereas
+//! the upper bits are always stored in a [`BitVec`] over `usize` words.
+//!
 //!
 
 /// The default type for an Elias–Fano structure implementing an [`IndexedSeq`].
 ///
 /// You can start from this type to customize your Elias–Fano structure using
 /// different const parameters or a different selection structure altogether.
-pub type EfSeq = EliasFano<SelectAdaptConst<BitVec<Box<[usize]>>, Box<[usize]>, 12, 3>>;
+pub type EfSeq<W = usize> =
+    EliasFano<W, SelectAdaptConst<BitVec<Box<[usize]>>, Box<[usize]>, 12, 3>>;
 
 /// The default type for an Elias–Fano structure implementing an
 /// [`SuccUnchecked`] and [`PredUnchecked`].
 ///
 /// You can start from this type to customize your Elias–Fano structure using
 /// different const parameters or a different selection structure altogether.
-pub type EfDict = EliasFano<SelectZeroAdaptConst<BitVec<Box<[usize]>>, Box<[usize]>, 12, 3>>;
+pub type EfDict<W = usize> =
+    EliasFano<W, SelectZeroAdaptConst<BitVec<Box<[usize]>>, Box<[usize]>, 12, 3>>;
 
 /// The default type for an Elias–Fano structure implementing an
 /// [`IndexedDict`], [`Succ`], and [`Pred`].
 ///
 /// You can start from this type to customize your Elias–Fano structure using
 /// different const parameters or different selection structures altogether.
-pub type EfSeqDict = EliasFano<
+pub type EfSeqDict<W = usize> = EliasFano<
+    W,
     SelectZeroAdaptConst<
         SelectAdaptConst<BitVec<Box<[usize]>>, Box<[usize]>, 12, 3>,
         Box<[usize]>,
@@ -80,11 +88,22 @@ pub type EfSeqDict = EliasFano<
 >;
 
 use crate::prelude::*;
+use crate::rank_sel::stream::{copy_words, temp_file, InventoryBuilder, StreamedSlice};
 use crate::traits::bit_field_slice::*;
+use crate::utils::lenders::RewindableIoLender;
+use anyhow::Context;
+use common_traits::{AsBytes, AtomicUnsignedInt, CastableFrom, CastableInto, IntoAtomic};
 use core::sync::atomic::Ordering;
+use epserde::ser::{self, Serialize};
+use epserde::traits::*;
 use epserde::*;
 use mem_dbg::*;
 use std::borrow::Borrow;
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::str::FromStr;
 
 /// An [`IndexedDict`] that stores a monotone sequence of integers using the
 /// Elias–Fano representation.
@@ -205,13 +224,29 @@ use std::borrow::Borrow;
 /// assert_eq!(ef.get(0), 0);
 /// assert_eq!(ef.get(1), 2);
 /// ```
+///
+/// Using a different type for the values:
+/// ```rust
+/// # use sux::dict::{EliasFano, EliasFanoBuilder};
+/// # use sux::traits::{Types,IndexedSeq,IndexedDict,Succ};
+/// // The lower bits are stored in u32 words
+/// let ef: EliasFano<u32> = vec![0_u32, 2, 8, 10].into();
+/// assert_eq!(ef.iter().collect::<Vec<_>>(), vec![0, 2, 8, 10]);
+///
+/// // Values larger than usize::MAX
+/// let mut efb = EliasFanoBuilder::<u128>::new_generic(3, u128::MAX);
+/// efb.extend([0, 1 << 100, u128::MAX]);
+/// let ef = efb.build_with_seq_and_dict();
+/// assert_eq!(ef.get(1), 1 << 100);
+/// assert_eq!(ef.succ(2), Some((1, 1 << 100)));
+/// ```
 
 #[derive(Epserde, Debug, Clone, Hash, MemDbg, MemSize)]
-pub struct EliasFano<H = BitVec<Box<[usize]>>, L = BitFieldVec<usize, Box<[usize]>>> {
+pub struct EliasFano<W = usize, H = BitVec<Box<[usize]>>, L = BitFieldVec<W, Box<[W]>>> {
     /// The number of values.
     n: usize,
     /// An upper bound to the values.
-    u: usize,
+    u: W,
     /// The number of lower bits.
     l: usize,
     /// The lower-bits array.
@@ -220,7 +255,7 @@ pub struct EliasFano<H = BitVec<Box<[usize]>>, L = BitFieldVec<usize, Box<[usize
     high_bits: H,
 }
 
-impl<H, L> EliasFano<H, L> {
+impl<W: Copy, H, L> EliasFano<W, H, L> {
     /// Estimate the size of an instance.
     pub fn estimate_size(u: usize, n: usize) -> usize {
         2 * n + (n * (u as f64 / n as f64).log2().ceil() as usize)
@@ -235,13 +270,21 @@ impl<H, L> EliasFano<H, L> {
         self.n
     }
 
+    /// Returns the upper bound on the values provided at construction time.
+    ///
+    /// Note that the upper bound might be larger than the last value.
+    #[inline]
+    pub fn upper_bound(&self) -> W {
+        self.u
+    }
+
     /// Replaces the high bits.
     ///
     /// # Safety
     ///
     /// This method is unsafe because it is not possible to guarantee that the
     /// new high bits are identical to the old ones as a bit vector.
-    pub unsafe fn map_high_bits<F, H2>(self, func: F) -> EliasFano<H2, L>
+    pub unsafe fn map_high_bits<F, H2>(self, func: F) -> EliasFano<W, H2, L>
     where
         F: FnOnce(H) -> H2,
     {
@@ -260,7 +303,7 @@ impl<H, L> EliasFano<H, L> {
     ///
     /// This method is unsafe because it is not possible to guarantee that the
     /// new low bits are identical to the old ones as vector.
-    pub unsafe fn map_low_bits<F, L2>(self, func: F) -> EliasFano<H, L2>
+    pub unsafe fn map_low_bits<F, L2>(self, func: F) -> EliasFano<W, H, L2>
     where
         F: FnOnce(L) -> L2,
     {
@@ -274,36 +317,77 @@ impl<H, L> EliasFano<H, L> {
     }
 }
 
-impl<H: AsRef<[usize]>, L: BitFieldSlice<usize>> Types for EliasFano<H, L> {
-    type Output = usize;
-    type Input = usize;
+impl<W: Word, H: AsRef<[usize]>, L: BitFieldSlice<W>> Types for EliasFano<W, H, L> {
+    type Output = W;
+    type Input = W;
 }
 
-impl<H: AsRef<[usize]> + SelectUnchecked, L: BitFieldSlice<usize>> IndexedSeq for EliasFano<H, L> {
+impl<W: Word + CastableFrom<usize>, H: AsRef<[usize]> + SelectUnchecked, L: BitFieldSlice<W>>
+    IndexedSeq for EliasFano<W, H, L>
+{
     #[inline]
     fn len(&self) -> usize {
         self.n
     }
 
     #[inline(always)]
-    unsafe fn get_unchecked(&self, index: usize) -> usize {
+    unsafe fn get_unchecked(&self, index: usize) -> W {
         let high_bits = self.high_bits.select_unchecked(index) - index;
         let low_bits = self.low_bits.get_unchecked(index);
-        (high_bits << self.l) | low_bits
+        (W::cast_from(high_bits) << self.l) | low_bits
+    }
+}
+
+impl<H: AsRef<[usize]> + SelectUnchecked, L: BitFieldSlice<usize>> EliasFano<usize, H, L> {
+    /// Stores in `values` the values of given indices.
+    ///
+    /// The high bits are located using
+    /// [`select_unchecked_batch`](SelectUnchecked::select_unchecked_batch),
+    /// so if the selection structure supports batched queries the data needed
+    /// by the next queries is prefetched.
+    ///
+    /// # Panics
+    ///
+    /// If `indices` and `values` have different lengths, or if some index is
+    /// out of bounds.
+    pub fn get_batch(&self, indices: &[usize], values: &mut [usize]) {
+        for &index in indices {
+            panic_if_out_of_bounds!(index, self.n);
+        }
+        unsafe { self.get_unchecked_batch(indices, values) }
+    }
+
+    /// Stores in `values` the values of given indices without bound checks.
+    ///
+    /// # Safety
+    ///
+    /// All indices must be smaller than the length.
+    ///
+    /// # Panics
+    ///
+    /// If `indices` and `values` have different lengths.
+    pub unsafe fn get_unchecked_batch(&self, indices: &[usize], values: &mut [usize]) {
+        self.high_bits.select_unchecked_batch(indices, values);
+        for (&index, value) in indices.iter().zip(values.iter_mut()) {
+            *value = ((*value - index) << self.l) | self.low_bits.get_unchecked(index);
+        }
     }
 }
 
-impl<H: AsRef<[usize]> + SelectZeroUnchecked, L: BitFieldSlice<usize>> IndexedDict
-    for EliasFano<H, L>
+impl<
+        W: Word + CastableFrom<usize> + CastableInto<usize>,
+        H: AsRef<[usize]> + SelectZeroUnchecked,
+        L: BitFieldSlice<W>,
+    > IndexedDict for EliasFano<W, H, L>
 where
-    for<'b> &'b L: IntoUncheckedIterator<Item = usize>,
+    for<'b> &'b L: IntoUncheckedIterator<Item = W>,
 {
     fn index_of(&self, value: impl Borrow<Self::Input>) -> Option<usize> {
         let value = *value.borrow();
         if value > self.u {
             return None;
         }
-        let zeros_to_skip = value >> self.l;
+        let zeros_to_skip: usize = (value >> self.l).cast();
         let bit_pos = if zeros_to_skip == 0 {
             0
         } else {
@@ -334,7 +418,7 @@ where
             // compute the global bit index
             let high_bits = (word_idx * usize::BITS as usize) + bit_idx - rank;
             // compose the value
-            let res = (high_bits << self.l) | unsafe { iter.next_unchecked() };
+            let res = (W::cast_from(high_bits) << self.l) | unsafe { iter.next_unchecked() };
             if res == value {
                 return Some(rank);
             }
@@ -350,17 +434,20 @@ where
 }
 
 #[allow(clippy::collapsible_else_if)]
-impl<H: AsRef<[usize]> + SelectZeroUnchecked, L: BitFieldSlice<usize>> SuccUnchecked
-    for EliasFano<H, L>
+impl<
+        W: Word + CastableFrom<usize> + CastableInto<usize>,
+        H: AsRef<[usize]> + SelectZeroUnchecked,
+        L: BitFieldSlice<W>,
+    > SuccUnchecked for EliasFano<W, H, L>
 where
-    for<'b> &'b L: IntoUncheckedIterator<Item = usize>,
+    for<'b> &'b L: IntoUncheckedIterator<Item = W>,
 {
     unsafe fn succ_unchecked<const STRICT: bool>(
         &self,
         value: impl Borrow<Self::Input>,
     ) -> (usize, Self::Output) {
         let value = *value.borrow();
-        let zeros_to_skip = value >> self.l;
+        let zeros_to_skip: usize = (value >> self.l).cast();
         let bit_pos = if zeros_to_skip == 0 {
             0
         } else {
@@ -389,7 +476,7 @@ where
             // compute the global bit index
             let high_bits = (word_idx * usize::BITS as usize) + bit_idx - rank;
             // compose the value
-            let res = (high_bits << self.l) | unsafe { iter.next_unchecked() };
+            let res = (W::cast_from(high_bits) << self.l) | unsafe { iter.next_unchecked() };
 
             if STRICT {
                 if res > value {
@@ -408,29 +495,40 @@ where
     }
 }
 
-impl<H: AsRef<[usize]> + SelectUnchecked + SelectZeroUnchecked, L: BitFieldSlice<usize>> Succ
-    for EliasFano<H, L>
+impl<
+        W: Word + CastableFrom<usize> + CastableInto<usize>,
+        H: AsRef<[usize]> + SelectUnchecked + SelectZeroUnchecked,
+        L: BitFieldSlice<W>,
+    > Succ for EliasFano<W, H, L>
 where
-    for<'b> &'b L: IntoUncheckedIterator<Item = usize>,
+    for<'b> &'b L: IntoUncheckedIterator<Item = W>,
 {
 }
 
 #[allow(clippy::collapsible_else_if)]
-impl<H: AsRef<[usize]> + SelectZeroUnchecked, L: BitFieldSlice<usize>> PredUnchecked
-    for EliasFano<H, L>
+impl<
+        W: Word + CastableFrom<usize> + CastableInto<usize>,
+        H: AsRef<[usize]> + SelectZeroUnchecked,
+        L: BitFieldSlice<W>,
+    > PredUnchecked for EliasFano<W, H, L>
 where
-    for<'b> &'b L: IntoReverseUncheckedIterator<Item = usize>,
+    for<'b> &'b L: IntoReverseUncheckedIterator<Item = W>,
 {
     unsafe fn pred_unchecked<const STRICT: bool>(
         &self,
         value: impl Borrow<Self::Input>,
     ) -> (usize, Self::Output) {
         let value = *value.borrow();
-        let zeros_to_skip = value >> self.l;
+        if value > self.u {
+            // All values are smaller than the argument
+            return self.pred_unchecked::<false>(self.u);
+        }
+        let zeros_to_skip: usize = (value >> self.l).cast();
         let mut bit_pos = self.high_bits.select_zero_unchecked(zeros_to_skip) - 1;
 
         let mut rank = bit_pos - zeros_to_skip;
         let mut iter = self.low_bits.into_rev_unchecked_iter_from(rank + 1);
+        let value_low_bits = value & ((W::ONE << self.l) - W::ONE);
 
         // SAFETY: we are certainly iterating within the length of the arrays
         // and within the range of the iterator because there is a predecessor for sure.
@@ -448,24 +546,20 @@ where
                     window = unsafe { *self.high_bits.as_ref().get_unchecked(word_idx) };
                     zeros += usize::BITS as usize;
                 }
-                return (
-                    rank,
-                    (((usize::BITS as usize) - 1 + bit_pos
-                        - zeros
-                        - window.leading_zeros() as usize
-                        - rank)
-                        << self.l)
-                        | lower_bits,
-                );
+                let high_bits = (usize::BITS as usize) - 1 + bit_pos
+                    - zeros
+                    - window.leading_zeros() as usize
+                    - rank;
+                return (rank, (W::cast_from(high_bits) << self.l) | lower_bits);
             }
 
             if STRICT {
-                if lower_bits < value & ((1 << self.l) - 1) {
-                    return (rank, ((bit_pos - rank) << self.l) | lower_bits);
+                if lower_bits < value_low_bits {
+                    return (rank, (W::cast_from(bit_pos - rank) << self.l) | lower_bits);
                 }
             } else {
-                if lower_bits <= value & ((1 << self.l) - 1) {
-                    return (rank, ((bit_pos - rank) << self.l) | lower_bits);
+                if lower_bits <= value_low_bits {
+                    return (rank, (W::cast_from(bit_pos - rank) << self.l) | lower_bits);
                 }
             }
 
@@ -475,29 +569,141 @@ where
     }
 }
 
-impl<H: AsRef<[usize]> + SelectUnchecked + SelectZeroUnchecked, L: BitFieldSlice<usize>> Pred
-    for EliasFano<H, L>
+impl<
+        W: Word + CastableFrom<usize> + CastableInto<usize>,
+        H: AsRef<[usize]> + SelectUnchecked + SelectZeroUnchecked,
+        L: BitFieldSlice<W>,
+    > Pred for EliasFano<W, H, L>
 where
-    for<'b> &'b L: IntoReverseUncheckedIterator<Item = usize>,
+    for<'b> &'b L: IntoReverseUncheckedIterator<Item = W>,
 {
 }
 
-impl<H: AsRef<[usize]>, L: BitFieldSlice<usize>> EliasFano<H, L>
+/// Checks the parameters against the lengths of the lower and upper bits, and
+/// then verifies the upper bits (and their selection structures, if any). In
+/// [full](VerifyMode::Full) mode, it also checks that the upper bits contain
+/// exactly `n` ones and that the values are nondecreasing and bounded by `u`.
+///
+/// Values wider than `usize` are truncated in the reported errors.
+impl<
+        W: Word + CastableFrom<usize> + CastableInto<usize>,
+        H: AsRef<[usize]> + BitLength + Verify,
+        L: BitFieldSlice<W> + Verify,
+    > Verify for EliasFano<W, H, L>
+{
+    fn verify(&self, mode: VerifyMode) -> Result<(), VerifyError> {
+        self.high_bits.verify(mode)?;
+        self.low_bits.verify(mode)?;
+
+        if self.l >= W::BITS {
+            return Err(VerifyError::Param {
+                what: "number of lower bits",
+                expected: W::BITS - 1,
+                found: self.l,
+            });
+        }
+        if self.low_bits.bit_width() != self.l {
+            return Err(VerifyError::Param {
+                what: "bit width of the lower bits",
+                expected: self.l,
+                found: self.low_bits.bit_width(),
+            });
+        }
+        if self.low_bits.len() != self.n {
+            return Err(VerifyError::Param {
+                what: "length of the lower bits",
+                expected: self.n,
+                found: self.low_bits.len(),
+            });
+        }
+        let upper = self.u >> self.l;
+        let upper = if upper > W::cast_from(usize::MAX) {
+            usize::MAX
+        } else {
+            upper.cast()
+        };
+        let high_len = self
+            .n
+            .checked_add(upper)
+            .and_then(|len| len.checked_add(1))
+            .unwrap_or(usize::MAX);
+        if self.high_bits.len() != high_len {
+            return Err(VerifyError::Param {
+                what: "length of the upper bits",
+                expected: high_len,
+                found: self.high_bits.len(),
+            });
+        }
+
+        if mode == VerifyMode::Full {
+            let words = self.high_bits.as_ref();
+            let num_words = high_len.div_ceil(usize::BITS as usize);
+            let residual = high_len % usize::BITS as usize;
+            let mut index = 0;
+            let mut prev = W::ZERO;
+            for (word_idx, &word) in words[..num_words].iter().enumerate() {
+                let mut window = if residual != 0 && word_idx == num_words - 1 {
+                    word & ((1 << residual) - 1)
+                } else {
+                    word
+                };
+                while window != 0 {
+                    let pos = word_idx * usize::BITS as usize + window.trailing_zeros() as usize;
+                    window &= window - 1;
+                    if index < self.n {
+                        let value =
+                            (W::cast_from(pos - index) << self.l) | self.low_bits.get(index);
+                        if value > self.u {
+                            return Err(VerifyError::Value {
+                                what: "value",
+                                index,
+                                expected: self.u.cast(),
+                                found: value.cast(),
+                            });
+                        }
+                        if value < prev {
+                            return Err(VerifyError::Value {
+                                what: "value",
+                                index,
+                                expected: prev.cast(),
+                                found: value.cast(),
+                            });
+                        }
+                        prev = value;
+                    }
+                    index += 1;
+                }
+            }
+            if index != self.n {
+                return Err(VerifyError::Param {
+                    what: "number of ones in the upper bits",
+                    expected: self.n,
+                    found: index,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<W: Word, H: AsRef<[usize]>, L: BitFieldSlice<W>> EliasFano<W, H, L>
 where
-    for<'b> &'b L: IntoUncheckedIterator<Item = usize>,
+    for<'b> &'b L: IntoUncheckedIterator<Item = W>,
 {
     #[inline(always)]
-    pub fn iter(&self) -> EliasFanoIterator<'_, H, L> {
+    pub fn iter(&self) -> EliasFanoIterator<'_, W, H, L> {
         EliasFanoIterator::new(self)
     }
 }
 
-impl<'a, H: AsRef<[usize]>, L: BitFieldSlice<usize>> IntoIterator for &'a EliasFano<H, L>
+impl<'a, W: Word + CastableFrom<usize>, H: AsRef<[usize]>, L: BitFieldSlice<W>> IntoIterator
+    for &'a EliasFano<W, H, L>
 where
-    for<'b> &'b L: IntoUncheckedIterator<Item = usize>,
+    for<'b> &'b L: IntoUncheckedIterator<Item = W>,
 {
-    type Item = usize;
-    type IntoIter = EliasFanoIterator<'a, H, L>;
+    type Item = W;
+    type IntoIter = EliasFanoIterator<'a, W, H, L>;
 
     #[inline(always)]
     fn into_iter(self) -> Self::IntoIter {
@@ -505,36 +711,40 @@ where
     }
 }
 
-impl<H: AsRef<[usize]> + SelectUnchecked, L: BitFieldSlice<usize>> EliasFano<H, L>
+impl<W: Word, H: AsRef<[usize]> + SelectUnchecked, L: BitFieldSlice<W>> EliasFano<W, H, L>
 where
-    for<'b> &'b L: IntoUncheckedIterator<Item = usize>,
+    for<'b> &'b L: IntoUncheckedIterator<Item = W>,
 {
     #[inline(always)]
-    pub fn iter_from(&self, from: usize) -> EliasFanoIterator<'_, H, L> {
+    pub fn iter_from(&self, from: usize) -> EliasFanoIterator<'_, W, H, L> {
         EliasFanoIterator::new_from(self, from)
     }
 }
 
-impl<'a, H: AsRef<[usize]> + SelectUnchecked, L: BitFieldSlice<usize>> IntoIteratorFrom
-    for &'a EliasFano<H, L>
+impl<
+        'a,
+        W: Word + CastableFrom<usize>,
+        H: AsRef<[usize]> + SelectUnchecked,
+        L: BitFieldSlice<W>,
+    > IntoIteratorFrom for &'a EliasFano<W, H, L>
 where
-    for<'b> &'b L: IntoUncheckedIterator<Item = usize>,
+    for<'b> &'b L: IntoUncheckedIterator<Item = W>,
 {
-    type IntoIterFrom = EliasFanoIterator<'a, H, L>;
+    type IntoIterFrom = EliasFanoIterator<'a, W, H, L>;
 
     #[inline(always)]
-    fn into_iter_from(self, from: usize) -> EliasFanoIterator<'a, H, L> {
+    fn into_iter_from(self, from: usize) -> EliasFanoIterator<'a, W, H, L> {
         EliasFanoIterator::new_from(self, from)
     }
 }
 
 /// An iterator for [`EliasFano`].
 #[derive(MemDbg, MemSize)]
-pub struct EliasFanoIterator<'a, H: AsRef<[usize]>, L: BitFieldSlice<usize>>
+pub struct EliasFanoIterator<'a, W: Word, H: AsRef<[usize]>, L: BitFieldSlice<W>>
 where
-    for<'b> &'b L: IntoUncheckedIterator<Item = usize>,
+    for<'b> &'b L: IntoUncheckedIterator<Item = W>,
 {
-    ef: &'a EliasFano<H, L>,
+    ef: &'a EliasFano<W, H, L>,
     /// The index of the next value it will be returned when `next` is called.
     index: usize,
     /// Index of the word loaded in the `word` field.
@@ -545,11 +755,11 @@ where
     low_bits: <&'a L as IntoUncheckedIterator>::IntoUncheckedIter,
 }
 
-impl<'a, H: AsRef<[usize]>, L: BitFieldSlice<usize>> EliasFanoIterator<'a, H, L>
+impl<'a, W: Word, H: AsRef<[usize]>, L: BitFieldSlice<W>> EliasFanoIterator<'a, W, H, L>
 where
-    for<'b> &'b L: IntoUncheckedIterator<Item = usize>,
+    for<'b> &'b L: IntoUncheckedIterator<Item = W>,
 {
-    pub fn new(ef: &'a EliasFano<H, L>) -> Self {
+    pub fn new(ef: &'a EliasFano<W, H, L>) -> Self {
         let word = if ef.high_bits.as_ref().is_empty() {
             0
         } else {
@@ -565,14 +775,25 @@ where
     }
 }
 
-impl<'a, H: AsRef<[usize]> + SelectUnchecked, L: BitFieldSlice<usize>> EliasFanoIterator<'a, H, L>
+impl<'a, W: Word, H: AsRef<[usize]> + SelectUnchecked, L: BitFieldSlice<W>>
+    EliasFanoIterator<'a, W, H, L>
 where
-    for<'b> &'b L: IntoUncheckedIterator<Item = usize>,
+    for<'b> &'b L: IntoUncheckedIterator<Item = W>,
 {
-    pub fn new_from(ef: &'a EliasFano<H, L>, start_index: usize) -> Self {
+    pub fn new_from(ef: &'a EliasFano<W, H, L>, start_index: usize) -> Self {
         if start_index > ef.len() {
             panic!("Index out of bounds: {} > {}", start_index, ef.len());
         }
+        if start_index == ef.len() {
+            // There is no one to select: the iterator is exhausted
+            return Self {
+                ef,
+                index: start_index,
+                word_idx: 0,
+                window: 0,
+                low_bits: ef.low_bits.into_unchecked_iter_from(start_index),
+            };
+        }
         let bit_pos = unsafe { ef.high_bits.select_unchecked(start_index) };
         let word_idx = bit_pos / (usize::BITS as usize);
         let bits_to_clean = bit_pos % (usize::BITS as usize);
@@ -596,11 +817,12 @@ where
     }
 }
 
-impl<H: AsRef<[usize]>, L: BitFieldSlice<usize>> Iterator for EliasFanoIterator<'_, H, L>
+impl<W: Word + CastableFrom<usize>, H: AsRef<[usize]>, L: BitFieldSlice<W>> Iterator
+    for EliasFanoIterator<'_, W, H, L>
 where
-    for<'b> &'b L: IntoUncheckedIterator<Item = usize>,
+    for<'b> &'b L: IntoUncheckedIterator<Item = W>,
 {
-    type Item = usize;
+    type Item = W;
 
     #[inline(always)]
     fn next(&mut self) -> Option<Self::Item> {
@@ -620,7 +842,8 @@ where
         // clear the lowest bit set
         self.window &= self.window - 1;
         // compose the value
-        let res = (high_bits << self.ef.l) | unsafe { self.low_bits.next_unchecked() };
+        let res =
+            (W::cast_from(high_bits) << self.ef.l) | unsafe { self.low_bits.next_unchecked() };
         self.index += 1;
         Some(res)
     }
@@ -631,9 +854,10 @@ where
     }
 }
 
-impl<H: AsRef<[usize]>, L: BitFieldSlice<usize>> ExactSizeIterator for EliasFanoIterator<'_, H, L>
+impl<W: Word + CastableFrom<usize>, H: AsRef<[usize]>, L: BitFieldSlice<W>> ExactSizeIterator
+    for EliasFanoIterator<'_, W, H, L>
 where
-    for<'b> &'b L: IntoUncheckedIterator<Item = usize>,
+    for<'b> &'b L: IntoUncheckedIterator<Item = W>,
 {
     #[inline(always)]
     fn len(&self) -> usize {
@@ -641,25 +865,332 @@ where
     }
 }
 
+impl<W: Word, H: AsRef<[usize]>, L: BitFieldSlice<W>> EliasFano<W, H, L> {
+    /// Returns a [cursor](EliasFanoCursor) positioned on the first value of
+    /// the sequence, or past the end if the sequence is empty.
+    pub fn cursor(&self) -> EliasFanoCursor<'_, W, H, L> {
+        let mut cursor = EliasFanoCursor {
+            ef: self,
+            index: 0,
+            bit_pos: 0,
+        };
+        cursor.bit_pos = if self.n == 0 {
+            cursor.end_pos()
+        } else {
+            unsafe { cursor.next_one(0) }
+        };
+        cursor
+    }
+}
+
+impl<W: Word, H: AsRef<[usize]> + SelectUnchecked, L: BitFieldSlice<W>> EliasFano<W, H, L> {
+    /// Returns a [cursor](EliasFanoCursor) positioned on the value of given
+    /// index, or past the end if `index` is equal to the length of the
+    /// sequence.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `index` is greater than the length of the
+    /// sequence.
+    pub fn cursor_from(&self, index: usize) -> EliasFanoCursor<'_, W, H, L> {
+        if index > self.n {
+            panic!("Index out of bounds: {} > {}", index, self.n);
+        }
+        let mut cursor = EliasFanoCursor {
+            ef: self,
+            index,
+            bit_pos: 0,
+        };
+        cursor.bit_pos = if index == self.n {
+            cursor.end_pos()
+        } else {
+            unsafe { self.high_bits.select_unchecked(index) }
+        };
+        cursor
+    }
+}
+
+/// The maximum difference between the upper bits of the target of
+/// [`EliasFanoCursor::skip_to`] and those of the current value for which we
+/// scan linearly the upper bits instead of selecting a zero.
+const LINEAR_SCAN_THRESHOLD: usize = 8;
+
+/// A bidirectional cursor over an [`EliasFano`].
+///
+/// A cursor is positioned on a value of the sequence, or past its end:
+/// [`index`](Self::index) returns the index of the current value, or the
+/// length of the sequence past the end, and [`value`](Self::value) returns
+/// the current value, or `None` past the end. [`next`](Self::next) and
+/// [`prev`](Self::prev) move the cursor by one position, whereas
+/// [`skip_to`](Self::skip_to) moves it forward to the first value greater than
+/// or equal to a given bound, which is the basic step of the intersection of
+/// sequences.
+///
+/// Cursors are returned by [`EliasFano::cursor`], which is always available,
+/// and by [`EliasFano::cursor_from`], which requires a selection structure
+/// on the upper bits. [`skip_to`](Self::skip_to) requires a zero-selection
+/// structure on the upper bits, which it uses for long jumps.
+///
+/// # Examples
+///
+/// ```rust
+/// # use sux::dict::EliasFanoBuilder;
+/// let mut efb = EliasFanoBuilder::new(5, 100);
+/// efb.extend([3, 5, 5, 40, 100]);
+/// let ef = efb.build_with_seq_and_dict();
+///
+/// let mut cursor = ef.cursor();
+/// assert_eq!((cursor.index(), cursor.value()), (0, Some(3)));
+/// assert_eq!(cursor.next(), Some(5));
+/// assert_eq!(cursor.skip_to(6), Some(40));
+/// assert_eq!(cursor.index(), 3);
+/// assert_eq!(cursor.prev(), Some(5));
+/// assert_eq!(cursor.skip_to(101), None);
+/// assert_eq!(cursor.index(), 5);
+/// assert_eq!(cursor.prev(), Some(100));
+///
+/// let cursor = ef.cursor_from(2);
+/// assert_eq!(cursor.value(), Some(5));
+/// ```
+#[derive(MemDbg, MemSize)]
+pub struct EliasFanoCursor<'a, W, H, L> {
+    ef: &'a EliasFano<W, H, L>,
+    /// The index of the current value, or the length of the sequence if the
+    /// cursor is past the end.
+    index: usize,
+    /// The position in the upper bits of the one of the current value, or
+    /// the number of bits in the words of the upper bits if the cursor is
+    /// past the end.
+    bit_pos: usize,
+}
+
+impl<W, H, L> Clone for EliasFanoCursor<'_, W, H, L> {
+    fn clone(&self) -> Self {
+        Self {
+            ef: self.ef,
+            index: self.index,
+            bit_pos: self.bit_pos,
+        }
+    }
+}
+
+impl<W: Word, H: AsRef<[usize]>, L: BitFieldSlice<W>> EliasFanoCursor<'_, W, H, L> {
+    /// Returns the index of the current value, or the length of the sequence
+    /// if the cursor is past the end.
+    #[inline(always)]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the position used for the upper bits past the end.
+    #[inline(always)]
+    fn end_pos(&self) -> usize {
+        self.ef.high_bits.as_ref().len() * usize::BITS as usize
+    }
+
+    /// Moves the cursor past the end.
+    #[inline(always)]
+    fn move_to_end(&mut self) {
+        self.index = self.ef.n;
+        self.bit_pos = self.end_pos();
+    }
+
+    /// Returns the position of the first one in the upper bits at or after
+    /// `pos`.
+    ///
+    /// # Safety
+    ///
+    /// There must be a one at or after `pos`.
+    #[inline(always)]
+    unsafe fn next_one(&self, pos: usize) -> usize {
+        let words = self.ef.high_bits.as_ref();
+        let mut word_idx = pos / usize::BITS as usize;
+        let mut window = unsafe { *words.get_unchecked(word_idx) }
+            & (usize::MAX << (pos % usize::BITS as usize));
+        while window == 0 {
+            word_idx += 1;
+            debug_assert!(word_idx < words.len());
+            window = unsafe { *words.get_unchecked(word_idx) };
+        }
+        word_idx * usize::BITS as usize + window.trailing_zeros() as usize
+    }
+
+    /// Returns the position of the last one in the upper bits before `pos`.
+    ///
+    /// # Safety
+    ///
+    /// There must be a one before `pos`.
+    #[inline(always)]
+    unsafe fn prev_one(&self, pos: usize) -> usize {
+        let words = self.ef.high_bits.as_ref();
+        let mut word_idx = pos / usize::BITS as usize;
+        let bit_idx = pos % usize::BITS as usize;
+        // If bit_idx is zero, word_idx might be the number of words
+        let mut window = if bit_idx == 0 {
+            0
+        } else {
+            let word = unsafe { *words.get_unchecked(word_idx) };
+            word & ((1 << bit_idx) - 1)
+        };
+        while window == 0 {
+            word_idx -= 1;
+            window = unsafe { *words.get_unchecked(word_idx) };
+        }
+        word_idx * usize::BITS as usize + usize::BITS as usize - 1 - window.leading_zeros() as usize
+    }
+}
+
+impl<W: Word + CastableFrom<usize>, H: AsRef<[usize]>, L: BitFieldSlice<W>>
+    EliasFanoCursor<'_, W, H, L>
+{
+    /// Returns the current value, or `None` if the cursor is past the end.
+    #[inline(always)]
+    pub fn value(&self) -> Option<W> {
+        if self.index == self.ef.n {
+            None
+        } else {
+            Some(unsafe { self.value_unchecked() })
+        }
+    }
+
+    /// Returns the current value.
+    ///
+    /// # Safety
+    ///
+    /// The cursor must not be past the end.
+    #[inline(always)]
+    unsafe fn value_unchecked(&self) -> W {
+        (W::cast_from(self.bit_pos - self.index) << self.ef.l)
+            | unsafe { self.ef.low_bits.get_unchecked(self.index) }
+    }
+
+    /// Moves the cursor to the next value and returns it.
+    ///
+    /// If the cursor is on the last value, it is moved past the end and this
+    /// method returns `None`; if it is already past the end, it does not
+    /// move.
+    #[allow(clippy::should_implement_trait)]
+    #[inline]
+    pub fn next(&mut self) -> Option<W> {
+        if self.index >= self.ef.n {
+            return None;
+        }
+        self.index += 1;
+        if self.index == self.ef.n {
+            self.move_to_end();
+            return None;
+        }
+        // SAFETY: there is a value of index self.index
+        unsafe {
+            self.bit_pos = self.next_one(self.bit_pos + 1);
+            Some(self.value_unchecked())
+        }
+    }
+
+    /// Moves the cursor to the previous value and returns it.
+    ///
+    /// If the cursor is on the first value, it does not move and this method
+    /// returns `None`. If the cursor is past the end of a nonempty sequence,
+    /// it is moved on the last value.
+    #[inline]
+    pub fn prev(&mut self) -> Option<W> {
+        if self.index == 0 {
+            return None;
+        }
+        self.index -= 1;
+        // SAFETY: there is a value of index self.index
+        unsafe {
+            self.bit_pos = self.prev_one(self.bit_pos);
+            Some(self.value_unchecked())
+        }
+    }
+}
+
+impl<
+        W: Word + CastableFrom<usize> + CastableInto<usize>,
+        H: AsRef<[usize]> + SelectZeroUnchecked,
+        L: BitFieldSlice<W>,
+    > EliasFanoCursor<'_, W, H, L>
+{
+    /// Moves the cursor forward to the first value greater than or equal to
+    /// `value`, and returns it.
+    ///
+    /// If the current value is already greater than or equal to `value`, the
+    /// cursor does not move. If there is no such value, the cursor is moved
+    /// past the end and this method returns `None`.
+    ///
+    /// If the upper bits of `value` are close to those of the current value
+    /// the upper bits are scanned linearly; otherwise, the cursor jumps to
+    /// the first value with the same upper bits of `value` by selecting a zero
+    /// in the upper bits.
+    pub fn skip_to(&mut self, value: W) -> Option<W> {
+        if self.index == self.ef.n {
+            return None;
+        }
+        // SAFETY: the cursor is not past the end
+        let current = unsafe { self.value_unchecked() };
+        if current >= value {
+            return Some(current);
+        }
+        if value > self.ef.u {
+            self.move_to_end();
+            return None;
+        }
+
+        let target_high: usize = (value >> self.ef.l).cast();
+        // Since value > current, target_high >= self.bit_pos - self.index
+        if target_high - (self.bit_pos - self.index) > LINEAR_SCAN_THRESHOLD {
+            // SAFETY: target_high is positive and at most u >> l, so there are
+            // at least target_high zeros in the upper bits
+            let pos = unsafe { self.ef.high_bits.select_zero_unchecked(target_high - 1) } + 1;
+            self.index = pos - target_high;
+            if self.index == self.ef.n {
+                self.move_to_end();
+                return None;
+            }
+            // SAFETY: there is a value of index self.index
+            self.bit_pos = unsafe { self.next_one(pos) };
+        }
+
+        loop {
+            // We compose the value only when the upper bits are large enough
+            if self.bit_pos - self.index >= target_high {
+                // SAFETY: the cursor is not past the end
+                let current = unsafe { self.value_unchecked() };
+                if current >= value {
+                    return Some(current);
+                }
+            }
+            self.index += 1;
+            if self.index == self.ef.n {
+                self.move_to_end();
+                return None;
+            }
+            // SAFETY: there is a value of index self.index
+            self.bit_pos = unsafe { self.next_one(self.bit_pos + 1) };
+        }
+    }
+}
+
 /// Convenience constructor that iterates over a slice.
 ///
 /// Note that this implementation requires a first scan to check monotonicity
 /// and find the maximum value, but then it uses
 /// [`EliasFanoBuilder::push_unchecked`], thus partially compensating for the
 /// cost of the first scan.
-impl<A: AsRef<[usize]>> From<A> for EliasFano {
+impl<W: Word + CastableFrom<usize> + CastableInto<usize>, A: AsRef<[W]>> From<A> for EliasFano<W> {
     fn from(values: A) -> Self {
         let values = values.as_ref();
-        let mut max = 0;
-        let mut prev = 0;
+        let mut max = W::ZERO;
+        let mut prev = W::ZERO;
         for &value in values {
             if value < prev {
                 panic!("The values provided are not monotone: {} < {}", value, prev);
             }
-            max = max.max(value);
+            max = Ord::max(max, value);
             prev = value;
         }
-        let mut builder = EliasFanoBuilder::new(values.len(), max);
+        let mut builder = EliasFanoBuilder::new_generic(values.len(), max);
         for &value in values {
             // SAFETY: pre-scan checked monotonicity and max.
             unsafe {
@@ -670,12 +1201,43 @@ impl<A: AsRef<[usize]>> From<A> for EliasFano {
     }
 }
 
+/// Returns the number of lower bits of an Elias–Fano representation of `n`
+/// values smaller than or equal to `u`, that is, ⌊lg(*u*/*n*)⌋, or zero if
+/// *u* < *n*.
+///
+/// An empty sequence is treated as a sequence of length one, so that the
+/// upper bits remain small even if `u` is large.
+pub(crate) fn num_lower_bits<W: Word + CastableFrom<usize> + CastableInto<usize>>(
+    n: usize,
+    u: W,
+) -> usize {
+    let n = n.max(1);
+    // If n does not fit into W, it is certainly larger than u
+    if n > W::MAX.cast() || u < W::cast_from(n) {
+        0
+    } else {
+        (u / W::cast_from(n)).ilog2() as usize
+    }
+}
+
 /// A sequential builder for [`EliasFano`].
 ///
 /// After creating an instance, you can use [`EliasFanoBuilder::push`] to add
 /// new values, and then call [`EliasFanoBuilder::build`] to create the
 /// [`EliasFano`] instance.
 ///
+/// The builder is generic in the type `W` of the values, which defaults to
+/// `usize`. [`EliasFanoBuilder::new`] creates a builder for `usize` values;
+/// for other types, use [`EliasFanoBuilder::new_generic`].
+///
+/// The number of values `n` passed at construction time is an upper bound:
+/// if fewer values are added, the build methods return a structure
+/// containing just the values added. The number of lower bits, however, is
+/// computed using `n`, so the structure might be larger than necessary; this
+/// is useful when the number of values is known only approximately, as in
+/// the case of the results of the
+/// [operations on Elias–Fano sequences](crate::dict::elias_fano_ops).
+///
 /// # Examples
 ///
 /// ```rust
@@ -694,15 +1256,20 @@ impl<A: AsRef<[usize]>> From<A> for EliasFano {
 /// assert_eq!(iter.next(), Some(8));
 /// assert_eq!(iter.next(), Some(10));
 /// assert_eq!(iter.next(), None);
+///
+/// let mut efb = EliasFanoBuilder::<u32>::new_generic(2, u32::MAX);
+/// efb.push(1);
+/// efb.push(u32::MAX);
+/// assert_eq!(efb.build().iter().collect::<Vec<_>>(), vec![1, u32::MAX]);
 /// ```
 #[derive(Debug, Clone, MemDbg, MemSize)]
-pub struct EliasFanoBuilder {
+pub struct EliasFanoBuilder<W: Word = usize> {
     n: usize,
-    u: usize,
+    u: W,
     l: usize,
-    low_bits: BitFieldVec,
+    low_bits: BitFieldVec<W>,
     high_bits: BitVec,
-    last_value: usize,
+    last_value: W,
     count: usize,
 }
 
@@ -710,19 +1277,24 @@ impl EliasFanoBuilder {
     /// Creates a builder for an [`EliasFano`] containing
     /// `n` numbers smaller than or equal to `u`.
     pub fn new(n: usize, u: usize) -> Self {
-        let l = if u >= n {
-            (u as f64 / n as f64).log2().floor() as usize
-        } else {
-            0
-        };
+        Self::new_generic(n, u)
+    }
+}
+
+impl<W: Word + CastableFrom<usize> + CastableInto<usize>> EliasFanoBuilder<W> {
+    /// Creates a builder for an [`EliasFano`] containing
+    /// `n` numbers of type `W` smaller than or equal to `u`.
+    pub fn new_generic(n: usize, u: W) -> Self {
+        let l = num_lower_bits(n, u);
+        let upper: usize = (u >> l).cast();
 
         Self {
             n,
             u,
             l,
             low_bits: BitFieldVec::new(l, n),
-            high_bits: BitVec::new(n + (u >> l) + 1),
-            last_value: 0,
+            high_bits: BitVec::new(n + upper + 1),
+            last_value: W::ZERO,
             count: 0,
         }
     }
@@ -732,7 +1304,7 @@ impl EliasFanoBuilder {
     /// # Panic
     /// May panic if the value is smaller than the last provided
     /// value, or if too many values are provided.
-    pub fn push(&mut self, value: usize) {
+    pub fn push(&mut self, value: W) {
         if self.count == self.n {
             panic!("Too many values");
         }
@@ -754,11 +1326,11 @@ impl EliasFanoBuilder {
     ///
     /// Values passed to this function must be smaller than or equal `u` and must be monotone.
     /// Moreover, the function should not be called more than `n` times.
-    pub unsafe fn push_unchecked(&mut self, value: usize) {
-        let low = value & ((1 << self.l) - 1);
+    pub unsafe fn push_unchecked(&mut self, value: W) {
+        let low = value & ((W::ONE << self.l) - W::ONE);
         self.low_bits.set(self.count, low);
 
-        let high = (value >> self.l) + self.count;
+        let high = (value >> self.l).cast() + self.count;
         self.high_bits.set(high, true);
 
         self.count += 1;
@@ -776,13 +1348,32 @@ impl EliasFanoBuilder {
     /// [`build_with_dict`](EliasFanoConcurrentBuilder::build_with_dict), and
     /// [`build_with_seq_and_dict`](EliasFanoConcurrentBuilder::build_with_seq_and_dict)
     /// methods are more convenient.
-    pub fn build(self) -> EliasFano {
-        let high_bits: BitVec<Box<[usize]>> = self.high_bits.into();
+    ///
+    /// If fewer than `n` values have been added, the structure will contain
+    /// just the values added. This makes it possible to size a builder using
+    /// an upper bound on the number of values, albeit the number of lower bits
+    /// will be computed using the upper bound.
+    pub fn build(self) -> EliasFano<W> {
+        let mut low_bits = self.low_bits;
+        let mut high_bits = self.high_bits;
+        if self.count < self.n {
+            // Shrink the bit vectors to the size they would have with n
+            // equal to the number of values added
+            let (mut words, l, _) = low_bits.into_raw_parts();
+            words.truncate(Ord::max(1, (self.count * l).div_ceil(W::BITS)));
+            low_bits = unsafe { BitFieldVec::from_raw_parts(words, l, self.count) };
+            let upper: usize = (self.u >> self.l).cast();
+            let high_len = self.count + upper + 1;
+            let (mut words, _) = high_bits.into_raw_parts();
+            words.truncate(high_len.div_ceil(usize::BITS as usize));
+            high_bits = unsafe { BitVec::from_raw_parts(words, high_len) };
+        }
+        let high_bits: BitVec<Box<[usize]>> = high_bits.into();
         EliasFano {
-            n: self.n,
+            n: self.count,
             u: self.u,
             l: self.l,
-            low_bits: self.low_bits.into(),
+            low_bits: low_bits.into(),
             // SAFETY: n is the number of ones in the high_bits.
             high_bits,
         }
@@ -793,7 +1384,7 @@ impl EliasFanoBuilder {
     ///
     /// The resulting structure implements [`IndexedSeq`], but not [`IndexedDict`],
     /// [`Succ`], or [`Pred`].
-    pub fn build_with_seq(self) -> EfSeq {
+    pub fn build_with_seq(self) -> EfSeq<W> {
         let ef = self.build();
         unsafe { ef.map_high_bits(SelectAdaptConst::<_, _, 12, 3>::new) }
     }
@@ -803,7 +1394,7 @@ impl EliasFanoBuilder {
     ///
     /// The resulting structure implements [`SuccUnchecked`], and [`PredUnchecked`],
     /// but not [`IndexedSeq`].
-    pub fn build_with_dict(self) -> EfDict {
+    pub fn build_with_dict(self) -> EfDict<W> {
         let ef = self.build();
         unsafe { ef.map_high_bits(SelectZeroAdaptConst::<_, _, 12, 3>::new) }
     }
@@ -813,7 +1404,7 @@ impl EliasFanoBuilder {
     ///
     /// The resulting structure implements [`IndexedDict`], [`Succ`],
     /// [`Pred`], and [`IndexedSeq`].
-    pub fn build_with_seq_and_dict(self) -> EfSeqDict {
+    pub fn build_with_seq_and_dict(self) -> EfSeqDict<W> {
         let ef = self.build();
         unsafe {
             ef.map_high_bits(SelectAdaptConst::<_, _, 12, 3>::new)
@@ -822,14 +1413,148 @@ impl EliasFanoBuilder {
     }
 }
 
-impl Extend<usize> for EliasFanoBuilder {
-    fn extend<T: IntoIterator<Item = usize>>(&mut self, iter: T) {
+impl<W: Word + CastableFrom<usize> + CastableInto<usize>> Extend<W> for EliasFanoBuilder<W> {
+    fn extend<T: IntoIterator<Item = W>>(&mut self, iter: T) {
         for value in iter {
             self.push(value);
         }
     }
 }
 
+/// Errors returned when filling an [`EliasFanoBuilder`] from a
+/// [rewindable lender](RewindableIoLender).
+#[derive(thiserror::Error, Debug)]
+pub enum EfLenderError<W, E> {
+    #[error("Error reading the value of index {index}: {source}")]
+    /// The lender returned an error.
+    Lender { index: usize, source: E },
+    #[error("Error rewinding the lender: {0}")]
+    /// The lender could not be rewound.
+    Rewind(#[source] E),
+    #[error("Cannot parse the value of index {index}: {line:?}")]
+    /// A line could not be parsed as a value.
+    Parse { index: usize, line: String },
+    #[error("The values provided are not monotone: value {value} at index {index} is smaller than {prev}")]
+    /// A value is smaller than the previous one.
+    NotMonotone { index: usize, value: W, prev: W },
+    #[error("The lender returned after rewinding a different sequence (first difference at index {index})")]
+    /// The second pass did not return the same values of the first pass.
+    Changed { index: usize },
+}
+
+impl<W: Word + CastableFrom<usize> + CastableInto<usize>> EliasFanoBuilder<W> {
+    /// Creates a builder containing the values returned by a
+    /// [rewindable lender](RewindableIoLender).
+    ///
+    /// The lender is scanned twice: a first time to compute the number of
+    /// values and their upper bound, checking monotonicity, and a second time,
+    /// after rewinding, to store the values. Thus, neither the number of
+    /// values nor their upper bound need to be known in advance.
+    ///
+    /// Errors of the lender, lack of monotonicity, and differences between the
+    /// two passes are reported as an [`EfLenderError`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use sux::dict::EliasFanoBuilder;
+    /// # use sux::utils::FromIntoIterator;
+    /// # use sux::traits::IndexedSeq;
+    /// let efb = EliasFanoBuilder::from_lender(FromIntoIterator::from(vec![1_usize, 5, 5, 100]))?;
+    /// let ef = efb.build_with_seq();
+    /// assert_eq!(ef.len(), 4);
+    /// assert_eq!(ef.get(3), 100);
+    ///
+    /// assert!(EliasFanoBuilder::from_lender(FromIntoIterator::from(vec![1_usize, 0])).is_err());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn from_lender<L: RewindableIoLender<W>>(
+        lender: L,
+    ) -> Result<Self, EfLenderError<W, L::Error>> {
+        Self::from_lender_with(lender, |&value, _| Ok(value))
+    }
+
+    /// Creates a builder containing the values obtained by parsing the lines
+    /// returned by a [rewindable lender](RewindableIoLender) of strings, such
+    /// as a [`LineLender`](crate::utils::LineLender) or a
+    /// [`ZstdLineLender`](crate::utils::ZstdLineLender).
+    ///
+    /// Leading and trailing whitespace is ignored. This method works as
+    /// [`from_lender`](EliasFanoBuilder::from_lender), but lines that cannot
+    /// be parsed are reported as [`EfLenderError::Parse`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use sux::dict::EliasFanoBuilder;
+    /// # use sux::utils::LineLender;
+    /// # use sux::traits::IndexedSeq;
+    /// let lines = std::io::Cursor::new("0\n10\n20\n1000\n");
+    /// let efb = EliasFanoBuilder::<u64>::from_line_lender(LineLender::new(lines))?;
+    /// let ef = efb.build_with_seq();
+    /// assert_eq!(ef.get(2), 20);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn from_line_lender<L: RewindableIoLender<str>>(
+        lender: L,
+    ) -> Result<Self, EfLenderError<W, L::Error>>
+    where
+        W: FromStr,
+    {
+        Self::from_lender_with(lender, |line, index| {
+            line.trim().parse().map_err(|_| EfLenderError::Parse {
+                index,
+                line: line.to_owned(),
+            })
+        })
+    }
+
+    fn from_lender_with<T: ?Sized, L: RewindableIoLender<T>>(
+        mut lender: L,
+        mut value: impl FnMut(&T, usize) -> Result<W, EfLenderError<W, L::Error>>,
+    ) -> Result<Self, EfLenderError<W, L::Error>> {
+        // First pass: number of values, upper bound, and monotonicity
+        let mut n = 0;
+        let mut prev = W::ZERO;
+        while let Some(result) = lender.next() {
+            let item = result.map_err(|source| EfLenderError::Lender { index: n, source })?;
+            let value = value(item, n)?;
+            if value < prev {
+                return Err(EfLenderError::NotMonotone {
+                    index: n,
+                    value,
+                    prev,
+                });
+            }
+            prev = value;
+            n += 1;
+        }
+
+        let mut lender = lender.rewind().map_err(EfLenderError::Rewind)?;
+        let mut builder = Self::new_generic(n, prev);
+
+        // Second pass: we check again the values, as the safety of
+        // push_unchecked depends on them
+        while let Some(result) = lender.next() {
+            let index = builder.count;
+            let item = result.map_err(|source| EfLenderError::Lender { index, source })?;
+            let value = value(item, index)?;
+            if index == n || value > builder.u || value < builder.last_value {
+                return Err(EfLenderError::Changed { index });
+            }
+            // SAFETY: we just checked monotonicity, upper bound, and length
+            unsafe { builder.push_unchecked(value) };
+        }
+        if builder.count != n {
+            return Err(EfLenderError::Changed {
+                index: builder.count,
+            });
+        }
+
+        Ok(builder)
+    }
+}
+
 /// A concurrent builder for [`EliasFano`].
 ///
 /// After creating an instance, you can use [`EliasFanoConcurrentBuilder::set`]
@@ -837,6 +1562,11 @@ impl Extend<usize> for EliasFanoBuilder {
 /// unsafe as no check is performed on the provided data (e.g., duplicate
 /// indices and lack of monotonicity are not detected).
 ///
+/// As in the case of [`EliasFanoBuilder`], [`EliasFanoConcurrentBuilder::new`]
+/// creates a builder for `usize` values, whereas
+/// [`EliasFanoConcurrentBuilder::new_generic`] can be used for other types
+/// with an atomic counterpart.
+///
 /// # Examples
 ///
 /// ```rust
@@ -859,11 +1589,11 @@ impl Extend<usize> for EliasFanoBuilder {
 /// ```
 
 #[derive(MemDbg, MemSize)]
-pub struct EliasFanoConcurrentBuilder {
+pub struct EliasFanoConcurrentBuilder<W: Word + IntoAtomic = usize> {
     n: usize,
-    u: usize,
+    u: W,
     l: usize,
-    low_bits: AtomicBitFieldVec,
+    low_bits: AtomicBitFieldVec<W>,
     high_bits: AtomicBitVec,
 }
 
@@ -871,18 +1601,26 @@ impl EliasFanoConcurrentBuilder {
     /// Creates a concurrent builder for a sequence containing `n` nonnegative
     /// numbers smaller than or equal to `u`.
     pub fn new(n: usize, u: usize) -> Self {
-        let l = if u >= n {
-            (u as f64 / n as f64).log2().floor() as usize
-        } else {
-            0
-        };
+        Self::new_generic(n, u)
+    }
+}
+
+impl<W: Word + IntoAtomic + CastableFrom<usize> + CastableInto<usize>> EliasFanoConcurrentBuilder<W>
+where
+    W::AtomicType: AtomicUnsignedInt + AsBytes,
+{
+    /// Creates a concurrent builder for a sequence containing `n` nonnegative
+    /// numbers of type `W` smaller than or equal to `u`.
+    pub fn new_generic(n: usize, u: W) -> Self {
+        let l = num_lower_bits(n, u);
+        let upper: usize = (u >> l).cast();
 
         Self {
             u,
             n,
             l,
             low_bits: AtomicBitFieldVec::new(l, n),
-            high_bits: AtomicBitVec::new(n + (u >> l) + 1),
+            high_bits: AtomicBitVec::new(n + upper + 1),
         }
     }
 
@@ -893,14 +1631,14 @@ impl EliasFanoConcurrentBuilder {
     /// - All values must be smaller than or equal to `u`.
     /// - All indices must be smaller than `n`.
     /// - You must call this function exactly `n` times.
-    pub unsafe fn set(&self, index: usize, value: usize) {
-        let low = value & ((1 << self.l) - 1);
+    pub unsafe fn set(&self, index: usize, value: W) {
+        let low = value & ((W::ONE << self.l) - W::ONE);
         // Note that the concurrency guarantees of BitFieldVec
         // are sufficient for us.
         self.low_bits
             .set_atomic_unchecked(index, low, Ordering::Relaxed);
 
-        let high = (value >> self.l) + index;
+        let high = (value >> self.l).cast() + index;
         self.high_bits.set(high, true, Ordering::Relaxed);
     }
 
@@ -915,11 +1653,11 @@ impl EliasFanoConcurrentBuilder {
     /// [`build_with_dict`](EliasFanoBuilder::build_with_dict), and
     /// [`build_with_seq_and_dict`](EliasFanoBuilder::build_with_seq_and_dict)
     /// methods are more convenient.
-    pub fn build(self) -> EliasFano {
+    pub fn build(self) -> EliasFano<W> {
         let high_bits: BitVec<Vec<usize>> = self.high_bits.into();
         let high_bits: BitVec<Box<[usize]>> = high_bits.into();
-        let low_bits: BitFieldVec<usize, Vec<usize>> = self.low_bits.into();
-        let low_bits: BitFieldVec<usize, Box<[usize]>> = low_bits.into();
+        let low_bits: BitFieldVec<W, Vec<W>> = self.low_bits.into();
+        let low_bits: BitFieldVec<W, Box<[W]>> = low_bits.into();
         EliasFano {
             n: self.n,
             u: self.u,
@@ -934,7 +1672,7 @@ impl EliasFanoConcurrentBuilder {
     ///
     /// The resulting structure implements [`IndexedSeq`], but not [`IndexedDict`],
     /// [`Succ`], or [`Pred`].
-    pub fn build_with_seq(self) -> EfSeq {
+    pub fn build_with_seq(self) -> EfSeq<W> {
         let ef = self.build();
         unsafe { ef.map_high_bits(SelectAdaptConst::<_, _, 12, 3>::new) }
     }
@@ -944,7 +1682,7 @@ impl EliasFanoConcurrentBuilder {
     ///
     /// The resulting structure implements [`IndexedDict`], [`Succ`], and [`Pred`],
     /// but not [`IndexedSeq`].
-    pub fn build_with_dict(self) -> EfDict {
+    pub fn build_with_dict(self) -> EfDict<W> {
         let ef = self.build();
         unsafe { ef.map_high_bits(SelectZeroAdaptConst::<_, _, 12, 3>::new) }
     }
@@ -954,7 +1692,7 @@ impl EliasFanoConcurrentBuilder {
     ///
     /// The resulting structure implements [`IndexedDict`], [`Succ`],
     /// [`Pred`], and [`IndexedSeq`].
-    pub fn build_with_seq_and_dict(self) -> EfSeqDict {
+    pub fn build_with_seq_and_dict(self) -> EfSeqDict<W> {
         let ef = self.build();
         unsafe {
             ef.map_high_bits(SelectAdaptConst::<_, _, 12, 3>::new)
@@ -962,3 +1700,351 @@ impl EliasFanoConcurrentBuilder {
         }
     }
 }
+
+/// An external-memory builder for [`EliasFano`].
+///
+/// [`EliasFanoBuilder`] and [`EliasFanoConcurrentBuilder`] keep in memory the
+/// lower and upper bits of the structure, that is, about 2 + log(*u* / *n*)
+/// bits per element. This builder, instead, writes both to temporary files,
+/// keeping in memory only two buffers whose size is specified at construction
+/// time, and builds at the same time, in a streaming fashion, the inventories
+/// of the selection structures on the upper bits.
+///
+/// The method [`build_with_seq_and_dict`](Self::build_with_seq_and_dict)
+/// writes to a [`Write`](std::io::Write) the [ε-serde](epserde) serialization
+/// of an [`EfSeqDict`] identical to the one returned by
+/// [`EliasFanoBuilder::build_with_seq_and_dict`] on the same values; the
+/// result can thus be memory-mapped or loaded with the usual ε-serde methods.
+/// Apart from the buffers, the builder uses a small amount of memory that is
+/// independent of the number of values.
+///
+/// Temporary files are created in the default temporary directory by
+/// [`new`](Self::new), or in a given directory by [`new_in`](Self::new_in).
+/// As in the case of [`EliasFanoBuilder`], values of type `W` other than
+/// `usize` can be used by means of [`new_generic`](Self::new_generic) and
+/// [`new_generic_in`](Self::new_generic_in).
+///
+/// # Examples
+///
+/// ```rust
+/// # use sux::prelude::*;
+/// # use sux::dict::elias_fano::{EfSeqDict, EliasFanoStreamBuilder};
+/// # use epserde::prelude::*;
+/// # use std::fs::File;
+/// # fn main() -> anyhow::Result<()> {
+/// # let dir = tempfile::TempDir::new()?;
+/// # let path = dir.path().join("ef");
+/// // At most 1MiB of buffers
+/// let mut efsb = EliasFanoStreamBuilder::new(1000, 10_000, 1 << 20)?;
+/// for i in 0..1000 {
+///     efsb.push(i * 10)?;
+/// }
+/// efsb.build_with_seq_and_dict(File::create(&path)?)?;
+///
+/// let ef = <EfSeqDict>::mmap(&path, Flags::empty())?;
+/// assert_eq!(ef.get(10), 100);
+/// assert_eq!(ef.succ(95), Some((10, 100)));
+/// # Ok(())
+/// # }
+/// ```
+pub struct EliasFanoStreamBuilder<W: Word = usize> {
+    n: usize,
+    u: W,
+    l: usize,
+    count: usize,
+    last_value: W,
+    /// The maximum number of words in the buffer of upper bits.
+    buffer_words: usize,
+    /// The maximum number of words in the buffer of lower bits.
+    low_buffer_words: usize,
+    low_file: File,
+    /// The number of words of lower bits written to `low_file`.
+    low_len: usize,
+    low_buffer: Vec<W>,
+    /// The lower bits of the current word.
+    low_word: W,
+    /// The number of lower bits in `low_word`.
+    low_fill: usize,
+    high_file: File,
+    /// The number of words of upper bits written to `high_file`.
+    high_len: usize,
+    high_buffer: Vec<usize>,
+    /// The current word of upper bits, whose index is `high_len +
+    /// high_buffer.len()`.
+    high_word: usize,
+    ones: InventoryBuilder,
+    zeros: InventoryBuilder,
+}
+
+impl EliasFanoStreamBuilder {
+    /// Creates an external-memory builder for a sequence containing `n`
+    /// nonnegative numbers smaller than or equal to `u`, using buffers of
+    /// `buffer_size` bytes overall and the default temporary directory.
+    pub fn new(n: usize, u: usize, buffer_size: usize) -> anyhow::Result<Self> {
+        Self::new_generic(n, u, buffer_size)
+    }
+
+    /// Creates an external-memory builder for a sequence containing `n`
+    /// nonnegative numbers smaller than or equal to `u`, using buffers of
+    /// `buffer_size` bytes overall and creating temporary files in
+    /// `temp_dir`.
+    pub fn new_in(
+        n: usize,
+        u: usize,
+        buffer_size: usize,
+        temp_dir: impl AsRef<Path>,
+    ) -> anyhow::Result<Self> {
+        Self::new_generic_in(n, u, buffer_size, temp_dir)
+    }
+}
+
+impl<W: Word + CastableFrom<usize> + CastableInto<usize> + bytemuck::Pod>
+    EliasFanoStreamBuilder<W>
+{
+    /// Creates an external-memory builder for a sequence containing `n`
+    /// numbers of type `W` smaller than or equal to `u`, using buffers of
+    /// `buffer_size` bytes overall and the default temporary directory.
+    pub fn new_generic(n: usize, u: W, buffer_size: usize) -> anyhow::Result<Self> {
+        Self::with_temp_dir(n, u, buffer_size, None)
+    }
+
+    /// Creates an external-memory builder for a sequence containing `n`
+    /// numbers of type `W` smaller than or equal to `u`, using buffers of
+    /// `buffer_size` bytes overall and creating temporary files in
+    /// `temp_dir`.
+    pub fn new_generic_in(
+        n: usize,
+        u: W,
+        buffer_size: usize,
+        temp_dir: impl AsRef<Path>,
+    ) -> anyhow::Result<Self> {
+        Self::with_temp_dir(n, u, buffer_size, Some(temp_dir.as_ref()))
+    }
+
+    fn with_temp_dir(
+        n: usize,
+        u: W,
+        buffer_size: usize,
+        temp_dir: Option<&Path>,
+    ) -> anyhow::Result<Self> {
+        // Half of the space for the lower bits, half for the upper bits
+        let buffer_words = Ord::max(1, buffer_size / 2 / size_of::<usize>());
+        let low_buffer_words = Ord::max(1, buffer_size / 2 / size_of::<W>());
+        Ok(Self {
+            n,
+            u,
+            l: num_lower_bits(n, u),
+            count: 0,
+            last_value: W::ZERO,
+            buffer_words,
+            low_buffer_words,
+            low_file: temp_file(temp_dir)?,
+            low_len: 0,
+            low_buffer: Vec::with_capacity(low_buffer_words),
+            low_word: W::ZERO,
+            low_fill: 0,
+            high_file: temp_file(temp_dir)?,
+            high_len: 0,
+            high_buffer: Vec::with_capacity(buffer_words),
+            high_word: 0,
+            ones: InventoryBuilder::new(12, 3, temp_dir)?,
+            zeros: InventoryBuilder::new(12, 3, temp_dir)?,
+        })
+    }
+
+    /// Adds a new value to the builder.
+    ///
+    /// # Errors
+    ///
+    /// This method returns an error if an I/O error occurs while writing the
+    /// buffers to the temporary files.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the value is smaller than the last provided
+    /// value, if it is larger than `u`, or if too many values are provided.
+    pub fn push(&mut self, value: W) -> anyhow::Result<()> {
+        if self.count == self.n {
+            panic!("Too many values");
+        }
+        if value > self.u {
+            panic!("Value too large: {} > {}", value, self.u);
+        }
+        if value < self.last_value {
+            panic!(
+                "The values provided are not monotone: {} < {}",
+                value, self.last_value
+            );
+        }
+
+        if self.l != 0 {
+            let low = value & ((W::ONE << self.l) - W::ONE);
+            self.low_word |= low << self.low_fill;
+            self.low_fill += self.l;
+            if self.low_fill >= W::BITS {
+                let word = self.low_word;
+                self.push_low_word(word)?;
+                self.low_fill -= W::BITS;
+                // If low_fill is zero we must shift by l bits, which would
+                // overflow if l is a word, but l is smaller than a word
+                self.low_word = low >> (self.l - self.low_fill);
+            }
+        }
+
+        let high = (value >> self.l).cast() + self.count;
+        let word_idx = high / usize::BITS as usize;
+        while self.high_len + self.high_buffer.len() < word_idx {
+            let word = core::mem::take(&mut self.high_word);
+            self.push_high_word(word)?;
+        }
+        self.high_word |= 1 << (high % usize::BITS as usize);
+
+        self.count += 1;
+        self.last_value = value;
+        Ok(())
+    }
+
+    fn push_low_word(&mut self, word: W) -> anyhow::Result<()> {
+        if self.low_buffer.len() == self.low_buffer_words {
+            self.flush_low()?;
+        }
+        self.low_buffer.push(word);
+        Ok(())
+    }
+
+    fn flush_low(&mut self) -> anyhow::Result<()> {
+        self.low_file
+            .write_all(bytemuck::cast_slice(&self.low_buffer))
+            .context("Cannot write lower bits")?;
+        self.low_len += self.low_buffer.len();
+        self.low_buffer.clear();
+        Ok(())
+    }
+
+    fn push_high_word(&mut self, word: usize) -> anyhow::Result<()> {
+        if self.high_buffer.len() == self.buffer_words {
+            self.flush_high(usize::MAX)?;
+        }
+        self.high_buffer.push(word);
+        Ok(())
+    }
+
+    /// Writes the buffered words of upper bits to the temporary file, and
+    /// passes them to the inventory builders.
+    ///
+    /// The complement of the last word is and-ed with `last_mask` before
+    /// passing it to the inventory builder for zeros.
+    fn flush_high(&mut self, last_mask: usize) -> anyhow::Result<()> {
+        self.high_file
+            .write_all(bytemuck::cast_slice(&self.high_buffer))
+            .context("Cannot write upper bits")?;
+        self.ones.push_words(self.high_len, &self.high_buffer)?;
+        self.high_buffer.iter_mut().for_each(|word| *word = !*word);
+        if let Some(word) = self.high_buffer.last_mut() {
+            *word &= last_mask;
+        }
+        self.zeros.push_words(self.high_len, &self.high_buffer)?;
+        self.high_len += self.high_buffer.len();
+        self.high_buffer.clear();
+        Ok(())
+    }
+
+    /// Writes to `output` the [ε-serde](epserde) serialization of an
+    /// [`EfSeqDict`] containing the values added to the builder.
+    ///
+    /// If fewer than `n` values have been added, the structure will contain
+    /// just the values added, as in the case of [`EliasFanoBuilder::build`].
+    ///
+    /// # Errors
+    ///
+    /// This method returns an error if an I/O error occurs.
+    pub fn build_with_seq_and_dict(mut self, output: impl Write) -> anyhow::Result<()>
+    where
+        W: ZeroCopy + TypeHash + ReprHash + ser::SerializeInner<SerType = W>,
+    {
+        let upper: usize = (self.u >> self.l).cast();
+        let num_bits = self.count + upper + 1;
+        let num_words = num_bits.div_ceil(usize::BITS as usize);
+
+        // Complete the upper bits
+        let word = self.high_word;
+        self.push_high_word(word)?;
+        while self.high_len + self.high_buffer.len() < num_words {
+            self.push_high_word(0)?;
+        }
+        self.flush_high(match num_bits % usize::BITS as usize {
+            0 => usize::MAX,
+            bits => (1 << bits) - 1,
+        })?;
+
+        // Complete the lower bits, using at least one word as BitFieldVec::new
+        if self.low_fill != 0 || self.low_len + self.low_buffer.len() == 0 {
+            let word = self.low_word;
+            self.push_low_word(word)?;
+        }
+        self.flush_low()?;
+
+        let (ones_inventory, ones_spill) = self.ones.finish(num_bits, self.count)?;
+        let (zeros_inventory, zeros_spill) = self.zeros.finish(num_bits, num_bits - self.count)?;
+        let (ones_inventory_len, ones_inventory) = ones_inventory;
+        let (ones_spill_len, ones_spill) = ones_spill;
+        let (zeros_inventory_len, zeros_inventory) = zeros_inventory;
+        let (zeros_spill_len, zeros_spill) = zeros_spill;
+
+        let rewind = |mut file: File| -> anyhow::Result<BufReader<File>> {
+            file.seek(SeekFrom::Start(0))?;
+            Ok(BufReader::new(file))
+        };
+        let low_len = self.low_len;
+        let low_file = rewind(self.low_file)?;
+        let high_len = self.high_len;
+        let high_file = rewind(self.high_file)?;
+
+        let error = RefCell::new(None);
+        fn streamed<T: bytemuck::Pod>(
+            len: usize,
+            mut file: BufReader<File>,
+            error: &RefCell<Option<anyhow::Error>>,
+        ) -> StreamedSlice<'_, T> {
+            StreamedSlice::new(len, move |push| copy_words(&mut file, len, push, error))
+        }
+        let low_bits = unsafe {
+            BitFieldVec::<W, _>::from_raw_parts(
+                streamed::<W>(low_len, low_file, &error),
+                self.l,
+                self.count,
+            )
+        };
+        let high_bits = unsafe {
+            BitVec::from_raw_parts(streamed::<usize>(high_len, high_file, &error), num_bits)
+        };
+        // SAFETY: the inventories and the spill buffers are built by
+        // InventoryBuilder, which follows the layout of the constructors
+        let high_bits = unsafe {
+            SelectZeroAdaptConst::<_, _, 12, 3>::from_raw_parts(
+                SelectAdaptConst::<_, _, 12, 3>::from_raw_parts(
+                    high_bits,
+                    streamed::<usize>(ones_inventory_len, ones_inventory, &error),
+                    streamed::<usize>(ones_spill_len, ones_spill, &error),
+                ),
+                streamed::<usize>(zeros_inventory_len, zeros_inventory, &error),
+                streamed::<usize>(zeros_spill_len, zeros_spill, &error),
+            )
+        };
+        let ef = EliasFano {
+            n: self.count,
+            u: self.u,
+            l: self.l,
+            low_bits,
+            high_bits,
+        };
+
+        let result = ef.serialize(&mut BufWriter::new(output));
+        drop(ef);
+        if let Some(e) = error.into_inner() {
+            return Err(e);
+        }
+        result.context("Cannot serialize Elias-Fano structure")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,405 @@
+/*
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Set operations on Elias–Fano sequences.
+//!
+//! This module provides the k-way intersection ([`EfIntersection`]) and union
+//! ([`EfUnion`]) of [`EliasFano`] sequences, and the difference
+//! ([`EfDifference`]) of two [`EliasFano`] sequences. Sequences are treated
+//! as sets: repeated values are returned once.
+//!
+//! All operations are iterators returning values in increasing order, and
+//! they can be turned into an [`EliasFanoBuilder`] sized using an upper
+//! bound on the number of values of the result using the `into_builder`
+//! method, so that the result can be stored directly in a new [`EliasFano`].
+//!
+//! Intersections and unions accept sequences through the object-safe traits
+//! [`EfOperand`] and [`EfSuccOperand`], which are implemented by all
+//! [`EliasFano`] sequences of `usize` values, whatever their high-bits and
+//! low-bits types. Sequences of the same type can be passed directly; to
+//! combine sequences of different types, for example an
+//! [`EfDict`](crate::dict::elias_fano::EfDict) and an
+//! [`EfSeqDict`](crate::dict::elias_fano::EfSeqDict), pass them as trait
+//! objects. The operations using [successor](SuccUnchecked) queries
+//! (intersection, and the subtrahend of a difference) need a selection
+//! structure on zeros, as in
+//! [`EfDict`](crate::dict::elias_fano::EfDict) or
+//! [`EfSeqDict`](crate::dict::elias_fano::EfSeqDict).
+//!
+//! # Examples
+//!
+//! ```rust
+//! # use sux::prelude::*;
+//! # use sux::dict::elias_fano::{EfDict, EfSeqDict};
+//! let mut efb = EliasFanoBuilder::new(5, 100);
+//! efb.extend([1, 5, 10, 50, 100]);
+//! let a = efb.build_with_dict();
+//! let mut efb = EliasFanoBuilder::new(4, 100);
+//! efb.extend([5, 6, 50, 100]);
+//! let b = efb.build_with_dict();
+//!
+//! assert_eq!(EfIntersection::new([&a, &b]).collect::<Vec<_>>(), vec![5, 50, 100]);
+//! assert_eq!(EfUnion::new([&a, &b]).collect::<Vec<_>>(), vec![1, 5, 6, 10, 50, 100]);
+//! assert_eq!(EfDifference::new(&a, &b).collect::<Vec<_>>(), vec![1, 10]);
+//!
+//! // Store directly the result in a new structure
+//! let c: EfSeqDict = EfIntersection::new([&a, &b]).into_builder().build_with_seq_and_dict();
+//! assert_eq!(c.len(), 3);
+//! assert_eq!(c.get(1), 50);
+//!
+//! // Sequences of different types are passed as trait objects
+//! let mut efb = EliasFanoBuilder::new(2, 50);
+//! efb.extend([5, 50]);
+//! let d = efb.build_with_seq_and_dict();
+//! assert_eq!(
+//!     EfIntersection::new([&a as &dyn EfSuccOperand, &d]).collect::<Vec<_>>(),
+//!     vec![5, 50]
+//! );
+//! ```
+
+use crate::dict::elias_fano::EliasFanoIterator;
+use crate::prelude::*;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// An object-safe view of an [`EliasFano`] sequence of `usize` values used by
+/// [`EfUnion`].
+///
+/// This trait is implemented by all [`EliasFano`] sequences of `usize`
+/// values, whatever their high-bits and low-bits types, so that sequences of
+/// different types can be combined as trait objects.
+pub trait EfOperand {
+    /// Returns the number of values in the sequence.
+    fn len(&self) -> usize;
+
+    /// Returns true if the sequence is empty.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the upper bound of the sequence.
+    fn upper_bound(&self) -> usize;
+
+    /// Returns a boxed iterator on the values of the sequence.
+    fn boxed_iter(&self) -> Box<dyn Iterator<Item = usize> + '_>;
+}
+
+/// An object-safe view of an [`EliasFano`] sequence of `usize` values with a
+/// selection structure on zeros used by [`EfIntersection`].
+///
+/// This trait is implemented by all [`EliasFano`] sequences of `usize`
+/// values whose high bits implement [`SelectZeroUnchecked`], so that, for
+/// example, an [`EfDict`](crate::dict::elias_fano::EfDict) and an
+/// [`EfSeqDict`](crate::dict::elias_fano::EfSeqDict) can be intersected.
+pub trait EfSuccOperand: EfOperand {
+    /// Returns the last value of the sequence, or `None` if the sequence is
+    /// empty.
+    fn last(&self) -> Option<usize>;
+
+    /// Returns the first value of the sequence greater than or equal to
+    /// `value`.
+    ///
+    /// # Safety
+    ///
+    /// `value` must be at most the last value of the sequence.
+    unsafe fn succ_value_unchecked(&self, value: usize) -> usize;
+}
+
+impl<H: AsRef<[usize]>, L: BitFieldSlice<usize>> EfOperand for EliasFano<usize, H, L>
+where
+    for<'b> &'b L: IntoUncheckedIterator<Item = usize>,
+{
+    fn len(&self) -> usize {
+        EliasFano::len(self)
+    }
+
+    fn upper_bound(&self) -> usize {
+        EliasFano::upper_bound(self)
+    }
+
+    fn boxed_iter(&self) -> Box<dyn Iterator<Item = usize> + '_> {
+        Box::new(self.iter())
+    }
+}
+
+impl<H: AsRef<[usize]> + SelectZeroUnchecked, L: BitFieldSlice<usize>> EfSuccOperand
+    for EliasFano<usize, H, L>
+where
+    for<'b> &'b L: IntoUncheckedIterator<Item = usize> + IntoReverseUncheckedIterator<Item = usize>,
+{
+    fn last(&self) -> Option<usize> {
+        if EliasFano::len(self) == 0 {
+            None
+        } else {
+            // SAFETY: the sequence is not empty
+            Some(unsafe { self.pred_unchecked::<false>(usize::MAX).1 })
+        }
+    }
+
+    unsafe fn succ_value_unchecked(&self, value: usize) -> usize {
+        unsafe { self.succ_unchecked::<false>(value).1 }
+    }
+}
+
+/// The k-way intersection of [`EliasFano`] sequences.
+///
+/// The intersection is computed by skipping: a candidate value is checked
+/// against each sequence in turn by a [successor](SuccUnchecked) query, which
+/// uses the selection structure on zeros to jump directly to the bucket of
+/// the candidate, and whenever the successor is larger it becomes the new
+/// candidate. Sequences are sorted by increasing length, so that candidates
+/// are generated by the shortest sequence.
+///
+/// The type parameter `S` is the type of the sequences: sequences of
+/// different types can be intersected by passing them as `&dyn`
+/// [`EfSuccOperand`].
+///
+/// The intersection of an empty set of sequences is empty.
+pub struct EfIntersection<'a, S: ?Sized> {
+    /// The sequences, sorted by increasing length.
+    lists: Vec<&'a S>,
+    /// The smallest last value of a sequence.
+    max: usize,
+    /// The next candidate, or `None` if the iterator is exhausted.
+    next: Option<usize>,
+}
+
+impl<'a, S: EfSuccOperand + ?Sized> EfIntersection<'a, S> {
+    /// Creates the intersection of the given sequences.
+    pub fn new(lists: impl IntoIterator<Item = &'a S>) -> Self {
+        let mut lists = lists.into_iter().collect::<Vec<_>>();
+        lists.sort_by_key(|list| list.len());
+        let max = lists.iter().map(|list| list.last()).min().flatten();
+        Self {
+            lists,
+            max: max.unwrap_or(0),
+            next: max.map(|_| 0),
+        }
+    }
+
+    /// Returns a builder containing the remaining values of the intersection.
+    ///
+    /// The builder is sized using the length of the shortest sequence, and
+    /// its upper bound is the smallest last value of a sequence.
+    pub fn into_builder(self) -> EliasFanoBuilder {
+        let n = self.lists.first().map_or(0, |list| list.len());
+        let mut builder = EliasFanoBuilder::new(n, self.max);
+        builder.extend(self);
+        builder
+    }
+}
+
+impl<S: EfSuccOperand + ?Sized> Iterator for EfIntersection<'_, S> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let mut candidate = self.next?;
+        let k = self.lists.len();
+        // The number of consecutive sequences containing the candidate
+        let mut agreeing = 0;
+        let mut i = 0;
+        while agreeing < k {
+            if candidate > self.max {
+                self.next = None;
+                return None;
+            }
+            // SAFETY: the candidate is at most the last value of each sequence
+            let succ = unsafe { self.lists[i].succ_value_unchecked(candidate) };
+            if succ == candidate {
+                agreeing += 1;
+            } else {
+                candidate = succ;
+                agreeing = 1;
+            }
+            i = (i + 1) % k;
+        }
+        self.next = candidate.checked_add(1);
+        Some(candidate)
+    }
+}
+
+/// The k-way union of [`EliasFano`] sequences.
+///
+/// The union is computed by merging iterators on the sequences using a heap,
+/// so no selection structure is needed. Sequences of different types can be
+/// merged by passing them as `&dyn` [`EfOperand`].
+pub struct EfUnion<'a> {
+    /// An iterator for each sequence.
+    iters: Vec<Box<dyn Iterator<Item = usize> + 'a>>,
+    /// The next value of each iterator, paired with the index of the iterator.
+    heap: BinaryHeap<Reverse<(usize, usize)>>,
+    /// The sum of the lengths of the sequences.
+    max_len: usize,
+    /// The largest upper bound of a sequence.
+    upper_bound: usize,
+}
+
+impl<'a> EfUnion<'a> {
+    /// Creates the union of the given sequences.
+    pub fn new<S: EfOperand + ?Sized + 'a>(lists: impl IntoIterator<Item = &'a S>) -> Self {
+        let mut iters = vec![];
+        let mut heap = BinaryHeap::new();
+        let mut max_len = 0;
+        let mut upper_bound = 0;
+        for (i, list) in lists.into_iter().enumerate() {
+            max_len += list.len();
+            upper_bound = upper_bound.max(list.upper_bound());
+            let mut iter = list.boxed_iter();
+            if let Some(value) = iter.next() {
+                heap.push(Reverse((value, i)));
+            }
+            iters.push(iter);
+        }
+        Self {
+            iters,
+            heap,
+            max_len,
+            upper_bound,
+        }
+    }
+
+    /// Returns a builder containing the remaining values of the union.
+    ///
+    /// The builder is sized using the sum of the lengths of the sequences,
+    /// and its upper bound is the largest upper bound of a sequence.
+    pub fn into_builder(self) -> EliasFanoBuilder {
+        let mut builder = EliasFanoBuilder::new(self.max_len, self.upper_bound);
+        builder.extend(self);
+        builder
+    }
+}
+
+impl Iterator for EfUnion<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let Reverse((value, i)) = self.heap.pop()?;
+        if let Some(next) = self.iters[i].next() {
+            self.heap.push(Reverse((next, i)));
+        }
+        // Skip the occurrences of the value in all sequences
+        while let Some(&Reverse((next, i))) = self.heap.peek() {
+            if next != value {
+                break;
+            }
+            self.heap.pop();
+            if let Some(next) = self.iters[i].next() {
+                self.heap.push(Reverse((next, i)));
+            }
+        }
+        Some(value)
+    }
+}
+
+/// The difference of two [`EliasFano`] sequences.
+///
+/// The values of the first sequence are enumerated using an
+/// [iterator](EliasFanoIterator), and excluded if they appear in the second
+/// sequence. The successor in the second sequence of the last value checked
+/// is cached, so [successor](SuccUnchecked) queries are performed only when a
+/// value of the first sequence is larger than the cached successor. Thus,
+/// only the second sequence needs a selection structure on zeros.
+pub struct EfDifference<'a, H1: AsRef<[usize]>, L1: BitFieldSlice<usize>, H2, L2>
+where
+    for<'b> &'b L1: IntoUncheckedIterator<Item = usize>,
+{
+    /// The iterator on the first sequence.
+    iter: EliasFanoIterator<'a, usize, H1, L1>,
+    /// The second sequence.
+    other: &'a EliasFano<usize, H2, L2>,
+    /// The last value of the second sequence, or `None` if it is empty.
+    other_max: Option<usize>,
+    /// The successor in the second sequence of the last value checked.
+    other_succ: usize,
+    /// The last value returned by the iterator on the first sequence.
+    last: Option<usize>,
+    /// The length of the first sequence.
+    max_len: usize,
+    /// The upper bound of the first sequence.
+    upper_bound: usize,
+}
+
+impl<
+        'a,
+        H1: AsRef<[usize]>,
+        L1: BitFieldSlice<usize>,
+        H2: AsRef<[usize]> + SelectZeroUnchecked,
+        L2: BitFieldSlice<usize>,
+    > EfDifference<'a, H1, L1, H2, L2>
+where
+    for<'b> &'b L1: IntoUncheckedIterator<Item = usize>,
+    for<'b> &'b L2:
+        IntoUncheckedIterator<Item = usize> + IntoReverseUncheckedIterator<Item = usize>,
+{
+    /// Creates the difference between the values of `list` and the values of
+    /// `other`.
+    pub fn new(list: &'a EliasFano<usize, H1, L1>, other: &'a EliasFano<usize, H2, L2>) -> Self {
+        let (other_max, other_succ) = if other.len() == 0 {
+            (None, 0)
+        } else {
+            // SAFETY: the sequence is not empty
+            unsafe {
+                (
+                    Some(other.pred_unchecked::<false>(usize::MAX).1),
+                    other.succ_unchecked::<false>(0).1,
+                )
+            }
+        };
+        Self {
+            iter: list.iter(),
+            other,
+            other_max,
+            other_succ,
+            last: None,
+            max_len: list.len(),
+            upper_bound: list.upper_bound(),
+        }
+    }
+
+    /// Returns a builder containing the remaining values of the difference.
+    ///
+    /// The builder is sized using the length of the first sequence, and its
+    /// upper bound is the upper bound of the first sequence.
+    pub fn into_builder(self) -> EliasFanoBuilder {
+        let mut builder = EliasFanoBuilder::new(self.max_len, self.upper_bound);
+        builder.extend(self);
+        builder
+    }
+}
+
+impl<
+        H1: AsRef<[usize]>,
+        L1: BitFieldSlice<usize>,
+        H2: AsRef<[usize]> + SelectZeroUnchecked,
+        L2: BitFieldSlice<usize>,
+    > Iterator for EfDifference<'_, H1, L1, H2, L2>
+where
+    for<'b> &'b L1: IntoUncheckedIterator<Item = usize>,
+    for<'b> &'b L2:
+        IntoUncheckedIterator<Item = usize> + IntoReverseUncheckedIterator<Item = usize>,
+{
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        loop {
+            let value = self.iter.next()?;
+            if self.last == Some(value) {
+                continue;
+            }
+            self.last = Some(value);
+            if self.other_max.is_some_and(|max| value <= max) {
+                if value > self.other_succ {
+                    // SAFETY: the value is at most the last value
+                    self.other_succ = unsafe { self.other.succ_unchecked::<false>(value).1 };
+                }
+                if value == self.other_succ {
+                    continue;
+                }
+            }
+            return Some(value);
+        }
+    }
+}
@@ -8,10 +8,22 @@
 //! Indexed dictionaries.
 
 pub mod elias_fano;
-pub use elias_fano::{EliasFano, EliasFanoBuilder, EliasFanoConcurrentBuilder};
+pub use elias_fano::{
+    EfLenderError, EliasFano, EliasFanoBuilder, EliasFanoConcurrentBuilder, EliasFanoCursor,
+    EliasFanoStreamBuilder,
+};
+
+pub mod elias_fano_ops;
+pub use elias_fano_ops::{EfDifference, EfIntersection, EfOperand, EfSuccOperand, EfUnion};
+
+pub mod partitioned_elias_fano;
+pub use partitioned_elias_fano::{PartitionedEliasFano, PartitionedEliasFanoBuilder};
 
 pub mod rear_coded_list;
 pub use rear_coded_list::{RearCodedList, RearCodedListBuilder};
 
+pub mod signed_elias_fano;
+pub use signed_elias_fano::{SignedEliasFano, SignedEliasFanoBuilder};
+
 pub mod slice_seq;
-pub use slice_seq::SliceSeq;
+pub use slice_seq::{BitFieldSeq, SliceSeq};
@@ -0,0 +1,907 @@
+/*
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Partitioned Elias–Fano representation of monotone sequences.
+//!
+//! The [Elias–Fano representation](crate::dict::elias_fano) uses a single
+//! split between lower and upper bits, which depends only on the number of
+//! elements and on the upper bound of the sequence. Sequences with a
+//! clustered distribution (e.g., posting lists, or positions of ones in a
+//! sparse bit vector with dense regions) can be represented much more
+//! compactly by partitioning them in *chunks*, each represented independently
+//! as an Elias–Fano sequence, as a dense bitmap, or, if the chunk contains a
+//! run of consecutive integers, implicitly.
+//!
+//! The partition is computed by the approximation algorithm described by
+//! Giuseppe Ottaviano and Rossano Venturini in “[Partitioned Elias–Fano
+//! indexes](https://doi.org/10.1145/2600428.2609615)”, *Proceedings of the
+//! 37th International ACM SIGIR Conference on Research & Development in
+//! Information Retrieval*, pages 273–282, ACM, 2014, which finds in linear
+//! time a partition whose cost is within a factor (1 + ε) of the optimal one.
+//!
+//! Differently from the original proposal, sequences may contain repeated
+//! values, in which case chunks with repeated values are always represented
+//! as Elias–Fano sequences.
+
+use crate::dict::elias_fano::EfSeqDict;
+use crate::prelude::*;
+use crate::traits::IntoIteratorFrom;
+use crate::utils::word_ops::select_in_word;
+use epserde::*;
+use mem_dbg::*;
+use std::borrow::Borrow;
+
+const BITS: usize = usize::BITS as usize;
+
+/// An estimate of the cost in bits of the endpoints of a chunk in the
+/// top-level structures.
+const FIX_COST: usize = 64;
+/// The approximation on the cost of the fixed part of each chunk: windows
+/// are not extended past chunks of cost [`FIX_COST`] / ε₁.
+const EPS1: f64 = 0.03;
+/// The approximation on the cost of the chunks: there is a window for each
+/// cost bound (1 + ε₂)ᵏ · [`FIX_COST`].
+const EPS2: f64 = 0.3;
+
+/// A monotone sequence of integers stored using a partitioned Elias–Fano
+/// representation.
+///
+/// The sequence is divided into chunks, and each chunk stores the difference
+/// between its values and the last value of the previous chunk (or zero, for
+/// the first chunk) using the cheapest representation among:
+///
+/// - an Elias–Fano representation with upper bound the last value of the
+///   chunk;
+/// - a dense bitmap in which the bit of index *x* is set for each value *x*;
+/// - nothing at all, if the chunk is a run of consecutive integers.
+///
+/// The last value of each chunk is stored in a top-level [`EliasFano`]
+/// structure, which is used to locate the chunk containing a value;
+/// similarly, the index of the first value of each chunk and the offset of
+/// the chunk in the concatenated chunk data are stored in two further
+/// [`EliasFano`] structures. All three structures are of type `E`, and the
+/// concatenated chunk data is of type `D`, so instances can be serialized and
+/// deserialized with ε-serde.
+///
+/// The structure implements [`IndexedSeq`], [`IndexedDict`], [`Succ`], and
+/// [`Pred`]. [`iter`](PartitionedEliasFano::iter) and
+/// [`iter_from`](PartitionedEliasFano::iter_from) decode sequentially the
+/// chunks, and are much faster than repeated calls to
+/// [`get`](IndexedSeq::get).
+///
+/// Instances can be built using a [`PartitionedEliasFanoBuilder`], or from a
+/// slice using the [`From`] implementation.
+///
+/// # Examples
+///
+/// ```rust
+/// # use sux::prelude::*;
+/// let mut pefb = PartitionedEliasFanoBuilder::new();
+/// pefb.extend(0..1000);
+/// pefb.extend((0..100).map(|x| 1_000_000 + x * 1000));
+/// let pef = pefb.build();
+///
+/// assert_eq!(pef.len(), 1100);
+/// assert_eq!(pef.get(10), 10);
+/// assert_eq!(pef.get(1001), 1_001_000);
+/// assert_eq!(pef.index_of(1_002_000), Some(1002));
+/// assert_eq!(pef.succ(1000), Some((1000, 1_000_000)));
+/// assert_eq!(pef.pred(500_000), Some((999, 999)));
+/// assert_eq!(pef.iter_from(999).take(2).collect::<Vec<_>>(), vec![999, 1_000_000]);
+///
+/// // Convenience constructor from a slice
+/// let pef: PartitionedEliasFano = vec![0, 1, 2, 3, 100].into();
+/// assert_eq!(pef.iter().collect::<Vec<_>>(), vec![0, 1, 2, 3, 100]);
+/// ```
+#[derive(Epserde, Debug, Clone, Hash, MemDbg, MemSize)]
+pub struct PartitionedEliasFano<E = EfSeqDict, D = Box<[usize]>> {
+    /// The number of values.
+    n: usize,
+    /// The last value of each chunk.
+    upper: E,
+    /// The index of the first value of each chunk, followed by `n`.
+    starts: E,
+    /// The offset in bits of each chunk in `data`, followed by the length
+    /// in bits of `data`.
+    offsets: E,
+    /// The concatenated chunks.
+    data: D,
+}
+
+impl<E, D> PartitionedEliasFano<E, D> {
+    /// Returns the number elements in the sequence.
+    ///
+    /// This method is equivalent to [`IndexedSeq::len`], but it is provided to
+    /// reduce ambiguity in method resolution.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.n
+    }
+}
+
+/// The representation of a chunk.
+#[derive(Debug, Clone, Copy)]
+enum ChunkKind {
+    /// An Elias–Fano representation with the given number of lower bits:
+    /// the lower bits are followed by the upper bits.
+    EliasFano { l: usize },
+    /// A dense bitmap.
+    Bitmap,
+    /// A run of consecutive integers.
+    Run,
+}
+
+/// The decoding information of a chunk.
+#[derive(Debug, Clone, Copy)]
+struct Chunk {
+    /// The index of the first value of the chunk.
+    start: usize,
+    /// The number of values in the chunk.
+    len: usize,
+    /// The value subtracted from the values of the chunk.
+    lower: usize,
+    /// The last value of the chunk minus `lower`.
+    u: usize,
+    /// The offset in bits of the data of the chunk, after the tag bit.
+    offset: usize,
+    kind: ChunkKind,
+}
+
+/// Returns the number of lower bits of an Elias–Fano chunk with `n` values
+/// and upper bound `u`.
+#[inline(always)]
+fn low_bits(n: usize, u: usize) -> usize {
+    if u >= n {
+        (u / n).ilog2() as usize
+    } else {
+        0
+    }
+}
+
+/// Returns the representation and the number of bits of a chunk with `n`
+/// values and upper bound `u`.
+///
+/// A chunk can be a dense bitmap only if it is `strict`ly increasing, and it
+/// can be implicit only if it is a `run` of consecutive integers. Except for
+/// runs, the chunk data starts with a tag bit that is set for bitmaps.
+fn encoding(n: usize, u: usize, strict: bool, run: bool) -> (ChunkKind, usize) {
+    if run {
+        return (ChunkKind::Run, 0);
+    }
+    let l = low_bits(n, u);
+    let ef_bits = 2 + n * (l + 1) + (u >> l);
+    if strict && u.saturating_add(2) < ef_bits {
+        (ChunkKind::Bitmap, u + 2)
+    } else {
+        (ChunkKind::EliasFano { l }, ef_bits)
+    }
+}
+
+/// A window of the approximate dynamic program: a chunk starting at the
+/// current position and ending at `end`, whose cost is extended until it
+/// exceeds `bound`.
+struct Window {
+    /// The end of the chunk (exclusive).
+    end: usize,
+    /// The number of values of the chunk equal to the previous one.
+    dups: usize,
+    /// The cost bound of the window.
+    bound: usize,
+}
+
+impl Window {
+    fn cost(&self, values: &[usize], start: usize) -> usize {
+        let n = self.end - start;
+        let lower = if start == 0 { 0 } else { values[start - 1] };
+        let last = values[self.end - 1];
+        let strict = self.dups == 0;
+        let run = strict && last - values[start] == n - 1;
+        encoding(n, last - lower, strict, run).1 + FIX_COST
+    }
+
+    fn advance_end(&mut self, values: &[usize], start: usize) {
+        if self.end > start && values[self.end] == values[self.end - 1] {
+            self.dups += 1;
+        }
+        self.end += 1;
+    }
+
+    fn advance_start(&mut self, values: &[usize], start: usize) {
+        if self.end > start + 1 && values[start + 1] == values[start] {
+            self.dups -= 1;
+        }
+    }
+}
+
+/// Computes an approximately optimal partition of a monotone sequence,
+/// returning the end (exclusive) of each chunk.
+fn partition(values: &[usize]) -> Vec<usize> {
+    let n = values.len();
+    if n == 0 {
+        return vec![];
+    }
+    let single_chunk_cost = Window {
+        end: n,
+        dups: values.windows(2).filter(|w| w[0] == w[1]).count(),
+        bound: 0,
+    }
+    .cost(values, 0);
+
+    let mut windows = vec![];
+    let mut bound = FIX_COST as f64;
+    while bound < FIX_COST as f64 / EPS1 {
+        windows.push(Window {
+            end: 0,
+            dups: 0,
+            bound: bound as usize,
+        });
+        if bound as usize >= single_chunk_cost {
+            break;
+        }
+        bound *= 1.0 + EPS2;
+    }
+
+    // min_cost[i] is the cost of the best partition of the first i values
+    // found so far, and path[i] the start of its last chunk
+    let mut min_cost = vec![usize::MAX; n + 1];
+    let mut path = vec![0; n + 1];
+    min_cost[0] = 0;
+
+    for start in 0..n {
+        let mut last_end = start + 1;
+        for window in &mut windows {
+            while window.end < last_end {
+                window.advance_end(values, start);
+            }
+            loop {
+                let cost = window.cost(values, start);
+                if min_cost[start] + cost < min_cost[window.end] {
+                    min_cost[window.end] = min_cost[start] + cost;
+                    path[window.end] = start;
+                }
+                last_end = window.end;
+                if window.end == n || cost >= window.bound {
+                    break;
+                }
+                window.advance_end(values, start);
+            }
+            window.advance_start(values, start);
+        }
+    }
+
+    let mut ends = vec![];
+    let mut pos = n;
+    while pos != 0 {
+        ends.push(pos);
+        pos = path[pos];
+    }
+    ends.reverse();
+    ends
+}
+
+/// Returns whether the bit of given index is set.
+#[inline(always)]
+fn get_bit(data: &[usize], pos: usize) -> bool {
+    data[pos / BITS] & (1 << (pos % BITS)) != 0
+}
+
+/// Sets the bit of given index.
+#[inline(always)]
+fn set_bit(data: &mut [usize], pos: usize) {
+    data[pos / BITS] |= 1 << (pos % BITS);
+}
+
+/// Reads `width` bits starting at the given position.
+#[inline(always)]
+fn read_bits(data: &[usize], pos: usize, width: usize) -> usize {
+    if width == 0 {
+        return 0;
+    }
+    let word_idx = pos / BITS;
+    let bit_idx = pos % BITS;
+    let mut value = data[word_idx] >> bit_idx;
+    if bit_idx + width > BITS {
+        value |= data[word_idx + 1] << (BITS - bit_idx);
+    }
+    value & (usize::MAX >> (BITS - width))
+}
+
+/// Writes `width` bits starting at the given position, assuming they are
+/// currently zero.
+#[inline(always)]
+fn write_bits(data: &mut [usize], pos: usize, value: usize, width: usize) {
+    if width == 0 {
+        return;
+    }
+    let word_idx = pos / BITS;
+    let bit_idx = pos % BITS;
+    data[word_idx] |= value << bit_idx;
+    if bit_idx + width > BITS {
+        data[word_idx + 1] |= value >> (BITS - bit_idx);
+    }
+}
+
+/// Returns the position of the one of given rank among the bits starting at
+/// `from`.
+#[inline(always)]
+fn select(data: &[usize], from: usize, mut rank: usize) -> usize {
+    let mut word_idx = from / BITS;
+    let mut word = data[word_idx] & (usize::MAX << (from % BITS));
+    loop {
+        let ones = word.count_ones() as usize;
+        if rank < ones {
+            return word_idx * BITS + select_in_word(word, rank);
+        }
+        rank -= ones;
+        word_idx += 1;
+        word = data[word_idx];
+    }
+}
+
+/// Returns the position of the zero of given rank among the bits starting at
+/// `from`.
+#[inline(always)]
+fn select_zero(data: &[usize], from: usize, mut rank: usize) -> usize {
+    let mut word_idx = from / BITS;
+    let mut word = !data[word_idx] & (usize::MAX << (from % BITS));
+    loop {
+        let zeros = word.count_ones() as usize;
+        if rank < zeros {
+            return word_idx * BITS + select_in_word(word, rank);
+        }
+        rank -= zeros;
+        word_idx += 1;
+        word = !data[word_idx];
+    }
+}
+
+/// Returns the position of the first one at or after `from`.
+#[inline(always)]
+fn next_one(data: &[usize], from: usize) -> usize {
+    let mut word_idx = from / BITS;
+    let mut word = data[word_idx] & (usize::MAX << (from % BITS));
+    while word == 0 {
+        word_idx += 1;
+        word = data[word_idx];
+    }
+    word_idx * BITS + word.trailing_zeros() as usize
+}
+
+/// Returns the number of ones in the bits in `from..to`.
+#[inline(always)]
+fn count_ones(data: &[usize], from: usize, to: usize) -> usize {
+    if from == to {
+        return 0;
+    }
+    let first = from / BITS;
+    let last = (to - 1) / BITS;
+    let mut count = 0;
+    for (i, &word) in data[first..=last].iter().enumerate() {
+        let mut word = word;
+        if i == 0 {
+            word &= usize::MAX << (from % BITS);
+        }
+        if first + i == last {
+            word &= usize::MAX >> (BITS - 1 - (to - 1) % BITS);
+        }
+        count += word.count_ones() as usize;
+    }
+    count
+}
+
+impl Chunk {
+    /// Returns the value of given index in the chunk.
+    #[inline(always)]
+    fn get(&self, data: &[usize], index: usize) -> usize {
+        self.lower
+            + match self.kind {
+                ChunkKind::Run => self.u - (self.len - 1) + index,
+                ChunkKind::Bitmap => select(data, self.offset, index) - self.offset,
+                ChunkKind::EliasFano { l } => {
+                    let high_start = self.offset + self.len * l;
+                    let high = select(data, high_start, index) - high_start - index;
+                    (high << l) | read_bits(data, self.offset + index * l, l)
+                }
+            }
+    }
+
+    /// Returns the index and the value of the first value of the chunk
+    /// greater than or equal to `value`, which must exist and must be
+    /// greater than or equal to `lower`.
+    #[inline(always)]
+    fn first_geq(&self, data: &[usize], value: usize) -> (usize, usize) {
+        let r = value - self.lower;
+        let (index, r) = match self.kind {
+            ChunkKind::Run => {
+                let first = self.u - (self.len - 1);
+                if r <= first {
+                    (0, first)
+                } else {
+                    (r - first, r)
+                }
+            }
+            ChunkKind::Bitmap => {
+                let pos = next_one(data, self.offset + r);
+                (
+                    count_ones(data, self.offset, self.offset + r),
+                    pos - self.offset,
+                )
+            }
+            ChunkKind::EliasFano { l } => {
+                let high_start = self.offset + self.len * l;
+                let zeros_to_skip = r >> l;
+                let mut pos = if zeros_to_skip == 0 {
+                    high_start
+                } else {
+                    select_zero(data, high_start, zeros_to_skip - 1) + 1
+                };
+                let mut index = pos - high_start - zeros_to_skip;
+                loop {
+                    pos = next_one(data, pos);
+                    let res = ((pos - high_start - index) << l)
+                        | read_bits(data, self.offset + index * l, l);
+                    if res >= r {
+                        break (index, res);
+                    }
+                    pos += 1;
+                    index += 1;
+                }
+            }
+        };
+        (self.start + index, self.lower + r)
+    }
+}
+
+impl<
+        E: Succ<Input = usize, Output = usize> + Pred<Input = usize, Output = usize>,
+        D: AsRef<[usize]>,
+    > PartitionedEliasFano<E, D>
+{
+    /// Returns the number of chunks.
+    pub fn num_chunks(&self) -> usize {
+        self.upper.len()
+    }
+
+    /// Returns the decoding information of a chunk.
+    ///
+    /// # Safety
+    ///
+    /// `chunk` must be smaller than the number of chunks.
+    #[inline(always)]
+    unsafe fn chunk(&self, chunk: usize) -> Chunk {
+        let start = self.starts.get_unchecked(chunk);
+        let len = self.starts.get_unchecked(chunk + 1) - start;
+        let lower = if chunk == 0 {
+            0
+        } else {
+            self.upper.get_unchecked(chunk - 1)
+        };
+        let u = self.upper.get_unchecked(chunk) - lower;
+        let offset = self.offsets.get_unchecked(chunk);
+        let kind = if offset == self.offsets.get_unchecked(chunk + 1) {
+            ChunkKind::Run
+        } else if get_bit(self.data.as_ref(), offset) {
+            ChunkKind::Bitmap
+        } else {
+            ChunkKind::EliasFano {
+                l: low_bits(len, u),
+            }
+        };
+        Chunk {
+            start,
+            len,
+            lower,
+            u,
+            offset: offset + 1,
+            kind,
+        }
+    }
+
+    /// Returns the index and the value of the first value greater than or
+    /// equal to `value`, if any.
+    #[inline(always)]
+    fn first_geq(&self, value: usize) -> Option<(usize, usize)> {
+        let num_chunks = self.upper.len();
+        if num_chunks == 0 || value > unsafe { self.upper.get_unchecked(num_chunks - 1) } {
+            return None;
+        }
+        unsafe {
+            let (chunk, _) = self.upper.succ_unchecked::<false>(value);
+            Some(self.chunk(chunk).first_geq(self.data.as_ref(), value))
+        }
+    }
+
+    /// Returns an iterator over the values of the sequence.
+    #[inline(always)]
+    pub fn iter(&self) -> PartitionedEliasFanoIterator<'_, E, D> {
+        PartitionedEliasFanoIterator::new_from(self, 0)
+    }
+
+    /// Returns an iterator over the values of the sequence starting at the
+    /// given index.
+    ///
+    /// # Panics
+    ///
+    /// If `from` is greater than the length of the sequence.
+    #[inline(always)]
+    pub fn iter_from(&self, from: usize) -> PartitionedEliasFanoIterator<'_, E, D> {
+        PartitionedEliasFanoIterator::new_from(self, from)
+    }
+}
+
+impl<E, D> Types for PartitionedEliasFano<E, D> {
+    type Input = usize;
+    type Output = usize;
+}
+
+impl<
+        E: Succ<Input = usize, Output = usize> + Pred<Input = usize, Output = usize>,
+        D: AsRef<[usize]>,
+    > IndexedSeq for PartitionedEliasFano<E, D>
+{
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.n
+    }
+
+    #[inline(always)]
+    unsafe fn get_unchecked(&self, index: usize) -> usize {
+        let (chunk, start) = self.starts.pred_unchecked::<false>(index);
+        self.chunk(chunk).get(self.data.as_ref(), index - start)
+    }
+}
+
+impl<
+        E: Succ<Input = usize, Output = usize> + Pred<Input = usize, Output = usize>,
+        D: AsRef<[usize]>,
+    > IndexedDict for PartitionedEliasFano<E, D>
+{
+    fn index_of(&self, value: impl Borrow<Self::Input>) -> Option<usize> {
+        let value = *value.borrow();
+        self.first_geq(value)
+            .filter(|&(_, res)| res == value)
+            .map(|(index, _)| index)
+    }
+}
+
+impl<
+        E: Succ<Input = usize, Output = usize> + Pred<Input = usize, Output = usize>,
+        D: AsRef<[usize]>,
+    > SuccUnchecked for PartitionedEliasFano<E, D>
+{
+    unsafe fn succ_unchecked<const STRICT: bool>(
+        &self,
+        value: impl Borrow<Self::Input>,
+    ) -> (usize, Self::Output) {
+        let value = *value.borrow();
+        // Since the successor exists, value + 1 cannot overflow if STRICT
+        let value = if STRICT { value + 1 } else { value };
+        self.first_geq(value).unwrap_unchecked()
+    }
+}
+
+impl<
+        E: Succ<Input = usize, Output = usize> + Pred<Input = usize, Output = usize>,
+        D: AsRef<[usize]>,
+    > Succ for PartitionedEliasFano<E, D>
+{
+}
+
+impl<
+        E: Succ<Input = usize, Output = usize> + Pred<Input = usize, Output = usize>,
+        D: AsRef<[usize]>,
+    > PredUnchecked for PartitionedEliasFano<E, D>
+{
+    unsafe fn pred_unchecked<const STRICT: bool>(
+        &self,
+        value: impl Borrow<Self::Input>,
+    ) -> (usize, Self::Output) {
+        let value = *value.borrow();
+        // The predecessor precedes the first value that is not smaller than
+        // (or equal to, if not STRICT) the argument
+        let next = if STRICT {
+            self.first_geq(value)
+        } else {
+            value.checked_add(1).and_then(|value| self.first_geq(value))
+        };
+        let index = next.map_or(self.n, |(index, _)| index) - 1;
+        (index, self.get_unchecked(index))
+    }
+}
+
+impl<
+        E: Succ<Input = usize, Output = usize> + Pred<Input = usize, Output = usize>,
+        D: AsRef<[usize]>,
+    > Pred for PartitionedEliasFano<E, D>
+{
+}
+
+impl<
+        'a,
+        E: Succ<Input = usize, Output = usize> + Pred<Input = usize, Output = usize>,
+        D: AsRef<[usize]>,
+    > IntoIterator for &'a PartitionedEliasFano<E, D>
+{
+    type Item = usize;
+    type IntoIter = PartitionedEliasFanoIterator<'a, E, D>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        PartitionedEliasFanoIterator::new_from(self, 0)
+    }
+}
+
+impl<
+        'a,
+        E: Succ<Input = usize, Output = usize> + Pred<Input = usize, Output = usize>,
+        D: AsRef<[usize]>,
+    > IntoIteratorFrom for &'a PartitionedEliasFano<E, D>
+{
+    type IntoIterFrom = PartitionedEliasFanoIterator<'a, E, D>;
+
+    #[inline(always)]
+    fn into_iter_from(self, from: usize) -> Self::IntoIterFrom {
+        PartitionedEliasFanoIterator::new_from(self, from)
+    }
+}
+
+/// An iterator for [`PartitionedEliasFano`].
+///
+/// The iterator decodes sequentially the chunks, scanning the words of
+/// bitmaps and of the upper bits of Elias–Fano chunks.
+pub struct PartitionedEliasFanoIterator<'a, E, D> {
+    pef: &'a PartitionedEliasFano<E, D>,
+    /// The index of the next value it will be returned when `next` is called.
+    index: usize,
+    /// The index of the current chunk.
+    chunk_idx: usize,
+    /// The current chunk.
+    chunk: Chunk,
+    /// The index in the current chunk of the next value.
+    chunk_index: usize,
+    /// Index of the word loaded in the `window` field.
+    word_idx: usize,
+    /// Current window on the bitmap or on the upper bits.
+    window: usize,
+}
+
+impl<
+        'a,
+        E: Succ<Input = usize, Output = usize> + Pred<Input = usize, Output = usize>,
+        D: AsRef<[usize]>,
+    > PartitionedEliasFanoIterator<'a, E, D>
+{
+    /// Creates an iterator starting at the given index.
+    ///
+    /// # Panics
+    ///
+    /// If `from` is greater than the length of the sequence.
+    pub fn new_from(pef: &'a PartitionedEliasFano<E, D>, from: usize) -> Self {
+        if from > pef.n {
+            panic!("Index out of bounds: {} > {}", from, pef.n);
+        }
+        let mut iter = Self {
+            pef,
+            index: from,
+            chunk_idx: 0,
+            chunk: Chunk {
+                start: from,
+                len: 0,
+                lower: 0,
+                u: 0,
+                offset: 0,
+                kind: ChunkKind::Run,
+            },
+            chunk_index: 0,
+            word_idx: 0,
+            window: 0,
+        };
+        if from < pef.n {
+            let (chunk_idx, start) = unsafe { pef.starts.pred_unchecked::<false>(from) };
+            iter.load(chunk_idx, from - start);
+        }
+        iter
+    }
+
+    /// Moves the iterator to the given index of the given chunk.
+    #[inline(always)]
+    fn load(&mut self, chunk_idx: usize, chunk_index: usize) {
+        self.chunk_idx = chunk_idx;
+        self.chunk = unsafe { self.pef.chunk(chunk_idx) };
+        self.chunk_index = chunk_index;
+        let data = self.pef.data.as_ref();
+        let pos = match self.chunk.kind {
+            ChunkKind::Run => return,
+            ChunkKind::Bitmap => select(data, self.chunk.offset, chunk_index),
+            ChunkKind::EliasFano { l } => {
+                select(data, self.chunk.offset + self.chunk.len * l, chunk_index)
+            }
+        };
+        self.word_idx = pos / BITS;
+        self.window = data[self.word_idx] & (usize::MAX << (pos % BITS));
+    }
+
+    /// Returns the position of the next one in the window, and clears it.
+    #[inline(always)]
+    fn next_one(&mut self) -> usize {
+        while self.window == 0 {
+            self.word_idx += 1;
+            self.window = unsafe { *self.pef.data.as_ref().get_unchecked(self.word_idx) };
+        }
+        let pos = self.word_idx * BITS + self.window.trailing_zeros() as usize;
+        self.window &= self.window - 1;
+        pos
+    }
+}
+
+impl<
+        E: Succ<Input = usize, Output = usize> + Pred<Input = usize, Output = usize>,
+        D: AsRef<[usize]>,
+    > Iterator for PartitionedEliasFanoIterator<'_, E, D>
+{
+    type Item = usize;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<usize> {
+        if self.index >= self.pef.n {
+            return None;
+        }
+        if self.chunk_index == self.chunk.len {
+            self.load(self.chunk_idx + 1, 0);
+        }
+        let chunk = self.chunk;
+        let value = match chunk.kind {
+            ChunkKind::Run => chunk.u - (chunk.len - 1) + self.chunk_index,
+            ChunkKind::Bitmap => self.next_one() - chunk.offset,
+            ChunkKind::EliasFano { l } => {
+                let high_start = chunk.offset + chunk.len * l;
+                let high = self.next_one() - high_start - self.chunk_index;
+                (high << l)
+                    | read_bits(
+                        self.pef.data.as_ref(),
+                        chunk.offset + self.chunk_index * l,
+                        l,
+                    )
+            }
+        };
+        self.chunk_index += 1;
+        self.index += 1;
+        Some(chunk.lower + value)
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len(), Some(self.len()))
+    }
+}
+
+impl<
+        E: Succ<Input = usize, Output = usize> + Pred<Input = usize, Output = usize>,
+        D: AsRef<[usize]>,
+    > ExactSizeIterator for PartitionedEliasFanoIterator<'_, E, D>
+{
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.pef.n - self.index
+    }
+}
+
+/// A builder for [`PartitionedEliasFano`].
+///
+/// After creating an instance, you can use
+/// [`push`](PartitionedEliasFanoBuilder::push) or
+/// [`extend`](Extend::extend) to add values, and then call
+/// [`build`](PartitionedEliasFanoBuilder::build) to compute the partition
+/// and create the [`PartitionedEliasFano`] instance. Since the partition
+/// depends on the whole sequence, values are accumulated in memory.
+#[derive(Debug, Clone, Default, MemDbg, MemSize)]
+pub struct PartitionedEliasFanoBuilder {
+    values: Vec<usize>,
+}
+
+impl PartitionedEliasFanoBuilder {
+    /// Creates an empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a new value to the builder.
+    ///
+    /// # Panics
+    ///
+    /// If the value is smaller than the last provided value.
+    pub fn push(&mut self, value: usize) {
+        if let Some(&last) = self.values.last() {
+            if value < last {
+                panic!("The values provided are not monotone: {} < {}", value, last);
+            }
+        }
+        self.values.push(value);
+    }
+
+    /// Computes an approximately optimal partition of the values and builds
+    /// a partitioned Elias–Fano structure.
+    pub fn build(self) -> PartitionedEliasFano {
+        let values = self.values;
+        let n = values.len();
+        let ends = partition(&values);
+        let num_chunks = ends.len();
+
+        let mut upper = EliasFanoBuilder::new(num_chunks, values.last().copied().unwrap_or(0));
+        let mut starts = EliasFanoBuilder::new(num_chunks + 1, n);
+        let mut chunks = Vec::with_capacity(num_chunks);
+        let mut offset = 0;
+        let mut start = 0;
+        for &end in &ends {
+            let chunk = &values[start..end];
+            let lower = if start == 0 { 0 } else { values[start - 1] };
+            let last = chunk[chunk.len() - 1];
+            let strict = chunk.windows(2).all(|w| w[0] < w[1]);
+            let run = strict && last - chunk[0] == chunk.len() - 1;
+            let (kind, bits) = encoding(chunk.len(), last - lower, strict, run);
+            upper.push(last);
+            starts.push(start);
+            chunks.push((kind, offset));
+            offset += bits;
+            start = end;
+        }
+        starts.push(n);
+
+        let mut data = vec![0; offset.div_ceil(BITS)];
+        let mut offsets = EliasFanoBuilder::new(num_chunks + 1, offset);
+        start = 0;
+        for (&end, &(kind, offset)) in ends.iter().zip(chunks.iter()) {
+            let chunk = &values[start..end];
+            let lower = if start == 0 { 0 } else { values[start - 1] };
+            offsets.push(offset);
+            match kind {
+                ChunkKind::Run => {}
+                ChunkKind::Bitmap => {
+                    set_bit(&mut data, offset);
+                    for &value in chunk {
+                        set_bit(&mut data, offset + 1 + value - lower);
+                    }
+                }
+                ChunkKind::EliasFano { l } => {
+                    let high_start = offset + 1 + chunk.len() * l;
+                    for (i, &value) in chunk.iter().enumerate() {
+                        let value = value - lower;
+                        let low = value & ((1 << l) - 1);
+                        write_bits(&mut data, offset + 1 + i * l, low, l);
+                        set_bit(&mut data, high_start + (value >> l) + i);
+                    }
+                }
+            }
+            start = end;
+        }
+        offsets.push(offset);
+
+        PartitionedEliasFano {
+            n,
+            upper: upper.build_with_seq_and_dict(),
+            starts: starts.build_with_seq_and_dict(),
+            offsets: offsets.build_with_seq_and_dict(),
+            data: data.into_boxed_slice(),
+        }
+    }
+}
+
+impl Extend<usize> for PartitionedEliasFanoBuilder {
+    fn extend<T: IntoIterator<Item = usize>>(&mut self, iter: T) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+/// Convenience constructor that iterates over a slice.
+impl<A: AsRef<[usize]>> From<A> for PartitionedEliasFano {
+    fn from(values: A) -> Self {
+        let mut builder = PartitionedEliasFanoBuilder::new();
+        builder.extend(values.as_ref().iter().copied());
+        builder.build()
+    }
+}
@@ -0,0 +1,401 @@
+/*
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Elias–Fano representation of monotone sequences of signed integers.
+//!
+//! A [`SignedEliasFano`] stores a monotone sequence of `isize` values by
+//! subtracting from each value an offset (usually, the first value of the
+//! sequence) and storing the result in an unsigned structure, by default an
+//! [`EliasFano`]. All indexed-dictionary traits implemented by the underlying
+//! structure are implemented by the signed variant, too.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # use sux::dict::{SignedEliasFano, SignedEliasFanoBuilder};
+//! # use sux::traits::{IndexedSeq, IndexedDict, Succ, Pred};
+//! let mut efb = SignedEliasFanoBuilder::new(4, -10, 10);
+//! efb.push(-10);
+//! efb.push(-2);
+//! efb.push(3);
+//! efb.push(10);
+//! let ef = efb.build_with_seq_and_dict();
+//!
+//! assert_eq!(ef.get(0), -10);
+//! assert_eq!(ef.get(1), -2);
+//! assert_eq!(ef.index_of(3), Some(2));
+//! assert_eq!(ef.succ(-5), Some((1, -2)));
+//! assert_eq!(ef.pred(-5), Some((0, -10)));
+//! assert_eq!(ef.pred(-11), None);
+//!
+//! // Convenience constructor from a slice
+//! let ef: SignedEliasFano = vec![-5, -5, 0, 7].into();
+//! assert_eq!(ef.iter().collect::<Vec<_>>(), vec![-5, -5, 0, 7]);
+//! ```
+
+use crate::dict::elias_fano::{EfDict, EfSeq, EfSeqDict, EliasFanoIterator};
+use crate::prelude::*;
+use crate::traits::IntoIteratorFrom;
+use epserde::*;
+use mem_dbg::*;
+use std::borrow::Borrow;
+
+/// An Elias–Fano representation of a monotone sequence of signed integers.
+///
+/// Values are stored in an underlying structure `E`, by default an
+/// [`EliasFano`], after subtracting an offset. You can build instances using a
+/// [`SignedEliasFanoBuilder`], using the [`From`] implementation on slices of
+/// `isize`, or wrapping an existing unsigned structure with
+/// [`from_parts`](SignedEliasFano::from_parts).
+#[derive(Epserde, Debug, Clone, Hash, MemDbg, MemSize)]
+pub struct SignedEliasFano<E = EliasFano> {
+    /// The underlying unsigned structure.
+    ef: E,
+    /// The offset to add to values returned by the underlying structure.
+    offset: isize,
+}
+
+impl<E> SignedEliasFano<E> {
+    /// Creates a signed structure from an unsigned structure and an offset
+    /// that will be added to the values of the unsigned structure.
+    pub fn from_parts(ef: E, offset: isize) -> Self {
+        Self { ef, offset }
+    }
+
+    /// Returns the underlying unsigned structure and the offset.
+    pub fn into_parts(self) -> (E, isize) {
+        (self.ef, self.offset)
+    }
+
+    /// Returns the offset added to the values of the underlying structure.
+    pub fn offset(&self) -> isize {
+        self.offset
+    }
+
+    /// Replaces the underlying structure.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe because it is not possible to guarantee that the
+    /// new structure represents the same sequence as the old one.
+    pub unsafe fn map<F, E2>(self, func: F) -> SignedEliasFano<E2>
+    where
+        F: FnOnce(E) -> E2,
+    {
+        SignedEliasFano {
+            ef: func(self.ef),
+            offset: self.offset,
+        }
+    }
+
+    /// Converts a signed value into the unsigned value stored in the
+    /// underlying structure.
+    ///
+    /// The value must be greater than or equal to the offset.
+    #[inline(always)]
+    fn to_unsigned(&self, value: isize) -> usize {
+        debug_assert!(value >= self.offset);
+        value.wrapping_sub(self.offset) as usize
+    }
+
+    /// Converts an unsigned value stored in the underlying structure into the
+    /// corresponding signed value.
+    #[inline(always)]
+    fn to_signed(&self, value: usize) -> isize {
+        (value as isize).wrapping_add(self.offset)
+    }
+}
+
+impl<H, L> SignedEliasFano<EliasFano<usize, H, L>> {
+    /// Returns the number elements in the sequence.
+    ///
+    /// This method is equivalent to [`IndexedSeq::len`], but it is provided to
+    /// reduce ambiguity in method resolution.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.ef.len()
+    }
+}
+
+impl<E: Types<Input = usize, Output = usize>> Types for SignedEliasFano<E> {
+    type Input = isize;
+    type Output = isize;
+}
+
+impl<E: IndexedSeq<Input = usize, Output = usize>> IndexedSeq for SignedEliasFano<E> {
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.ef.len()
+    }
+
+    #[inline(always)]
+    unsafe fn get_unchecked(&self, index: usize) -> isize {
+        self.to_signed(self.ef.get_unchecked(index))
+    }
+}
+
+impl<E: IndexedDict<Input = usize, Output = usize>> IndexedDict for SignedEliasFano<E> {
+    fn index_of(&self, value: impl Borrow<Self::Input>) -> Option<usize> {
+        let value = *value.borrow();
+        if value < self.offset {
+            return None;
+        }
+        self.ef.index_of(self.to_unsigned(value))
+    }
+}
+
+impl<E: SuccUnchecked<Input = usize, Output = usize>> SuccUnchecked for SignedEliasFano<E> {
+    unsafe fn succ_unchecked<const STRICT: bool>(
+        &self,
+        value: impl Borrow<Self::Input>,
+    ) -> (usize, Self::Output) {
+        let value = *value.borrow();
+        // All values are greater than or equal to the offset
+        let (index, succ) = if value < self.offset {
+            self.ef.succ_unchecked::<false>(0)
+        } else {
+            self.ef.succ_unchecked::<STRICT>(self.to_unsigned(value))
+        };
+        (index, self.to_signed(succ))
+    }
+}
+
+impl<
+        E: SuccUnchecked<Input = usize, Output = usize> + IndexedSeq<Input = usize, Output = usize>,
+    > Succ for SignedEliasFano<E>
+{
+}
+
+impl<E: PredUnchecked<Input = usize, Output = usize>> PredUnchecked for SignedEliasFano<E> {
+    unsafe fn pred_unchecked<const STRICT: bool>(
+        &self,
+        value: impl Borrow<Self::Input>,
+    ) -> (usize, Self::Output) {
+        // Since the predecessor exists, the value is greater than or equal to
+        // the offset
+        let (index, pred) = self
+            .ef
+            .pred_unchecked::<STRICT>(self.to_unsigned(*value.borrow()));
+        (index, self.to_signed(pred))
+    }
+}
+
+impl<
+        E: PredUnchecked<Input = usize, Output = usize> + IndexedSeq<Input = usize, Output = usize>,
+    > Pred for SignedEliasFano<E>
+{
+}
+
+impl<H: AsRef<[usize]>, L: BitFieldSlice<usize>> SignedEliasFano<EliasFano<usize, H, L>>
+where
+    for<'b> &'b L: IntoUncheckedIterator<Item = usize>,
+{
+    /// Returns an iterator over the values of the sequence.
+    #[inline(always)]
+    pub fn iter(&self) -> SignedEliasFanoIterator<EliasFanoIterator<'_, usize, H, L>> {
+        SignedEliasFanoIterator {
+            iter: self.ef.iter(),
+            offset: self.offset,
+        }
+    }
+}
+
+impl<'a, H: AsRef<[usize]>, L: BitFieldSlice<usize>> IntoIterator
+    for &'a SignedEliasFano<EliasFano<usize, H, L>>
+where
+    for<'b> &'b L: IntoUncheckedIterator<Item = usize>,
+{
+    type Item = isize;
+    type IntoIter = SignedEliasFanoIterator<EliasFanoIterator<'a, usize, H, L>>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<H: AsRef<[usize]> + SelectUnchecked, L: BitFieldSlice<usize>>
+    SignedEliasFano<EliasFano<usize, H, L>>
+where
+    for<'b> &'b L: IntoUncheckedIterator<Item = usize>,
+{
+    /// Returns an iterator over the values of the sequence starting at the
+    /// given position.
+    #[inline(always)]
+    pub fn iter_from(
+        &self,
+        from: usize,
+    ) -> SignedEliasFanoIterator<EliasFanoIterator<'_, usize, H, L>> {
+        SignedEliasFanoIterator {
+            iter: self.ef.iter_from(from),
+            offset: self.offset,
+        }
+    }
+}
+
+impl<'a, H: AsRef<[usize]> + SelectUnchecked, L: BitFieldSlice<usize>> IntoIteratorFrom
+    for &'a SignedEliasFano<EliasFano<usize, H, L>>
+where
+    for<'b> &'b L: IntoUncheckedIterator<Item = usize>,
+{
+    type IntoIterFrom = SignedEliasFanoIterator<EliasFanoIterator<'a, usize, H, L>>;
+
+    #[inline(always)]
+    fn into_iter_from(self, from: usize) -> Self::IntoIterFrom {
+        self.iter_from(from)
+    }
+}
+
+/// An iterator for [`SignedEliasFano`], adding the offset to the values
+/// returned by an iterator on the underlying structure.
+#[derive(Debug, Clone, MemDbg, MemSize)]
+pub struct SignedEliasFanoIterator<I> {
+    iter: I,
+    offset: isize,
+}
+
+impl<I: Iterator<Item = usize>> Iterator for SignedEliasFanoIterator<I> {
+    type Item = isize;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<isize> {
+        self.iter
+            .next()
+            .map(|value| (value as isize).wrapping_add(self.offset))
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<I: ExactSizeIterator<Item = usize>> ExactSizeIterator for SignedEliasFanoIterator<I> {
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+/// Convenience constructor that iterates over a slice.
+///
+/// The offset is the first value of the slice.
+impl<A: AsRef<[isize]>> From<A> for SignedEliasFano {
+    fn from(values: A) -> Self {
+        let values = values.as_ref();
+        let offset = values.first().copied().unwrap_or(0);
+        let mut prev = offset;
+        for &value in values {
+            if value < prev {
+                panic!("The values provided are not monotone: {} < {}", value, prev);
+            }
+            prev = value;
+        }
+        let mut builder = SignedEliasFanoBuilder::new(values.len(), offset, prev);
+        for &value in values {
+            // SAFETY: pre-scan checked monotonicity and bounds.
+            unsafe {
+                builder.push_unchecked(value);
+            }
+        }
+        builder.build()
+    }
+}
+
+/// A sequential builder for [`SignedEliasFano`].
+///
+/// After creating an instance with a lower and an upper bound, you can use
+/// [`SignedEliasFanoBuilder::push`] to add new values, and then call
+/// [`SignedEliasFanoBuilder::build`] (or one of its variants) to create the
+/// [`SignedEliasFano`] instance. The lower bound is used as offset.
+#[derive(Debug, Clone, MemDbg, MemSize)]
+pub struct SignedEliasFanoBuilder {
+    builder: EliasFanoBuilder,
+    lower: isize,
+    upper: isize,
+}
+
+impl SignedEliasFanoBuilder {
+    /// Creates a builder for a [`SignedEliasFano`] containing `n` numbers
+    /// greater than or equal to `lower` and smaller than or equal to `upper`.
+    pub fn new(n: usize, lower: isize, upper: isize) -> Self {
+        if lower > upper {
+            panic!(
+                "The lower bound is larger than the upper bound: {} > {}",
+                lower, upper
+            );
+        }
+        Self {
+            builder: EliasFanoBuilder::new(n, upper.wrapping_sub(lower) as usize),
+            lower,
+            upper,
+        }
+    }
+
+    /// Adds a new value to the builder.
+    ///
+    /// # Panic
+    /// May panic if the value is smaller than the last provided value, if it
+    /// is out of bounds, or if too many values are provided.
+    pub fn push(&mut self, value: isize) {
+        if value < self.lower {
+            panic!("Value too small: {} < {}", value, self.lower);
+        }
+        if value > self.upper {
+            panic!("Value too large: {} > {}", value, self.upper);
+        }
+        self.builder.push(value.wrapping_sub(self.lower) as usize);
+    }
+
+    /// # Safety
+    ///
+    /// Values passed to this function must be within the bounds and must be
+    /// monotone. Moreover, the function should not be called more than `n`
+    /// times.
+    pub unsafe fn push_unchecked(&mut self, value: isize) {
+        self.builder
+            .push_unchecked(value.wrapping_sub(self.lower) as usize);
+    }
+
+    /// Builds a signed Elias-Fano structure.
+    ///
+    /// See [`EliasFanoBuilder::build`].
+    pub fn build(self) -> SignedEliasFano {
+        SignedEliasFano::from_parts(self.builder.build(), self.lower)
+    }
+
+    /// Builds a signed Elias-Fano structure with constant-time access, using
+    /// default values.
+    ///
+    /// See [`EliasFanoBuilder::build_with_seq`].
+    pub fn build_with_seq(self) -> SignedEliasFano<EfSeq> {
+        SignedEliasFano::from_parts(self.builder.build_with_seq(), self.lower)
+    }
+
+    /// Builds a signed Elias-Fano structure with constant-time indexing, using
+    /// default values.
+    ///
+    /// See [`EliasFanoBuilder::build_with_dict`].
+    pub fn build_with_dict(self) -> SignedEliasFano<EfDict> {
+        SignedEliasFano::from_parts(self.builder.build_with_dict(), self.lower)
+    }
+
+    /// Builds a signed Elias-Fano structure with constant-time access and
+    /// indexing, using default values.
+    ///
+    /// See [`EliasFanoBuilder::build_with_seq_and_dict`].
+    pub fn build_with_seq_and_dict(self) -> SignedEliasFano<EfSeqDict> {
+        SignedEliasFano::from_parts(self.builder.build_with_seq_and_dict(), self.lower)
+    }
+}
+
+impl Extend<isize> for SignedEliasFanoBuilder {
+    fn extend<T: IntoIterator<Item = isize>>(&mut self, iter: T) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
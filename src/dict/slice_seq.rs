@@ -5,9 +5,11 @@
  * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
  */
 
-//! Adapters from reference to slices to [indexed sequences](crate::traits::IndexedSeq).
+//! Adapters from reference to slices and from [bit-field
+//! slices](crate::traits::BitFieldSlice) to [indexed
+//! sequences](crate::traits::IndexedSeq).
 
-use crate::traits::{IndexedSeq, IntoIteratorFrom, Types};
+use crate::traits::{BitFieldSlice, IndexedSeq, IntoIteratorFrom, Types};
 
 /// A newtype exhibiting a reference to a slice as an [indexed
 /// sequence](crate::traits::IndexedSeq).
@@ -96,3 +98,51 @@ where
         self.iter().skip(from)
     }
 }
+
+/// A newtype exhibiting a reference to a [bit-field slice](BitFieldSlice) as
+/// an [indexed sequence](crate::traits::IndexedSeq).
+///
+/// You can create a [`BitFieldSeq`] with [`BitFieldSeq::new`], or with the
+/// equivalent [`From`] implementation.
+///
+/// As in the case of [`SliceSeq`], a blanket implementation would cause
+/// ambiguity problems, as both traits have `get` and `len` methods.
+///
+/// # Examples
+///
+/// ```rust
+/// use sux::prelude::*;
+///
+/// let values = bit_field_vec![4; 3, 1, 4];
+/// let seq = BitFieldSeq::new(&values);
+/// assert_eq!(IndexedSeq::get(&seq, 2), 4);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BitFieldSeq<'a, B: BitFieldSlice<usize> + ?Sized>(&'a B);
+
+impl<'a, B: BitFieldSlice<usize> + ?Sized> BitFieldSeq<'a, B> {
+    pub fn new(slice: &'a B) -> Self {
+        Self(slice)
+    }
+}
+
+impl<'a, B: BitFieldSlice<usize> + ?Sized> From<&'a B> for BitFieldSeq<'a, B> {
+    fn from(slice: &'a B) -> Self {
+        Self::new(slice)
+    }
+}
+
+impl<B: BitFieldSlice<usize> + ?Sized> Types for BitFieldSeq<'_, B> {
+    type Input = usize;
+    type Output = usize;
+}
+
+impl<B: BitFieldSlice<usize> + ?Sized> IndexedSeq for BitFieldSeq<'_, B> {
+    unsafe fn get_unchecked(&self, index: usize) -> Self::Output {
+        unsafe { BitFieldSlice::get_unchecked(self.0, index) }
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
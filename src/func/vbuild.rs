@@ -678,7 +678,7 @@ impl<
 where
     SigVal<S, ()>: RadixKey + Send + Sync,
     Vec<W>: BitFieldSliceMut<W> + BitFieldSlice<W>,
-    u64: CastableInto<W>,
+    u128: CastableInto<W>,
 {
     pub fn try_build_filter(
         mut self,
@@ -686,7 +686,7 @@ where
         pl: &mut (impl ProgressLog + Clone + Send + Sync),
     ) -> anyhow::Result<VFilter<W, VFunc<T, W, Vec<W>, S, SHARDED>>> {
         let filter_mask = W::MAX;
-        let get_val = |sig_val: &SigVal<S, ()>| sig_val.sig.sig_u64().cast();
+        let get_val = |sig_val: &SigVal<S, ()>| filter_value::<W, S>(&sig_val.sig);
         let new_data = |_bit_width: usize, len: usize| vec![W::ZERO; len];
 
         Ok(VFilter {
@@ -750,7 +750,7 @@ impl<
 where
     SigVal<S, ()>: RadixKey + Send + Sync,
     Vec<W>: BitFieldSliceMut<W> + BitFieldSlice<W>,
-    u64: CastableInto<W>,
+    u128: CastableInto<W>,
 {
     pub fn try_build_filter(
         mut self,
@@ -761,7 +761,7 @@ where
         assert!(filter_bits > 0);
         assert!(filter_bits <= W::BITS);
         let filter_mask = W::MAX >> (W::BITS - filter_bits);
-        let get_val = |sig_val: &SigVal<S, ()>| sig_val.sig.sig_u64().cast() & filter_mask;
+        let get_val = |sig_val: &SigVal<S, ()>| filter_value::<W, S>(&sig_val.sig) & filter_mask;
         let new_data = |bit_width, len| BitFieldVec::<W>::new(bit_width, len);
 
         Ok(VFilter {
@@ -7,6 +7,7 @@
 
 use crate::bits::*;
 use crate::traits::bit_field_slice::*;
+use crate::traits::{Verify, VerifyError, VerifyMode};
 use crate::utils::*;
 use common_traits::CastableInto;
 use epserde::prelude::*;
@@ -52,6 +53,24 @@ pub struct VFilter<W: ZeroCopy + Word, F> {
     pub(in crate::func) filter_mask: W,
 }
 
+/// Returns the value associated by a filter with a signature.
+///
+/// For words of at most 64 bits the value is derived from
+/// [`Sig::sig_u64`], and from [`Sig::sig_u128`] otherwise. Since the lower 64
+/// bits of the two methods coincide, the value is the same in both cases, once
+/// masked to the filter bits.
+#[inline(always)]
+pub(in crate::func) fn filter_value<W: Word, S: Sig>(sig: &S) -> W
+where
+    u128: CastableInto<W>,
+{
+    if W::BITS <= u64::BITS as usize {
+        (sig.sig_u64() as u128).cast()
+    } else {
+        sig.sig_u128().cast()
+    }
+}
+
 /// Shard and edge information.
 ///
 /// This trait is used to derive shards and edges from key signatures. The
@@ -206,6 +225,69 @@ impl<
     }
 }
 
+/// Checks that the parameters of the function are consistent, and that the
+/// data contains exactly the vertices of the hypergraph; there are no counters
+/// or inventories, so the mode is irrelevant.
+impl<
+        T: ?Sized + ToSig<S>,
+        W: ZeroCopy + Word,
+        D: BitFieldSlice<W> + Verify,
+        S: Sig,
+        const SHARDED: bool,
+    > Verify for VFunc<T, W, D, S, SHARDED>
+{
+    fn verify(&self, mode: VerifyMode) -> Result<(), VerifyError> {
+        self.data.verify(mode)?;
+
+        let max_shard_high_bits = if SHARDED { u32::BITS - 1 } else { 0 };
+        if self.shard_high_bits > max_shard_high_bits {
+            return Err(VerifyError::Param {
+                what: "number of high bits used for sharding",
+                expected: max_shard_high_bits as usize,
+                found: self.shard_high_bits as usize,
+            });
+        }
+        let shard_mask = (1u32 << self.shard_high_bits) - 1;
+        if self.shard_mask != shard_mask {
+            return Err(VerifyError::Param {
+                what: "shard mask",
+                expected: shard_mask as usize,
+                found: self.shard_mask as usize,
+            });
+        }
+        if self.log2_seg_size >= usize::BITS {
+            return Err(VerifyError::Param {
+                what: "log2 of segment size",
+                expected: usize::BITS as usize - 1,
+                found: self.log2_seg_size as usize,
+            });
+        }
+
+        // The number of vertices of each shard is (l + 2) << log2_seg_size
+        let data_len = self
+            .l
+            .checked_add(2)
+            .and_then(|segments| segments.checked_shl(self.log2_seg_size))
+            .filter(|&vertices| vertices >> self.log2_seg_size == self.l + 2)
+            .and_then(|vertices| vertices.checked_mul(1 << self.shard_high_bits))
+            .unwrap_or(usize::MAX);
+        if self.data.len() != data_len {
+            return Err(VerifyError::Param {
+                what: "data length",
+                expected: data_len,
+                found: self.data.len(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl<W: ZeroCopy + Word, F: Verify> Verify for VFilter<W, F> {
+    fn verify(&self, mode: VerifyMode) -> Result<(), VerifyError> {
+        self.func.verify(mode)
+    }
+}
+
 impl<
         T: ?Sized + ToSig<S>,
         W: ZeroCopy + Word,
@@ -214,7 +296,7 @@ impl<
         const SHARDED: bool,
     > VFilter<W, VFunc<T, W, D, S, SHARDED>>
 where
-    u64: CastableInto<W>,
+    u128: CastableInto<W>,
 {
     /// Return the value associated with the given signature by the underlying
     /// function, or a random value if the signature is not the signature of a
@@ -243,7 +325,7 @@ where
     /// [`contains`](VFilter::contains).
     #[inline(always)]
     pub fn contains_by_sig(&self, sig: &S) -> bool {
-        self.func.get_by_sig(sig) == sig.sig_u64().cast() & self.filter_mask
+        self.func.get_by_sig(sig) == filter_value::<W, S>(sig) & self.filter_mask
     }
 
     /// Return whether a key is contained in the filter.
@@ -275,7 +357,7 @@ impl<
         B: Borrow<T>,
     > Index<B> for VFilter<W, VFunc<T, W, D, S, SHARDED>>
 where
-    u64: CastableInto<W>,
+    u128: CastableInto<W>,
 {
     type Output = bool;
 
@@ -17,8 +17,10 @@ pub mod bits;
 pub mod dict;
 pub mod func;
 pub mod rank_sel;
+pub mod seq;
 pub mod solvers;
 pub mod traits;
+pub mod tree;
 pub mod utils;
 
 #[cfg(feature = "fuzz")]
@@ -32,9 +34,11 @@ pub mod prelude {
     pub use crate::func::*;
     pub use crate::rank_sel::*;
     pub use crate::rank_small;
+    pub use crate::seq::*;
     pub use crate::solvers::*;
     pub use crate::traits::bit_field_slice;
     pub use crate::traits::*;
+    pub use crate::tree::*;
 }
 
 #[ambassador::delegatable_trait_remote]
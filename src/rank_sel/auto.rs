@@ -0,0 +1,200 @@
+/*
+ *
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use super::{
+    Rank9, Select9, SelectAdapt, SelectAdaptConst, SelectSmall, SelectZero9, SelectZeroAdapt,
+    SelectZeroAdaptConst, SelectZeroSmall,
+};
+use crate::bits::BitVec;
+use crate::rank_small;
+use crate::traits::{BitCount, DynRankSelect};
+use core::fmt;
+use mem_dbg::SizeFlags;
+use std::cmp::max;
+
+/// The tradeoff favored by [`auto`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AutoPreference {
+    /// Minimize space, accepting slower queries. The resulting structures
+    /// use less than 3% of additional space.
+    Space,
+    /// Minimize query time, accepting more space. The resulting structures
+    /// use about 40–65% of additional space.
+    Speed,
+}
+
+/// A report about the structure chosen by [`auto`].
+///
+/// The [`Display`](fmt::Display) implementation prints the overhead in the
+/// same terms of the `mem_usage` binary.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AutoReport {
+    /// The type of the structure.
+    pub structure: &'static str,
+    /// The fraction of ones in the bit vector.
+    pub density: f64,
+    /// Whether the ones (or the zeros, if they are fewer) appear to be
+    /// distributed uniformly.
+    pub uniform: bool,
+    /// The space used by the structure in excess of the bit vector, expressed
+    /// as a percentage of the length of the bit vector.
+    pub overhead: f64,
+}
+
+impl fmt::Display for AutoReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Memory cost of {}: {}%", self.structure, self.overhead)
+    }
+}
+
+/// A bit value is rare for [`AutoPreference::Speed`] if it occurs in less than
+/// 1/2^`LOG2_SPEED_RARE` of the bits.
+const LOG2_SPEED_RARE: usize = 6;
+/// A bit value is rare for [`AutoPreference::Space`] if it occurs in less than
+/// 1/2^`LOG2_SPACE_RARE` of the bits.
+const LOG2_SPACE_RARE: usize = 12;
+/// Number of segments used to estimate the distribution of the ones.
+const DISTRIBUTION_SEGMENTS: usize = 64;
+/// Minimum number of words in a segment.
+const MIN_SEGMENT_WORDS: usize = 1 << 10;
+/// Minimum expected number of rarest bits in a segment.
+const MIN_SEGMENT_EXPECTED: usize = 64;
+
+/// Returns whether the rarest bit value is distributed approximately
+/// uniformly, that is, whether the number of occurrences in every segment is
+/// within a factor of two from the expected value.
+fn is_uniform(bits: &BitVec, num_ones: usize) -> bool {
+    let len = bits.len();
+    let num_zeros = len - num_ones;
+    let rare = num_ones.min(num_zeros);
+    if rare == 0 {
+        return true;
+    }
+    let words: &[usize] = bits.as_ref();
+    let segment_words = max(
+        max(MIN_SEGMENT_WORDS, words.len() / DISTRIBUTION_SEGMENTS),
+        (MIN_SEGMENT_EXPECTED * len).div_ceil(rare * usize::BITS as usize),
+    );
+    if words.len() < 2 * segment_words {
+        return true;
+    }
+
+    // We skip the last, partial segment
+    words
+        .chunks_exact(segment_words)
+        .enumerate()
+        .all(|(i, segment)| {
+            let start = i * segment_words * usize::BITS as usize;
+            let segment_bits = (len - start).min(segment_words * usize::BITS as usize);
+            let ones = segment
+                .iter()
+                .map(|w| w.count_ones() as usize)
+                .sum::<usize>();
+            let count = if num_ones <= num_zeros {
+                ones
+            } else {
+                segment_bits - ones
+            };
+            // count / segment_bits must be within a factor of two of rare / len
+            2 * count * len >= rare * segment_bits && count * len <= 2 * rare * segment_bits
+        })
+}
+
+fn boxed<S: DynRankSelect + 'static>(
+    s: S,
+    structure: &'static str,
+) -> (Box<dyn DynRankSelect>, &'static str, f64) {
+    let len = s.len();
+    // Same formula of the mem_usage binary
+    let overhead = ((s.mem_size(SizeFlags::default()) * 8 - len) * 100) as f64 / max(1, len) as f64;
+    (Box::new(s), structure, overhead)
+}
+
+/// Builds automatically a structure supporting ranking and selection on ones
+/// and zeros over the given bit vector.
+///
+/// The structure is chosen depending on the given [preference](AutoPreference),
+/// on the density of the ones, and on their distribution:
+///
+/// - with [`AutoPreference::Speed`], selection is provided by a
+///   [`SelectAdaptConst`] and a [`SelectZeroAdaptConst`] over a [`Rank9`];
+///   however, if one of the bit values is rare (less than 1/64 of the bits) and
+///   distributed uniformly, we use a [`Select9`] (or a [`SelectZero9`]) for
+///   it, as its inventory will be small and its queries very fast;
+///
+/// - with [`AutoPreference::Space`], selection is provided by a
+///   [`SelectSmall`] and a [`SelectZeroSmall`] over a [`RankSmall`] with
+///   1.56% overhead; however, if one of the bit values is very rare (less
+///   than 1/4096 of the bits) we use a [`SelectAdapt`] (or a
+///   [`SelectZeroAdapt`]) for it, as it will be an order of magnitude faster
+///   using about 1.5% of additional space.
+///
+/// The function returns the structure as a boxed [`DynRankSelect`] and an
+/// [`AutoReport`] describing the choice, including the actual space overhead
+/// as computed by [`MemSize`](mem_dbg::MemSize).
+///
+/// # Examples
+///
+/// ```rust
+/// use sux::bits::BitVec;
+/// use sux::rank_sel::{auto, AutoPreference};
+///
+/// let bits: BitVec = (0..1_000_000).map(|i| i % 3 == 0).collect();
+/// let (rank_sel, report) = auto(bits, AutoPreference::Space);
+///
+/// assert_eq!(rank_sel.rank(10), 4);
+/// assert_eq!(rank_sel.select(4), Some(12));
+/// assert_eq!(rank_sel.select_zero(4), Some(7));
+/// assert!(report.overhead < 5.0);
+/// println!("{}", report);
+/// ```
+///
+/// [`RankSmall`]: super::RankSmall
+pub fn auto(bits: BitVec, preference: AutoPreference) -> (Box<dyn DynRankSelect>, AutoReport) {
+    let len = bits.len();
+    let num_ones = bits.count_ones();
+    let density = num_ones as f64 / max(1, len) as f64;
+    let uniform = is_uniform(&bits, num_ones);
+    let num_zeros = len - num_ones;
+
+    let (rank_sel, structure, overhead) = match preference {
+        AutoPreference::Speed if uniform && num_ones < len >> LOG2_SPEED_RARE => boxed(
+            SelectZeroAdaptConst::<_, _>::new(Select9::new(Rank9::new(bits))),
+            "SelectZeroAdaptConst<Select9<Rank9>>",
+        ),
+        AutoPreference::Speed if uniform && num_zeros < len >> LOG2_SPEED_RARE => boxed(
+            SelectAdaptConst::<_, _>::new(SelectZero9::new(Rank9::new(bits))),
+            "SelectAdaptConst<SelectZero9<Rank9>>",
+        ),
+        AutoPreference::Speed => boxed(
+            SelectZeroAdaptConst::<_, _>::new(SelectAdaptConst::<_, _>::new(Rank9::new(bits))),
+            "SelectZeroAdaptConst<SelectAdaptConst<Rank9>>",
+        ),
+        AutoPreference::Space if num_ones < len >> LOG2_SPACE_RARE => boxed(
+            SelectAdapt::new(SelectZeroSmall::<3, 13, _>::new(rank_small![4; bits]), 0),
+            "SelectAdapt<SelectZeroSmall<3, 13, RankSmall<3, 13>>>",
+        ),
+        AutoPreference::Space if num_zeros < len >> LOG2_SPACE_RARE => boxed(
+            SelectZeroAdapt::new(SelectSmall::<3, 13, _>::new(rank_small![4; bits]), 0),
+            "SelectZeroAdapt<SelectSmall<3, 13, RankSmall<3, 13>>>",
+        ),
+        AutoPreference::Space => boxed(
+            SelectZeroSmall::<3, 13, _>::new(SelectSmall::<3, 13, _>::new(rank_small![4; bits])),
+            "SelectZeroSmall<3, 13, SelectSmall<3, 13, RankSmall<3, 13>>>",
+        ),
+    };
+
+    (
+        rank_sel,
+        AutoReport {
+            structure,
+            density,
+            uniform,
+            overhead,
+        },
+    )
+}
@@ -90,10 +90,11 @@
 //! of the [`AddNumBits`](crate::traits::AddNumBits) wrapper.
 //!
 //! Some structures depend on the internals of others, and thus cannot be
-//! composed freely: for example, a [`Select9`] must necessarily wrap a
-//! [`Rank9`]. In general, in any case, we suggest embedding structure in the
-//! order rank, select, and zero select, from inner to outer, because ranking
-//! structures usually implement [`NumBits`](crate::traits::NumBits).
+//! composed freely: for example, a [`Select9`] or a [`SelectZero9`] must
+//! necessarily wrap a [`Rank9`], possibly through each other. In general, in
+//! any case, we suggest embedding structure in the order rank, select, and zero
+//! select, from inner to outer, because ranking structures usually implement
+//! [`NumBits`](crate::traits::NumBits).
 
 mod select_adapt;
 pub use select_adapt::*;
@@ -119,5 +120,118 @@ pub use select_zero_small::*;
 mod rank9;
 pub use rank9::*;
 
+mod rank9_interleaved;
+pub use rank9_interleaved::*;
+
 mod select9;
 pub use select9::*;
+
+mod select_zero9;
+pub use select_zero9::*;
+
+mod symbol_rank;
+pub use symbol_rank::*;
+
+mod auto;
+pub use auto::*;
+
+pub mod stream;
+pub use stream::*;
+
+use crate::traits::{BitLength, NumBits, RankUnchecked, SelectUnchecked, SelectZeroUnchecked};
+
+// Implementations of NextOne, PrevOne, NextZero, and PrevZero for structures
+// with access to the underlying words: we scan the word containing the
+// position first, and we resort to ranking and selecting only if necessary.
+
+#[inline(always)]
+fn next_one_in_words<T: AsRef<[usize]> + BitLength + NumBits + RankUnchecked + SelectUnchecked>(
+    bits: &T,
+    pos: usize,
+) -> Option<usize> {
+    let len = bits.len();
+    if pos >= len {
+        return None;
+    }
+    let word_idx = pos / usize::BITS as usize;
+    let word = unsafe { *bits.as_ref().get_unchecked(word_idx) } >> (pos % usize::BITS as usize);
+    if word != 0 {
+        let next = pos + word.trailing_zeros() as usize;
+        return (next < len).then_some(next);
+    }
+    let next_word_pos = (word_idx + 1) * usize::BITS as usize;
+    if next_word_pos >= len {
+        return None;
+    }
+    let rank = unsafe { bits.rank_unchecked(next_word_pos) };
+    (rank < bits.num_ones()).then(|| unsafe { bits.select_unchecked(rank) })
+}
+
+#[inline(always)]
+fn prev_one_in_words<T: AsRef<[usize]> + BitLength + RankUnchecked + SelectUnchecked>(
+    bits: &T,
+    pos: usize,
+) -> Option<usize> {
+    let pos = pos.min(bits.len());
+    if pos == 0 {
+        return None;
+    }
+    let word_idx = (pos - 1) / usize::BITS as usize;
+    let word_pos = word_idx * usize::BITS as usize;
+    let word = unsafe { *bits.as_ref().get_unchecked(word_idx) }
+        << (usize::BITS as usize - (pos - word_pos));
+    if word != 0 {
+        return Some(pos - 1 - word.leading_zeros() as usize);
+    }
+    match unsafe { bits.rank_unchecked(word_pos) } {
+        0 => None,
+        rank => Some(unsafe { bits.select_unchecked(rank - 1) }),
+    }
+}
+
+#[inline(always)]
+fn next_zero_in_words<
+    T: AsRef<[usize]> + BitLength + NumBits + RankUnchecked + SelectZeroUnchecked,
+>(
+    bits: &T,
+    pos: usize,
+) -> Option<usize> {
+    let len = bits.len();
+    if pos >= len {
+        return None;
+    }
+    let word_idx = pos / usize::BITS as usize;
+    let word = !unsafe { *bits.as_ref().get_unchecked(word_idx) } >> (pos % usize::BITS as usize);
+    if word != 0 {
+        let next = pos + word.trailing_zeros() as usize;
+        return (next < len).then_some(next);
+    }
+    let next_word_pos = (word_idx + 1) * usize::BITS as usize;
+    if next_word_pos >= len {
+        return None;
+    }
+    let rank = next_word_pos - unsafe { bits.rank_unchecked(next_word_pos) };
+    (rank < bits.num_zeros()).then(|| unsafe { bits.select_zero_unchecked(rank) })
+}
+
+#[inline(always)]
+fn prev_zero_in_words<T: AsRef<[usize]> + BitLength + RankUnchecked + SelectZeroUnchecked>(
+    bits: &T,
+    pos: usize,
+) -> Option<usize> {
+    let pos = pos.min(bits.len());
+    if pos == 0 {
+        return None;
+    }
+    let word_idx = (pos - 1) / usize::BITS as usize;
+    let word_pos = word_idx * usize::BITS as usize;
+    let word = !unsafe { *bits.as_ref().get_unchecked(word_idx) }
+        << (usize::BITS as usize - (pos - word_pos));
+    if word != 0 {
+        return Some(pos - 1 - word.leading_zeros() as usize);
+    }
+    match word_pos - unsafe { bits.rank_unchecked(word_pos) } {
+        0 => None,
+        rank => Some(unsafe { bits.select_zero_unchecked(rank - 1) }),
+    }
+}
@@ -7,13 +7,18 @@
  */
 
 use crate::prelude::*;
-use ambassador::Delegate;
+use crate::traits::bit_field_slice::panic_if_out_of_bounds;
+use crate::utils::{prefetch_index, prefetched_batch};
+use ambassador::{delegatable_trait, Delegate};
 use epserde::*;
 use mem_dbg::*;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 
 use crate::ambassador_impl_AsRef;
 use crate::ambassador_impl_Index;
 use crate::traits::rank_sel::ambassador_impl_BitLength;
+use crate::traits::rank_sel::ambassador_impl_BitWords;
 use crate::traits::rank_sel::ambassador_impl_RankHinted;
 use crate::traits::rank_sel::ambassador_impl_Select;
 use crate::traits::rank_sel::ambassador_impl_SelectHinted;
@@ -23,6 +28,18 @@ use crate::traits::rank_sel::ambassador_impl_SelectZeroHinted;
 use crate::traits::rank_sel::ambassador_impl_SelectZeroUnchecked;
 use std::ops::Index;
 
+/// A trait abstracting the access to the internal counters of a [`Rank9`]
+/// structure.
+///
+/// This trait is implemented by [`Rank9`], but it is propagated by
+/// [`Select9`](crate::rank_sel::Select9) and
+/// [`SelectZero9`](crate::rank_sel::SelectZero9), making it possible to
+/// combine the two selection structures.
+#[delegatable_trait]
+pub trait Rank9Counters {
+    fn counts(&self) -> &[BlockCounters];
+}
+
 /// A ranking structure using 25% of additional space and providing the fastest
 /// available rank operations.
 ///
@@ -70,6 +87,7 @@ use std::ops::Index;
 
 #[derive(Epserde, Debug, Clone, MemDbg, MemSize, Delegate)]
 #[delegate(AsRef<[usize]>, target = "bits")]
+#[delegate(crate::traits::rank_sel::BitWords, target = "bits")]
 #[delegate(Index<usize>, target = "bits")]
 #[delegate(crate::traits::rank_sel::BitLength, target = "bits")]
 #[delegate(crate::traits::rank_sel::RankHinted<64>, target = "bits")]
@@ -189,6 +207,213 @@ impl<B: AsRef<[usize]> + BitLength> Rank9<B, Box<[BlockCounters]>> {
             counts: counts.into(),
         }
     }
+
+    /// Creates a new Rank9 structure from a given bit vector, computing in
+    /// parallel the counters of each block.
+    ///
+    /// The result is identical to that of [`new`](Rank9::new).
+    #[cfg(feature = "rayon")]
+    pub fn par_new(bits: B) -> Self
+    where
+        B: Sync,
+    {
+        let num_bits = bits.len();
+        let num_words = num_bits.div_ceil(usize::BITS as usize);
+
+        // We first compute the relative counters, temporarily storing in the
+        // absolute counter the number of ones in the block
+        let mut counts = bits.as_ref()[..num_words]
+            .par_chunks(Self::WORDS_PER_BLOCK)
+            .map(|block| {
+                let mut count = BlockCounters::default();
+                let mut block_ones = block[0].count_ones() as usize;
+                for j in 1..8 {
+                    count.set_rel(j, block_ones);
+                    if j < block.len() {
+                        block_ones += block[j].count_ones() as usize;
+                    }
+                }
+                count.absolute = block_ones;
+                count
+            })
+            .collect::<Vec<_>>();
+
+        let mut num_ones = 0;
+        for count in counts.iter_mut() {
+            let block_ones = count.absolute;
+            count.absolute = num_ones;
+            num_ones += block_ones;
+        }
+
+        counts.push(BlockCounters {
+            absolute: num_ones,
+            relative: 0,
+        });
+
+        Self {
+            bits,
+            counts: counts.into(),
+        }
+    }
+}
+
+impl<B: AsRef<[usize]> + AsMut<[usize]> + BitLength, C: AsMut<[BlockCounters]>> Rank9<B, C> {
+    /// Returns the index of the block containing the bit of given position.
+    ///
+    /// This is the block that must be passed to
+    /// [`recompute_from`](Rank9::recompute_from) after a call to
+    /// [`set_lazy`](Rank9::set_lazy).
+    #[inline(always)]
+    pub fn block_of(&self, pos: usize) -> usize {
+        pos / (usize::BITS as usize * Self::WORDS_PER_BLOCK)
+    }
+
+    /// Sets the bit of given position to the given value, updating the
+    /// counters.
+    ///
+    /// The relative counters of the block containing the bit are recomputed,
+    /// and the absolute counters of all following blocks are adjusted, so the
+    /// cost of an update is linear in the number of following blocks. If you
+    /// need to change many bits, use [`set_lazy`](Rank9::set_lazy) followed by
+    /// a single call to [`recompute_from`](Rank9::recompute_from).
+    ///
+    /// Selection structures built on this structure are not updated: since
+    /// they take ownership of the ranking structure, you must recover it with
+    /// `into_inner`, update it, and build them again.
+    ///
+    /// # Panics
+    ///
+    /// If `pos` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sux::bit_vec;
+    /// use sux::prelude::{Rank, Rank9};
+    ///
+    /// let mut rank9 = Rank9::new(bit_vec![1, 0, 1, 1, 0, 1, 0, 1]);
+    /// rank9.set(1, true);
+    /// assert_eq!(rank9.rank(8), 6);
+    /// rank9.set(0, false);
+    /// assert_eq!(rank9.rank(2), 1);
+    /// ```
+    pub fn set(&mut self, pos: usize, value: bool) {
+        if !self.set_bit(pos, value) {
+            return;
+        }
+
+        let block = self.block_of(pos);
+        self.recompute_relative(block);
+        let counts = self.counts.as_mut();
+        if value {
+            counts[block + 1..].iter_mut().for_each(|c| c.absolute += 1);
+        } else {
+            counts[block + 1..].iter_mut().for_each(|c| c.absolute -= 1);
+        }
+    }
+
+    /// Sets the bit of given position to the given value without updating the
+    /// counters.
+    ///
+    /// After a batch of calls to this method, counters must be brought up to
+    /// date by calling [`recompute_from`](Rank9::recompute_from) with the
+    /// smallest [block](Rank9::block_of) containing a modified bit.
+    ///
+    /// # Panics
+    ///
+    /// If `pos` is out of bounds.
+    ///
+    /// # Safety
+    ///
+    /// Until [`recompute_from`](Rank9::recompute_from) is called, the
+    /// counters are inconsistent with the underlying bit vector, and the
+    /// results of all ranking operations are unspecified.
+    pub unsafe fn set_lazy(&mut self, pos: usize, value: bool) {
+        self.set_bit(pos, value);
+    }
+
+    /// Recomputes the counters of all blocks starting from the given one.
+    ///
+    /// The counters of blocks preceding `block` are assumed to be correct. The
+    /// cost is linear in the number of words from the start of `block` to the
+    /// end of the bit vector.
+    ///
+    /// As in the case of [`set`](Rank9::set), selection structures built on
+    /// this structure must be rebuilt.
+    ///
+    /// # Panics
+    ///
+    /// If `block` is greater than the number of blocks.
+    pub fn recompute_from(&mut self, block: usize) {
+        let num_words = self.bits.len().div_ceil(usize::BITS as usize);
+        let bits = self.bits.as_ref();
+        let counts = self.counts.as_mut();
+        let num_blocks = counts.len() - 1;
+        assert!(
+            block <= num_blocks,
+            "Block out of bounds: {} > {}",
+            block,
+            num_blocks
+        );
+
+        let mut num_ones = counts[block].absolute;
+        for (b, count) in counts[block..num_blocks].iter_mut().enumerate() {
+            let start = (block + b) * Self::WORDS_PER_BLOCK;
+            let end = num_words.min(start + Self::WORDS_PER_BLOCK);
+            let (relative, block_ones) = Self::relative_counters(&bits[start..end]);
+            *count = BlockCounters {
+                absolute: num_ones,
+                relative,
+            };
+            num_ones += block_ones;
+        }
+
+        counts[num_blocks].absolute = num_ones;
+    }
+
+    /// Sets a bit, returning whether its value has changed.
+    fn set_bit(&mut self, pos: usize, value: bool) -> bool {
+        panic_if_out_of_bounds!(pos, self.bits.len());
+        let word = &mut self.bits.as_mut()[pos / usize::BITS as usize];
+        let mask = 1 << (pos % usize::BITS as usize);
+        let old = *word;
+        if value {
+            *word |= mask;
+        } else {
+            *word &= !mask;
+        }
+        old != *word
+    }
+
+    /// Recomputes the relative counters of the given block.
+    fn recompute_relative(&mut self, block: usize) {
+        let num_words = self.bits.len().div_ceil(usize::BITS as usize);
+        let start = block * Self::WORDS_PER_BLOCK;
+        let end = num_words.min(start + Self::WORDS_PER_BLOCK);
+        let (relative, _) = Self::relative_counters(&self.bits.as_ref()[start..end]);
+        self.counts.as_mut()[block].relative = relative;
+    }
+
+    /// Returns the packed relative counters of a block, given its words, and
+    /// its number of ones.
+    fn relative_counters(block: &[usize]) -> (usize, usize) {
+        let mut count = BlockCounters::default();
+        let mut block_ones = block[0].count_ones() as usize;
+        for j in 1..8 {
+            count.set_rel(j, block_ones);
+            if j < block.len() {
+                block_ones += block[j].count_ones() as usize;
+            }
+        }
+        (count.relative, block_ones)
+    }
+}
+
+impl<B, C: AsRef<[BlockCounters]>> Rank9Counters for Rank9<B, C> {
+    #[inline(always)]
+    fn counts(&self) -> &[BlockCounters] {
+        self.counts.as_ref()
+    }
 }
 
 impl<B: BitLength, C: AsRef<[BlockCounters]>> NumBits for Rank9<B, C> {
@@ -206,6 +431,70 @@ impl<B: BitLength, C: AsRef<[BlockCounters]>> BitCount for Rank9<B, C> {
     }
 }
 
+/// Recomputes the counters of each (or each sampled) block from the
+/// underlying bit vector, checking both the relative counters and the
+/// difference between the absolute counters of the block and of the
+/// following one.
+impl<B: AsRef<[usize]> + BitLength + Verify, C: AsRef<[BlockCounters]>> Verify for Rank9<B, C> {
+    fn verify(&self, mode: VerifyMode) -> Result<(), VerifyError> {
+        self.bits.verify(mode)?;
+
+        let num_words = self.bits.len().div_ceil(usize::BITS as usize);
+        let num_counts = num_words.div_ceil(Self::WORDS_PER_BLOCK);
+        let words = &self.bits.as_ref()[..num_words];
+        let counts = self.counts.as_ref();
+
+        if counts.len() != num_counts + 1 {
+            return Err(VerifyError::Param {
+                what: "number of counters",
+                expected: num_counts + 1,
+                found: counts.len(),
+            });
+        }
+        if counts[0].absolute != 0 {
+            return Err(VerifyError::Value {
+                what: "absolute counter",
+                index: 0,
+                expected: 0,
+                found: counts[0].absolute,
+            });
+        }
+
+        for block in mode.indices(num_counts) {
+            let block_words = &words
+                [block * Self::WORDS_PER_BLOCK..num_words.min((block + 1) * Self::WORDS_PER_BLOCK)];
+            let mut expected = BlockCounters::default();
+            let mut block_ones = block_words[0].count_ones() as usize;
+            for j in 1..8 {
+                expected.set_rel(j, block_ones);
+                if j < block_words.len() {
+                    block_ones += block_words[j].count_ones() as usize;
+                }
+            }
+
+            if counts[block].relative != expected.relative {
+                return Err(VerifyError::Value {
+                    what: "relative counters",
+                    index: block,
+                    expected: expected.relative,
+                    found: counts[block].relative,
+                });
+            }
+            let expected = counts[block].absolute.wrapping_add(block_ones);
+            if counts[block + 1].absolute != expected {
+                return Err(VerifyError::Value {
+                    what: "absolute counter",
+                    index: block + 1,
+                    expected,
+                    found: counts[block + 1].absolute,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl<B: AsRef<[usize]> + BitLength, C: AsRef<[BlockCounters]>> RankUnchecked for Rank9<B, C> {
     /// # Safety
     ///
@@ -251,7 +540,20 @@ impl<B: AsRef<[usize]> + BitLength, C: AsRef<[BlockCounters]>> RankUnchecked for
     }
 }
 
-impl<B: AsRef<[usize]> + BitLength, C: AsRef<[BlockCounters]>> Rank for Rank9<B, C> {}
+impl<B: AsRef<[usize]> + BitLength, C: AsRef<[BlockCounters]>> Rank for Rank9<B, C> {
+    fn rank_batch(&self, pos: &[usize], ranks: &mut [usize]) {
+        prefetched_batch(
+            pos,
+            ranks,
+            |pos| {
+                let word_pos = pos / usize::BITS as usize;
+                prefetch_index(self.counts.as_ref(), word_pos / Self::WORDS_PER_BLOCK);
+                prefetch_index(self.bits.as_ref(), word_pos);
+            },
+            |pos| self.rank(pos),
+        )
+    }
+}
 impl<B: AsRef<[usize]> + BitLength, C: AsRef<[BlockCounters]>> RankZero for Rank9<B, C> {}
 
 #[cfg(test)]
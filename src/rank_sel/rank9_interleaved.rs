@@ -0,0 +1,355 @@
+/*
+ *
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use crate::prelude::*;
+use crate::utils::word_ops::select_in_word;
+use epserde::*;
+use mem_dbg::*;
+use std::ops::Index;
+
+/// A ranking structure using 25% of additional space in which counters are
+/// stored inline with the bits, so that ranking touches at most two adjacent
+/// cache lines.
+///
+/// This structure uses the same counters of [`Rank9`], but instead of
+/// storing them in a separate array, each pair of absolute and relative
+/// counters is stored immediately before the 512-bit block it refers to. As a
+/// result, the counters and the word containing the position to rank lie in
+/// an 80-byte block, so a rank operation touches at most two adjacent cache
+/// lines, which are usually fetched together by the adjacent-line prefetcher.
+/// Blocks are not padded to the cache-line size, so the bound holds when the
+/// backend is 16-byte aligned, as it happens with the system allocator on
+/// 64-bit platforms; otherwise, the two counters might straddle a cache-line
+/// boundary, and a third line might be touched.
+/// This layout is the one originally proposed in the [paper describing
+/// `Rank9`](Rank9), and it is particularly useful on very large bit vectors,
+/// where rank latency is dominated by memory misses.
+///
+/// The price to pay is that the bits are no longer contiguous in memory: thus,
+/// this structure does not implement `AsRef<[usize]>`. Word-based access is
+/// provided by the [`BitWords`] trait, which is sufficient to build selection
+/// structures such as [`SelectAdapt`] and [`SelectZeroAdapt`] on top of it.
+/// Moreover, the structure implements [`SelectHinted`] and
+/// [`SelectZeroHinted`] using the inline counters to skip whole blocks.
+///
+/// The structure is built from any bit vector implementing [`BitWords`] and
+/// [`BitLength`], such as a [`BitVec`], which is not needed anymore after
+/// construction.
+///
+/// # Examples
+///
+/// ```rust
+/// use sux::bit_vec;
+/// use sux::prelude::*;
+///
+/// let rank9 = Rank9Interleaved::new(bit_vec![1, 0, 1, 1, 0, 1, 0, 1]);
+/// assert_eq!(rank9.rank(0), 0);
+/// assert_eq!(rank9.rank(1), 1);
+/// assert_eq!(rank9.rank(2), 1);
+/// assert_eq!(rank9.rank(3), 2);
+/// assert_eq!(rank9.rank(4), 3);
+/// assert_eq!(rank9.rank(5), 3);
+/// assert_eq!(rank9.rank(6), 4);
+/// assert_eq!(rank9.rank(7), 4);
+/// assert_eq!(rank9.rank(8), 5);
+///
+/// // Access to the bits is provided directly
+/// assert_eq!(rank9[0], true);
+/// assert_eq!(rank9[1], false);
+///
+/// // Selection structures can be layered on top of it
+/// let sel = SelectZeroAdapt::new(SelectAdapt::new(rank9, 3), 3);
+/// assert_eq!(sel.rank(8), 5);
+/// assert_eq!(sel.select(2), Some(3));
+/// assert_eq!(sel.select_zero(1), Some(4));
+/// ```
+#[derive(Epserde, Debug, Clone, MemDbg, MemSize)]
+pub struct Rank9Interleaved<B = Box<[usize]>> {
+    /// For each block, the absolute counter, the relative counters and the
+    /// eight words of the block. At the end, the total number of ones,
+    /// followed by two zero words.
+    data: B,
+    /// The length of the bit vector in bits.
+    len: usize,
+}
+
+impl<B> Rank9Interleaved<B> {
+    const WORDS_PER_BLOCK: usize = 8;
+    /// The number of words used by a block, counters included.
+    const BLOCK_SIZE: usize = Self::WORDS_PER_BLOCK + 2;
+
+    /// Returns the position in the data of the word of given index.
+    #[inline(always)]
+    fn word_pos(index: usize) -> usize {
+        (index / Self::WORDS_PER_BLOCK) * Self::BLOCK_SIZE + 2 + index % Self::WORDS_PER_BLOCK
+    }
+
+    /// Returns the backend and the length in bits.
+    pub fn into_raw_parts(self) -> (B, usize) {
+        (self.data, self.len)
+    }
+
+    /// Creates a new structure from a backend and a length.
+    ///
+    /// # Safety
+    ///
+    /// `data` and `len` must come from a call to
+    /// [`into_raw_parts`](Rank9Interleaved::into_raw_parts).
+    pub unsafe fn from_raw_parts(data: B, len: usize) -> Self {
+        Self { data, len }
+    }
+
+    /// Returns the number of bits in the underlying bit vector.
+    ///
+    /// This method is equivalent to
+    /// [`BitLength::len`](crate::traits::BitLength::len), but it is provided to
+    /// reduce ambiguity in method resolution.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<B: AsRef<[usize]>> Rank9Interleaved<B> {
+    /// Returns the bit of given position.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than or equal to the length of the bit
+    /// vector.
+    #[inline]
+    pub fn get(&self, index: usize) -> bool {
+        if index >= self.len {
+            panic!("Bit index out of bounds: {} >= {}", index, self.len)
+        }
+        let word = self.data.as_ref()[Self::word_pos(index / usize::BITS as usize)];
+        (word >> (index % usize::BITS as usize)) & 1 != 0
+    }
+
+    /// Returns a copy of the underlying bit vector.
+    pub fn to_bit_vec(&self) -> BitVec {
+        let num_words = self.len.div_ceil(usize::BITS as usize);
+        let bits: Vec<usize> = (0..num_words).map(|i| self.word(i)).collect();
+        // SAFETY: we have exactly the number of words necessary for len bits
+        unsafe { BitVec::from_raw_parts(bits, self.len) }
+    }
+}
+
+impl Rank9Interleaved<Box<[usize]>> {
+    /// Creates a new structure from a given bit vector.
+    pub fn new<T: BitWords + BitLength>(bits: T) -> Self {
+        let len = bits.len();
+        let num_words = len.div_ceil(usize::BITS as usize);
+        let num_blocks = num_words.div_ceil(Self::WORDS_PER_BLOCK);
+
+        let mut data = Vec::with_capacity(num_blocks * Self::BLOCK_SIZE + 3);
+        let mut num_ones = 0;
+
+        for block in 0..num_blocks {
+            let mut counts = BlockCounters {
+                absolute: num_ones,
+                relative: 0,
+            };
+            let counts_pos = data.len();
+            data.extend([0, 0]);
+
+            for j in 0..Self::WORDS_PER_BLOCK {
+                let i = block * Self::WORDS_PER_BLOCK + j;
+                if j != 0 {
+                    counts.set_rel(j, num_ones - counts.absolute);
+                }
+                // The last block is padded with zeros, and we clear the
+                // unused bits of the last word
+                let word = if i < num_words {
+                    let residual = len - i * usize::BITS as usize;
+                    if residual < usize::BITS as usize {
+                        bits.word(i) & ((1 << residual) - 1)
+                    } else {
+                        bits.word(i)
+                    }
+                } else {
+                    0
+                };
+                num_ones += word.count_ones() as usize;
+                data.push(word);
+            }
+
+            data[counts_pos] = counts.absolute;
+            data[counts_pos + 1] = counts.relative;
+        }
+
+        // The final counters contain the number of ones; the additional zero
+        // word makes it possible to rank at the length of the bit vector
+        // when it is a multiple of the block size.
+        data.extend([num_ones, 0, 0]);
+
+        Self {
+            data: data.into(),
+            len,
+        }
+    }
+}
+
+impl<B> BitLength for Rank9Interleaved<B> {
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<B: AsRef<[usize]>> BitWords for Rank9Interleaved<B> {
+    /// Returns the number of words of the bit vector, including the zero
+    /// words padding the last block.
+    #[inline(always)]
+    fn num_words(&self) -> usize {
+        self.len
+            .div_ceil(usize::BITS as usize * Self::WORDS_PER_BLOCK)
+            * Self::WORDS_PER_BLOCK
+    }
+
+    #[inline(always)]
+    fn word(&self, index: usize) -> usize {
+        assert!(
+            index < self.num_words(),
+            "Word index out of bounds: {} >= {}",
+            index,
+            self.num_words()
+        );
+        self.data.as_ref()[Self::word_pos(index)]
+    }
+}
+
+impl<B: AsRef<[usize]>> NumBits for Rank9Interleaved<B> {
+    #[inline(always)]
+    fn num_ones(&self) -> usize {
+        let data = self.data.as_ref();
+        // SAFETY: The final counters are always present
+        unsafe { *data.get_unchecked(data.len() - 3) }
+    }
+}
+
+impl<B: AsRef<[usize]>> BitCount for Rank9Interleaved<B> {
+    #[inline(always)]
+    fn count_ones(&self) -> usize {
+        self.num_ones()
+    }
+}
+
+impl<B: AsRef<[usize]>> RankUnchecked for Rank9Interleaved<B> {
+    /// # Safety
+    ///
+    /// The implementation of [`RankUnchecked`] for [`Rank9Interleaved`] has a
+    /// weakened safety requirement: it is always possible to call this method
+    /// with `pos` equal to [the length of the underlying bit
+    /// vector](crate::traits::BitLength::len).
+    #[inline(always)]
+    unsafe fn rank_unchecked(&self, pos: usize) -> usize {
+        let word_pos = pos / usize::BITS as usize;
+        let bit_pos = pos % usize::BITS as usize;
+        let block = word_pos / Self::WORDS_PER_BLOCK;
+        let offset = word_pos % Self::WORDS_PER_BLOCK;
+        let data = self.data.as_ref();
+        let base = block * Self::BLOCK_SIZE;
+
+        let absolute = *data.get_unchecked(base);
+        let relative = *data.get_unchecked(base + 1);
+        let word = *data.get_unchecked(base + 2 + offset);
+
+        absolute
+            + ((relative >> (9 * (offset ^ 7))) & 0x1FF)
+            + (word & ((1 << bit_pos) - 1)).count_ones() as usize
+    }
+}
+
+impl<B: AsRef<[usize]>> Rank for Rank9Interleaved<B> {}
+impl<B: AsRef<[usize]>> RankZero for Rank9Interleaved<B> {}
+
+impl<B: AsRef<[usize]>> SelectHinted for Rank9Interleaved<B> {
+    unsafe fn select_hinted(&self, rank: usize, hint_pos: usize, hint_rank: usize) -> usize {
+        let data = self.data.as_ref();
+        let mut word_index = hint_pos / usize::BITS as usize;
+        let mut block = word_index / Self::WORDS_PER_BLOCK;
+        let mut residual;
+        let mut word;
+
+        if *data.get_unchecked((block + 1) * Self::BLOCK_SIZE) <= rank {
+            // Skip whole blocks; the final counters guarantee termination
+            block += 1;
+            while *data.get_unchecked((block + 1) * Self::BLOCK_SIZE) <= rank {
+                block += 1;
+            }
+            word_index = block * Self::WORDS_PER_BLOCK;
+            residual = rank - *data.get_unchecked(block * Self::BLOCK_SIZE);
+            word = *data.get_unchecked(block * Self::BLOCK_SIZE + 2);
+        } else {
+            let bit_index = hint_pos % usize::BITS as usize;
+            residual = rank - hint_rank;
+            word = (*data.get_unchecked(Self::word_pos(word_index)) >> bit_index) << bit_index;
+        }
+
+        loop {
+            let bit_count = word.count_ones() as usize;
+            if residual < bit_count {
+                return word_index * usize::BITS as usize + select_in_word(word, residual);
+            }
+            word_index += 1;
+            word = *data.get_unchecked(Self::word_pos(word_index));
+            residual -= bit_count;
+        }
+    }
+}
+
+impl<B: AsRef<[usize]>> SelectZeroHinted for Rank9Interleaved<B> {
+    unsafe fn select_zero_hinted(&self, rank: usize, hint_pos: usize, hint_rank: usize) -> usize {
+        const BLOCK_BITS: usize = usize::BITS as usize * 8;
+        let data = self.data.as_ref();
+        let mut word_index = hint_pos / usize::BITS as usize;
+        let mut block = word_index / Self::WORDS_PER_BLOCK;
+        let mut residual;
+        let mut word;
+
+        // The number of zeros before a block, padding included
+        let zeros_before =
+            |block: usize| block * BLOCK_BITS - *data.get_unchecked(block * Self::BLOCK_SIZE);
+
+        if zeros_before(block + 1) <= rank {
+            // Skip whole blocks; the final counters guarantee termination
+            block += 1;
+            while zeros_before(block + 1) <= rank {
+                block += 1;
+            }
+            word_index = block * Self::WORDS_PER_BLOCK;
+            residual = rank - zeros_before(block);
+            word = !*data.get_unchecked(block * Self::BLOCK_SIZE + 2);
+        } else {
+            let bit_index = hint_pos % usize::BITS as usize;
+            residual = rank - hint_rank;
+            word = (!*data.get_unchecked(Self::word_pos(word_index)) >> bit_index) << bit_index;
+        }
+
+        loop {
+            let bit_count = word.count_ones() as usize;
+            if residual < bit_count {
+                return word_index * usize::BITS as usize + select_in_word(word, residual);
+            }
+            word_index += 1;
+            word = !*data.get_unchecked(Self::word_pos(word_index));
+            residual -= bit_count;
+        }
+    }
+}
+
+impl<B: AsRef<[usize]>> Index<usize> for Rank9Interleaved<B> {
+    type Output = bool;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        match self.get(index) {
+            false => &false,
+            true => &true,
+        }
+    }
+}
@@ -9,16 +9,20 @@
 use ambassador::{delegatable_trait, Delegate};
 use epserde::*;
 use mem_dbg::*;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 use std::ptr::{addr_of, addr_of_mut, read_unaligned, write_unaligned};
 
 use crate::{
     prelude::{BitLength, BitVec, Rank, RankHinted, RankUnchecked, RankZero},
-    traits::{BitCount, NumBits},
+    traits::{bit_field_slice::panic_if_out_of_bounds, BitCount, NumBits},
+    utils::{prefetch_index, prefetched_batch},
 };
 
 use crate::ambassador_impl_AsRef;
 use crate::ambassador_impl_Index;
 use crate::traits::rank_sel::ambassador_impl_BitLength;
+use crate::traits::rank_sel::ambassador_impl_BitWords;
 use crate::traits::rank_sel::ambassador_impl_RankHinted;
 use crate::traits::rank_sel::ambassador_impl_Select;
 use crate::traits::rank_sel::ambassador_impl_SelectHinted;
@@ -117,6 +121,7 @@ pub trait SmallCounters<const NUM_U32S: usize, const COUNTER_WIDTH: usize> {
 /// assert_eq!(rank_small[7], true);
 #[derive(Epserde, Debug, Clone, MemDbg, MemSize, Delegate)]
 #[delegate(AsRef<[usize]>, target = "bits")]
+#[delegate(crate::traits::rank_sel::BitWords, target = "bits")]
 #[delegate(Index<usize>, target = "bits")]
 #[delegate(crate::traits::rank_sel::BitLength, target = "bits")]
 #[delegate(crate::traits::rank_sel::RankHinted<64>, target = "bits")]
@@ -381,6 +386,232 @@ macro_rules! impl_rank_small {
                     num_ones: past_ones,
                 }
             }
+
+            /// Creates a new RankSmall structure from a given bit vector,
+            /// computing in parallel the counters of each block.
+            ///
+            /// The result is identical to that of [`new`](RankSmall::new).
+            #[cfg(feature = "rayon")]
+            pub fn par_new(bits: B) -> Self
+            where
+                B: Sync,
+            {
+                let num_bits = bits.len();
+                let num_words = num_bits.div_ceil(64 as usize);
+                let num_upper_counts = num_bits.div_ceil(1usize << 32);
+
+                // We first compute the relative counters, temporarily storing
+                // in the absolute counter the number of ones in the block
+                let mut counts = bits.as_ref()[..num_words]
+                    .par_chunks(Self::WORDS_PER_BLOCK)
+                    .map(|block| {
+                        let mut count = Block32Counters::<$NUM_U32S, $COUNTER_WIDTH>::default();
+                        let mut block_ones = block[0].count_ones() as usize;
+                        for j in 1..Self::WORDS_PER_BLOCK {
+                            #[allow(clippy::modulo_one)]
+                            if j % Self::WORDS_PER_SUBBLOCK == 0 {
+                                count.set_rel(j / Self::WORDS_PER_SUBBLOCK, block_ones);
+                            }
+                            if j < block.len() {
+                                block_ones += block[j].count_ones() as usize;
+                            }
+                        }
+                        count.absolute = block_ones as u32;
+                        count
+                    })
+                    .collect::<Vec<_>>();
+
+                let mut upper_counts = Vec::with_capacity(num_upper_counts);
+                let mut past_ones = 0;
+                let mut upper_count = 0;
+
+                for (b, count) in counts.iter_mut().enumerate() {
+                    if (b * Self::WORDS_PER_BLOCK) % (1usize << 26) == 0 {
+                        upper_count = past_ones;
+                        upper_counts.push(upper_count);
+                    }
+                    let block_ones = count.absolute as usize;
+                    count.absolute = (past_ones - upper_count) as u32;
+                    past_ones += block_ones;
+                }
+
+                assert_eq!(upper_counts.len(), num_upper_counts);
+
+                Self {
+                    bits,
+                    upper_counts: upper_counts.into_boxed_slice(),
+                    counts: counts.into_boxed_slice(),
+                    num_ones: past_ones,
+                }
+            }
+        }
+        impl<
+                B: AsRef<[usize]> + AsMut<[usize]> + BitLength,
+                C1: AsMut<[usize]>,
+                C2: AsMut<[Block32Counters<$NUM_U32S, $COUNTER_WIDTH>]>,
+            > RankSmall<$NUM_U32S, $COUNTER_WIDTH, B, C1, C2>
+        {
+            const BLOCKS_PER_UPPER_COUNT: usize = (1 << 26) / Self::WORDS_PER_BLOCK;
+
+            /// Returns the index of the block containing the bit of given
+            /// position.
+            ///
+            /// This is the block that must be passed to
+            /// [`recompute_from`](RankSmall::recompute_from) after a call to
+            /// [`set_lazy`](RankSmall::set_lazy).
+            #[inline(always)]
+            pub fn block_of(&self, pos: usize) -> usize {
+                pos / (64 * Self::WORDS_PER_BLOCK)
+            }
+
+            /// Sets the bit of given position to the given value, updating
+            /// the counters.
+            ///
+            /// The relative counters of the block containing the bit are
+            /// recomputed, and the absolute counters of the following blocks
+            /// are adjusted, so the cost of an update is linear in the number
+            /// of following blocks. If you need to change many bits, use
+            /// [`set_lazy`](RankSmall::set_lazy) followed by a single call to
+            /// [`recompute_from`](RankSmall::recompute_from).
+            ///
+            /// Selection structures built on this structure are not updated:
+            /// since they take ownership of the ranking structure, you must
+            /// recover it with `into_inner`, update it, and build them again.
+            ///
+            /// # Panics
+            ///
+            /// If `pos` is out of bounds.
+            pub fn set(&mut self, pos: usize, value: bool) {
+                if !self.set_bit(pos, value) {
+                    return;
+                }
+
+                let block = self.block_of(pos);
+                let (count, _) = Self::block_counters(self.block_words(block));
+                let counts = self.counts.as_mut();
+                counts[block].relative = count.relative;
+
+                let upper = block / Self::BLOCKS_PER_UPPER_COUNT;
+                let end = counts.len().min((upper + 1) * Self::BLOCKS_PER_UPPER_COUNT);
+                let upper_counts = self.upper_counts.as_mut();
+                if value {
+                    counts[block + 1..end]
+                        .iter_mut()
+                        .for_each(|c| c.absolute += 1);
+                    upper_counts[upper + 1..].iter_mut().for_each(|c| *c += 1);
+                    self.num_ones += 1;
+                } else {
+                    counts[block + 1..end]
+                        .iter_mut()
+                        .for_each(|c| c.absolute -= 1);
+                    upper_counts[upper + 1..].iter_mut().for_each(|c| *c -= 1);
+                    self.num_ones -= 1;
+                }
+            }
+
+            /// Sets the bit of given position to the given value without
+            /// updating the counters.
+            ///
+            /// After a batch of calls to this method, counters must be brought
+            /// up to date by calling
+            /// [`recompute_from`](RankSmall::recompute_from) with the smallest
+            /// [block](RankSmall::block_of) containing a modified bit.
+            ///
+            /// # Panics
+            ///
+            /// If `pos` is out of bounds.
+            ///
+            /// # Safety
+            ///
+            /// Until [`recompute_from`](RankSmall::recompute_from) is called,
+            /// the counters are inconsistent with the underlying bit vector,
+            /// and the results of all ranking operations are unspecified.
+            pub unsafe fn set_lazy(&mut self, pos: usize, value: bool) {
+                self.set_bit(pos, value);
+            }
+
+            /// Recomputes the counters of all blocks starting from the given
+            /// one.
+            ///
+            /// The counters of blocks preceding `block` are assumed to be
+            /// correct. The cost is linear in the number of words from the
+            /// start of `block` to the end of the bit vector.
+            ///
+            /// As in the case of [`set`](RankSmall::set), selection
+            /// structures built on this structure must be rebuilt.
+            ///
+            /// # Panics
+            ///
+            /// If `block` is greater than the number of blocks.
+            pub fn recompute_from(&mut self, block: usize) {
+                let num_blocks = self.counts.as_mut().len();
+                assert!(
+                    block <= num_blocks,
+                    "Block out of bounds: {} > {}",
+                    block,
+                    num_blocks
+                );
+                if block == num_blocks {
+                    return;
+                }
+
+                let upper = block / Self::BLOCKS_PER_UPPER_COUNT;
+                let mut upper_count = self.upper_counts.as_mut()[upper];
+                let mut past_ones = upper_count + self.counts.as_mut()[block].absolute as usize;
+
+                for b in block..num_blocks {
+                    if b % Self::BLOCKS_PER_UPPER_COUNT == 0 {
+                        upper_count = past_ones;
+                        self.upper_counts.as_mut()[b / Self::BLOCKS_PER_UPPER_COUNT] = upper_count;
+                    }
+                    let (mut count, block_ones) = Self::block_counters(self.block_words(b));
+                    count.absolute = (past_ones - upper_count) as u32;
+                    self.counts.as_mut()[b] = count;
+                    past_ones += block_ones;
+                }
+
+                self.num_ones = past_ones;
+            }
+
+            /// Sets a bit, returning whether its value has changed.
+            fn set_bit(&mut self, pos: usize, value: bool) -> bool {
+                panic_if_out_of_bounds!(pos, self.bits.len());
+                let word = &mut self.bits.as_mut()[pos / 64];
+                let mask = 1 << (pos % 64);
+                let old = *word;
+                if value {
+                    *word |= mask;
+                } else {
+                    *word &= !mask;
+                }
+                old != *word
+            }
+
+            /// Returns the words of the given block.
+            fn block_words(&self, block: usize) -> &[usize] {
+                let num_words = self.bits.len().div_ceil(64);
+                let start = block * Self::WORDS_PER_BLOCK;
+                &self.bits.as_ref()[start..num_words.min(start + Self::WORDS_PER_BLOCK)]
+            }
+
+            /// Returns the counters of a block, given its words, with a zero
+            /// absolute counter, and its number of ones.
+            fn block_counters(
+                block: &[usize],
+            ) -> (Block32Counters<$NUM_U32S, $COUNTER_WIDTH>, usize) {
+                let mut count = Block32Counters::<$NUM_U32S, $COUNTER_WIDTH>::default();
+                let mut block_ones = block[0].count_ones() as usize;
+                for j in 1..Self::WORDS_PER_BLOCK {
+                    #[allow(clippy::modulo_one)]
+                    if j % Self::WORDS_PER_SUBBLOCK == 0 {
+                        count.set_rel(j / Self::WORDS_PER_SUBBLOCK, block_ones);
+                    }
+                    if j < block.len() {
+                        block_ones += block[j].count_ones() as usize;
+                    }
+                }
+                (count, block_ones)
+            }
         }
         impl<
                 B: AsRef<[usize]> + BitLength + RankHinted<64>,
@@ -423,11 +654,28 @@ impl_rank_small!(1; 10);
 impl_rank_small!(1; 11);
 impl_rank_small!(3; 13);
 
-impl<const NUM_U32S: usize, const COUNTER_WIDTH: usize, B, C1, C2> Rank
-    for RankSmall<NUM_U32S, COUNTER_WIDTH, B, C1, C2>
+impl<
+        const NUM_U32S: usize,
+        const COUNTER_WIDTH: usize,
+        B: AsRef<[usize]>,
+        C1,
+        C2: AsRef<[Block32Counters<NUM_U32S, COUNTER_WIDTH>]>,
+    > Rank for RankSmall<NUM_U32S, COUNTER_WIDTH, B, C1, C2>
 where
     RankSmall<NUM_U32S, COUNTER_WIDTH, B, C1, C2>: BitLength + NumBits + RankUnchecked,
 {
+    fn rank_batch(&self, pos: &[usize], ranks: &mut [usize]) {
+        prefetched_batch(
+            pos,
+            ranks,
+            |pos| {
+                let word_pos = pos / usize::BITS as usize;
+                prefetch_index(self.counts.as_ref(), word_pos / Self::WORDS_PER_BLOCK);
+                prefetch_index(self.bits.as_ref(), word_pos);
+            },
+            |pos| self.rank(pos),
+        )
+    }
 }
 
 impl<const NUM_U32S: usize, const COUNTER_WIDTH: usize, B, C1, C2> RankZero
@@ -6,18 +6,18 @@
  * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
  */
 
-use super::rank9::BlockCounters;
-use super::Rank9;
+use super::{BlockCounters, Rank9, Rank9Counters};
+use crate::utils::word_ops::select_in_word;
 use crate::{
     prelude::SelectUnchecked,
-    traits::{BitLength, NumBits, Select},
+    traits::{BitLength, NextOne, NumBits, PrevOne, Rank, Select},
+    utils::{prefetch_index, prefetched_batch},
 };
 use ambassador::Delegate;
-use common_traits::SelectInWord;
 use epserde::Epserde;
 use mem_dbg::{MemDbg, MemSize};
 
-const ONES_STEP_9: usize = (1usize << 0)
+pub(super) const ONES_STEP_9: usize = (1usize << 0)
     | (1usize << 9)
     | (1usize << 18)
     | (1usize << 27)
@@ -25,10 +25,11 @@ const ONES_STEP_9: usize = (1usize << 0)
     | (1usize << 45)
     | (1usize << 54);
 
-const MSBS_STEP_9: usize = 0x100usize * ONES_STEP_9;
+pub(super) const MSBS_STEP_9: usize = 0x100usize * ONES_STEP_9;
 
-const ONES_STEP_16: usize = (1usize << 0) | (1usize << 16) | (1usize << 32) | (1usize << 48);
-const MSBS_STEP_16: usize = 0x8000usize * ONES_STEP_16;
+pub(super) const ONES_STEP_16: usize =
+    (1usize << 0) | (1usize << 16) | (1usize << 32) | (1usize << 48);
+pub(super) const MSBS_STEP_16: usize = 0x8000usize * ONES_STEP_16;
 
 macro_rules! ULEQ_STEP_9 {
     ($x:ident, $y:ident) => {
@@ -43,11 +44,18 @@ macro_rules! ULEQ_STEP_16 {
     };
 }
 
+pub(super) use ULEQ_STEP_16;
+pub(super) use ULEQ_STEP_9;
+
 use crate::ambassador_impl_AsRef;
 use crate::ambassador_impl_Index;
+use crate::rank_sel::ambassador_impl_Rank9Counters;
 use crate::traits::rank_sel::ambassador_impl_BitCount;
 use crate::traits::rank_sel::ambassador_impl_BitLength;
+use crate::traits::rank_sel::ambassador_impl_BitWords;
+use crate::traits::rank_sel::ambassador_impl_NextZero;
 use crate::traits::rank_sel::ambassador_impl_NumBits;
+use crate::traits::rank_sel::ambassador_impl_PrevZero;
 use crate::traits::rank_sel::ambassador_impl_Rank;
 use crate::traits::rank_sel::ambassador_impl_RankHinted;
 use crate::traits::rank_sel::ambassador_impl_RankUnchecked;
@@ -116,6 +124,7 @@ use std::ops::Index;
 
 #[derive(Epserde, Debug, Clone, MemDbg, MemSize, Delegate)]
 #[delegate(AsRef<[usize]>, target = "rank9")]
+#[delegate(crate::traits::rank_sel::BitWords, target = "rank9")]
 #[delegate(Index<usize>, target = "rank9")]
 #[delegate(crate::traits::rank_sel::BitCount, target = "rank9")]
 #[delegate(crate::traits::rank_sel::BitLength, target = "rank9")]
@@ -128,6 +137,9 @@ use std::ops::Index;
 #[delegate(crate::traits::rank_sel::SelectZero, target = "rank9")]
 #[delegate(crate::traits::rank_sel::SelectZeroHinted, target = "rank9")]
 #[delegate(crate::traits::rank_sel::SelectZeroUnchecked, target = "rank9")]
+#[delegate(crate::rank_sel::Rank9Counters, target = "rank9")]
+#[delegate(crate::traits::rank_sel::NextZero, target = "rank9")]
+#[delegate(crate::traits::rank_sel::PrevZero, target = "rank9")]
 pub struct Select9<R = Rank9, I = Box<[usize]>> {
     rank9: R,
     inventory: I,
@@ -141,8 +153,8 @@ impl<R, I> Select9<R, I> {
         self.rank9
     }
 
-    const LOG2_ZEROS_PER_INVENTORY: usize = 9;
-    const ONES_PER_INVENTORY: usize = 1 << Self::LOG2_ZEROS_PER_INVENTORY;
+    const LOG2_ONES_PER_INVENTORY: usize = 9;
+    const ONES_PER_INVENTORY: usize = 1 << Self::LOG2_ONES_PER_INVENTORY;
 }
 
 impl<R: BitLength, I> Select9<R, I> {
@@ -157,8 +169,8 @@ impl<R: BitLength, I> Select9<R, I> {
     }
 }
 
-impl<B: AsRef<[usize]> + BitLength, C: AsRef<[BlockCounters]>> Select9<Rank9<B, C>, Box<[usize]>> {
-    pub fn new(rank9: Rank9<B, C>) -> Self {
+impl<R: Rank9Counters + AsRef<[usize]> + BitLength + NumBits> Select9<R, Box<[usize]>> {
+    pub fn new(rank9: R) -> Self {
         let num_bits = rank9.len();
         let num_words = (num_bits + 63) / 64;
         let inventory_size = rank9.num_ones().div_ceil(Self::ONES_PER_INVENTORY);
@@ -172,11 +184,11 @@ impl<B: AsRef<[usize]> + BitLength, C: AsRef<[BlockCounters]>> Select9<Rank9<B,
         // construct the inventory
         let mut curr_num_ones = 0;
         let mut next_quantum = 0;
-        for (i, word) in rank9.bits.as_ref().iter().copied().enumerate() {
+        for (i, word) in rank9.as_ref().iter().copied().enumerate() {
             let ones_in_word = word.count_ones() as usize;
 
             while curr_num_ones + ones_in_word > next_quantum {
-                let in_word_index = word.select_in_word(next_quantum - curr_num_ones);
+                let in_word_index = select_in_word(word, next_quantum - curr_num_ones);
                 let index = (i * u64::BITS as usize) + in_word_index;
 
                 inventory.push(index);
@@ -190,7 +202,7 @@ impl<B: AsRef<[usize]> + BitLength, C: AsRef<[BlockCounters]>> Select9<Rank9<B,
         let inventory = inventory.into_boxed_slice();
 
         let iter = 0..inventory_size;
-        let counts = rank9.counts.as_ref();
+        let counts = rank9.counts();
 
         // construct the subinventory
         iter.for_each(|inventory_idx| {
@@ -252,11 +264,11 @@ impl<B: AsRef<[usize]> + BitLength, C: AsRef<[BlockCounters]>> Select9<Rank9<B,
                 // clean up the lower bits
                 let mut word_idx = inventory[inventory_idx] / usize::BITS as usize;
                 let bit_idx = inventory[inventory_idx] % usize::BITS as usize;
-                let mut word = (rank9.bits.as_ref()[word_idx] >> bit_idx) << bit_idx;
+                let mut word = (rank9.as_ref()[word_idx] >> bit_idx) << bit_idx;
 
                 let start_bit_idx = inventory[inventory_idx];
                 let end_bit_idx = inventory[inventory_idx + 1];
-                let end_word_idx = end_bit_idx.div_ceil(u64::BITS as usize);
+                let end_word_idx = end_bit_idx.div_ceil(u64::BITS as usize).min(num_words);
                 let mut subinventory_idx = 0;
                 'outer: loop {
                     while word != 0 {
@@ -302,7 +314,7 @@ impl<B: AsRef<[usize]> + BitLength, C: AsRef<[BlockCounters]>> Select9<Rank9<B,
                     }
 
                     // read the next word
-                    word = rank9.bits.as_ref()[word_idx];
+                    word = rank9.as_ref()[word_idx];
                 }
             }
         });
@@ -317,11 +329,11 @@ impl<B: AsRef<[usize]> + BitLength, C: AsRef<[BlockCounters]>> Select9<Rank9<B,
     }
 }
 
-impl<B: AsRef<[usize]> + BitLength, C: AsRef<[BlockCounters]>, I: AsRef<[usize]>> SelectUnchecked
-    for Select9<Rank9<B, C>, I>
+impl<R: Rank9Counters + AsRef<[usize]> + BitLength, I: AsRef<[usize]>> SelectUnchecked
+    for Select9<R, I>
 {
     unsafe fn select_unchecked(&self, rank: usize) -> usize {
-        let inventory_index_left = rank >> Self::LOG2_ZEROS_PER_INVENTORY;
+        let inventory_index_left = rank >> Self::LOG2_ONES_PER_INVENTORY;
 
         debug_assert!(inventory_index_left <= self.inventory_size);
         let inventory_left = *self.inventory.as_ref().get_unchecked(inventory_index_left);
@@ -337,7 +349,7 @@ impl<B: AsRef<[usize]> + BitLength, C: AsRef<[BlockCounters]>, I: AsRef<[usize]>
         let subinv_pos = block_left / 4;
         let subinv_ref = self.subinventory.as_ref();
 
-        let counts = self.rank9.counts.as_ref();
+        let counts = self.rank9.counts();
 
         let mut count_left;
         let rank_in_block;
@@ -345,14 +357,14 @@ impl<B: AsRef<[usize]> + BitLength, C: AsRef<[BlockCounters]>, I: AsRef<[usize]>
         match span {
             0..=1 => {
                 block_left &= !7;
-                count_left = block_left / Rank9::<B, C>::WORDS_PER_BLOCK;
+                count_left = block_left / <Rank9>::WORDS_PER_BLOCK;
 
                 debug_assert!(rank < counts.get_unchecked(count_left + 1).absolute);
                 rank_in_block = rank - counts.get_unchecked(count_left).absolute;
             }
             2..=15 => {
                 block_left &= !7;
-                count_left = block_left / Rank9::<B, C>::WORDS_PER_BLOCK;
+                count_left = block_left / <Rank9>::WORDS_PER_BLOCK;
                 let rank_in_superblock = rank - counts.get_unchecked(count_left).absolute;
 
                 let rank_in_superblock_step_16 = rank_in_superblock * ONES_STEP_16;
@@ -375,7 +387,7 @@ impl<B: AsRef<[usize]> + BitLength, C: AsRef<[BlockCounters]>, I: AsRef<[usize]>
             }
             16..=127 => {
                 block_left &= !7;
-                count_left = block_left / Rank9::<B, C>::WORDS_PER_BLOCK;
+                count_left = block_left / <Rank9>::WORDS_PER_BLOCK;
                 let rank_in_superblock = rank - counts.get_unchecked(count_left).absolute;
                 let rank_in_superblock_step_16 = rank_in_superblock * ONES_STEP_16;
 
@@ -423,7 +435,7 @@ impl<B: AsRef<[usize]> + BitLength, C: AsRef<[BlockCounters]>, I: AsRef<[usize]>
                 return *s.get_unchecked(rank % Self::ONES_PER_INVENTORY) as usize + inventory_left;
             }
             _ => {
-                return *subinv_ref.get_unchecked(rank % Self::ONES_PER_INVENTORY);
+                return *subinv_ref.get_unchecked(subinv_pos + rank % Self::ONES_PER_INVENTORY);
             }
         }
 
@@ -436,17 +448,51 @@ impl<B: AsRef<[usize]> + BitLength, C: AsRef<[BlockCounters]>, I: AsRef<[usize]>
         let word = block_left + offset_in_block;
         let rank_in_word = rank_in_block - counts.get_unchecked(count_left).rel(offset_in_block);
 
-        word * 64
-            + self
-                .rank9
-                .bits
-                .as_ref()
-                .get_unchecked(word)
-                .select_in_word(rank_in_word)
+        word * 64 + select_in_word(*self.rank9.as_ref().get_unchecked(word), rank_in_word)
+    }
+
+    unsafe fn select_unchecked_batch(&self, ranks: &[usize], pos: &mut [usize]) {
+        prefetched_batch(
+            ranks,
+            pos,
+            |rank| self.prefetch_inventory(rank),
+            |rank| self.select_unchecked(rank),
+        )
     }
 }
 
-impl<B: AsRef<[usize]> + BitLength, C: AsRef<[BlockCounters]>, I: AsRef<[usize]>> Select
-    for Select9<Rank9<B, C>, I>
+impl<R: Rank9Counters + AsRef<[usize]> + BitLength + NumBits, I: AsRef<[usize]>> Select
+    for Select9<R, I>
 {
+    fn select_batch(&self, ranks: &[usize], pos: &mut [Option<usize>]) {
+        prefetched_batch(
+            ranks,
+            pos,
+            |rank| self.prefetch_inventory(rank),
+            |rank| self.select(rank),
+        )
+    }
+}
+
+impl<R: Rank9Counters + AsRef<[usize]> + Rank, I: AsRef<[usize]>> NextOne for Select9<R, I> {
+    fn next_one(&self, pos: usize) -> Option<usize> {
+        super::next_one_in_words(self, pos)
+    }
+}
+
+impl<R: Rank9Counters + AsRef<[usize]> + Rank, I: AsRef<[usize]>> PrevOne for Select9<R, I> {
+    fn prev_one(&self, pos: usize) -> Option<usize> {
+        super::prev_one_in_words(self, pos)
+    }
+}
+
+impl<R, I: AsRef<[usize]>> Select9<R, I> {
+    /// Prefetches the inventory entry needed to answer a query of given rank.
+    #[inline(always)]
+    fn prefetch_inventory(&self, rank: usize) {
+        prefetch_index(
+            self.inventory.as_ref(),
+            rank >> Self::LOG2_ONES_PER_INVENTORY,
+        );
+    }
 }
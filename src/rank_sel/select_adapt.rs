@@ -6,22 +6,29 @@
  * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
  */
 
+use crate::utils::word_ops::select_in_word;
 use ambassador::Delegate;
-use common_traits::SelectInWord;
 use epserde::Epserde;
 use mem_dbg::{MemDbg, MemSize};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 use std::cmp::{max, min};
+use std::ops::Range;
 
 use crate::{
-    prelude::{BitCount, BitFieldSlice, BitLength, Select, SelectHinted},
-    traits::{NumBits, SelectUnchecked},
+    prelude::{BitCount, BitFieldSlice, BitLength, BitWords, Select, SelectHinted},
+    traits::{NextOne, NumBits, PrevOne, Rank, SelectUnchecked, Verify, VerifyError, VerifyMode},
+    utils::{prefetch_index, prefetched_batch},
 };
 
 use crate::ambassador_impl_AsRef;
 use crate::ambassador_impl_Index;
 use crate::traits::rank_sel::ambassador_impl_BitCount;
 use crate::traits::rank_sel::ambassador_impl_BitLength;
+use crate::traits::rank_sel::ambassador_impl_BitWords;
+use crate::traits::rank_sel::ambassador_impl_NextZero;
 use crate::traits::rank_sel::ambassador_impl_NumBits;
+use crate::traits::rank_sel::ambassador_impl_PrevZero;
 use crate::traits::rank_sel::ambassador_impl_Rank;
 use crate::traits::rank_sel::ambassador_impl_RankHinted;
 use crate::traits::rank_sel::ambassador_impl_RankUnchecked;
@@ -209,6 +216,7 @@ use std::ops::Index;
 
 #[derive(Epserde, Debug, Clone, MemDbg, MemSize, Delegate)]
 #[delegate(AsRef<[usize]>, target = "bits")]
+#[delegate(crate::traits::rank_sel::BitWords, target = "bits")]
 #[delegate(Index<usize>, target = "bits")]
 #[delegate(crate::traits::rank_sel::BitCount, target = "bits")]
 #[delegate(crate::traits::rank_sel::BitLength, target = "bits")]
@@ -221,6 +229,8 @@ use std::ops::Index;
 #[delegate(crate::traits::rank_sel::SelectZero, target = "bits")]
 #[delegate(crate::traits::rank_sel::SelectZeroHinted, target = "bits")]
 #[delegate(crate::traits::rank_sel::SelectZeroUnchecked, target = "bits")]
+#[delegate(crate::traits::rank_sel::NextZero, target = "bits")]
+#[delegate(crate::traits::rank_sel::PrevZero, target = "bits")]
 pub struct SelectAdapt<B, I = Box<[usize]>> {
     bits: B,
     inventory: I,
@@ -300,6 +310,654 @@ impl SpanType {
     }
 }
 
+/// Appends to `positions` the position of each one of rank multiple of
+/// `ones_per_inventory` in the given range of words, given the number of ones
+/// before the range, and returns the number of ones up to the end of the
+/// range.
+pub(super) fn inventory_positions<B: BitWords>(
+    bits: &B,
+    words: Range<usize>,
+    ones_before: usize,
+    ones_per_inventory: usize,
+    positions: &mut Vec<usize>,
+) -> usize {
+    let mut past_ones = ones_before;
+    let mut next_quantum = ones_before.next_multiple_of(ones_per_inventory);
+    for i in words {
+        let word = bits.word(i);
+        let ones_in_word = word.count_ones() as usize;
+        while past_ones + ones_in_word > next_quantum {
+            let in_word_index = select_in_word(word, next_quantum - past_ones);
+            positions.push((i * usize::BITS as usize) + in_word_index);
+            next_quantum += ones_per_inventory;
+        }
+        past_ones += ones_in_word;
+    }
+    past_ones
+}
+
+/// Returns the base-2 logarithm of the number of ones between consecutive
+/// subinventory entries of an inventory entry with given span.
+#[inline(always)]
+pub(super) fn log2_quantum(span: usize, log2_ones_per_sub16: usize) -> usize {
+    match SpanType::from_span(span) {
+        SpanType::U16 => log2_ones_per_sub16,
+        SpanType::U32 => SelectAdapt::<()>::log2_ones_per_sub32(span, log2_ones_per_sub16),
+        SpanType::U64 => 0,
+    }
+}
+
+/// Returns the number of words of the spill buffer used by an inventory entry
+/// with given span containing `ones` ones.
+pub(super) fn spill_len(
+    span: usize,
+    ones: usize,
+    log2_ones_per_sub16: usize,
+    u64_per_subinventory: usize,
+) -> usize {
+    match SpanType::from_span(span) {
+        SpanType::U16 => 0,
+        // We store the entries first in the subinventory and then in the
+        // spill buffer. The first u64 word will be used to store the position
+        // of the entry in the spill buffer. Using the first word gives a cache
+        // advantage to entries that will need another cache miss to be read
+        // from the spill buffer.
+        SpanType::U32 => ones
+            .div_ceil(1 << log2_quantum(span, log2_ones_per_sub16))
+            .div_ceil(2)
+            .saturating_sub(u64_per_subinventory - 1),
+        // We store an inventory entry for each one after the first.
+        SpanType::U64 => (ones - 1).saturating_sub(u64_per_subinventory - 1),
+    }
+}
+
+/// Writes an inventory entry and its subinventory.
+///
+/// `entry` is the part of the inventory containing the entry and its
+/// subinventory, and `spill` is the part of the spill buffer used by the
+/// entry, whose length must be given by [`spill_len`], and which starts at
+/// position `spilled` of the spill buffer. `positions` must return the
+/// positions of the ones of the entry whose rank in the entry is a nonzero
+/// multiple of the [quantum](log2_quantum); the first one is at `start`.
+pub(super) fn write_entry(
+    entry: &mut [usize],
+    spill: &mut [usize],
+    spilled: usize,
+    start: usize,
+    span: usize,
+    positions: impl IntoIterator<Item = usize>,
+) {
+    let u64_per_subinventory = entry.len() - 1;
+    // If the span is 16-bit or 32-bit the first subinventory element is
+    // always zero, so we don't write it explicitly. Moreover, in the U64 case
+    // we don't write it at all.
+    let positions = positions.into_iter().zip(1..);
+    entry[0] = start;
+    match SpanType::from_span(span) {
+        SpanType::U16 => {
+            entry[0].set_u16_span();
+            // SAFETY: u16 has smaller alignment than usize
+            let subinventory: &mut [u16] = unsafe { entry[1..].align_to_mut().1 };
+            for (pos, i) in positions {
+                subinventory[i] = (pos - start) as u16;
+            }
+        }
+        SpanType::U32 => {
+            entry[0].set_u32_span();
+            // The first word of the subinventory is used to store the spill
+            // index.
+            entry[1] = spilled;
+            let locally_stored_u32s = 2 * (u64_per_subinventory - 1);
+            // SAFETY: u32 has smaller alignment than usize
+            let subinventory: &mut [u32] = unsafe { entry[2..].align_to_mut().1 };
+            let u32_spill: &mut [u32] = unsafe { spill.align_to_mut().1 };
+            for (pos, i) in positions {
+                if i < locally_stored_u32s {
+                    subinventory[i] = (pos - start) as u32;
+                } else {
+                    u32_spill[i - locally_stored_u32s] = (pos - start) as u32;
+                }
+            }
+        }
+        SpanType::U64 => {
+            entry[0].set_u64_span();
+            // The first word of the subinventory is used to store the spill
+            // index.
+            entry[1] = spilled;
+            for (pos, i) in positions {
+                if i < u64_per_subinventory {
+                    entry[1 + i] = pos;
+                } else {
+                    spill[i - u64_per_subinventory] = pos;
+                }
+            }
+        }
+    }
+}
+
+/// An iterator on the positions of the ones of a bit vector whose rank,
+/// counted from a given one, is a nonzero multiple of a quantum.
+struct QuantumOnes<'a, B> {
+    bits: &'a B,
+    word_idx: usize,
+    word: usize,
+    past_ones: usize,
+    next_quantum: usize,
+    quantum: usize,
+    remaining: usize,
+}
+
+impl<'a, B: BitWords> QuantumOnes<'a, B> {
+    /// Creates an iterator on the ones of rank multiple of 2<sup>`log2_quantum`</sup>
+    /// among the `ones` ones starting from the one at `start`, excluding the
+    /// latter.
+    fn new(bits: &'a B, start: usize, ones: usize, log2_quantum: usize) -> Self {
+        let word_idx = start / usize::BITS as usize;
+        let bit_idx = start % usize::BITS as usize;
+        Self {
+            bits,
+            word_idx,
+            // Clear the lower bits
+            word: (bits.word(word_idx) >> bit_idx) << bit_idx,
+            past_ones: 0,
+            next_quantum: 1 << log2_quantum,
+            quantum: 1 << log2_quantum,
+            remaining: ones.div_ceil(1 << log2_quantum) - 1,
+        }
+    }
+}
+
+impl<B: BitWords> Iterator for QuantumOnes<'_, B> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.remaining == 0 {
+            return None;
+        }
+        loop {
+            let ones_in_word = self.word.count_ones() as usize;
+            // The quantum can be in this word multiple times if it is small
+            if self.past_ones + ones_in_word > self.next_quantum {
+                let in_word_index = select_in_word(self.word, self.next_quantum - self.past_ones);
+                self.next_quantum += self.quantum;
+                self.remaining -= 1;
+                return Some(self.word_idx * usize::BITS as usize + in_word_index);
+            }
+            self.past_ones += ones_in_word;
+            self.word_idx += 1;
+            self.word = self.bits.word(self.word_idx);
+        }
+    }
+}
+
+/// Builds the inventory and the spill buffer of [`SelectAdapt`] and
+/// [`SelectAdaptConst`](super::SelectAdaptConst), given the positions of the
+/// ones of rank multiple of the number of ones per inventory, followed by the
+/// length of the bit vector.
+fn build_inventory<B: BitWords>(
+    bits: &B,
+    num_ones: usize,
+    positions: &[usize],
+    log2_ones_per_inventory: usize,
+    log2_u64_per_subinventory: usize,
+) -> (Box<[usize]>, Box<[usize]>) {
+    let ones_per_inventory = 1 << log2_ones_per_inventory;
+    let inventory_size = positions.len() - 1;
+    let u64_per_subinventory = 1 << log2_u64_per_subinventory;
+    let u64_per_inventory = u64_per_subinventory + 1;
+    let log2_ones_per_sub16 = log2_ones_per_inventory.saturating_sub(log2_u64_per_subinventory + 2);
+    let entry_ones = |i: usize| min(num_ones - i * ones_per_inventory, ones_per_inventory);
+
+    // We compute the exact spill size
+    let spill_size = (0..inventory_size)
+        .map(|i| {
+            let span = positions[i + 1] - positions[i];
+            spill_len(
+                span,
+                entry_ones(i),
+                log2_ones_per_sub16,
+                u64_per_subinventory,
+            )
+        })
+        .sum();
+
+    let mut inventory = vec![0; inventory_size * u64_per_inventory + 1];
+    // In the last inventory we write the number of bits
+    inventory[inventory_size * u64_per_inventory] = positions[inventory_size];
+    let mut spill = vec![0; spill_size];
+
+    // We fill the subinventories and the spill
+    let mut spilled = 0;
+    for (inventory_idx, entry) in inventory
+        .chunks_exact_mut(u64_per_inventory)
+        .take(inventory_size)
+        .enumerate()
+    {
+        let start = positions[inventory_idx];
+        let span = positions[inventory_idx + 1] - start;
+        let ones = entry_ones(inventory_idx);
+        let len = spill_len(span, ones, log2_ones_per_sub16, u64_per_subinventory);
+        let log2_quantum = log2_quantum(span, log2_ones_per_sub16);
+        write_entry(
+            entry,
+            &mut spill[spilled..spilled + len],
+            spilled,
+            start,
+            span,
+            QuantumOnes::new(bits, start, ones, log2_quantum),
+        );
+        spilled += len;
+    }
+
+    (inventory.into(), spill.into())
+}
+
+/// Builds in parallel the inventory and the spill buffer of [`SelectAdapt`]
+/// and [`SelectAdaptConst`](super::SelectAdaptConst).
+///
+/// The result is identical to that of the sequential constructors: we locate
+/// in parallel the indexed ones in chunks of words, we compute the spill size
+/// of each inventory entry, and then we fill in parallel the subinventories,
+/// giving to each inventory entry a disjoint slice of the spill buffer.
+#[cfg(feature = "rayon")]
+pub(super) fn par_build_inventory<B: BitWords + BitLength + Sync>(
+    bits: &B,
+    num_ones: usize,
+    log2_ones_per_inventory: usize,
+    log2_u64_per_subinventory: usize,
+) -> (Box<[usize]>, Box<[usize]>) {
+    // Number of words scanned by a single task in the first phase
+    const WORDS_PER_CHUNK: usize = 1 << 16;
+
+    let num_bits = max(1, bits.len());
+    let num_words = bits.num_words();
+    let ones_per_inventory = 1 << log2_ones_per_inventory;
+    let inventory_size = num_ones.div_ceil(ones_per_inventory);
+    let u64_per_subinventory = 1 << log2_u64_per_subinventory;
+    let u64_per_inventory = u64_per_subinventory + 1;
+    let log2_ones_per_sub16 = log2_ones_per_inventory.saturating_sub(log2_u64_per_subinventory + 2);
+    let entry_ones = |i: usize| min(num_ones - i * ones_per_inventory, ones_per_inventory);
+
+    // First phase: we count the ones in each chunk, and then we find in each
+    // chunk the positions of the ones out of ones_per_inventory.
+    let chunk_range = |c: usize| c * WORDS_PER_CHUNK..min((c + 1) * WORDS_PER_CHUNK, num_words);
+    let mut chunk_ones = (0..num_words.div_ceil(WORDS_PER_CHUNK))
+        .into_par_iter()
+        .map(|c| {
+            chunk_range(c)
+                .map(|i| bits.word(i).count_ones() as usize)
+                .sum::<usize>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut past_ones = 0;
+    for ones in chunk_ones.iter_mut() {
+        let ones_in_chunk = *ones;
+        *ones = past_ones;
+        past_ones += ones_in_chunk;
+    }
+    assert_eq!(past_ones, num_ones);
+
+    let mut positions = chunk_ones
+        .par_iter()
+        .enumerate()
+        .flat_map_iter(|(c, &ones_before)| {
+            let mut positions = vec![];
+            inventory_positions(
+                bits,
+                chunk_range(c),
+                ones_before,
+                ones_per_inventory,
+                &mut positions,
+            );
+            positions
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(positions.len(), inventory_size);
+    positions.push(num_bits);
+
+    // Second phase: we compute the exact spill size of each inventory entry.
+    let spill_sizes = (0..inventory_size)
+        .into_par_iter()
+        .map(|i| {
+            let span = positions[i + 1] - positions[i];
+            spill_len(
+                span,
+                entry_ones(i),
+                log2_ones_per_sub16,
+                u64_per_subinventory,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let mut inventory = vec![0; inventory_size * u64_per_inventory + 1];
+    inventory[inventory_size * u64_per_inventory] = num_bits;
+    let mut spill = vec![0; spill_sizes.iter().sum()];
+
+    // We split the spill buffer in the slices of each inventory entry.
+    let mut spill_slices = Vec::with_capacity(inventory_size);
+    let mut rest = spill.as_mut_slice();
+    let mut spilled = 0;
+    for &spill_size in &spill_sizes {
+        let (slice, tail) = std::mem::take(&mut rest).split_at_mut(spill_size);
+        spill_slices.push((spilled, slice));
+        spilled += spill_size;
+        rest = tail;
+    }
+
+    // Third phase: we fill in parallel the subinventories and the spill.
+    inventory[..inventory_size * u64_per_inventory]
+        .par_chunks_mut(u64_per_inventory)
+        .zip(spill_slices)
+        .enumerate()
+        .for_each(|(inventory_idx, (entry, (spilled, spill)))| {
+            let start = positions[inventory_idx];
+            let span = positions[inventory_idx + 1] - start;
+            let ones = entry_ones(inventory_idx);
+            let log2_quantum = log2_quantum(span, log2_ones_per_sub16);
+            write_entry(
+                entry,
+                spill,
+                spilled,
+                start,
+                span,
+                QuantumOnes::new(bits, start, ones, log2_quantum),
+            );
+        });
+
+    (inventory.into(), spill.into())
+}
+
+/// Returns the positions of the ones of a bit vector, given as a function
+/// returning its words, starting from a given position.
+#[derive(Clone)]
+struct OnesScanner<F> {
+    word: F,
+    num_bits: usize,
+    word_idx: usize,
+    window: usize,
+}
+
+impl<F: Fn(usize) -> usize> OnesScanner<F> {
+    fn new(word: F, num_bits: usize, pos: usize) -> Self {
+        let word_idx = pos / usize::BITS as usize;
+        let bit_idx = pos % usize::BITS as usize;
+        let window = if pos < num_bits {
+            (word(word_idx) >> bit_idx) << bit_idx
+        } else {
+            0
+        };
+        Self {
+            word,
+            num_bits,
+            word_idx,
+            window,
+        }
+    }
+
+    /// Returns the position of the next one, or the number of bits if there
+    /// are no more ones.
+    fn next_one(&mut self) -> usize {
+        let num_words = self.num_bits.div_ceil(usize::BITS as usize);
+        while self.window == 0 {
+            if self.word_idx + 1 >= num_words {
+                self.word_idx = num_words;
+                return self.num_bits;
+            }
+            self.word_idx += 1;
+            self.window = (self.word)(self.word_idx);
+        }
+        let pos = self.word_idx * usize::BITS as usize + self.window.trailing_zeros() as usize;
+        self.window &= self.window - 1;
+        pos
+    }
+}
+
+/// Verifies an inventory and a spill buffer in the format of [`SelectAdapt`]
+/// and its variants against a bit vector of `num_bits` bits, given as a
+/// function `word` returning its words. Bits beyond `num_bits` are ignored.
+///
+/// Entries are checked directly, rather than through selection, so that
+/// corrupted inventories cannot cause undefined behavior. In sampled mode, the
+/// ones of a sampled inventory entry are located starting from the position
+/// stored in the previous entry, and the bit vector is not scanned to count
+/// its ones: the number of inventory entries is deduced from the length of
+/// the inventory, and the last entry, if sampled, is checked to cover all
+/// remaining ones.
+pub(super) fn verify_inventory(
+    word: impl Fn(usize) -> usize,
+    num_bits: usize,
+    inventory: &[usize],
+    spill: &[usize],
+    log2_ones_per_inventory: usize,
+    log2_u64_per_subinventory: usize,
+    mode: VerifyMode,
+) -> Result<(), VerifyError> {
+    if log2_ones_per_inventory >= usize::BITS as usize {
+        return Err(VerifyError::Param {
+            what: "log2 of ones per inventory",
+            expected: usize::BITS as usize - 1,
+            found: log2_ones_per_inventory,
+        });
+    }
+    if log2_u64_per_subinventory >= usize::BITS as usize / 2 {
+        return Err(VerifyError::Param {
+            what: "log2 of words per subinventory",
+            expected: usize::BITS as usize / 2 - 1,
+            found: log2_u64_per_subinventory,
+        });
+    }
+
+    let num_words = num_bits.div_ceil(usize::BITS as usize);
+    let residual = num_bits % usize::BITS as usize;
+    let word = &|i: usize| {
+        if residual != 0 && i == num_words - 1 {
+            word(i) & ((1 << residual) - 1)
+        } else {
+            word(i)
+        }
+    };
+    let ones_per_inventory = 1 << log2_ones_per_inventory;
+    let u64_per_subinventory = 1 << log2_u64_per_subinventory;
+    let u64_per_inventory = u64_per_subinventory + 1;
+    let num_ones = match mode {
+        VerifyMode::Full => Some(
+            (0..num_words)
+                .map(|i| word(i).count_ones() as usize)
+                .sum::<usize>(),
+        ),
+        VerifyMode::Sampled { .. } => None,
+    };
+    let inventory_size = match num_ones {
+        Some(num_ones) => num_ones.div_ceil(ones_per_inventory),
+        None => inventory.len().saturating_sub(1) / u64_per_inventory,
+    };
+    let log2_ones_per_sub16 = log2_ones_per_inventory.saturating_sub(log2_u64_per_subinventory + 2);
+    let locally_stored_u32s = 2 * (u64_per_subinventory - 1);
+
+    let inventory_words = inventory_size
+        .checked_mul(u64_per_inventory)
+        .and_then(|words| words.checked_add(1))
+        .unwrap_or(usize::MAX);
+    if inventory.len() != inventory_words {
+        return Err(VerifyError::Param {
+            what: "inventory length",
+            expected: inventory_words,
+            found: inventory.len(),
+        });
+    }
+    let sentinel = max(1, num_bits);
+    if inventory[inventory_words - 1] != sentinel {
+        return Err(VerifyError::Value {
+            what: "inventory",
+            index: inventory_words - 1,
+            expected: sentinel,
+            found: inventory[inventory_words - 1],
+        });
+    }
+
+    let mut ones = OnesScanner::new(word, num_bits, 0);
+
+    for inventory_idx in mode.indices(inventory_size) {
+        let start = inventory_idx * u64_per_inventory;
+        let end = start + u64_per_inventory;
+        let past_ones = inventory_idx * ones_per_inventory;
+
+        if let VerifyMode::Sampled { .. } = mode {
+            ones = if inventory_idx == 0 {
+                OnesScanner::new(word, num_bits, 0)
+            } else {
+                let anchor = inventory[start - u64_per_inventory].get();
+                let mut ones = OnesScanner::new(word, num_bits, anchor);
+                for _ in 0..ones_per_inventory {
+                    ones.next_one();
+                }
+                ones
+            };
+        }
+
+        let block_ones = match num_ones {
+            Some(num_ones) => min(ones_per_inventory, num_ones - past_ones),
+            None if inventory_idx + 1 < inventory_size => ones_per_inventory,
+            None => {
+                // The last entry must cover all remaining ones
+                let mut ahead = ones.clone();
+                let mut count = 0;
+                while count <= ones_per_inventory && ahead.next_one() < num_bits {
+                    count += 1;
+                }
+                if count == 0 || count > ones_per_inventory {
+                    let expected = if count == 0 {
+                        inventory_words - u64_per_inventory
+                    } else {
+                        inventory_words + u64_per_inventory
+                    };
+                    return Err(VerifyError::Param {
+                        what: "inventory length",
+                        expected,
+                        found: inventory.len(),
+                    });
+                }
+                count
+            }
+        };
+        let first = ones.next_one();
+        let next = if inventory_idx + 1 == inventory_size {
+            sentinel
+        } else {
+            let mut ahead = ones.clone();
+            for _ in 1..ones_per_inventory {
+                ahead.next_one();
+            }
+            ahead.next_one()
+        };
+
+        let span = next.saturating_sub(first);
+        let span_type = SpanType::from_span(span);
+        let mut expected = first;
+        match span_type {
+            SpanType::U16 => expected.set_u16_span(),
+            SpanType::U32 => expected.set_u32_span(),
+            SpanType::U64 => expected.set_u64_span(),
+        }
+        if inventory[start] != expected {
+            return Err(VerifyError::Value {
+                what: "inventory",
+                index: start,
+                expected,
+                found: inventory[start],
+            });
+        }
+        if inventory[end].get() != next {
+            return Err(VerifyError::Value {
+                what: "inventory",
+                index: end,
+                expected: next,
+                found: inventory[end].get(),
+            });
+        }
+
+        let subinventory = &inventory[start + 1..end];
+        let log2_quantum = match span_type {
+            SpanType::U16 => log2_ones_per_sub16,
+            SpanType::U32 => SelectAdapt::<(), ()>::log2_ones_per_sub32(span, log2_ones_per_sub16),
+            SpanType::U64 => 0,
+        };
+        let quantum_mask = (1 << log2_quantum) - 1;
+        // Only meaningful for 32-bit and 64-bit spans
+        let spill_start = subinventory[0];
+
+        let mut pos = first;
+        for subrank in 0..block_ones {
+            if subrank != 0 {
+                pos = ones.next_one();
+            }
+            if subrank & quantum_mask != 0 {
+                continue;
+            }
+            let k = subrank >> log2_quantum;
+
+            // The expected value, the value found, and the spill length
+            // necessary to read it
+            let (expected, found, spill_len) = match span_type {
+                SpanType::U16 => {
+                    let u16s = unsafe { subinventory.align_to::<u16>().1 };
+                    (pos - first, u16s.get(k).map(|&x| x as usize), 0)
+                }
+                SpanType::U32 if k < locally_stored_u32s => {
+                    let u32s = unsafe { subinventory[1..].align_to::<u32>().1 };
+                    (pos - first, u32s.get(k).map(|&x| x as usize), 0)
+                }
+                SpanType::U32 => {
+                    let k = k - locally_stored_u32s;
+                    let found = spill
+                        .get(spill_start..)
+                        .and_then(|spilled| unsafe { spilled.align_to::<u32>().1 }.get(k));
+                    (
+                        pos - first,
+                        found.map(|&x| x as usize),
+                        spill_start.saturating_add(k / 2 + 1),
+                    )
+                }
+                SpanType::U64 if subrank == 0 => continue,
+                SpanType::U64 if subrank < u64_per_subinventory => {
+                    (pos, subinventory.get(subrank).copied(), 0)
+                }
+                SpanType::U64 => {
+                    let spill_idx = spill_start.saturating_add(subrank - u64_per_subinventory);
+                    (
+                        pos,
+                        spill.get(spill_idx).copied(),
+                        spill_idx.saturating_add(1),
+                    )
+                }
+            };
+
+            match found {
+                Some(found) if found == expected => {}
+                Some(found) => {
+                    return Err(VerifyError::Value {
+                        what: "subinventory",
+                        index: past_ones + subrank,
+                        expected,
+                        found,
+                    })
+                }
+                None => {
+                    return Err(VerifyError::Param {
+                        what: "spill length",
+                        expected: spill_len,
+                        found: spill.len(),
+                    })
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
 impl<B, I> SelectAdapt<B, I> {
     pub fn into_inner(self) -> B {
         self.bits
@@ -307,7 +965,7 @@ impl<B, I> SelectAdapt<B, I> {
 
     // Compute adaptively the number of 32-bit subinventory entries
     #[inline(always)]
-    fn log2_ones_per_sub32(span: usize, log2_ones_per_sub16: usize) -> usize {
+    pub(super) fn log2_ones_per_sub32(span: usize, log2_ones_per_sub16: usize) -> usize {
         debug_assert!(span >= 1 << 16);
         // Since span >= 2^16, (span >> 15).ilog2() >= 0, which implies in any case
         // at least doubling the frequency of the subinventory with respect to the
@@ -352,7 +1010,7 @@ impl<B: BitLength, C> SelectAdapt<B, C> {
     }
 }
 
-impl<B: AsRef<[usize]> + BitCount> SelectAdapt<B, Box<[usize]>> {
+impl<B: BitWords + BitCount> SelectAdapt<B, Box<[usize]>> {
     /// Creates a new selection structure over a bit vecotr using a
     /// [default target inventory
     /// span](SelectAdapt::DEFAULT_TARGET_INVENTORY_SPAN).
@@ -471,256 +1129,101 @@ impl<B: AsRef<[usize]> + BitCount> SelectAdapt<B, Box<[usize]>> {
         let log2_u64_per_subinventory =
             max_log2_u64_per_subinventory.min(log2_ones_per_inventory.saturating_sub(2));
 
-        let u64_per_subinventory = 1 << log2_u64_per_subinventory;
-        // A u64 for the inventory, and u64_per_inventory for the subinventory
-        let u64_per_inventory = u64_per_subinventory + 1;
-
         let log2_ones_per_sub16 =
             log2_ones_per_inventory.saturating_sub(log2_u64_per_subinventory + 2);
         let ones_per_sub16 = 1 << log2_ones_per_sub16;
         let ones_per_sub16_mask = ones_per_sub16 - 1;
 
-        let inventory_words = inventory_size * u64_per_inventory + 1;
-        let mut inventory = Vec::with_capacity(inventory_words);
-
-        let mut past_ones = 0;
-        let mut next_quantum = 0;
-        let mut spilled = 0;
-
-        // First phase: we build an inventory for each one out of ones_per_inventory.
-        for (i, word) in bits.as_ref().iter().copied().enumerate() {
-            let ones_in_word = word.count_ones() as usize;
-
-            while past_ones + ones_in_word > next_quantum {
-                let in_word_index = word.select_in_word(next_quantum - past_ones);
-                let index = (i * usize::BITS as usize) + in_word_index;
-
-                // write the position of the one in the inventory
-                inventory.push(index);
-                // make space for the subinventory
-                inventory.resize(inventory.len() + u64_per_subinventory, 0);
-
-                next_quantum += ones_per_inventory;
-            }
-            past_ones += ones_in_word;
-        }
-
+        // First phase: we find the position of each one out of
+        // ones_per_inventory.
+        let mut positions = Vec::with_capacity(inventory_size + 1);
+        let past_ones = inventory_positions(
+            &bits,
+            0..bits.num_words(),
+            0,
+            ones_per_inventory,
+            &mut positions,
+        );
         assert_eq!(past_ones, num_ones);
-        // in the last inventory write the number of bits
-        inventory.push(num_bits);
-        assert_eq!(inventory.len(), inventory_words);
-
-        // We estimate the subinventory and exact spill size
-        for (i, inv) in inventory[..inventory_size * u64_per_inventory]
-            .iter()
-            .copied()
-            .step_by(u64_per_inventory)
-            .enumerate()
-        {
-            let start = inv;
-            let span = inventory[i * u64_per_inventory + u64_per_inventory] - start;
-            past_ones = i * ones_per_inventory;
-            let ones = min(num_ones - past_ones, ones_per_inventory);
-
-            debug_assert!(start + span == num_bits || ones == ones_per_inventory);
-
-            match SpanType::from_span(span) {
-                // We store the entries first in the subinventory and then in
-                // the spill buffer. The first u64 word will be used to store
-                // the position of the entry in the spill buffer. Using the
-                // first word gives a cache advantage to entries that will need
-                // another cache miss to be read from the spill buffer.
-                SpanType::U32 => {
-                    // We store an inventory entry each 1 << log2_ones_per_sub32 ones.
-                    let log2_ones_per_sub32 = Self::log2_ones_per_sub32(span, log2_ones_per_sub16);
-                    let num_u32s = ones.div_ceil(1 << log2_ones_per_sub32);
-                    let num_u64s = num_u32s.div_ceil(2);
-                    let spilled_u64s = num_u64s.saturating_sub(u64_per_subinventory - 1);
-                    spilled += spilled_u64s;
-                }
-                SpanType::U64 => {
-                    // We store an inventory entry for each one after the first.
-                    spilled += (ones - 1).saturating_sub(u64_per_subinventory - 1);
-                }
-                _ => {}
-            }
-        }
-
-        let spill_size = spilled;
-
-        let mut inventory: Box<[usize]> = inventory.into();
-        let mut spill: Box<[usize]> = vec![0; spill_size].into();
-
-        spilled = 0;
-        let locally_stored_u32s = 2 * (u64_per_subinventory - 1);
+        positions.push(num_bits);
 
         // Second phase: we fill the subinventories and the spill.
-        for inventory_idx in 0..inventory_size {
-            // Get the start and end indices of the current inventory
-            let start_inv_idx = inventory_idx * u64_per_inventory;
-            let end_inv_idx = start_inv_idx + u64_per_inventory;
-            // Read the first-level index to get the start and end bit indices
-            let start_bit_idx = inventory[start_inv_idx];
-            let end_bit_idx = inventory[end_inv_idx];
-            // compute the span of the inventory
-            let span = end_bit_idx - start_bit_idx;
-            let span_type = SpanType::from_span(span);
-
-            // Compute the number of ones before the current inventory
-            let mut past_ones = inventory_idx * ones_per_inventory;
-            let mut next_quantum = past_ones;
-            let log2_quantum;
-
-            match span_type {
-                SpanType::U16 => {
-                    log2_quantum = log2_ones_per_sub16;
-                    inventory[start_inv_idx].set_u16_span();
-                }
-                SpanType::U32 => {
-                    log2_quantum = Self::log2_ones_per_sub32(span, log2_ones_per_sub16);
-                    inventory[start_inv_idx].set_u32_span();
-                    // The first word of the subinventory is used to store the spill index.
-                    inventory[start_inv_idx + 1] = spilled;
-                }
-                SpanType::U64 => {
-                    log2_quantum = 0;
-                    inventory[start_inv_idx].set_u64_span();
-                    // The first word of the subinventory is used to store the spill index.
-                    inventory[start_inv_idx + 1] = spilled;
-                }
-            }
-
-            let quantum = 1 << log2_quantum;
+        let (inventory, spill) = build_inventory(
+            &bits,
+            num_ones,
+            &positions,
+            log2_ones_per_inventory,
+            log2_u64_per_subinventory,
+        );
 
-            // If the span is 16-bit or 32-bit the first subinventory element is
-            // always zero, so we don't write it explicitly. Moreover, in the
-            // U64 case we don't write it at all.
-            let mut subinventory_idx = 1;
-            next_quantum += quantum;
+        Self {
+            bits,
+            inventory,
+            spill,
+            log2_ones_per_inventory,
+            log2_ones_per_sub16,
+            log2_u64_per_subinventory,
+            ones_per_inventory_mask,
+            ones_per_sub16_mask,
+        }
+    }
+}
 
-            let mut word_idx = start_bit_idx / usize::BITS as usize;
-            let end_word_idx = end_bit_idx.div_ceil(usize::BITS as usize);
-            let bit_idx = start_bit_idx % usize::BITS as usize;
+#[cfg(feature = "rayon")]
+impl<B: BitWords + BitCount + Sync> SelectAdapt<B, Box<[usize]>> {
+    /// Creates a new selection structure in parallel using a [default target
+    /// inventory span](SelectAdapt::DEFAULT_TARGET_INVENTORY_SPAN).
+    ///
+    /// The result is identical to that of [`new`](SelectAdapt::new).
+    pub fn par_new(bits: B, max_log2_u64_per_subinv: usize) -> Self {
+        Self::par_with_span(
+            bits,
+            Self::DEFAULT_TARGET_INVENTORY_SPAN,
+            max_log2_u64_per_subinv,
+        )
+    }
 
-            // Clear the lower bits
-            let mut word = (bits.as_ref()[word_idx] >> bit_idx) << bit_idx;
-
-            'outer: loop {
-                let ones_in_word = word.count_ones() as usize;
-
-                // If the quantum is in this word, write it in the subinventory.
-                // Note that this can happen multiple times in the same word if
-                // the quantum is small, hence the following loop.
-                while past_ones + ones_in_word > next_quantum {
-                    debug_assert!(next_quantum <= end_bit_idx);
-                    // find the quantum bit in the word
-                    let in_word_index = word.select_in_word(next_quantum - past_ones);
-                    // compute the global index of the quantum bit in the bitvec
-                    let bit_index = (word_idx * usize::BITS as usize) + in_word_index;
-
-                    // This exit is necessary in case the number of ones per
-                    // inventory is larger than the number of available
-                    // subinventory entries, which can happen if the bit vector
-                    // is very sparse, or if we are in the last inventory entry.
-                    if bit_index >= end_bit_idx {
-                        break 'outer;
-                    }
-
-                    // Compute the offset of the quantum bit from the start of
-                    // the subinventory
-                    let sub_offset = bit_index - start_bit_idx;
-
-                    match span_type {
-                        SpanType::U16 => {
-                            let subinventory: &mut [u16] = unsafe {
-                                inventory[start_inv_idx + 1..end_inv_idx].align_to_mut().1
-                            };
-
-                            subinventory[subinventory_idx] = sub_offset as u16;
-                            subinventory_idx += 1;
-                            // This exit is not necessary for correctness, but
-                            // it avoids the additional loop iterations that
-                            // would be necessary to find the position of the
-                            // next one (i.e., end_bit_idx).
-                            if subinventory_idx << log2_quantum == ones_per_inventory {
-                                break 'outer;
-                            }
-                        }
-                        SpanType::U32 => {
-                            if subinventory_idx < locally_stored_u32s {
-                                let subinventory: &mut [u32] = unsafe {
-                                    inventory[start_inv_idx + 2..end_inv_idx].align_to_mut().1
-                                };
-
-                                debug_assert_eq!(subinventory[subinventory_idx], 0);
-                                subinventory[subinventory_idx] = sub_offset as u32;
-                            } else {
-                                let u32_spill: &mut [u32] =
-                                    unsafe { spill[spilled..].align_to_mut().1 };
-                                debug_assert_eq!(
-                                    u32_spill[subinventory_idx - locally_stored_u32s],
-                                    0
-                                );
-                                u32_spill[subinventory_idx - locally_stored_u32s] =
-                                    sub_offset as u32;
-                            }
-
-                            subinventory_idx += 1;
-                            // This exit is not necessary for correctness, but
-                            // it avoids the additional loop iterations that
-                            // would be necessary to find the position of the
-                            // next one (i.e., end_bit_idx).
-                            if subinventory_idx << log2_quantum == ones_per_inventory {
-                                break 'outer;
-                            }
-                        }
-                        SpanType::U64 => {
-                            if subinventory_idx < u64_per_subinventory {
-                                inventory[start_inv_idx + 1 + subinventory_idx] = bit_index;
-                                subinventory_idx += 1;
-                            } else {
-                                assert!(spilled < spill_size);
-                                spill[spilled] = bit_index;
-                                spilled += 1;
-                            }
-                            // This exit is not necessary for correctness, but
-                            // it avoids the additional loop iterations that
-                            // would be necessary to find the position of the
-                            // next one (i.e., end_bit_idx). Note that here
-                            // log2_quantum == 0.
-                            if subinventory_idx == ones_per_inventory {
-                                break 'outer;
-                            }
-                        }
-                    }
-
-                    next_quantum += quantum;
-                }
+    /// Creates a new selection structure in parallel with a specified target
+    /// inventory span.
+    ///
+    /// The result is identical to that of [`with_span`](SelectAdapt::with_span).
+    pub fn par_with_span(
+        bits: B,
+        target_inventory_span: usize,
+        max_log2_u64_per_subinventory: usize,
+    ) -> Self {
+        let num_bits = max(1usize, bits.len());
+        let num_ones = bits.count_ones();
 
-                // We are done with the word, so update the number of ones
-                past_ones += ones_in_word;
-                // Move to the next word and check whether it is the last one
-                word_idx += 1;
-                if word_idx == end_word_idx {
-                    break;
-                }
+        let log2_ones_per_inventory = (num_ones * target_inventory_span)
+            .div_ceil(num_bits)
+            .max(1)
+            .ilog2() as usize;
 
-                // Read the next word
-                word = bits.as_ref()[word_idx];
-            }
+        Self::par_with_inv(bits, log2_ones_per_inventory, max_log2_u64_per_subinventory)
+    }
 
-            // If we are in the U32 case, we need to update the number of used
-            // element in the spill buffer. The update must be done after the
-            // loop, as for the last inventory entry only at this point we know
-            // the actual number of elements in the subinventory.
-            if span_type == SpanType::U32 {
-                spilled += subinventory_idx
-                    .saturating_sub(locally_stored_u32s)
-                    .div_ceil(2);
-            }
-        }
+    /// Creates a new selection structure in parallel with a specified distance
+    /// between indexed ones.
+    ///
+    /// The result is identical to that of [`with_inv`](SelectAdapt::with_inv).
+    pub fn par_with_inv(
+        bits: B,
+        log2_ones_per_inventory: usize,
+        max_log2_u64_per_subinventory: usize,
+    ) -> Self {
+        let num_ones = bits.count_ones();
+        let log2_u64_per_subinventory =
+            max_log2_u64_per_subinventory.min(log2_ones_per_inventory.saturating_sub(2));
+        let log2_ones_per_sub16 =
+            log2_ones_per_inventory.saturating_sub(log2_u64_per_subinventory + 2);
 
-        assert_eq!(spilled, spill_size);
+        let (inventory, spill) = par_build_inventory(
+            &bits,
+            num_ones,
+            log2_ones_per_inventory,
+            log2_u64_per_subinventory,
+        );
 
         Self {
             bits,
@@ -729,15 +1232,13 @@ impl<B: AsRef<[usize]> + BitCount> SelectAdapt<B, Box<[usize]>> {
             log2_ones_per_inventory,
             log2_ones_per_sub16,
             log2_u64_per_subinventory,
-            ones_per_inventory_mask,
-            ones_per_sub16_mask,
+            ones_per_inventory_mask: (1 << log2_ones_per_inventory) - 1,
+            ones_per_sub16_mask: (1 << log2_ones_per_sub16) - 1,
         }
     }
 }
 
-impl<B: AsRef<[usize]> + BitLength + SelectHinted, I: AsRef<[usize]>> SelectUnchecked
-    for SelectAdapt<B, I>
-{
+impl<B: BitLength + SelectHinted, I: AsRef<[usize]>> SelectUnchecked for SelectAdapt<B, I> {
     unsafe fn select_unchecked(&self, rank: usize) -> usize {
         let inventory = self.inventory.as_ref();
         let inventory_index = rank >> self.log2_ones_per_inventory;
@@ -811,9 +1312,94 @@ impl<B: AsRef<[usize]> + BitLength + SelectHinted, I: AsRef<[usize]>> SelectUnch
         debug_assert!(spill_idx < self.spill.as_ref().len());
         self.spill.get_unchecked(spill_idx)
     }
+
+    unsafe fn select_unchecked_batch(&self, ranks: &[usize], pos: &mut [usize]) {
+        prefetched_batch(
+            ranks,
+            pos,
+            |rank| self.prefetch_inventory(rank),
+            |rank| self.select_unchecked(rank),
+        )
+    }
+}
+
+impl<B: BitWords + BitLength + Verify, I: AsRef<[usize]>> Verify for SelectAdapt<B, I> {
+    fn verify(&self, mode: VerifyMode) -> Result<(), VerifyError> {
+        self.bits.verify(mode)?;
+        verify_inventory(
+            |i| self.bits.word(i),
+            self.bits.len(),
+            self.inventory.as_ref(),
+            self.spill.as_ref(),
+            self.log2_ones_per_inventory,
+            self.log2_u64_per_subinventory,
+            mode,
+        )?;
+
+        let log2_ones_per_sub16 = self
+            .log2_ones_per_inventory
+            .saturating_sub(self.log2_u64_per_subinventory + 2);
+        for (what, expected, found) in [
+            (
+                "log2 of ones per 16-bit subinventory entry",
+                log2_ones_per_sub16,
+                self.log2_ones_per_sub16,
+            ),
+            (
+                "ones-per-inventory mask",
+                (1 << self.log2_ones_per_inventory) - 1,
+                self.ones_per_inventory_mask,
+            ),
+            (
+                "ones-per-16-bit-subinventory-entry mask",
+                (1 << log2_ones_per_sub16) - 1,
+                self.ones_per_sub16_mask,
+            ),
+        ] {
+            if expected != found {
+                return Err(VerifyError::Param {
+                    what,
+                    expected,
+                    found,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<B: SelectHinted + NumBits, I: AsRef<[usize]>> Select for SelectAdapt<B, I> {
+    fn select_batch(&self, ranks: &[usize], pos: &mut [Option<usize>]) {
+        prefetched_batch(
+            ranks,
+            pos,
+            |rank| self.prefetch_inventory(rank),
+            |rank| self.select(rank),
+        )
+    }
 }
 
-impl<B: SelectHinted + AsRef<[usize]> + NumBits, I: AsRef<[usize]>> Select for SelectAdapt<B, I> {}
+impl<B: SelectHinted + Rank, I: AsRef<[usize]>> NextOne for SelectAdapt<B, I> {}
+
+impl<B: SelectHinted + Rank, I: AsRef<[usize]>> PrevOne for SelectAdapt<B, I> {}
+
+impl<B, I: AsRef<[usize]>> SelectAdapt<B, I> {
+    /// Prefetches the inventory entry, and the 16-bit subinventory entry,
+    /// needed to answer a query of given rank.
+    #[inline(always)]
+    fn prefetch_inventory(&self, rank: usize) {
+        let inventory = self.inventory.as_ref();
+        let inventory_index = rank >> self.log2_ones_per_inventory;
+        let inventory_start_pos =
+            (inventory_index << self.log2_u64_per_subinventory).wrapping_add(inventory_index);
+        let subrank = rank & self.ones_per_inventory_mask;
+        prefetch_index(inventory, inventory_start_pos);
+        prefetch_index(
+            inventory,
+            inventory_start_pos.wrapping_add(1 + (subrank >> self.log2_ones_per_sub16) / 4),
+        );
+    }
+}
 
 #[cfg(test)]
 mod tests {
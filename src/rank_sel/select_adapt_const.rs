@@ -6,23 +6,31 @@
  * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
  */
 
-use super::{Inventory, SpanType};
+#[cfg(feature = "rayon")]
+use super::par_build_inventory;
+use super::{verify_inventory, Inventory, SpanType};
+#[cfg(feature = "rayon")]
+use crate::traits::BitWords;
+use crate::utils::word_ops::select_in_word;
 use ambassador::Delegate;
-use common_traits::SelectInWord;
 use epserde::Epserde;
 use mem_dbg::{MemDbg, MemSize};
 use std::cmp::{max, min};
 
 use crate::{
     prelude::{BitCount, BitFieldSlice, BitLength, Select, SelectHinted},
-    traits::{NumBits, SelectUnchecked},
+    traits::{NextOne, NumBits, PrevOne, Rank, SelectUnchecked, Verify, VerifyError, VerifyMode},
+    utils::{prefetch_index, prefetched_batch},
 };
 
 use crate::ambassador_impl_AsRef;
 use crate::ambassador_impl_Index;
 use crate::traits::rank_sel::ambassador_impl_BitCount;
 use crate::traits::rank_sel::ambassador_impl_BitLength;
+use crate::traits::rank_sel::ambassador_impl_BitWords;
+use crate::traits::rank_sel::ambassador_impl_NextZero;
 use crate::traits::rank_sel::ambassador_impl_NumBits;
+use crate::traits::rank_sel::ambassador_impl_PrevZero;
 use crate::traits::rank_sel::ambassador_impl_Rank;
 use crate::traits::rank_sel::ambassador_impl_RankHinted;
 use crate::traits::rank_sel::ambassador_impl_RankUnchecked;
@@ -143,6 +151,7 @@ use std::ops::Index;
 
 #[derive(Epserde, Debug, Clone, MemDbg, MemSize, Delegate)]
 #[delegate(AsRef<[usize]>, target = "bits")]
+#[delegate(crate::traits::rank_sel::BitWords, target = "bits")]
 #[delegate(Index<usize>, target = "bits")]
 #[delegate(crate::traits::rank_sel::BitCount, target = "bits")]
 #[delegate(crate::traits::rank_sel::BitLength, target = "bits")]
@@ -155,6 +164,8 @@ use std::ops::Index;
 #[delegate(crate::traits::rank_sel::SelectZero, target = "bits")]
 #[delegate(crate::traits::rank_sel::SelectZeroHinted, target = "bits")]
 #[delegate(crate::traits::rank_sel::SelectZeroUnchecked, target = "bits")]
+#[delegate(crate::traits::rank_sel::NextZero, target = "bits")]
+#[delegate(crate::traits::rank_sel::PrevZero, target = "bits")]
 pub struct SelectAdaptConst<
     B,
     I = Box<[usize]>,
@@ -190,13 +201,34 @@ impl<B, I, const LOG2_ONES_PER_INVENTORY: usize, const LOG2_U64_PER_SUBINVENTORY
         self.bits
     }
 
+    /// Creates a new structure from a backend, an inventory and a spill
+    /// buffer.
+    ///
+    /// This constructor is used to build the structure in external memory.
+    ///
+    /// # Safety
+    ///
+    /// `inventory` and `spill` must be the inventory and the spill buffer that
+    /// [`new`](SelectAdaptConst::new) would build on `bits` with the same constant
+    /// parameters.
+    pub(crate) unsafe fn from_raw_parts(bits: B, inventory: I, spill: I) -> Self {
+        Self {
+            bits,
+            inventory,
+            spill,
+        }
+    }
+
     /// Replaces the backend with a new one implementing [`SelectHinted`].
     ///
     /// # Safety
     ///
     /// This method is unsafe because it is not possible to guarantee that the
     /// new backend is identical to the old one as a bit vector.
-    pub unsafe fn map<C>(self, f: impl FnOnce(B) -> C) -> SelectAdaptConst<C, I>
+    pub unsafe fn map<C>(
+        self,
+        f: impl FnOnce(B) -> C,
+    ) -> SelectAdaptConst<C, I, LOG2_ONES_PER_INVENTORY, LOG2_U64_PER_SUBINVENTORY>
     where
         C: SelectHinted,
     {
@@ -257,7 +289,7 @@ impl<
             let ones_in_word = word.count_ones() as usize;
 
             while past_ones + ones_in_word > next_quantum {
-                let in_word_index = word.select_in_word(next_quantum - past_ones);
+                let in_word_index = select_in_word(word, next_quantum - past_ones);
                 let index = (i * usize::BITS as usize) + in_word_index;
 
                 // write the position of the one in the inventory
@@ -379,7 +411,7 @@ impl<
                 while past_ones + ones_in_word > next_quantum {
                     debug_assert!(next_quantum <= end_bit_idx);
                     // find the quantum bit in the word
-                    let in_word_index = word.select_in_word(next_quantum - past_ones);
+                    let in_word_index = select_in_word(word, next_quantum - past_ones);
                     // compute the global index of the quantum bit in the bitvec
                     let bit_index = (word_idx * usize::BITS as usize) + in_word_index;
 
@@ -495,6 +527,54 @@ impl<
     }
 }
 
+#[cfg(feature = "rayon")]
+impl<
+        B: BitWords + BitCount + Sync,
+        const LOG2_ONES_PER_INVENTORY: usize,
+        const LOG2_U64_PER_SUBINVENTORY: usize,
+    > SelectAdaptConst<B, Box<[usize]>, LOG2_ONES_PER_INVENTORY, LOG2_U64_PER_SUBINVENTORY>
+{
+    /// Creates a new selection structure in parallel.
+    ///
+    /// The result is identical to that of [`new`](SelectAdaptConst::new).
+    pub fn par_new(bits: B) -> Self {
+        let num_ones = bits.count_ones();
+        let (inventory, spill) = par_build_inventory(
+            &bits,
+            num_ones,
+            LOG2_ONES_PER_INVENTORY,
+            LOG2_U64_PER_SUBINVENTORY,
+        );
+
+        Self {
+            bits,
+            inventory,
+            spill,
+        }
+    }
+}
+
+impl<
+        B: AsRef<[usize]> + BitLength + Verify,
+        I: AsRef<[usize]>,
+        const LOG2_ONES_PER_INVENTORY: usize,
+        const LOG2_U64_PER_SUBINVENTORY: usize,
+    > Verify for SelectAdaptConst<B, I, LOG2_ONES_PER_INVENTORY, LOG2_U64_PER_SUBINVENTORY>
+{
+    fn verify(&self, mode: VerifyMode) -> Result<(), VerifyError> {
+        self.bits.verify(mode)?;
+        verify_inventory(
+            |i| self.bits.as_ref()[i],
+            self.bits.len(),
+            self.inventory.as_ref(),
+            self.spill.as_ref(),
+            LOG2_ONES_PER_INVENTORY,
+            LOG2_U64_PER_SUBINVENTORY,
+            mode,
+        )
+    }
+}
+
 impl<
         B: AsRef<[usize]> + BitLength + SelectHinted,
         I: AsRef<[usize]>,
@@ -575,6 +655,15 @@ impl<
         debug_assert!(spill_idx < self.spill.as_ref().len());
         self.spill.get_unchecked(spill_idx)
     }
+
+    unsafe fn select_unchecked_batch(&self, ranks: &[usize], pos: &mut [usize]) {
+        prefetched_batch(
+            ranks,
+            pos,
+            |rank| self.prefetch_inventory(rank),
+            |rank| self.select_unchecked(rank),
+        )
+    }
 }
 
 impl<
@@ -584,6 +673,56 @@ impl<
         const LOG2_U64_PER_SUBINVENTORY: usize,
     > Select for SelectAdaptConst<B, I, LOG2_ONES_PER_INVENTORY, LOG2_U64_PER_SUBINVENTORY>
 {
+    fn select_batch(&self, ranks: &[usize], pos: &mut [Option<usize>]) {
+        prefetched_batch(
+            ranks,
+            pos,
+            |rank| self.prefetch_inventory(rank),
+            |rank| self.select(rank),
+        )
+    }
+}
+
+impl<
+        B: AsRef<[usize]> + SelectHinted + Rank,
+        I: AsRef<[usize]>,
+        const LOG2_ONES_PER_INVENTORY: usize,
+        const LOG2_U64_PER_SUBINVENTORY: usize,
+    > NextOne for SelectAdaptConst<B, I, LOG2_ONES_PER_INVENTORY, LOG2_U64_PER_SUBINVENTORY>
+{
+}
+
+impl<
+        B: AsRef<[usize]> + SelectHinted + Rank,
+        I: AsRef<[usize]>,
+        const LOG2_ONES_PER_INVENTORY: usize,
+        const LOG2_U64_PER_SUBINVENTORY: usize,
+    > PrevOne for SelectAdaptConst<B, I, LOG2_ONES_PER_INVENTORY, LOG2_U64_PER_SUBINVENTORY>
+{
+}
+
+impl<
+        B,
+        I: AsRef<[usize]>,
+        const LOG2_ONES_PER_INVENTORY: usize,
+        const LOG2_U64_PER_SUBINVENTORY: usize,
+    > SelectAdaptConst<B, I, LOG2_ONES_PER_INVENTORY, LOG2_U64_PER_SUBINVENTORY>
+{
+    /// Prefetches the inventory entry, and the 16-bit subinventory entry,
+    /// needed to answer a query of given rank.
+    #[inline(always)]
+    fn prefetch_inventory(&self, rank: usize) {
+        let inventory = self.inventory.as_ref();
+        let inventory_index = rank >> LOG2_ONES_PER_INVENTORY;
+        let inventory_start_pos =
+            (inventory_index << LOG2_U64_PER_SUBINVENTORY).wrapping_add(inventory_index);
+        let subrank = rank & Self::ONES_PER_INVENTORY_MASK;
+        prefetch_index(inventory, inventory_start_pos);
+        prefetch_index(
+            inventory,
+            inventory_start_pos.wrapping_add(1 + (subrank >> Self::LOG2_ONES_PER_SUB16) / 4),
+        );
+    }
 }
 
 #[cfg(test)]
@@ -8,8 +8,9 @@
 
 use super::SmallCounters;
 use crate::prelude::*;
+use crate::utils::word_ops::select_in_word;
+use crate::utils::{prefetch_index, prefetched_batch};
 use ambassador::Delegate;
-use common_traits::SelectInWord;
 use epserde::Epserde;
 use mem_dbg::{MemDbg, MemSize};
 
@@ -18,7 +19,10 @@ use crate::ambassador_impl_Index;
 use crate::rank_sel::ambassador_impl_SmallCounters;
 use crate::traits::rank_sel::ambassador_impl_BitCount;
 use crate::traits::rank_sel::ambassador_impl_BitLength;
+use crate::traits::rank_sel::ambassador_impl_BitWords;
+use crate::traits::rank_sel::ambassador_impl_NextZero;
 use crate::traits::rank_sel::ambassador_impl_NumBits;
+use crate::traits::rank_sel::ambassador_impl_PrevZero;
 use crate::traits::rank_sel::ambassador_impl_Rank;
 use crate::traits::rank_sel::ambassador_impl_RankHinted;
 use crate::traits::rank_sel::ambassador_impl_RankUnchecked;
@@ -107,6 +111,7 @@ use std::ops::Index;
 /// ```
 #[derive(Epserde, Debug, Clone, MemDbg, MemSize, Delegate)]
 #[delegate(AsRef<[usize]>, target = "small_counters")]
+#[delegate(crate::traits::rank_sel::BitWords, target = "small_counters")]
 #[delegate(Index<usize>, target = "small_counters")]
 #[delegate(crate::traits::rank_sel::BitCount, target = "small_counters")]
 #[delegate(crate::traits::rank_sel::BitLength, target = "small_counters")]
@@ -123,6 +128,8 @@ use std::ops::Index;
     target = "small_counters"
 )]
 #[delegate(crate::rank_sel::SmallCounters<NUM_U32S, COUNTER_WIDTH>, target = "small_counters")]
+#[delegate(crate::traits::rank_sel::NextZero, target = "small_counters")]
+#[delegate(crate::traits::rank_sel::PrevZero, target = "small_counters")]
 pub struct SelectSmall<
     const NUM_U32S: usize,
     const COUNTER_WIDTH: usize,
@@ -136,6 +143,19 @@ pub struct SelectSmall<
     log2_ones_per_inventory: usize,
 }
 
+impl<const NUM_U32S: usize, const COUNTER_WIDTH: usize, C, I: AsRef<[u32]>, O>
+    SelectSmall<NUM_U32S, COUNTER_WIDTH, C, I, O>
+{
+    /// Prefetches the inventory entry needed to answer a query of given rank.
+    #[inline(always)]
+    fn prefetch_inventory(&self, rank: usize) {
+        prefetch_index(
+            self.inventory.as_ref(),
+            rank >> self.log2_ones_per_inventory,
+        );
+    }
+}
+
 impl<const NUM_U32S: usize, const COUNTER_WIDTH: usize, C, I, O>
     SelectSmall<NUM_U32S, COUNTER_WIDTH, C, I, O>
 {
@@ -218,7 +238,7 @@ macro_rules! impl_rank_small_sel {
                         let ones_in_word = word.count_ones() as usize;
 
                         while past_ones + ones_in_word > next_quantum {
-                            let in_word_index = word.select_in_word(next_quantum - past_ones);
+                            let in_word_index = select_in_word(word, next_quantum - past_ones);
                             let in_superblock_index = i * usize::BITS as usize + in_word_index;
                             if first {
                                 inventory_begin.push(inventory.len());
@@ -258,7 +278,9 @@ macro_rules! impl_rank_small_sel {
                     + BitLength
                     + NumBits
                     + SelectHinted,
-            > SelectUnchecked for SelectSmall<$NUM_U32S, $COUNTER_WIDTH, C>
+                I: AsRef<[u32]>,
+                O: AsRef<[usize]>,
+            > SelectUnchecked for SelectSmall<$NUM_U32S, $COUNTER_WIDTH, C, I, O>
         {
             unsafe fn select_unchecked(&self, rank: usize) -> usize {
                 let upper_counts = self.small_counters.upper_counts();
@@ -350,6 +372,15 @@ macro_rules! impl_rank_small_sel {
 
                 self.complete_select(block_count, hint_pos, rank, hint_rank)
             }
+
+            unsafe fn select_unchecked_batch(&self, ranks: &[usize], pos: &mut [usize]) {
+                prefetched_batch(
+                    ranks,
+                    pos,
+                    |rank| self.prefetch_inventory(rank),
+                    |rank| self.select_unchecked(rank),
+                )
+            }
         }
 
         impl<
@@ -358,13 +389,50 @@ macro_rules! impl_rank_small_sel {
                     + BitLength
                     + NumBits
                     + SelectHinted,
-            > Select for SelectSmall<$NUM_U32S, $COUNTER_WIDTH, C>
+                I: AsRef<[u32]>,
+                O: AsRef<[usize]>,
+            > Select for SelectSmall<$NUM_U32S, $COUNTER_WIDTH, C, I, O>
+        {
+            fn select_batch(&self, ranks: &[usize], pos: &mut [Option<usize>]) {
+                prefetched_batch(
+                    ranks,
+                    pos,
+                    |rank| self.prefetch_inventory(rank),
+                    |rank| self.select(rank),
+                )
+            }
+        }
+
+        impl<
+                C: SmallCounters<$NUM_U32S, $COUNTER_WIDTH> + AsRef<[usize]> + Rank + SelectHinted,
+                I: AsRef<[u32]>,
+                O: AsRef<[usize]>,
+            > NextOne for SelectSmall<$NUM_U32S, $COUNTER_WIDTH, C, I, O>
+        {
+            fn next_one(&self, pos: usize) -> Option<usize> {
+                super::next_one_in_words(self, pos)
+            }
+        }
+
+        impl<
+                C: SmallCounters<$NUM_U32S, $COUNTER_WIDTH> + AsRef<[usize]> + Rank + SelectHinted,
+                I: AsRef<[u32]>,
+                O: AsRef<[usize]>,
+            > PrevOne for SelectSmall<$NUM_U32S, $COUNTER_WIDTH, C, I, O>
         {
+            fn prev_one(&self, pos: usize) -> Option<usize> {
+                super::prev_one_in_words(self, pos)
+            }
         }
     };
 }
 
-impl<C: SmallCounters<2, 9> + AsRef<[usize]> + BitLength + NumBits> SelectSmall<2, 9, C> {
+impl<
+        C: SmallCounters<2, 9> + AsRef<[usize]> + BitLength + NumBits,
+        I: AsRef<[u32]>,
+        O: AsRef<[usize]>,
+    > SelectSmall<2, 9, C, I, O>
+{
     #[inline(always)]
     unsafe fn complete_select(
         &self,
@@ -397,15 +465,18 @@ impl<C: SmallCounters<2, 9> + AsRef<[usize]> + BitLength + NumBits> SelectSmall<
         hint_pos += offset_in_block * Self::SUBBLOCK_BIT_SIZE;
 
         hint_pos
-            + self
-                .as_ref()
-                .get_unchecked(hint_pos / usize::BITS as usize)
-                .select_in_word(rank_in_word)
+            + select_in_word(
+                *self.as_ref().get_unchecked(hint_pos / usize::BITS as usize),
+                rank_in_word,
+            )
     }
 }
 
-impl<C: SmallCounters<1, 9> + AsRef<[usize]> + BitLength + NumBits + SelectHinted>
-    SelectSmall<1, 9, C>
+impl<
+        C: SmallCounters<1, 9> + AsRef<[usize]> + BitLength + NumBits + SelectHinted,
+        I: AsRef<[u32]>,
+        O: AsRef<[usize]>,
+    > SelectSmall<1, 9, C, I, O>
 {
     #[inline(always)]
     unsafe fn complete_select(
@@ -439,8 +510,11 @@ impl<C: SmallCounters<1, 9> + AsRef<[usize]> + BitLength + NumBits + SelectHinte
     }
 }
 
-impl<C: SmallCounters<1, 10> + AsRef<[usize]> + BitLength + NumBits + SelectHinted>
-    SelectSmall<1, 10, C>
+impl<
+        C: SmallCounters<1, 10> + AsRef<[usize]> + BitLength + NumBits + SelectHinted,
+        I: AsRef<[u32]>,
+        O: AsRef<[usize]>,
+    > SelectSmall<1, 10, C, I, O>
 {
     #[inline(always)]
     unsafe fn complete_select(
@@ -474,8 +548,11 @@ impl<C: SmallCounters<1, 10> + AsRef<[usize]> + BitLength + NumBits + SelectHint
     }
 }
 
-impl<C: SmallCounters<1, 11> + AsRef<[usize]> + BitLength + NumBits + SelectHinted>
-    SelectSmall<1, 11, C>
+impl<
+        C: SmallCounters<1, 11> + AsRef<[usize]> + BitLength + NumBits + SelectHinted,
+        I: AsRef<[u32]>,
+        O: AsRef<[usize]>,
+    > SelectSmall<1, 11, C, I, O>
 {
     #[inline(always)]
     unsafe fn complete_select(
@@ -509,8 +586,11 @@ impl<C: SmallCounters<1, 11> + AsRef<[usize]> + BitLength + NumBits + SelectHint
     }
 }
 
-impl<C: SmallCounters<3, 13> + AsRef<[usize]> + BitLength + NumBits + SelectHinted>
-    SelectSmall<3, 13, C>
+impl<
+        C: SmallCounters<3, 13> + AsRef<[usize]> + BitLength + NumBits + SelectHinted,
+        I: AsRef<[u32]>,
+        O: AsRef<[usize]>,
+    > SelectSmall<3, 13, C, I, O>
 {
     unsafe fn complete_select(
         &self,
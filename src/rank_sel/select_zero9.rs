@@ -0,0 +1,476 @@
+/*
+ *
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use super::select9::{MSBS_STEP_16, MSBS_STEP_9, ONES_STEP_16, ONES_STEP_9};
+use super::select9::{ULEQ_STEP_16, ULEQ_STEP_9};
+use super::{BlockCounters, Rank9, Rank9Counters};
+use crate::utils::word_ops::select_in_word;
+use crate::{
+    prelude::SelectZeroUnchecked,
+    traits::{BitLength, NextZero, NumBits, PrevZero, RankZero, SelectZero},
+    utils::{prefetch_index, prefetched_batch},
+};
+use ambassador::Delegate;
+use epserde::Epserde;
+use mem_dbg::{MemDbg, MemSize};
+
+/// The relative counters of a [`Rank9`] block made of zeros.
+const ZEROS_RELATIVE: usize =
+    (64 << 54) | (128 << 45) | (192 << 36) | (256 << 27) | (320 << 18) | (384 << 9) | 448;
+
+/// Returns the number of zeros before the given [`Rank9`] block, including the
+/// zeros padding the end of the bit vector.
+#[inline(always)]
+fn zeros_before(counts: &[BlockCounters], block: usize) -> usize {
+    block * 64 * <Rank9>::WORDS_PER_BLOCK - counts[block].absolute
+}
+
+use crate::ambassador_impl_AsRef;
+use crate::ambassador_impl_Index;
+use crate::rank_sel::ambassador_impl_Rank9Counters;
+use crate::traits::rank_sel::ambassador_impl_BitCount;
+use crate::traits::rank_sel::ambassador_impl_BitLength;
+use crate::traits::rank_sel::ambassador_impl_BitWords;
+use crate::traits::rank_sel::ambassador_impl_NextOne;
+use crate::traits::rank_sel::ambassador_impl_NumBits;
+use crate::traits::rank_sel::ambassador_impl_PrevOne;
+use crate::traits::rank_sel::ambassador_impl_Rank;
+use crate::traits::rank_sel::ambassador_impl_RankHinted;
+use crate::traits::rank_sel::ambassador_impl_RankUnchecked;
+use crate::traits::rank_sel::ambassador_impl_RankZero;
+use crate::traits::rank_sel::ambassador_impl_Select;
+use crate::traits::rank_sel::ambassador_impl_SelectHinted;
+use crate::traits::rank_sel::ambassador_impl_SelectUnchecked;
+use crate::traits::rank_sel::ambassador_impl_SelectZeroHinted;
+use std::ops::Index;
+
+/// A selection structure for zeros over [`Rank9`] using 25%–37.5% additional
+/// space and providing constant-time selection.
+///
+/// [`SelectZero9`] is the analogous of [`Select9`](super::Select9) for zeros:
+/// it uses an absolute inventory and a relative subinventory to locate the
+/// [`Rank9`] block containing the desired zero, and then performs broadword
+/// operations using the [`Rank9`] counters, from which the number of zeros is
+/// computed on the fly.
+///
+/// Both [`SelectZero9`] and [`Select9`](super::Select9) can be built on a
+/// [`Rank9`], or on each other, as they forward the access to the [`Rank9`]
+/// counters, so you can get constant-time selection on both ones and zeros
+/// using just one ranking structure.
+///
+/// # Examples
+///
+/// ```rust
+/// use sux::bit_vec;
+/// use sux::prelude::{Rank, Rank9, Select, Select9, SelectZero, SelectZero9};
+/// // A SelectZero9 structure is built on a Rank9 structure
+/// let select_zero9 = SelectZero9::new(Rank9::new(bit_vec![1, 0, 1, 1, 0, 1, 0, 1]));
+///
+/// assert_eq!(select_zero9.select_zero(0), Some(1));
+/// assert_eq!(select_zero9.select_zero(1), Some(4));
+/// assert_eq!(select_zero9.select_zero(2), Some(6));
+/// assert_eq!(select_zero9.select_zero(3), None);
+///
+/// // Rank methods are forwarded
+/// assert_eq!(select_zero9.rank(4), 3);
+///
+/// // Select9 and SelectZero9 can be combined
+/// let sel = Select9::new(select_zero9);
+/// assert_eq!(sel.select(1), Some(2));
+/// assert_eq!(sel.select_zero(1), Some(4));
+/// ```
+
+#[derive(Epserde, Debug, Clone, MemDbg, MemSize, Delegate)]
+#[delegate(AsRef<[usize]>, target = "rank9")]
+#[delegate(crate::traits::rank_sel::BitWords, target = "rank9")]
+#[delegate(Index<usize>, target = "rank9")]
+#[delegate(crate::traits::rank_sel::BitCount, target = "rank9")]
+#[delegate(crate::traits::rank_sel::BitLength, target = "rank9")]
+#[delegate(crate::traits::rank_sel::NumBits, target = "rank9")]
+#[delegate(crate::traits::rank_sel::Rank, target = "rank9")]
+#[delegate(crate::traits::rank_sel::RankHinted<64>, target = "rank9")]
+#[delegate(crate::traits::rank_sel::RankUnchecked, target = "rank9")]
+#[delegate(crate::traits::rank_sel::RankZero, target = "rank9")]
+#[delegate(crate::traits::rank_sel::Select, target = "rank9")]
+#[delegate(crate::traits::rank_sel::SelectHinted, target = "rank9")]
+#[delegate(crate::traits::rank_sel::SelectUnchecked, target = "rank9")]
+#[delegate(crate::traits::rank_sel::SelectZeroHinted, target = "rank9")]
+#[delegate(crate::rank_sel::Rank9Counters, target = "rank9")]
+#[delegate(crate::traits::rank_sel::NextOne, target = "rank9")]
+#[delegate(crate::traits::rank_sel::PrevOne, target = "rank9")]
+pub struct SelectZero9<R = Rank9, I = Box<[usize]>> {
+    rank9: R,
+    inventory: I,
+    subinventory: I,
+    inventory_size: usize,
+    subinventory_size: usize,
+}
+
+impl<R, I> SelectZero9<R, I> {
+    pub fn into_inner(self) -> R {
+        self.rank9
+    }
+
+    const LOG2_ZEROS_PER_INVENTORY: usize = 9;
+    const ZEROS_PER_INVENTORY: usize = 1 << Self::LOG2_ZEROS_PER_INVENTORY;
+}
+
+impl<R: BitLength, I> SelectZero9<R, I> {
+    /// Returns the number of bits in the underlying bit vector.
+    ///
+    /// This method is equivalent to
+    /// [`BitLength::len`](crate::traits::BitLength::len), but it is provided to
+    /// reduce ambiguity in method resolution.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        BitLength::len(self)
+    }
+}
+
+impl<R: Rank9Counters + AsRef<[usize]> + BitLength + NumBits> SelectZero9<R, Box<[usize]>> {
+    pub fn new(rank9: R) -> Self {
+        let num_bits = rank9.len();
+        let num_words = num_bits.div_ceil(64);
+        let inventory_size = rank9.num_zeros().div_ceil(Self::ZEROS_PER_INVENTORY);
+
+        let u64_per_subinventory = 4;
+        let subinventory_size = num_words.div_ceil(u64_per_subinventory);
+
+        let mut inventory = Vec::with_capacity(inventory_size + 1);
+        let mut subinventory = vec![0; subinventory_size].into_boxed_slice();
+
+        // construct the inventory
+        let num_zeros = rank9.num_zeros();
+        let mut curr_num_ones = 0;
+        let mut next_quantum = 0;
+        for (i, word) in rank9.as_ref()[..num_words]
+            .iter()
+            .copied()
+            .map(|b| !b)
+            .enumerate()
+        {
+            // Zeros after the end of the bit vector are not counted
+            let ones_in_word = (word.count_ones() as usize).min(num_zeros - curr_num_ones);
+
+            while curr_num_ones + ones_in_word > next_quantum {
+                let in_word_index = select_in_word(word, next_quantum - curr_num_ones);
+                let index = (i * u64::BITS as usize) + in_word_index;
+
+                inventory.push(index);
+
+                next_quantum += Self::ZEROS_PER_INVENTORY;
+            }
+            curr_num_ones += ones_in_word;
+        }
+        inventory.push(((num_words + 3) & !3) * 64);
+        assert!(inventory.len() == inventory_size + 1);
+        let inventory = inventory.into_boxed_slice();
+
+        let iter = 0..inventory_size;
+        let counts = rank9.counts();
+
+        // construct the subinventory
+        iter.for_each(|inventory_idx| {
+            let subinv_start = (inventory[inventory_idx] / 64) / u64_per_subinventory;
+            let subinv_end = (inventory[inventory_idx + 1] / 64) / u64_per_subinventory;
+            let span = subinv_end - subinv_start;
+            let block_left = (inventory[inventory_idx] / 64) / 8;
+            let block_span = (inventory[inventory_idx + 1] / 64) / 8 - block_left;
+            let counts_at_start = zeros_before(counts, block_left);
+
+            let mut state = -1;
+            let s16: &mut [u16] =
+                unsafe { subinventory[subinv_start..subinv_end].align_to_mut().1 };
+            match span {
+                0..=1 => {}
+                2..=15 => {
+                    debug_assert!(((block_span + 8) & !7) <= span * 4);
+                    for (k, v) in s16.iter_mut().enumerate().take(block_span) {
+                        debug_assert!(*v == 0);
+                        *v = (zeros_before(counts, block_left + k + 1) - counts_at_start) as u16;
+                    }
+                    for v in s16.iter_mut().take((block_span + 8) & !7).skip(block_span) {
+                        debug_assert!(*v == 0);
+                        *v = 0xFFFFu16;
+                    }
+                }
+                16..=127 => {
+                    debug_assert!(((block_span + 8) & !7) + 8 <= span * 4);
+                    debug_assert!(block_span / 8 <= 8);
+                    for k in 0..block_span {
+                        debug_assert!(s16[k + 8] == 0);
+                        s16[k + 8] =
+                            (zeros_before(counts, block_left + k + 1) - counts_at_start) as u16;
+                    }
+                    for k in block_span..((block_span + 8) & !7) {
+                        debug_assert!(s16[k + 8] == 0);
+                        s16[k + 8] = 0xFFFFu16;
+                    }
+                    for (k, v) in s16.iter_mut().enumerate().take(block_span / 8) {
+                        debug_assert!(*v == 0);
+                        *v = (zeros_before(counts, block_left + (k + 1) * 8) - counts_at_start)
+                            as u16;
+                    }
+                    for v in s16.iter_mut().take(8).skip(block_span / 8) {
+                        debug_assert!(*v == 0);
+                        *v = 0xFFFFu16;
+                    }
+                }
+                128..=255 => {
+                    state = 2;
+                }
+                256..=511 => {
+                    state = 1;
+                }
+                _ => {
+                    state = 0;
+                }
+            }
+
+            if state != -1 {
+                // clean up the lower bits
+                let mut word_idx = inventory[inventory_idx] / usize::BITS as usize;
+                let bit_idx = inventory[inventory_idx] % usize::BITS as usize;
+                let mut word = (!rank9.as_ref()[word_idx] >> bit_idx) << bit_idx;
+
+                let start_bit_idx = inventory[inventory_idx];
+                let end_bit_idx = inventory[inventory_idx + 1];
+                let end_word_idx = end_bit_idx.div_ceil(u64::BITS as usize).min(num_words);
+                let mut subinventory_idx = 0;
+                'outer: loop {
+                    while word != 0 {
+                        let in_word_index = word.trailing_zeros() as usize;
+                        let bit_index = (word_idx * u64::BITS as usize) + in_word_index;
+                        let sub_offset = bit_index - start_bit_idx;
+                        match state {
+                            0 => {
+                                debug_assert!(subinventory[subinv_start + subinventory_idx] == 0);
+                                subinventory[subinv_start + subinventory_idx] = bit_index;
+                            }
+                            1 => {
+                                let s32: &mut [u32] = unsafe {
+                                    subinventory[subinv_start..subinv_end].align_to_mut().1
+                                };
+                                debug_assert!(s32[subinventory_idx] == 0);
+                                debug_assert!((bit_index - start_bit_idx) < (1 << 32));
+                                s32[subinventory_idx] = sub_offset as u32;
+                            }
+                            2 => {
+                                let s16: &mut [u16] = unsafe {
+                                    subinventory[subinv_start..subinv_end].align_to_mut().1
+                                };
+                                debug_assert!(s16[subinventory_idx] == 0);
+                                debug_assert!(bit_index - start_bit_idx < (1 << 16));
+                                s16[subinventory_idx] = (bit_index - start_bit_idx) as u16;
+                            }
+                            _ => unreachable!(),
+                        }
+
+                        subinventory_idx += 1;
+                        if subinventory_idx == Self::ZEROS_PER_INVENTORY {
+                            break 'outer;
+                        }
+
+                        word &= word - 1;
+                    }
+
+                    // move to the next word and boundcheck
+                    word_idx += 1;
+                    if word_idx == end_word_idx {
+                        break;
+                    }
+
+                    // read the next word
+                    word = !rank9.as_ref()[word_idx];
+                }
+            }
+        });
+
+        Self {
+            rank9,
+            inventory,
+            subinventory,
+            inventory_size,
+            subinventory_size,
+        }
+    }
+}
+
+impl<R: Rank9Counters + AsRef<[usize]> + BitLength, I: AsRef<[usize]>> SelectZeroUnchecked
+    for SelectZero9<R, I>
+{
+    unsafe fn select_zero_unchecked(&self, rank: usize) -> usize {
+        let inventory_index_left = rank >> Self::LOG2_ZEROS_PER_INVENTORY;
+
+        debug_assert!(inventory_index_left <= self.inventory_size);
+        let inventory_left = *self.inventory.as_ref().get_unchecked(inventory_index_left);
+
+        let block_right = (*self
+            .inventory
+            .as_ref()
+            .get_unchecked(inventory_index_left + 1))
+            / 64;
+        let mut block_left = inventory_left / 64;
+        let span = block_right / 4 - block_left / 4;
+
+        let subinv_pos = block_left / 4;
+        let subinv_ref = self.subinventory.as_ref();
+
+        let counts = self.rank9.counts();
+
+        let mut count_left;
+        let rank_in_block;
+
+        match span {
+            0..=1 => {
+                block_left &= !7;
+                count_left = block_left / <Rank9>::WORDS_PER_BLOCK;
+
+                debug_assert!(rank < zeros_before(counts, count_left + 1));
+                rank_in_block = rank - zeros_before(counts, count_left);
+            }
+            2..=15 => {
+                block_left &= !7;
+                count_left = block_left / <Rank9>::WORDS_PER_BLOCK;
+                let rank_in_superblock = rank - zeros_before(counts, count_left);
+
+                let rank_in_superblock_step_16 = rank_in_superblock * ONES_STEP_16;
+
+                let first = *subinv_ref.get_unchecked(subinv_pos);
+                let second = *subinv_ref.get_unchecked(subinv_pos + 1);
+
+                let where_: usize = (ULEQ_STEP_16!(first, rank_in_superblock_step_16).count_ones()
+                    as usize
+                    + ULEQ_STEP_16!(second, rank_in_superblock_step_16).count_ones() as usize)
+                    * 2;
+
+                debug_assert!(where_ <= 16);
+
+                block_left += where_ * 4;
+                count_left += where_ / 2;
+
+                rank_in_block = rank - zeros_before(counts, count_left);
+                debug_assert!(rank_in_block < 512);
+            }
+            16..=127 => {
+                block_left &= !7;
+                count_left = block_left / <Rank9>::WORDS_PER_BLOCK;
+                let rank_in_superblock = rank - zeros_before(counts, count_left);
+                let rank_in_superblock_step_16 = rank_in_superblock * ONES_STEP_16;
+
+                let first = *subinv_ref.get_unchecked(subinv_pos);
+                let second = *subinv_ref.get_unchecked(subinv_pos + 1);
+
+                let where0 = (ULEQ_STEP_16!(first, rank_in_superblock_step_16).count_ones()
+                    as usize
+                    + ULEQ_STEP_16!(second, rank_in_superblock_step_16).count_ones() as usize)
+                    * 2;
+
+                debug_assert!(where0 <= 16);
+
+                let first_bis = *self
+                    .subinventory
+                    .as_ref()
+                    .get_unchecked(subinv_pos + where0 + 2);
+                let second_bis = *self
+                    .subinventory
+                    .as_ref()
+                    .get_unchecked(subinv_pos + where0 + 2 + 1);
+
+                let where1 = where0 * 8
+                    + (ULEQ_STEP_16!(first_bis, rank_in_superblock_step_16).count_ones() as usize
+                        + ULEQ_STEP_16!(second_bis, rank_in_superblock_step_16).count_ones()
+                            as usize)
+                        * 2;
+
+                block_left += where1 * 4;
+                count_left += where1 / 2;
+                rank_in_block = rank - zeros_before(counts, count_left);
+
+                debug_assert!(rank_in_block < 512);
+            }
+            128..=255 => {
+                let (_, s, _) = subinv_ref
+                    .get_unchecked(subinv_pos..self.subinventory_size)
+                    .align_to::<u16>();
+                return *s.get_unchecked(rank % Self::ZEROS_PER_INVENTORY) as usize
+                    + inventory_left;
+            }
+            256..=511 => {
+                let (_, s, _) = subinv_ref
+                    .get_unchecked(subinv_pos..self.subinventory_size)
+                    .align_to::<u32>();
+                return *s.get_unchecked(rank % Self::ZEROS_PER_INVENTORY) as usize
+                    + inventory_left;
+            }
+            _ => {
+                return *subinv_ref.get_unchecked(subinv_pos + rank % Self::ZEROS_PER_INVENTORY);
+            }
+        }
+
+        let rank_in_block_step_9 = rank_in_block * ONES_STEP_9;
+        let relative = ZEROS_RELATIVE - counts.get_unchecked(count_left).relative;
+
+        let offset_in_block = ULEQ_STEP_9!(relative, rank_in_block_step_9).count_ones() as usize;
+        debug_assert!(offset_in_block <= 7);
+
+        let word = block_left + offset_in_block;
+        let rank_in_word = rank_in_block
+            - (offset_in_block * 64 - counts.get_unchecked(count_left).rel(offset_in_block));
+
+        word * 64 + select_in_word(!self.rank9.as_ref().get_unchecked(word), rank_in_word)
+    }
+
+    unsafe fn select_zero_unchecked_batch(&self, ranks: &[usize], pos: &mut [usize]) {
+        prefetched_batch(
+            ranks,
+            pos,
+            |rank| self.prefetch_inventory(rank),
+            |rank| self.select_zero_unchecked(rank),
+        )
+    }
+}
+
+impl<R: Rank9Counters + AsRef<[usize]> + BitLength + NumBits, I: AsRef<[usize]>> SelectZero
+    for SelectZero9<R, I>
+{
+    fn select_zero_batch(&self, ranks: &[usize], pos: &mut [Option<usize>]) {
+        prefetched_batch(
+            ranks,
+            pos,
+            |rank| self.prefetch_inventory(rank),
+            |rank| self.select_zero(rank),
+        )
+    }
+}
+
+impl<R: Rank9Counters + AsRef<[usize]> + RankZero, I: AsRef<[usize]>> NextZero
+    for SelectZero9<R, I>
+{
+    fn next_zero(&self, pos: usize) -> Option<usize> {
+        super::next_zero_in_words(self, pos)
+    }
+}
+
+impl<R: Rank9Counters + AsRef<[usize]> + RankZero, I: AsRef<[usize]>> PrevZero
+    for SelectZero9<R, I>
+{
+    fn prev_zero(&self, pos: usize) -> Option<usize> {
+        super::prev_zero_in_words(self, pos)
+    }
+}
+
+impl<R, I: AsRef<[usize]>> SelectZero9<R, I> {
+    /// Prefetches the inventory entry needed to answer a query of given rank.
+    #[inline(always)]
+    fn prefetch_inventory(&self, rank: usize) {
+        prefetch_index(
+            self.inventory.as_ref(),
+            rank >> Self::LOG2_ZEROS_PER_INVENTORY,
+        );
+    }
+}
@@ -6,23 +6,30 @@
  * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
  */
 
-use super::{Inventory, SpanType};
+use super::{verify_inventory, Inventory, SpanType};
+use crate::utils::word_ops::select_in_word;
 use ambassador::Delegate;
-use common_traits::SelectInWord;
 use epserde::Epserde;
 use mem_dbg::{MemDbg, MemSize};
 use std::cmp::{max, min};
 
 use crate::{
-    prelude::{BitCount, BitFieldSlice, BitLength, SelectZeroHinted},
-    traits::{NumBits, SelectZero, SelectZeroUnchecked},
+    prelude::{BitCount, BitFieldSlice, BitLength, BitWords, SelectZeroHinted},
+    traits::{
+        NextZero, NumBits, PrevZero, RankZero, SelectZero, SelectZeroUnchecked, Verify,
+        VerifyError, VerifyMode,
+    },
+    utils::{prefetch_index, prefetched_batch},
 };
 
 use crate::ambassador_impl_AsRef;
 use crate::ambassador_impl_Index;
 use crate::traits::rank_sel::ambassador_impl_BitCount;
 use crate::traits::rank_sel::ambassador_impl_BitLength;
+use crate::traits::rank_sel::ambassador_impl_BitWords;
+use crate::traits::rank_sel::ambassador_impl_NextOne;
 use crate::traits::rank_sel::ambassador_impl_NumBits;
+use crate::traits::rank_sel::ambassador_impl_PrevOne;
 use crate::traits::rank_sel::ambassador_impl_Rank;
 use crate::traits::rank_sel::ambassador_impl_RankHinted;
 use crate::traits::rank_sel::ambassador_impl_RankUnchecked;
@@ -128,6 +135,7 @@ use std::ops::Index;
 
 #[derive(Epserde, Debug, Clone, MemDbg, MemSize, Delegate)]
 #[delegate(AsRef<[usize]>, target = "bits")]
+#[delegate(crate::traits::rank_sel::BitWords, target = "bits")]
 #[delegate(Index<usize>, target = "bits")]
 #[delegate(crate::traits::rank_sel::BitCount, target = "bits")]
 #[delegate(crate::traits::rank_sel::BitLength, target = "bits")]
@@ -140,6 +148,8 @@ use std::ops::Index;
 #[delegate(crate::traits::rank_sel::SelectHinted, target = "bits")]
 #[delegate(crate::traits::rank_sel::SelectUnchecked, target = "bits")]
 #[delegate(crate::traits::rank_sel::SelectZeroHinted, target = "bits")]
+#[delegate(crate::traits::rank_sel::NextOne, target = "bits")]
+#[delegate(crate::traits::rank_sel::PrevOne, target = "bits")]
 pub struct SelectZeroAdapt<B, I = Box<[usize]>> {
     bits: B,
     inventory: I,
@@ -203,7 +213,7 @@ impl<B: BitLength, C> SelectZeroAdapt<B, C> {
     }
 }
 
-impl<B: AsRef<[usize]> + BitCount> SelectZeroAdapt<B, Box<[usize]>> {
+impl<B: BitWords + BitCount> SelectZeroAdapt<B, Box<[usize]>> {
     /// Creates a new selection structure over a bit vecotr using a
     /// [default target inventory
     /// span](SelectZeroAdapt::DEFAULT_TARGET_INVENTORY_SPAN).
@@ -339,11 +349,11 @@ impl<B: AsRef<[usize]> + BitCount> SelectZeroAdapt<B, Box<[usize]>> {
         let mut spilled = 0;
 
         // First phase: we build an inventory for each one out of ones_per_inventory.
-        for (i, word) in bits.as_ref().iter().copied().map(|b| !b).enumerate() {
+        for (i, word) in (0..bits.num_words()).map(|i| !bits.word(i)).enumerate() {
             let ones_in_word = (word.count_ones() as usize).min(num_ones - past_ones);
 
             while past_ones + ones_in_word > next_quantum {
-                let in_word_index = word.select_in_word(next_quantum - past_ones);
+                let in_word_index = select_in_word(word, next_quantum - past_ones);
                 let index = (i * usize::BITS as usize) + in_word_index;
 
                 // write the position of the one in the inventory
@@ -454,7 +464,7 @@ impl<B: AsRef<[usize]> + BitCount> SelectZeroAdapt<B, Box<[usize]>> {
             let bit_idx = start_bit_idx % usize::BITS as usize;
 
             // Clear the lower bits
-            let mut word = (!bits.as_ref()[word_idx] >> bit_idx) << bit_idx;
+            let mut word = (!bits.word(word_idx) >> bit_idx) << bit_idx;
 
             'outer: loop {
                 let ones_in_word = word.count_ones() as usize;
@@ -465,7 +475,7 @@ impl<B: AsRef<[usize]> + BitCount> SelectZeroAdapt<B, Box<[usize]>> {
                 while past_ones + ones_in_word > next_quantum {
                     debug_assert!(next_quantum <= end_bit_idx);
                     // find the quantum bit in the word
-                    let in_word_index = word.select_in_word(next_quantum - past_ones);
+                    let in_word_index = select_in_word(word, next_quantum - past_ones);
                     // compute the global index of the quantum bit in the bitvec
                     let bit_index = (word_idx * usize::BITS as usize) + in_word_index;
 
@@ -557,7 +567,7 @@ impl<B: AsRef<[usize]> + BitCount> SelectZeroAdapt<B, Box<[usize]>> {
                 }
 
                 // Read the next word
-                word = !bits.as_ref()[word_idx];
+                word = !bits.word(word_idx);
             }
 
             // If we are in the U32 case, we need to update the number of used
@@ -586,7 +596,7 @@ impl<B: AsRef<[usize]> + BitCount> SelectZeroAdapt<B, Box<[usize]>> {
     }
 }
 
-impl<B: AsRef<[usize]> + BitLength + SelectZeroHinted, I: AsRef<[usize]>> SelectZeroUnchecked
+impl<B: BitLength + SelectZeroHinted, I: AsRef<[usize]>> SelectZeroUnchecked
     for SelectZeroAdapt<B, I>
 {
     unsafe fn select_zero_unchecked(&self, rank: usize) -> usize {
@@ -666,11 +676,93 @@ impl<B: AsRef<[usize]> + BitLength + SelectZeroHinted, I: AsRef<[usize]>> Select
         debug_assert!(spill_idx < self.spill.as_ref().len());
         self.spill.get_unchecked(spill_idx)
     }
+
+    unsafe fn select_zero_unchecked_batch(&self, ranks: &[usize], pos: &mut [usize]) {
+        prefetched_batch(
+            ranks,
+            pos,
+            |rank| self.prefetch_inventory(rank),
+            |rank| self.select_zero_unchecked(rank),
+        )
+    }
 }
 
-impl<B: AsRef<[usize]> + NumBits + SelectZeroHinted, I: AsRef<[usize]>> SelectZero
-    for SelectZeroAdapt<B, I>
-{
+impl<B: BitWords + BitLength + Verify, I: AsRef<[usize]>> Verify for SelectZeroAdapt<B, I> {
+    fn verify(&self, mode: VerifyMode) -> Result<(), VerifyError> {
+        self.bits.verify(mode)?;
+        verify_inventory(
+            |i| !self.bits.word(i),
+            self.bits.len(),
+            self.inventory.as_ref(),
+            self.spill.as_ref(),
+            self.log2_ones_per_inventory,
+            self.log2_u64_per_subinventory,
+            mode,
+        )?;
+
+        let log2_ones_per_sub16 = self
+            .log2_ones_per_inventory
+            .saturating_sub(self.log2_u64_per_subinventory + 2);
+        for (what, expected, found) in [
+            (
+                "log2 of zeros per 16-bit subinventory entry",
+                log2_ones_per_sub16,
+                self.log2_ones_per_sub16,
+            ),
+            (
+                "zeros-per-inventory mask",
+                (1 << self.log2_ones_per_inventory) - 1,
+                self.ones_per_inventory_mask,
+            ),
+            (
+                "zeros-per-16-bit-subinventory-entry mask",
+                (1 << log2_ones_per_sub16) - 1,
+                self.ones_per_sub16_mask,
+            ),
+        ] {
+            if expected != found {
+                return Err(VerifyError::Param {
+                    what,
+                    expected,
+                    found,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<B: NumBits + SelectZeroHinted, I: AsRef<[usize]>> SelectZero for SelectZeroAdapt<B, I> {
+    fn select_zero_batch(&self, ranks: &[usize], pos: &mut [Option<usize>]) {
+        prefetched_batch(
+            ranks,
+            pos,
+            |rank| self.prefetch_inventory(rank),
+            |rank| self.select_zero(rank),
+        )
+    }
+}
+
+impl<B: SelectZeroHinted + RankZero, I: AsRef<[usize]>> NextZero for SelectZeroAdapt<B, I> {}
+
+impl<B: SelectZeroHinted + RankZero, I: AsRef<[usize]>> PrevZero for SelectZeroAdapt<B, I> {}
+
+impl<B, I: AsRef<[usize]>> SelectZeroAdapt<B, I> {
+    /// Prefetches the inventory entry, and the 16-bit subinventory entry,
+    /// needed to answer a query of given rank.
+    #[inline(always)]
+    fn prefetch_inventory(&self, rank: usize) {
+        let inventory = self.inventory.as_ref();
+        let inventory_index = rank >> self.log2_ones_per_inventory;
+        let inventory_start_pos =
+            (inventory_index << self.log2_u64_per_subinventory).wrapping_add(inventory_index);
+        let subrank = rank & self.ones_per_inventory_mask;
+        prefetch_index(inventory, inventory_start_pos);
+        prefetch_index(
+            inventory,
+            inventory_start_pos.wrapping_add(1 + (subrank >> self.log2_ones_per_sub16) / 4),
+        );
+    }
 }
 
 #[cfg(test)]
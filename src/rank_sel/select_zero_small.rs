@@ -8,8 +8,9 @@
 
 use super::SmallCounters;
 use crate::prelude::*;
+use crate::utils::word_ops::select_in_word;
+use crate::utils::{prefetch_index, prefetched_batch};
 use ambassador::Delegate;
-use common_traits::SelectInWord;
 use epserde::Epserde;
 use mem_dbg::{MemDbg, MemSize};
 
@@ -18,7 +19,10 @@ use crate::ambassador_impl_Index;
 use crate::rank_sel::ambassador_impl_SmallCounters;
 use crate::traits::rank_sel::ambassador_impl_BitCount;
 use crate::traits::rank_sel::ambassador_impl_BitLength;
+use crate::traits::rank_sel::ambassador_impl_BitWords;
+use crate::traits::rank_sel::ambassador_impl_NextOne;
 use crate::traits::rank_sel::ambassador_impl_NumBits;
+use crate::traits::rank_sel::ambassador_impl_PrevOne;
 use crate::traits::rank_sel::ambassador_impl_Rank;
 use crate::traits::rank_sel::ambassador_impl_RankHinted;
 use crate::traits::rank_sel::ambassador_impl_RankUnchecked;
@@ -72,6 +76,7 @@ use std::ops::Index;
 /// ```
 #[derive(Epserde, Debug, Clone, MemDbg, MemSize, Delegate)]
 #[delegate(AsRef<[usize]>, target = "small_counters")]
+#[delegate(crate::traits::rank_sel::BitWords, target = "small_counters")]
 #[delegate(Index<usize>, target = "small_counters")]
 #[delegate(crate::traits::rank_sel::BitCount, target = "small_counters")]
 #[delegate(crate::traits::rank_sel::BitLength, target = "small_counters")]
@@ -85,6 +90,8 @@ use std::ops::Index;
 #[delegate(crate::traits::rank_sel::SelectUnchecked, target = "small_counters")]
 #[delegate(crate::traits::rank_sel::SelectZeroHinted, target = "small_counters")]
 #[delegate(crate::rank_sel::SmallCounters<NUM_U32S, COUNTER_WIDTH>, target = "small_counters")]
+#[delegate(crate::traits::rank_sel::NextOne, target = "small_counters")]
+#[delegate(crate::traits::rank_sel::PrevOne, target = "small_counters")]
 pub struct SelectZeroSmall<
     const NUM_U32S: usize,
     const COUNTER_WIDTH: usize,
@@ -98,6 +105,19 @@ pub struct SelectZeroSmall<
     log2_ones_per_inventory: usize,
 }
 
+impl<const NUM_U32S: usize, const COUNTER_WIDTH: usize, C, I: AsRef<[u32]>, O>
+    SelectZeroSmall<NUM_U32S, COUNTER_WIDTH, C, I, O>
+{
+    /// Prefetches the inventory entry needed to answer a query of given rank.
+    #[inline(always)]
+    fn prefetch_inventory(&self, rank: usize) {
+        prefetch_index(
+            self.inventory.as_ref(),
+            rank >> self.log2_ones_per_inventory,
+        );
+    }
+}
+
 impl<const NUM_U32S: usize, const COUNTER_WIDTH: usize, C, I, O>
     SelectZeroSmall<NUM_U32S, COUNTER_WIDTH, C, I, O>
 {
@@ -178,7 +198,7 @@ macro_rules! impl_select_zero_small {
                         let ones_in_word = (word.count_ones() as usize).min(num_ones - past_ones);
 
                         while past_ones + ones_in_word > next_quantum {
-                            let in_word_index = word.select_in_word(next_quantum - past_ones);
+                            let in_word_index = select_in_word(word, next_quantum - past_ones);
                             let in_superblock_index = i * usize::BITS as usize + in_word_index;
                             if first {
                                 inventory_begin.push(inventory.len());
@@ -220,7 +240,9 @@ macro_rules! impl_select_zero_small {
                     + BitLength
                     + NumBits
                     + SelectZeroHinted,
-            > SelectZeroUnchecked for SelectZeroSmall<$NUM_U32S, $COUNTER_WIDTH, C>
+                I: AsRef<[u32]>,
+                O: AsRef<[usize]>,
+            > SelectZeroUnchecked for SelectZeroSmall<$NUM_U32S, $COUNTER_WIDTH, C, I, O>
         {
             unsafe fn select_zero_unchecked(&self, rank: usize) -> usize {
                 let upper_counts = self.small_counters.upper_counts();
@@ -316,6 +338,15 @@ macro_rules! impl_select_zero_small {
 
                 self.complete_select(block_count, hint_pos, rank, hint_rank)
             }
+
+            unsafe fn select_zero_unchecked_batch(&self, ranks: &[usize], pos: &mut [usize]) {
+                prefetched_batch(
+                    ranks,
+                    pos,
+                    |rank| self.prefetch_inventory(rank),
+                    |rank| self.select_zero_unchecked(rank),
+                )
+            }
         }
 
         impl<
@@ -324,13 +355,56 @@ macro_rules! impl_select_zero_small {
                     + BitLength
                     + NumBits
                     + SelectZeroHinted,
-            > SelectZero for SelectZeroSmall<$NUM_U32S, $COUNTER_WIDTH, C>
+                I: AsRef<[u32]>,
+                O: AsRef<[usize]>,
+            > SelectZero for SelectZeroSmall<$NUM_U32S, $COUNTER_WIDTH, C, I, O>
         {
+            fn select_zero_batch(&self, ranks: &[usize], pos: &mut [Option<usize>]) {
+                prefetched_batch(
+                    ranks,
+                    pos,
+                    |rank| self.prefetch_inventory(rank),
+                    |rank| self.select_zero(rank),
+                )
+            }
+        }
+
+        impl<
+                C: SmallCounters<$NUM_U32S, $COUNTER_WIDTH>
+                    + AsRef<[usize]>
+                    + RankZero
+                    + SelectZeroHinted,
+                I: AsRef<[u32]>,
+                O: AsRef<[usize]>,
+            > NextZero for SelectZeroSmall<$NUM_U32S, $COUNTER_WIDTH, C, I, O>
+        {
+            fn next_zero(&self, pos: usize) -> Option<usize> {
+                super::next_zero_in_words(self, pos)
+            }
+        }
+
+        impl<
+                C: SmallCounters<$NUM_U32S, $COUNTER_WIDTH>
+                    + AsRef<[usize]>
+                    + RankZero
+                    + SelectZeroHinted,
+                I: AsRef<[u32]>,
+                O: AsRef<[usize]>,
+            > PrevZero for SelectZeroSmall<$NUM_U32S, $COUNTER_WIDTH, C, I, O>
+        {
+            fn prev_zero(&self, pos: usize) -> Option<usize> {
+                super::prev_zero_in_words(self, pos)
+            }
         }
     };
 }
 
-impl<C: SmallCounters<2, 9> + AsRef<[usize]> + BitLength + NumBits> SelectZeroSmall<2, 9, C> {
+impl<
+        C: SmallCounters<2, 9> + AsRef<[usize]> + BitLength + NumBits,
+        I: AsRef<[u32]>,
+        O: AsRef<[usize]>,
+    > SelectZeroSmall<2, 9, C, I, O>
+{
     #[inline(always)]
     unsafe fn complete_select(
         &self,
@@ -376,16 +450,21 @@ impl<C: SmallCounters<2, 9> + AsRef<[usize]> + BitLength + NumBits> SelectZeroSm
         hint_pos += offset_in_block * (SUBBLOCK_BIT_SIZE as usize);
 
         hint_pos
-            + (!self
-                .small_counters
-                .as_ref()
-                .get_unchecked(hint_pos / usize::BITS as usize))
-            .select_in_word(rank_in_word)
+            + select_in_word(
+                !self
+                    .small_counters
+                    .as_ref()
+                    .get_unchecked(hint_pos / usize::BITS as usize),
+                rank_in_word,
+            )
     }
 }
 
-impl<C: SmallCounters<1, 9> + AsRef<[usize]> + BitLength + NumBits + SelectZeroHinted>
-    SelectZeroSmall<1, 9, C>
+impl<
+        C: SmallCounters<1, 9> + AsRef<[usize]> + BitLength + NumBits + SelectZeroHinted,
+        I: AsRef<[u32]>,
+        O: AsRef<[usize]>,
+    > SelectZeroSmall<1, 9, C, I, O>
 {
     #[inline(always)]
     unsafe fn complete_select(
@@ -425,8 +504,11 @@ impl<C: SmallCounters<1, 9> + AsRef<[usize]> + BitLength + NumBits + SelectZeroH
     }
 }
 
-impl<C: SmallCounters<1, 10> + AsRef<[usize]> + BitLength + NumBits + SelectZeroHinted>
-    SelectZeroSmall<1, 10, C>
+impl<
+        C: SmallCounters<1, 10> + AsRef<[usize]> + BitLength + NumBits + SelectZeroHinted,
+        I: AsRef<[u32]>,
+        O: AsRef<[usize]>,
+    > SelectZeroSmall<1, 10, C, I, O>
 {
     #[inline(always)]
     unsafe fn complete_select(
@@ -466,8 +548,11 @@ impl<C: SmallCounters<1, 10> + AsRef<[usize]> + BitLength + NumBits + SelectZero
     }
 }
 
-impl<C: SmallCounters<1, 11> + AsRef<[usize]> + BitLength + NumBits + SelectZeroHinted>
-    SelectZeroSmall<1, 11, C>
+impl<
+        C: SmallCounters<1, 11> + AsRef<[usize]> + BitLength + NumBits + SelectZeroHinted,
+        I: AsRef<[u32]>,
+        O: AsRef<[usize]>,
+    > SelectZeroSmall<1, 11, C, I, O>
 {
     #[inline(always)]
     unsafe fn complete_select(
@@ -507,8 +592,11 @@ impl<C: SmallCounters<1, 11> + AsRef<[usize]> + BitLength + NumBits + SelectZero
     }
 }
 
-impl<C: SmallCounters<3, 13> + AsRef<[usize]> + BitLength + NumBits + SelectZeroHinted>
-    SelectZeroSmall<3, 13, C>
+impl<
+        C: SmallCounters<3, 13> + AsRef<[usize]> + BitLength + NumBits + SelectZeroHinted,
+        I: AsRef<[u32]>,
+        O: AsRef<[usize]>,
+    > SelectZeroSmall<3, 13, C, I, O>
 {
     unsafe fn complete_select(
         &self,
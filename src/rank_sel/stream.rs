@@ -0,0 +1,724 @@
+/*
+ *
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! External-memory construction of ranking and selection structures.
+//!
+//! The functions in this module read the words of a bit vector sequentially
+//! from a [`Read`], and write to a [`Write`] the [ε-serde](epserde)
+//! serialization of a [`Rank9`] or of a [`SelectAdaptConst`] whose backend is
+//! the unit type `()`, that is, just their counters or inventories. The bit
+//! vector is never loaded in memory, so it is possible to build structures
+//! over bit vectors much larger than the available RAM.
+//!
+//! The input must contain the words of the bit vector in native byte order,
+//! that is, the raw content of the slice returned by
+//! [`AsRef<[usize]>`](crate::bits::BitVec::as_ref) on a
+//! [`BitVec`](crate::bits::BitVec); bits beyond the length of the bit vector
+//! in the last word are ignored.
+//!
+//! To use the result, memory-map the counters or the inventories with
+//! [`Deserialize::mmap`], memory-map the raw bits using
+//! [`MmapHelper`](crate::utils::MmapHelper), and replace the unit backend
+//! with the bits using [`Rank9::map`] or [`SelectAdaptConst::map`]:
+//!
+//! ```rust
+//! # use sux::prelude::*;
+//! # use sux::rank_sel::stream_rank9_select_adapt_const;
+//! # use sux::utils::MmapHelper;
+//! # use dsi_progress_logger::no_logging;
+//! # use epserde::prelude::*;
+//! # use mmap_rs::MmapFlags;
+//! # use std::fs::File;
+//! # use std::io::{BufReader, Write};
+//! # fn main() -> anyhow::Result<()> {
+//! # let dir = tempfile::TempDir::new()?;
+//! # let bits_path = dir.path().join("bits");
+//! # let counters_path = dir.path().join("counters");
+//! # let inventory_path = dir.path().join("inventory");
+//! # let num_bits = 1_000_000;
+//! # let bits: BitVec = (0..num_bits).map(|i| i % 3 == 0).collect();
+//! # File::create(&bits_path)?
+//! #     .write_all(bytemuck::cast_slice::<usize, u8>(bits.as_ref()))?;
+//! // Read the raw bits once, writing both counters and inventories
+//! let num_ones = stream_rank9_select_adapt_const::<12, 3>(
+//!     BufReader::new(File::open(&bits_path)?),
+//!     num_bits,
+//!     File::create(&counters_path)?,
+//!     File::create(&inventory_path)?,
+//!     no_logging![],
+//! )?;
+//! assert_eq!(num_ones, 333_334);
+//!
+//! let bits = MmapHelper::<usize>::mmap(&bits_path, MmapFlags::empty())?;
+//! let bits = unsafe { BitVec::from_raw_parts(bits, num_bits) };
+//! let counters = <Rank9<(), Box<[BlockCounters]>>>::mmap(&counters_path, Flags::empty())?;
+//! let inventory = <SelectAdaptConst<(), Box<[usize]>, 12, 3>>::mmap(
+//!     &inventory_path,
+//!     Flags::empty(),
+//! )?;
+//!
+//! // SAFETY: the counters and the inventory have been built on these bits
+//! let rank9 = unsafe { counters.clone().map(|_| bits) };
+//! let rank9_sel = unsafe { inventory.clone().map(|_| rank9) };
+//!
+//! assert_eq!(rank9_sel.rank(10), 4);
+//! assert_eq!(rank9_sel.select(4), Some(12));
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The counters of a [`Rank9`] are streamed directly to the output, as their
+//! number depends only on the length of the bit vector. The inventory and the
+//! spill buffer of a [`SelectAdaptConst`], instead, are first written to
+//! temporary files, and then copied to the output; since each inventory entry
+//! is completed as soon as the position of the next indexed one is known, the
+//! memory used is independent of the length of the bit vector.
+//!
+//! The same technique is used by
+//! [`EliasFanoStreamBuilder`](crate::dict::EliasFanoStreamBuilder) to build
+//! an Elias–Fano representation of a monotone sequence in external memory.
+
+use super::select_adapt::{log2_quantum, spill_len, write_entry};
+use super::{BlockCounters, Rank9, SelectAdaptConst};
+use crate::utils::word_ops::select_in_word;
+use anyhow::{ensure, Context, Result};
+use dsi_progress_logger::ProgressLog;
+use epserde::prelude::*;
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// The number of words read at a time.
+const CHUNK_WORDS: usize = 1 << 16;
+
+/// The maximum number of words of an inventory entry that are kept in memory;
+/// beyond this threshold, we store the positions of the ones instead.
+const MAX_DENSE_WORDS: usize = 1 << 10;
+
+/// Reads the counters of a [`Rank9`] from a bit vector.
+///
+/// The result is the [ε-serde](epserde) serialization of a `Rank9<(),
+/// Box<[BlockCounters]>>`, which can be memory-mapped and combined with the
+/// bits as explained in the [module documentation](self).
+///
+/// The function returns the number of ones in the bit vector.
+///
+/// # Errors
+///
+/// This function returns an error if `bits` contains less than
+/// `num_bits.div_ceil(usize::BITS)` words, or if an I/O error occurs.
+pub fn stream_rank9(
+    bits: impl Read,
+    num_bits: usize,
+    counters: impl Write,
+    pl: &mut impl ProgressLog,
+) -> Result<usize> {
+    stream::<12, 3>(bits, num_bits, Some(counters), None::<std::io::Sink>, pl)
+}
+
+/// Reads the inventory of a [`SelectAdaptConst`] from a bit vector.
+///
+/// The result is the [ε-serde](epserde) serialization of a
+/// `SelectAdaptConst<(), Box<[usize]>, LOG2_ONES_PER_INVENTORY,
+/// LOG2_U64_PER_SUBINVENTORY>`, which can be memory-mapped and combined with
+/// the bits, or with a ranking structure over the bits, as explained in the
+/// [module documentation](self). The inventory is identical to that built by
+/// [`SelectAdaptConst::new`].
+///
+/// The function returns the number of ones in the bit vector.
+///
+/// # Errors
+///
+/// This function returns an error if `bits` contains less than
+/// `num_bits.div_ceil(usize::BITS)` words, or if an I/O error occurs.
+pub fn stream_select_adapt_const<
+    const LOG2_ONES_PER_INVENTORY: usize,
+    const LOG2_U64_PER_SUBINVENTORY: usize,
+>(
+    bits: impl Read,
+    num_bits: usize,
+    inventory: impl Write,
+    pl: &mut impl ProgressLog,
+) -> Result<usize> {
+    stream::<LOG2_ONES_PER_INVENTORY, LOG2_U64_PER_SUBINVENTORY>(
+        bits,
+        num_bits,
+        None::<std::io::Sink>,
+        Some(inventory),
+        pl,
+    )
+}
+
+/// Reads both the counters of a [`Rank9`] and the inventory of a
+/// [`SelectAdaptConst`] from a bit vector, reading it just once.
+///
+/// The result is the same of [`stream_rank9`] and
+/// [`stream_select_adapt_const`].
+///
+/// # Errors
+///
+/// This function returns an error if `bits` contains less than
+/// `num_bits.div_ceil(usize::BITS)` words, or if an I/O error occurs.
+pub fn stream_rank9_select_adapt_const<
+    const LOG2_ONES_PER_INVENTORY: usize,
+    const LOG2_U64_PER_SUBINVENTORY: usize,
+>(
+    bits: impl Read,
+    num_bits: usize,
+    counters: impl Write,
+    inventory: impl Write,
+    pl: &mut impl ProgressLog,
+) -> Result<usize> {
+    stream::<LOG2_ONES_PER_INVENTORY, LOG2_U64_PER_SUBINVENTORY>(
+        bits,
+        num_bits,
+        Some(counters),
+        Some(inventory),
+        pl,
+    )
+}
+
+fn stream<const LOG2_ONES_PER_INVENTORY: usize, const LOG2_U64_PER_SUBINVENTORY: usize>(
+    mut bits: impl Read,
+    num_bits: usize,
+    counters: Option<impl Write>,
+    inventory: Option<impl Write>,
+    pl: &mut impl ProgressLog,
+) -> Result<usize> {
+    let mut builder = inventory
+        .is_some()
+        .then(|| InventoryBuilder::new(LOG2_ONES_PER_INVENTORY, LOG2_U64_PER_SUBINVENTORY, None))
+        .transpose()?;
+    let mut num_ones = 0;
+
+    pl.item_name("word")
+        .expected_updates(Some(num_bits.div_ceil(usize::BITS as usize)));
+    pl.start("Reading bits...");
+
+    match counters {
+        Some(counters) => {
+            let num_words = num_bits.div_ceil(usize::BITS as usize);
+            let num_counts = num_words.div_ceil(Rank9::<(), ()>::WORDS_PER_BLOCK) + 1;
+            let mut error = None;
+            let rank9 = Rank9 {
+                bits: (),
+                counts: StreamedSlice::new(num_counts, |push| {
+                    let mut counts = Vec::with_capacity(CHUNK_WORDS / 8);
+                    let result = read_words(&mut bits, num_bits, pl, |first_word, words| {
+                        counts.clear();
+                        for block in words.chunks(Rank9::<(), ()>::WORDS_PER_BLOCK) {
+                            let mut count = BlockCounters {
+                                absolute: num_ones,
+                                relative: 0,
+                            };
+                            num_ones += block[0].count_ones() as usize;
+                            for j in 1..8 {
+                                count.set_rel(j, num_ones - count.absolute);
+                                if j < block.len() {
+                                    num_ones += block[j].count_ones() as usize;
+                                }
+                            }
+                            counts.push(count);
+                        }
+                        push(&counts).context("Cannot write counters")?;
+                        if let Some(builder) = &mut builder {
+                            builder.push_words(first_word, words)?;
+                        }
+                        Ok(())
+                    });
+                    if let Err(e) = result {
+                        error = Some(e);
+                        return Err(ser::Error::WriteError);
+                    }
+                    push(&[BlockCounters {
+                        absolute: num_ones,
+                        relative: 0,
+                    }])
+                }),
+            };
+            let result = rank9.serialize(&mut BufWriter::new(counters));
+            drop(rank9);
+            if let Some(e) = error {
+                return Err(e);
+            }
+            result.context("Cannot serialize counters")?;
+        }
+        None => read_words(&mut bits, num_bits, pl, |first_word, words| {
+            num_ones += words.iter().map(|w| w.count_ones() as usize).sum::<usize>();
+            if let Some(builder) = &mut builder {
+                builder.push_words(first_word, words)?;
+            }
+            Ok(())
+        })?,
+    }
+
+    pl.done();
+
+    if let (Some(builder), Some(inventory)) = (builder, inventory) {
+        let (inventory_file, spill_file) = builder.finish(num_bits, num_ones)?;
+        let (inventory_len, mut inventory_file) = inventory_file;
+        let (spill_len, mut spill_file) = spill_file;
+        let error = RefCell::new(None);
+        // SAFETY: the inventory and the spill buffer are built by
+        // InventoryBuilder, which follows the layout of the constructor
+        let select = unsafe {
+            SelectAdaptConst::<(), _, LOG2_ONES_PER_INVENTORY, LOG2_U64_PER_SUBINVENTORY>::from_raw_parts(
+                (),
+                StreamedSlice::<usize>::new(inventory_len, |push| {
+                    copy_words(&mut inventory_file, inventory_len, push, &error)
+                }),
+                StreamedSlice::<usize>::new(spill_len, |push| {
+                    copy_words(&mut spill_file, spill_len, push, &error)
+                }),
+            )
+        };
+        let result = select.serialize(&mut BufWriter::new(inventory));
+        drop(select);
+        if let Some(e) = error.into_inner() {
+            return Err(e);
+        }
+        result.context("Cannot serialize inventory")?;
+    }
+
+    Ok(num_ones)
+}
+
+/// Reads the words of a bit vector of given length in chunks, passing to `f`
+/// the index of the first word of each chunk and the chunk.
+///
+/// Bits beyond the length of the bit vector in the last word are zeroed.
+fn read_words(
+    bits: &mut impl Read,
+    num_bits: usize,
+    pl: &mut impl ProgressLog,
+    mut f: impl FnMut(usize, &[usize]) -> Result<()>,
+) -> Result<()> {
+    let num_words = num_bits.div_ceil(usize::BITS as usize);
+    let mut buffer = vec![0_usize; CHUNK_WORDS.min(num_words)];
+    for first_word in (0..num_words).step_by(CHUNK_WORDS) {
+        let words = &mut buffer[..CHUNK_WORDS.min(num_words - first_word)];
+        bits.read_exact(bytemuck::cast_slice_mut(words))
+            .with_context(|| {
+                format!(
+                    "Cannot read words {}..{} of the bit vector",
+                    first_word,
+                    first_word + words.len()
+                )
+            })?;
+        if first_word + words.len() == num_words && !num_bits.is_multiple_of(usize::BITS as usize) {
+            words[words.len() - 1] &= (1 << (num_bits % usize::BITS as usize)) - 1;
+        }
+        f(first_word, words)?;
+        pl.update_with_count(words.len());
+    }
+    Ok(())
+}
+
+/// Passes to `push` the first `len` words of a temporary file.
+pub(crate) fn copy_words<T: bytemuck::Pod>(
+    file: &mut BufReader<File>,
+    len: usize,
+    push: &mut dyn FnMut(&[T]) -> ser::Result<()>,
+    error: &RefCell<Option<anyhow::Error>>,
+) -> ser::Result<()> {
+    let mut buffer = vec![T::zeroed(); CHUNK_WORDS.min(len)];
+    for start in (0..len).step_by(CHUNK_WORDS) {
+        let words = &mut buffer[..CHUNK_WORDS.min(len - start)];
+        if let Err(e) = file.read_exact(bytemuck::cast_slice_mut(words)) {
+            *error.borrow_mut() = Some(anyhow::Error::new(e).context("Cannot read temporary file"));
+            return Err(ser::Error::WriteError);
+        }
+        push(words)?;
+    }
+    Ok(())
+}
+
+type Filler<'a, T> =
+    Box<dyn FnMut(&mut dyn FnMut(&[T]) -> ser::Result<()>) -> ser::Result<()> + 'a>;
+
+/// A boxed slice of zero-copy values that are produced while serializing.
+///
+/// The slice is serialized exactly as a `Box<[T]>` of length `len`, but its
+/// content is provided by a closure that is called once and must pass to its
+/// argument, in chunks, exactly `len` values; otherwise, serialization fails
+/// with [`ser::Error::WriteError`].
+pub(crate) struct StreamedSlice<'a, T> {
+    len: usize,
+    fill: RefCell<Filler<'a, T>>,
+}
+
+impl<'a, T> StreamedSlice<'a, T> {
+    pub(crate) fn new(
+        len: usize,
+        fill: impl FnMut(&mut dyn FnMut(&[T]) -> ser::Result<()>) -> ser::Result<()> + 'a,
+    ) -> Self {
+        Self {
+            len,
+            fill: RefCell::new(Box::new(fill)),
+        }
+    }
+}
+
+impl<T: TypeHash> TypeHash for StreamedSlice<'_, T> {
+    fn type_hash(hasher: &mut impl core::hash::Hasher) {
+        <Box<[T]>>::type_hash(hasher);
+    }
+}
+
+impl<T: ReprHash> ReprHash for StreamedSlice<'_, T> {
+    fn repr_hash(hasher: &mut impl core::hash::Hasher, offset_of: &mut usize) {
+        <Box<[T]>>::repr_hash(hasher, offset_of);
+    }
+}
+
+impl<T: ZeroCopy + SerializeInner + TypeHash + ReprHash> SerializeInner for StreamedSlice<'_, T> {
+    type SerType = Box<[T]>;
+    const IS_ZERO_COPY: bool = false;
+    const ZERO_COPY_MISMATCH: bool = false;
+
+    // Duplicates ser::helpers::serialize_slice_zero, which needs the whole
+    // slice in memory. The ε-serde version is pinned in Cargo.toml, and the
+    // tests of this module check that the output is that of a boxed slice.
+    fn _serialize_inner(&self, backend: &mut impl ser::WriteWithNames) -> ser::Result<()> {
+        ser::helpers::check_zero_copy::<T>();
+        backend.write("len", &self.len)?;
+        backend.align::<T>()?;
+        let mut written = 0;
+        (self.fill.borrow_mut())(&mut |values: &[T]| {
+            written += values.len();
+            // We must not write more values than declared in the length
+            if written > self.len {
+                return Err(ser::Error::WriteError);
+            }
+            // SAFETY: T is zero-copy
+            let bytes = unsafe {
+                core::slice::from_raw_parts(values.as_ptr() as *const u8, size_of_val(values))
+            };
+            backend.write_bytes::<T>(bytes)
+        })?;
+        if written != self.len {
+            return Err(ser::Error::WriteError);
+        }
+        Ok(())
+    }
+}
+
+/// Builds incrementally the inventory and the spill buffer of a
+/// [`SelectAdaptConst`], writing them to temporary files.
+///
+/// The layout is the same of the sequential constructor. Each inventory entry
+/// is completed when the position of the first one of the next entry is
+/// known; in the meantime, we store the words of the entry or, if the entry
+/// spans more than [`MAX_DENSE_WORDS`] words, the positions of its ones.
+pub(crate) struct InventoryBuilder {
+    log2_ones_per_inventory: usize,
+    log2_u64_per_subinventory: usize,
+    log2_ones_per_sub16: usize,
+    /// The position of the first one of the current entry, if any.
+    start: Option<usize>,
+    /// The number of ones of the current entry seen so far.
+    ones: usize,
+    /// The index of the first word of the current entry.
+    first_word: usize,
+    /// The words of the current entry, if it is not sparse.
+    words: Vec<usize>,
+    /// The positions of the ones of the current entry, if it is sparse.
+    positions: Vec<usize>,
+    sparse: bool,
+    /// The number of inventory entries completed so far.
+    num_entries: usize,
+    inventory: BufWriter<File>,
+    inventory_len: usize,
+    spill: BufWriter<File>,
+    spill_len: usize,
+    /// Buffers for the current entry.
+    entry: Vec<usize>,
+    entry_spill: Vec<usize>,
+    offsets: Vec<usize>,
+}
+
+impl InventoryBuilder {
+    pub(crate) fn new(
+        log2_ones_per_inventory: usize,
+        log2_u64_per_subinventory: usize,
+        temp_dir: Option<&Path>,
+    ) -> Result<Self> {
+        Ok(Self {
+            log2_ones_per_inventory,
+            log2_u64_per_subinventory,
+            log2_ones_per_sub16: log2_ones_per_inventory
+                .saturating_sub(log2_u64_per_subinventory + 2),
+            start: None,
+            ones: 0,
+            first_word: 0,
+            words: Vec::with_capacity(MAX_DENSE_WORDS),
+            positions: vec![],
+            sparse: false,
+            num_entries: 0,
+            inventory: BufWriter::new(temp_file(temp_dir)?),
+            inventory_len: 0,
+            spill: BufWriter::new(temp_file(temp_dir)?),
+            spill_len: 0,
+            entry: vec![0; (1 << log2_u64_per_subinventory) + 1],
+            entry_spill: vec![],
+            offsets: vec![],
+        })
+    }
+
+    pub(crate) fn push_words(&mut self, first_word: usize, words: &[usize]) -> Result<()> {
+        for (i, &word) in words.iter().enumerate() {
+            if word != 0 {
+                self.push_word(first_word + i, word)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn push_word(&mut self, word_idx: usize, mut word: usize) -> Result<()> {
+        let ones_per_inventory = 1 << self.log2_ones_per_inventory;
+        while word != 0 {
+            if self.start.is_none() {
+                self.start = Some(word_idx * usize::BITS as usize + word.trailing_zeros() as usize);
+                self.first_word = word_idx;
+            }
+            let ones_in_word = word.count_ones() as usize;
+            let missing = ones_per_inventory - self.ones;
+            if ones_in_word <= missing {
+                self.record(word_idx, word);
+                self.ones += ones_in_word;
+                return Ok(());
+            }
+
+            // The word contains the first one of the next entry
+            let bit = select_in_word(word, missing);
+            self.record(word_idx, word & ((1 << bit) - 1));
+            self.ones += missing;
+            self.flush_entry(word_idx * usize::BITS as usize + bit)?;
+            word = (word >> bit) << bit;
+        }
+        Ok(())
+    }
+
+    /// Records a word of the current entry.
+    fn record(&mut self, word_idx: usize, word: usize) {
+        if !self.sparse && word_idx - self.first_word >= MAX_DENSE_WORDS {
+            // Switch to positions
+            for (i, &w) in self.words.iter().enumerate() {
+                push_positions(&mut self.positions, self.first_word + i, w);
+            }
+            self.words.clear();
+            self.sparse = true;
+        }
+        if self.sparse {
+            push_positions(&mut self.positions, word_idx, word);
+        } else {
+            self.words.resize(word_idx - self.first_word, 0);
+            self.words.push(word);
+        }
+    }
+
+    /// Completes the current entry, given the position of the first one of
+    /// the next entry (or the length of the bit vector).
+    fn flush_entry(&mut self, end: usize) -> Result<()> {
+        let start = self.start.take().expect("No current inventory entry");
+        let u64_per_subinventory = 1 << self.log2_u64_per_subinventory;
+        let span = end - start;
+        let log2_quantum = log2_quantum(span, self.log2_ones_per_sub16);
+
+        // The positions of the ones of rank multiple of the quantum, except
+        // for the first one, which is the start of the entry
+        let num_sub = self.ones.div_ceil(1 << log2_quantum);
+        self.offsets.clear();
+        if self.sparse {
+            self.offsets.extend(
+                self.positions
+                    .iter()
+                    .copied()
+                    .step_by(1 << log2_quantum)
+                    .take(num_sub)
+                    .skip(1),
+            );
+        } else {
+            let mut past_ones = 0;
+            let mut next_quantum = 1 << log2_quantum;
+            for (i, &word) in self.words.iter().enumerate() {
+                let ones_in_word = word.count_ones() as usize;
+                while past_ones + ones_in_word > next_quantum && next_quantum < self.ones {
+                    self.offsets.push(
+                        (self.first_word + i) * usize::BITS as usize
+                            + select_in_word(word, next_quantum - past_ones),
+                    );
+                    next_quantum += 1 << log2_quantum;
+                }
+                past_ones += ones_in_word;
+            }
+        }
+        debug_assert_eq!(self.offsets.len(), num_sub - 1);
+
+        self.entry.fill(0);
+        self.entry_spill.clear();
+        self.entry_spill.resize(
+            spill_len(
+                span,
+                self.ones,
+                self.log2_ones_per_sub16,
+                u64_per_subinventory,
+            ),
+            0,
+        );
+        write_entry(
+            &mut self.entry,
+            &mut self.entry_spill,
+            self.spill_len,
+            start,
+            span,
+            self.offsets.iter().copied(),
+        );
+
+        self.inventory
+            .write_all(bytemuck::cast_slice(&self.entry))
+            .context("Cannot write inventory")?;
+        self.spill
+            .write_all(bytemuck::cast_slice(&self.entry_spill))
+            .context("Cannot write spill")?;
+        self.inventory_len += self.entry.len();
+        self.spill_len += self.entry_spill.len();
+        self.num_entries += 1;
+
+        self.ones = 0;
+        self.words.clear();
+        self.positions.clear();
+        self.sparse = false;
+        Ok(())
+    }
+
+    /// Completes the inventory, returning the lengths and the content of the
+    /// temporary files containing the inventory and the spill buffer.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn finish(
+        mut self,
+        num_bits: usize,
+        num_ones: usize,
+    ) -> Result<((usize, BufReader<File>), (usize, BufReader<File>))> {
+        let num_bits = num_bits.max(1);
+        if self.start.is_some() {
+            self.flush_entry(num_bits)?;
+        }
+        ensure!(
+            self.num_entries == num_ones.div_ceil(1 << self.log2_ones_per_inventory),
+            "Wrong number of inventory entries: expected {}, found {}",
+            num_ones.div_ceil(1 << self.log2_ones_per_inventory),
+            self.num_entries
+        );
+        // In the last inventory entry we write the number of bits
+        self.inventory
+            .write_all(bytemuck::cast_slice(&[num_bits]))
+            .context("Cannot write inventory")?;
+        self.inventory_len += 1;
+
+        let rewind = |file: BufWriter<File>| -> Result<BufReader<File>> {
+            let mut file = file.into_inner()?;
+            file.seek(SeekFrom::Start(0))?;
+            Ok(BufReader::new(file))
+        };
+        Ok((
+            (self.inventory_len, rewind(self.inventory)?),
+            (self.spill_len, rewind(self.spill)?),
+        ))
+    }
+}
+
+/// Creates a temporary file in the given directory, or in the default
+/// temporary directory if `temp_dir` is `None`.
+pub(crate) fn temp_file(temp_dir: Option<&Path>) -> std::io::Result<File> {
+    match temp_dir {
+        Some(dir) => tempfile::tempfile_in(dir),
+        None => tempfile::tempfile(),
+    }
+}
+
+/// Appends to `positions` the positions of the ones in a word.
+fn push_positions(positions: &mut Vec<usize>, word_idx: usize, mut word: usize) {
+    while word != 0 {
+        positions.push(word_idx * usize::BITS as usize + word.trailing_zeros() as usize);
+        word &= word - 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Returns a streamed slice passing `values` in chunks of given size.
+    fn streamed<T: Copy>(values: &[T], chunk: usize) -> StreamedSlice<'_, T> {
+        StreamedSlice::new(values.len(), move |push| {
+            values.chunks(chunk).try_for_each(|values| push(values))
+        })
+    }
+
+    fn serialize(value: &impl Serialize) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        value.serialize(&mut bytes).unwrap();
+        bytes
+    }
+
+    // The serialization of a streamed slice duplicates that of boxed slices
+    // in ε-serde: these tests catch any change of layout
+    #[test]
+    fn test_streamed_slice() {
+        for len in [0, 1, 7, 1000] {
+            let values = (0..len).map(|x| x * x).collect::<Box<[usize]>>();
+            let counts = (0..len)
+                .map(|x| BlockCounters {
+                    absolute: x,
+                    relative: x * 3,
+                })
+                .collect::<Box<[BlockCounters]>>();
+            for chunk in [1, 3, 1024] {
+                assert_eq!(serialize(&streamed(&values, chunk)), serialize(&values),);
+
+                let rank9 = Rank9 {
+                    bits: (),
+                    counts: streamed(&counts, chunk),
+                };
+                let expected = Rank9 {
+                    bits: (),
+                    counts: counts.clone(),
+                };
+                assert_eq!(serialize(&rank9), serialize(&expected));
+
+                let spill = &values[..len / 2];
+                let select = unsafe {
+                    SelectAdaptConst::<(), _, 8, 2>::from_raw_parts(
+                        (),
+                        streamed(&values, chunk),
+                        streamed(spill, chunk),
+                    )
+                };
+                let expected = unsafe {
+                    SelectAdaptConst::<(), _, 8, 2>::from_raw_parts(
+                        (),
+                        values.clone(),
+                        Box::<[usize]>::from(spill),
+                    )
+                };
+                assert_eq!(serialize(&select), serialize(&expected));
+            }
+        }
+    }
+
+    #[test]
+    fn test_streamed_slice_wrong_len() {
+        let values = [1_usize, 2, 3];
+        let mut bytes = Vec::new();
+        let short = StreamedSlice::new(4, |push| push(&values));
+        assert!(short.serialize(&mut bytes).is_err());
+        let long = StreamedSlice::new(2, |push| push(&values));
+        assert!(long.serialize(&mut bytes).is_err());
+    }
+}
@@ -0,0 +1,401 @@
+/*
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use crate::prelude::*;
+use crate::traits::bit_field_slice::panic_if_out_of_bounds;
+use crate::utils::word_ops::select_in_word;
+use epserde::*;
+use mem_dbg::*;
+
+/// A ranking and selection structure for sequences of 2-bit or 4-bit symbols
+/// stored in a [`BitFieldVec`].
+///
+/// The structure answers [`rank(c, i)`](SymbolRank::rank), the number of
+/// occurrences of the symbol `c` before position `i`, and [`select(c,
+/// k)`](SymbolRank::select), the position of the occurrence of rank `k` of
+/// `c`, for every symbol of the alphabet, which makes it a building block for
+/// indices over DNA or categorical data. Storing a [`BitVec`] and a [`Rank9`]
+/// for each symbol would use about `1.25σ` bits per element, where σ is the
+/// size of the alphabet, and would require accessing a different bit vector
+/// for each symbol.
+///
+/// The design follows that of [`Rank9`]: symbols are divided in blocks of 512
+/// symbols, which are in turn divided in eight subblocks of 64 symbols (two
+/// words for 2-bit symbols, four words for 4-bit symbols). For each block and
+/// each symbol we store a 64-bit absolute cumulative counter and seven 9-bit
+/// relative cumulative counters, and the counters of all symbols of a block
+/// are interleaved. For 2-bit symbols the counters of a block occupy 64
+/// bytes, so ranking two symbols at the same position touches the same cache
+/// line (or two adjacent lines, if the counters are not aligned to 64 bytes);
+/// for 4-bit symbols they occupy 256 bytes, that is, four cache lines, and
+/// different symbols may use different lines. The occurrences of a symbol
+/// inside a subblock are counted using word-parallel symbol matching. The
+/// space overhead is thus 50% for 2-bit symbols and 100% for 4-bit symbols.
+///
+/// Selection is performed by a binary search on the absolute counters,
+/// followed by a scan of the relative counters and of the subblock; there are
+/// no inventories.
+///
+/// The parameter `WIDTH` is the width of the symbols in bits, and must be 2
+/// or 4; the bit width of the underlying [`BitFieldVec`] must be equal to
+/// `WIDTH`.
+///
+/// # Examples
+///
+/// ```rust
+/// use sux::prelude::*;
+///
+/// // A DNA sequence, with A = 0, C = 1, G = 2 and T = 3
+/// let dna = bit_field_vec![2; 0, 1, 2, 3, 0, 0, 3, 2, 1, 0];
+/// let symbol_rank = SymbolRank::<2>::new(dna);
+///
+/// assert_eq!(symbol_rank.rank(0, 5), 2);
+/// assert_eq!(symbol_rank.rank(3, 10), 2);
+/// assert_eq!(symbol_rank.count(0), 4);
+/// assert_eq!(symbol_rank.select(2, 1), Some(7));
+/// assert_eq!(symbol_rank.select(1, 2), None);
+///
+/// // Access to the underlying symbols is forwarded
+/// assert_eq!(symbol_rank.get(3), 3);
+/// ```
+#[derive(Epserde, Debug, Clone, MemDbg, MemSize)]
+pub struct SymbolRank<const WIDTH: usize, B = Vec<usize>, C = Box<[BlockCounters]>> {
+    symbols: BitFieldVec<usize, B>,
+    /// For each block, the counters of each symbol. At the end, the number of
+    /// occurrences of each symbol.
+    counts: C,
+}
+
+impl<const WIDTH: usize, B, C> SymbolRank<WIDTH, B, C> {
+    /// The number of symbols.
+    const SIGMA: usize = 1 << WIDTH;
+    const SYMBOLS_PER_BLOCK: usize = 512;
+    const SYMBOLS_PER_SUBBLOCK: usize = 64;
+    const WORDS_PER_SUBBLOCK: usize = WIDTH;
+    /// The lowest bit of each symbol in a word.
+    const LSB_MASK: usize = usize::MAX / ((1 << WIDTH) - 1);
+
+    /// Returns a word with the lowest bit of each symbol set if the symbol is
+    /// equal to `symbol`.
+    #[inline(always)]
+    fn matches(word: usize, symbol: usize) -> usize {
+        let mut x = word ^ (symbol * Self::LSB_MASK);
+        let mut shift = 1;
+        while shift < WIDTH {
+            x |= x >> shift;
+            shift *= 2;
+        }
+        !x & Self::LSB_MASK
+    }
+
+    /// Returns the number of symbols.
+    ///
+    /// This method is equivalent to
+    /// [`BitFieldSliceCore::len`](crate::traits::BitFieldSliceCore::len), but
+    /// it is provided to reduce ambiguity in method resolution.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn into_inner(self) -> BitFieldVec<usize, B> {
+        self.symbols
+    }
+
+    /// Replaces the backend of the underlying [`BitFieldVec`] with a new one.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe because it is not possible to guarantee that the
+    /// new backend contains the same symbols as the old one.
+    pub unsafe fn map<B1>(self, f: impl FnOnce(B) -> B1) -> SymbolRank<WIDTH, B1, C>
+    where
+        B1: AsRef<[usize]>,
+    {
+        let (bits, bit_width, len) = self.symbols.into_raw_parts();
+        SymbolRank {
+            symbols: BitFieldVec::from_raw_parts(f(bits), bit_width, len),
+            counts: self.counts,
+        }
+    }
+
+    /// Computes the counters of a block, storing in each absolute counter the
+    /// number of occurrences of the corresponding symbol in the block.
+    ///
+    /// `counts` must contain `SIGMA` counters.
+    fn block_counts(words: &[usize], len: usize, block: usize, counts: &mut [BlockCounters]) {
+        let num_words = (len * WIDTH).div_ceil(usize::BITS as usize);
+        let last_bits = (len * WIDTH) % usize::BITS as usize;
+        counts.fill(BlockCounters::default());
+
+        for sub in 0..8 {
+            if sub != 0 {
+                for count in counts.iter_mut() {
+                    count.set_rel(sub, count.absolute);
+                }
+            }
+            let start = (block * 8 + sub) * Self::WORDS_PER_SUBBLOCK;
+            let end = num_words.min(start + Self::WORDS_PER_SUBBLOCK);
+            for (w, &word) in words.iter().enumerate().take(end).skip(start) {
+                // Unused bits in the last word would match symbol zero
+                let valid = if w == num_words - 1 && last_bits != 0 {
+                    (1 << last_bits) - 1
+                } else {
+                    usize::MAX
+                };
+                for (symbol, count) in counts.iter_mut().enumerate() {
+                    count.absolute += (Self::matches(word, symbol) & valid).count_ones() as usize;
+                }
+            }
+        }
+    }
+}
+
+impl<const WIDTH: usize, B: AsRef<[usize]>> SymbolRank<WIDTH, B, Box<[BlockCounters]>> {
+    /// Creates a new structure from a given vector of symbols.
+    ///
+    /// # Panics
+    ///
+    /// If `WIDTH` is not 2 or 4, or if the bit width of `symbols` is not
+    /// `WIDTH`.
+    pub fn new(symbols: BitFieldVec<usize, B>) -> Self {
+        assert!(WIDTH == 2 || WIDTH == 4, "WIDTH must be 2 or 4");
+        assert_eq!(
+            symbols.bit_width(),
+            WIDTH,
+            "The bit width of the symbols must be {}",
+            WIDTH
+        );
+
+        let len = symbols.len();
+        let num_blocks = len.div_ceil(Self::SYMBOLS_PER_BLOCK);
+        let words = symbols.as_slice();
+
+        let mut counts = vec![BlockCounters::default(); (num_blocks + 1) * Self::SIGMA];
+        let mut totals = vec![0; Self::SIGMA];
+
+        for (block, block_counts) in counts.chunks_exact_mut(Self::SIGMA).enumerate() {
+            if block < num_blocks {
+                Self::block_counts(words, len, block, block_counts);
+            }
+            for (count, total) in block_counts.iter_mut().zip(totals.iter_mut()) {
+                let block_occurrences = count.absolute;
+                count.absolute = *total;
+                *total += block_occurrences;
+            }
+        }
+
+        Self {
+            symbols,
+            counts: counts.into(),
+        }
+    }
+}
+
+impl<const WIDTH: usize, B: AsRef<[usize]>, C: AsRef<[BlockCounters]>> SymbolRank<WIDTH, B, C> {
+    /// Returns the number of occurrences of a symbol.
+    ///
+    /// # Panics
+    ///
+    /// If `symbol` is not smaller than 2<sup>`WIDTH`</sup>.
+    #[inline(always)]
+    pub fn count(&self, symbol: usize) -> usize {
+        panic_if_out_of_bounds!(symbol, Self::SIGMA);
+        let counts = self.counts.as_ref();
+        counts[counts.len() - Self::SIGMA + symbol].absolute
+    }
+
+    /// Returns the number of occurrences of a symbol before the given position.
+    ///
+    /// If `pos` is greater than or equal to the number of symbols, the number
+    /// of occurrences of the symbol is returned.
+    ///
+    /// # Panics
+    ///
+    /// If `symbol` is not smaller than 2<sup>`WIDTH`</sup>.
+    #[inline(always)]
+    pub fn rank(&self, symbol: usize, pos: usize) -> usize {
+        panic_if_out_of_bounds!(symbol, Self::SIGMA);
+        // SAFETY: symbol and position are within bounds
+        unsafe { self.rank_unchecked(symbol, pos.min(self.len())) }
+    }
+
+    /// Returns the number of occurrences of a symbol before the given
+    /// position, without checking bounds.
+    ///
+    /// # Safety
+    ///
+    /// `symbol` must be smaller than 2<sup>`WIDTH`</sup>, and `pos` must be
+    /// at most the number of symbols.
+    #[inline(always)]
+    pub unsafe fn rank_unchecked(&self, symbol: usize, pos: usize) -> usize {
+        let words = self.symbols.as_slice();
+        let block = pos / Self::SYMBOLS_PER_BLOCK;
+        let sub = (pos / Self::SYMBOLS_PER_SUBBLOCK) % 8;
+        let counts = self
+            .counts
+            .as_ref()
+            .get_unchecked(block * Self::SIGMA + symbol);
+        let mut rank = counts.absolute + counts.rel(sub);
+
+        let bit_pos = pos * WIDTH;
+        let word_pos = bit_pos / usize::BITS as usize;
+        for w in (pos / Self::SYMBOLS_PER_SUBBLOCK) * Self::WORDS_PER_SUBBLOCK..word_pos {
+            rank += Self::matches(*words.get_unchecked(w), symbol).count_ones() as usize;
+        }
+        let bit_offset = bit_pos % usize::BITS as usize;
+        if bit_offset != 0 {
+            let matches = Self::matches(*words.get_unchecked(word_pos), symbol);
+            rank += (matches & ((1 << bit_offset) - 1)).count_ones() as usize;
+        }
+        rank
+    }
+
+    /// Returns the position of the occurrence of given rank of a symbol, or
+    /// `None` if there is no such occurrence.
+    ///
+    /// # Panics
+    ///
+    /// If `symbol` is not smaller than 2<sup>`WIDTH`</sup>.
+    #[inline]
+    pub fn select(&self, symbol: usize, rank: usize) -> Option<usize> {
+        if rank >= self.count(symbol) {
+            None
+        } else {
+            // SAFETY: symbol and rank are within bounds
+            Some(unsafe { self.select_unchecked(symbol, rank) })
+        }
+    }
+
+    /// Returns the position of the occurrence of given rank of a symbol,
+    /// without checking bounds.
+    ///
+    /// # Safety
+    ///
+    /// `symbol` must be smaller than 2<sup>`WIDTH`</sup>, and `rank` must be
+    /// smaller than the number of occurrences of `symbol`.
+    pub unsafe fn select_unchecked(&self, symbol: usize, rank: usize) -> usize {
+        let words = self.symbols.as_slice();
+        let counts = self.counts.as_ref();
+
+        // The last block whose absolute counter is at most rank
+        let mut lo = 0;
+        let mut hi = counts.len() / Self::SIGMA - 1;
+        while hi - lo > 1 {
+            let mid = (lo + hi) / 2;
+            if counts.get_unchecked(mid * Self::SIGMA + symbol).absolute <= rank {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+
+        let block_counts = counts.get_unchecked(lo * Self::SIGMA + symbol);
+        let mut rank = rank - block_counts.absolute;
+        let mut sub = 7;
+        while block_counts.rel(sub) > rank {
+            sub -= 1;
+        }
+        rank -= block_counts.rel(sub);
+
+        let mut w = (lo * 8 + sub) * Self::WORDS_PER_SUBBLOCK;
+        loop {
+            let matches = Self::matches(*words.get_unchecked(w), symbol);
+            let ones = matches.count_ones() as usize;
+            if rank < ones {
+                return (w * usize::BITS as usize + select_in_word(matches, rank)) / WIDTH;
+            }
+            rank -= ones;
+            w += 1;
+        }
+    }
+}
+
+impl<const WIDTH: usize, B, C> BitFieldSliceCore<usize> for SymbolRank<WIDTH, B, C> {
+    #[inline(always)]
+    fn bit_width(&self) -> usize {
+        WIDTH
+    }
+
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.symbols.len()
+    }
+}
+
+impl<const WIDTH: usize, B: AsRef<[usize]>, C> BitFieldSlice<usize> for SymbolRank<WIDTH, B, C> {
+    #[inline(always)]
+    unsafe fn get_unchecked(&self, index: usize) -> usize {
+        self.symbols.get_unchecked(index)
+    }
+}
+
+/// Recomputes the counters of each (or each sampled) block from the
+/// underlying symbols, checking both the relative counters and the difference
+/// between the absolute counters of the block and of the following one.
+impl<const WIDTH: usize, B: AsRef<[usize]>, C: AsRef<[BlockCounters]>> Verify
+    for SymbolRank<WIDTH, B, C>
+{
+    fn verify(&self, mode: VerifyMode) -> Result<(), VerifyError> {
+        self.symbols.verify(mode)?;
+
+        if self.symbols.bit_width() != WIDTH {
+            return Err(VerifyError::Param {
+                what: "bit width",
+                expected: WIDTH,
+                found: self.symbols.bit_width(),
+            });
+        }
+
+        let len = self.len();
+        let num_blocks = len.div_ceil(Self::SYMBOLS_PER_BLOCK);
+        let counts = self.counts.as_ref();
+        if counts.len() != (num_blocks + 1) * Self::SIGMA {
+            return Err(VerifyError::Param {
+                what: "number of counters",
+                expected: (num_blocks + 1) * Self::SIGMA,
+                found: counts.len(),
+            });
+        }
+        for (symbol, count) in counts[..Self::SIGMA].iter().enumerate() {
+            if count.absolute != 0 {
+                return Err(VerifyError::Value {
+                    what: "absolute counter",
+                    index: symbol,
+                    expected: 0,
+                    found: count.absolute,
+                });
+            }
+        }
+
+        let mut expected = vec![BlockCounters::default(); Self::SIGMA];
+        for block in mode.indices(num_blocks) {
+            Self::block_counts(self.symbols.as_slice(), len, block, &mut expected);
+            for (symbol, expected) in expected.iter().enumerate() {
+                let index = block * Self::SIGMA + symbol;
+                if counts[index].relative != expected.relative {
+                    return Err(VerifyError::Value {
+                        what: "relative counters",
+                        index,
+                        expected: expected.relative,
+                        found: counts[index].relative,
+                    });
+                }
+                let next = counts[index].absolute.wrapping_add(expected.absolute);
+                if counts[index + Self::SIGMA].absolute != next {
+                    return Err(VerifyError::Value {
+                        what: "absolute counter",
+                        index: index + Self::SIGMA,
+                        expected: next,
+                        found: counts[index + Self::SIGMA].absolute,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,394 @@
+/*
+ *
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! An FM-index over byte texts.
+//!
+//! An FM-index represents a text using its Burrows–Wheeler transform (BWT),
+//! making it possible to count and locate the occurrences of a pattern and to
+//! extract substrings without storing the text.
+//!
+//! The structure has been described by Paolo Ferragina and Giovanni Manzini
+//! in “[Opportunistic data structures with
+//! applications](https://doi.org/10.1109/SFCS.2000.892127)”, *Proceedings of
+//! the 41st Annual Symposium on Foundations of Computer Science*, pages
+//! 390–398, IEEE, 2000.
+
+use crate::prelude::*;
+use anyhow::{Context, Result};
+use dsi_progress_logger::ProgressLog;
+use epserde::*;
+use mem_dbg::*;
+use std::ops::{Deref, Index, Range};
+use std::path::Path;
+
+/// An FM-index over a byte text.
+///
+/// The Burrows–Wheeler transform of the text, terminated by a virtual
+/// end-of-text symbol smaller than every byte, is stored in a
+/// [`WaveletMatrix`]. The suffix array is sampled at text positions that are
+/// multiples of the *sample rate* *s*: the rows of the sampled suffixes are
+/// marked in a bit vector with a [`Rank9`] structure, and their positions,
+/// divided by *s*, are stored in a [`BitFieldVec`]; a second
+/// [`BitFieldVec`] stores the rows of the sampled suffixes in text order.
+///
+/// [`count`](FmIndex::count) performs two ranking operations on the wavelet
+/// matrix for each symbol of the pattern, whereas
+/// [`locate`](FmIndex::locate) and [`extract`](FmIndex::extract) need in
+/// addition at most *s* steps of the LF mapping for each occurrence and for
+/// each extraction, respectively. The sample rate trades space for speed:
+/// the samples use about 2 log *n* / *s* bits per byte.
+///
+/// The index can be built from a text in memory using [`new`](FmIndex::new)
+/// or from the content of a file using [`from_file`](FmIndex::from_file). The
+/// suffix array is built in memory by prefix doubling, using about 40 bytes
+/// per byte of text.
+///
+/// # Examples
+///
+/// ```rust
+/// use dsi_progress_logger::no_logging;
+/// use sux::prelude::*;
+///
+/// let fm = FmIndex::new(b"abracadabra", 4, no_logging![]);
+/// assert_eq!(fm.count(b"abra"), 2);
+/// assert_eq!(fm.count(b"cad"), 1);
+/// assert_eq!(fm.count(b"dab"), 1);
+/// assert_eq!(fm.count(b"bad"), 0);
+///
+/// let mut positions = fm.locate(b"a").collect::<Vec<_>>();
+/// positions.sort();
+/// assert_eq!(positions, vec![0, 3, 5, 7, 10]);
+///
+/// assert_eq!(fm.extract(4..8), b"cada");
+/// ```
+#[derive(Epserde, Debug, Clone, MemDbg, MemSize)]
+pub struct FmIndex<W = WaveletMatrix, S = Rank9, V = BitFieldVec, O = Box<[usize]>> {
+    /// The BWT; the end-of-text symbol is represented by a zero.
+    bwt: W,
+    /// For each byte *c*, the number of symbols smaller than *c* in the BWT,
+    /// including the end-of-text symbol.
+    counts: O,
+    /// The rows of sampled suffixes.
+    sampled: S,
+    /// The positions of sampled suffixes divided by the sample rate, in row
+    /// order.
+    sa_samples: V,
+    /// The rows of sampled suffixes, in text order.
+    isa_samples: V,
+    /// The row containing the end-of-text symbol in the BWT.
+    primary: usize,
+    /// The sample rate.
+    sample_rate: usize,
+}
+
+/// Returns the number of bits necessary to represent `value`.
+fn bit_width(value: usize) -> usize {
+    (usize::BITS - value.leading_zeros()) as usize
+}
+
+/// Returns the suffix array of `text` followed by a terminator smaller than
+/// every byte; the first element is thus the length of `text`.
+///
+/// The suffix array is computed by prefix doubling on the cyclic shifts of
+/// the terminated text, using counting sort at each round.
+fn suffix_array(text: &[u8], pl: &mut impl ProgressLog) -> Vec<usize> {
+    let m = text.len() + 1;
+    let symbol = |i: usize| {
+        if i < text.len() {
+            text[i] as usize + 1
+        } else {
+            0
+        }
+    };
+
+    pl.item_name("round");
+    pl.expected_updates(Some(bit_width(m)));
+    pl.start("Sorting suffixes...");
+
+    let mut sa = vec![0; m];
+    let mut class = vec![0; m];
+    let mut counts = vec![0; m.max(257)];
+    for i in 0..m {
+        counts[symbol(i)] += 1;
+    }
+    for c in 1..257 {
+        counts[c] += counts[c - 1];
+    }
+    for i in (0..m).rev() {
+        counts[symbol(i)] -= 1;
+        sa[counts[symbol(i)]] = i;
+    }
+    let mut num_classes = 1;
+    for i in 1..m {
+        if symbol(sa[i]) != symbol(sa[i - 1]) {
+            num_classes += 1;
+        }
+        class[sa[i]] = num_classes - 1;
+    }
+
+    let mut shifted = vec![0; m];
+    let mut new_class = vec![0; m];
+    let mut shift = 1;
+    while num_classes < m {
+        // Sort by the second half, which is already sorted in sa, and then
+        // stably by the first half
+        for (s, &p) in shifted.iter_mut().zip(sa.iter()) {
+            *s = (p + m - shift) % m;
+        }
+        counts[..num_classes].fill(0);
+        for &p in &shifted {
+            counts[class[p]] += 1;
+        }
+        for c in 1..num_classes {
+            counts[c] += counts[c - 1];
+        }
+        for &p in shifted.iter().rev() {
+            counts[class[p]] -= 1;
+            sa[counts[class[p]]] = p;
+        }
+
+        new_class[sa[0]] = 0;
+        num_classes = 1;
+        for i in 1..m {
+            let (cur, prev) = (sa[i], sa[i - 1]);
+            let (cur_next, prev_next) = ((cur + shift) % m, (prev + shift) % m);
+            if class[cur] != class[prev] || class[cur_next] != class[prev_next] {
+                num_classes += 1;
+            }
+            new_class[cur] = num_classes - 1;
+        }
+        std::mem::swap(&mut class, &mut new_class);
+        shift *= 2;
+        pl.update();
+    }
+
+    pl.done();
+    sa
+}
+
+impl FmIndex {
+    /// Creates a new FM-index for the given text with the given sample rate.
+    ///
+    /// # Panics
+    ///
+    /// If `sample_rate` is zero.
+    pub fn new(text: &[u8], sample_rate: usize, pl: &mut impl ProgressLog) -> Self {
+        assert!(sample_rate > 0, "The sample rate must be positive");
+        let n = text.len();
+        let sa = suffix_array(text, pl);
+
+        pl.item_name("row");
+        pl.expected_updates(Some(n + 1));
+        pl.start("Computing the BWT and sampling the suffix array...");
+
+        let mut bwt = BitFieldVec::<usize>::new(8, n + 1);
+        let mut sampled = BitVec::new(n + 1);
+        let num_samples = n / sample_rate + 1;
+        let mut sa_samples = BitFieldVec::with_capacity(bit_width(n / sample_rate), num_samples);
+        let mut isa_samples = BitFieldVec::<usize>::new(bit_width(n), num_samples);
+        let mut primary = 0;
+        for (row, &pos) in sa.iter().enumerate() {
+            if pos == 0 {
+                primary = row;
+            } else {
+                bwt.set(row, text[pos - 1] as usize);
+            }
+            if pos % sample_rate == 0 {
+                sampled.set(row, true);
+                sa_samples.push(pos / sample_rate);
+                isa_samples.set(pos / sample_rate, row);
+            }
+            pl.light_update();
+        }
+        drop(sa);
+        pl.done();
+
+        let mut counts = vec![0; 257];
+        counts[0] = 1;
+        for &c in text {
+            counts[c as usize + 1] += 1;
+        }
+        for c in 1..257 {
+            counts[c] += counts[c - 1];
+        }
+
+        pl.start("Building the wavelet matrix...");
+        let bwt = WaveletMatrix::new(&bwt);
+        pl.done();
+
+        Self {
+            bwt,
+            counts: counts.into_boxed_slice(),
+            sampled: Rank9::new(sampled),
+            sa_samples,
+            isa_samples,
+            primary,
+            sample_rate,
+        }
+    }
+
+    /// Creates a new FM-index for the content of the given file with the
+    /// given sample rate.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the file cannot be read.
+    ///
+    /// # Panics
+    ///
+    /// If `sample_rate` is zero.
+    pub fn from_file(
+        path: impl AsRef<Path>,
+        sample_rate: usize,
+        pl: &mut impl ProgressLog,
+    ) -> Result<Self> {
+        let path = path.as_ref();
+        let text =
+            std::fs::read(path).with_context(|| format!("Could not read {}", path.display()))?;
+        Ok(Self::new(&text, sample_rate, pl))
+    }
+}
+
+impl<W, S, V, O> FmIndex<W, S, V, O> {
+    /// Returns the sample rate.
+    #[inline(always)]
+    pub fn sample_rate(&self) -> usize {
+        self.sample_rate
+    }
+}
+
+impl<
+        R: Rank + SelectUnchecked + SelectZeroUnchecked + Index<usize, Output = bool>,
+        L: Deref<Target = [R]>,
+        P: AsRef<[usize]>,
+        S: Rank + Index<usize, Output = bool>,
+        V: BitFieldSlice<usize>,
+        O: AsRef<[usize]>,
+    > FmIndex<WaveletMatrix<L, P>, S, V, O>
+{
+    /// Returns the length of the text.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.bwt.len() - 1
+    }
+
+    /// Returns whether the text is empty.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of occurrences of `symbol` in the rows of the BWT
+    /// before `row`.
+    #[inline]
+    fn rank(&self, symbol: usize, row: usize) -> usize {
+        let rank = self.bwt.rank(symbol, row);
+        // The end-of-text symbol is stored as a zero
+        if symbol == 0 && self.primary < row {
+            rank - 1
+        } else {
+            rank
+        }
+    }
+
+    /// Returns the row of the suffix preceding the suffix of given row, or
+    /// `None` if the suffix of given row is the whole text.
+    #[inline]
+    fn lf(&self, row: usize) -> Option<(u8, usize)> {
+        if row == self.primary {
+            return None;
+        }
+        let symbol = self.bwt.get(row);
+        Some((
+            symbol as u8,
+            self.counts.as_ref()[symbol] + self.rank(symbol, row),
+        ))
+    }
+
+    /// Returns the range of rows of the suffixes starting with `pattern`.
+    pub fn rows(&self, pattern: &[u8]) -> Range<usize> {
+        let counts = self.counts.as_ref();
+        let mut rows = 0..self.bwt.len();
+        for &symbol in pattern.iter().rev() {
+            let symbol = symbol as usize;
+            rows = counts[symbol] + self.rank(symbol, rows.start)
+                ..counts[symbol] + self.rank(symbol, rows.end);
+            if rows.is_empty() {
+                return 0..0;
+            }
+        }
+        rows
+    }
+
+    /// Returns the number of occurrences of `pattern` in the text.
+    ///
+    /// The empty pattern occurs at every position, including the end of the
+    /// text.
+    #[inline]
+    pub fn count(&self, pattern: &[u8]) -> usize {
+        self.rows(pattern).len()
+    }
+
+    /// Returns the position in the text of the suffix of given row.
+    ///
+    /// # Panics
+    ///
+    /// If `row` is greater than the length of the text.
+    pub fn suffix(&self, mut row: usize) -> usize {
+        let mut steps = 0;
+        loop {
+            if self.sampled[row] {
+                return self.sa_samples.get(self.sampled.rank(row)) * self.sample_rate + steps;
+            }
+            // Unsampled rows are never the primary row, as position zero is
+            // sampled
+            row = self.lf(row).unwrap().1;
+            steps += 1;
+        }
+    }
+
+    /// Returns an iterator over the positions of the occurrences of
+    /// `pattern` in the text, in no particular order.
+    pub fn locate<'a>(&'a self, pattern: &[u8]) -> impl Iterator<Item = usize> + 'a {
+        self.rows(pattern).map(move |row| self.suffix(row))
+    }
+
+    /// Returns the bytes of the text in the given range.
+    ///
+    /// # Panics
+    ///
+    /// If `range` is out of bounds.
+    pub fn extract(&self, range: Range<usize>) -> Vec<u8> {
+        let len = self.len();
+        if range.end > len {
+            panic!("Range out of bounds: {} > {}", range.end, len);
+        }
+        if range.is_empty() {
+            return vec![];
+        }
+
+        // Start from the first sampled position after the range, or from
+        // the end of the text, whose suffix is in row zero
+        let mut pos = range.end.next_multiple_of(self.sample_rate).min(len);
+        let mut row = if pos == len {
+            0
+        } else {
+            self.isa_samples.get(pos / self.sample_rate)
+        };
+        let mut result = Vec::with_capacity(range.len());
+        while pos > range.start {
+            let (symbol, prev) = self.lf(row).unwrap();
+            pos -= 1;
+            if pos < range.end {
+                result.push(symbol);
+            }
+            row = prev;
+        }
+        result.reverse();
+        result
+    }
+}
@@ -0,0 +1,20 @@
+/*
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Indexed sequences supporting queries beyond access by position, and
+//! structures answering queries on sequences.
+
+pub mod wavelet_matrix;
+pub use wavelet_matrix::WaveletMatrix;
+
+pub mod rmq;
+pub use rmq::{RangeMax, RangeMin, Rmq};
+
+pub mod fm_index;
+pub use fm_index::FmIndex;
+
+pub mod permutation;
+pub use permutation::{Permutation, PermutationError};
@@ -0,0 +1,253 @@
+/*
+ *
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Succinct permutations with fast inverse.
+//!
+//! The representation stores a permutation π of *n* elements in *n*⌈log
+//! *n*⌉ bits, plus a back-pointer every *t* elements of each cycle, so that
+//! π⁻¹ can be computed in time proportional to *t*. It has been described by
+//! J. Ian Munro, Rajeev Raman, Venkatesh Raman, and S. Srinivasa Rao in
+//! “[Succinct representations of permutations and
+//! functions](https://doi.org/10.1016/j.tcs.2012.03.005)”, *Theoretical
+//! Computer Science*, 438:74–88, Elsevier, 2012.
+
+use crate::prelude::*;
+use epserde::*;
+use mem_dbg::*;
+
+/// Errors returned when building a [`Permutation`] from a sequence that is
+/// not a permutation.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum PermutationError {
+    #[error("Value {value} at index {index} is out of range for a permutation of length {len}")]
+    /// A value is greater than or equal to the length of the sequence.
+    OutOfRange {
+        index: usize,
+        value: usize,
+        len: usize,
+    },
+    #[error("Value {value} at index {index} already appeared")]
+    /// A value appears more than once.
+    Duplicate { index: usize, value: usize },
+}
+
+/// A succinct permutation supporting constant-time application and
+/// inversion in time proportional to a parameter *t*.
+///
+/// The permutation π is stored in a [`BitFieldVec`] of width ⌈log *n*⌉. Along
+/// each cycle longer than *t*, one element every *t* is marked in a bit
+/// vector with a [`Rank9`] structure, and for each marked element a second
+/// [`BitFieldVec`] stores the previous marked element in the cycle (a
+/// *back-pointer*).
+///
+/// [`apply`](Permutation::apply) is a single access, whereas
+/// [`inverse`](Permutation::inverse) follows the cycle of its argument for at
+/// most *t* steps, until it finds a marked element, and then follows the
+/// cycle again for at most *t* steps from the previous marked element. Thus,
+/// the structure uses (1 + 1/*t*)*n*⌈log *n*⌉ + 1.25*n* bits, instead of the
+/// 2*n*⌈log *n*⌉ bits necessary to store both π and π⁻¹.
+///
+/// # Examples
+///
+/// ```rust
+/// use sux::prelude::*;
+///
+/// let perm = Permutation::new(vec![3, 0, 4, 1, 2], 2)?;
+/// assert_eq!(perm.apply(0), 3);
+/// assert_eq!(perm.inverse(3), 0);
+/// assert_eq!(perm.inverse(2), 4);
+///
+/// assert!(Permutation::new(vec![0, 1, 1], 2).is_err());
+/// # Ok::<(), PermutationError>(())
+/// ```
+#[derive(Epserde, Debug, Clone, MemDbg, MemSize)]
+pub struct Permutation<V = BitFieldVec, R = Rank9> {
+    /// The permutation.
+    perm: V,
+    /// The marked elements.
+    marked: R,
+    /// For each marked element, in increasing order, the previous marked
+    /// element in its cycle.
+    back: V,
+    /// The maximum distance between marked elements in a cycle.
+    step: usize,
+}
+
+impl Permutation {
+    /// Creates a new permutation with the given values, marking an element
+    /// every `step` elements of each cycle.
+    ///
+    /// The values can be provided by any iterator; they are stored directly
+    /// in a [`BitFieldVec`], so no additional copy of the permutation is
+    /// made. If the iterator has an exact size, such as the one obtained
+    /// from a `Vec<usize>`, the bit width is known in advance; otherwise, it
+    /// is increased as needed, copying the values stored so far.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the values are not a permutation of
+    /// the integers smaller than their number.
+    ///
+    /// # Panics
+    ///
+    /// If `step` is zero.
+    pub fn new(
+        values: impl IntoIterator<Item = usize>,
+        step: usize,
+    ) -> Result<Self, PermutationError> {
+        assert!(step > 0, "The step must be positive");
+        let width = |x: usize| (usize::BITS - x.leading_zeros()) as usize;
+        let values = values.into_iter();
+        let (min_len, _) = values.size_hint();
+        let mut perm =
+            BitFieldVec::<usize>::with_capacity(width(min_len.saturating_sub(1)), min_len);
+        for value in values {
+            if width(value) > perm.bit_width() {
+                // If the values are distinct, the length is at most 2^w,
+                // where w is the current bit width, so the total cost of
+                // copying is linear
+                let mut wider = BitFieldVec::with_capacity(width(value), perm.len().max(min_len));
+                perm.iter().for_each(|x| wider.push(x));
+                perm = wider;
+            }
+            perm.push(value);
+        }
+        let n = perm.len();
+
+        // We use seen to check that values form a permutation, and then to
+        // track visited elements
+        let mut seen = BitVec::new(n);
+        for (index, value) in perm.iter().enumerate() {
+            if value >= n {
+                return Err(PermutationError::OutOfRange {
+                    index,
+                    value,
+                    len: n,
+                });
+            }
+            if seen.get(value) {
+                return Err(PermutationError::Duplicate { index, value });
+            }
+            seen.set(value, true);
+        }
+
+        // Mark an element every step elements of each cycle longer than step
+        let mut marked = BitVec::new(n);
+        for start in 0..n {
+            if !seen.get(start) {
+                continue;
+            }
+            let mut len = 0;
+            let mut i = start;
+            loop {
+                seen.set(i, false);
+                len += 1;
+                i = perm.get(i);
+                if i == start {
+                    break;
+                }
+            }
+            if len > step {
+                for k in 0..len {
+                    if k % step == 0 {
+                        marked.set(i, true);
+                    }
+                    i = perm.get(i);
+                }
+            }
+        }
+
+        // Link each marked element to the previous one in its cycle
+        let marked = Rank9::new(marked);
+        let mut back = BitFieldVec::<usize>::new(width(n.saturating_sub(1)), marked.num_ones());
+        for start in 0..n {
+            if seen.get(start) || !marked[start] {
+                continue;
+            }
+            let mut prev = start;
+            let mut i = perm.get(start);
+            while i != start {
+                seen.set(i, true);
+                if marked[i] {
+                    back.set(marked.rank(i), prev);
+                    prev = i;
+                }
+                i = perm.get(i);
+            }
+            seen.set(start, true);
+            back.set(marked.rank(start), prev);
+        }
+
+        Ok(Self {
+            perm,
+            marked,
+            back,
+            step,
+        })
+    }
+}
+
+impl<V, R> Permutation<V, R> {
+    /// Returns the maximum distance between marked elements in a cycle.
+    #[inline(always)]
+    pub fn step(&self) -> usize {
+        self.step
+    }
+
+    /// Returns the permutation, the marked elements, and the back-pointers.
+    pub fn into_inner(self) -> (V, R, V) {
+        (self.perm, self.marked, self.back)
+    }
+}
+
+impl<V: BitFieldSlice<usize>, R: Rank + std::ops::Index<usize, Output = bool>> Permutation<V, R> {
+    /// Returns the number of elements.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.perm.len()
+    }
+
+    /// Returns whether the permutation is empty.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.perm.len() == 0
+    }
+
+    /// Returns π(`index`).
+    ///
+    /// # Panics
+    ///
+    /// If `index` is out of bounds.
+    #[inline(always)]
+    pub fn apply(&self, index: usize) -> usize {
+        self.perm.get(index)
+    }
+
+    /// Returns π⁻¹(`value`).
+    ///
+    /// # Panics
+    ///
+    /// If `value` is out of bounds.
+    pub fn inverse(&self, value: usize) -> usize {
+        let mut i = value;
+        let mut jumped = false;
+        loop {
+            let next = self.perm.get(i);
+            if next == value {
+                return i;
+            }
+            if !jumped && self.marked[i] {
+                // Restart from the previous marked element, which precedes
+                // value by at most step elements
+                i = self.back.get(self.marked.rank(i));
+                jumped = true;
+            } else {
+                i = next;
+            }
+        }
+    }
+}
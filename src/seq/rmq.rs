@@ -0,0 +1,464 @@
+/*
+ *
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Succinct range-minimum (and range-maximum) queries.
+//!
+//! A range-minimum query returns the position of the minimum of a sequence in
+//! a range of positions. The structures in this module answer such queries in
+//! constant time without accessing the sequence, using the
+//! balanced-parentheses representation of a variant of its Cartesian tree,
+//! which uses 2*n* bits, as described by Gonzalo Navarro and Kunihiko
+//! Sadakane in “[Fully Functional Static and Dynamic Succinct
+//! Trees](https://doi.org/10.1145/2601073)”, *ACM Transactions on
+//! Algorithms*, 10(3):1–39, ACM, 2014. The position of the minimum excess in
+//! a range of parentheses is located using in-block minima and a sparse table
+//! over block minima, following the classical scheme of Johannes Fischer and
+//! Volker Heun in “[Space-Efficient Preprocessing Schemes for Range Minimum
+//! Queries on Static Arrays](https://doi.org/10.1137/090779759)”, *SIAM
+//! Journal on Computing*, 40(2):465–492, 2011.
+
+use crate::prelude::*;
+use crate::tree::bp_tree::BYTE_EXCESS;
+use epserde::*;
+use mem_dbg::*;
+use std::ops::Range;
+
+/// The base-2 logarithm of the number of parentheses in a block.
+const LOG2_BLOCK_BITS: usize = 9;
+/// The number of parentheses in a block.
+const BLOCK_BITS: usize = 1 << LOG2_BLOCK_BITS;
+/// The base-2 logarithm of the number of blocks in a superblock.
+const LOG2_SUPER_BLOCKS: usize = 5;
+/// The number of blocks in a superblock.
+const SUPER_BLOCKS: usize = 1 << LOG2_SUPER_BLOCKS;
+
+/// A structure answering range-minimum queries.
+///
+/// See [`Rmq`].
+pub type RangeMin<R = SelectAdapt<Rank9>> = Rmq<false, R>;
+
+/// A structure answering range-maximum queries.
+///
+/// See [`Rmq`].
+pub type RangeMax<R = SelectAdapt<Rank9>> = Rmq<true, R>;
+
+/// A succinct structure answering range-minimum (or range-maximum, if `MAX`
+/// is true) queries on a sequence in constant time.
+///
+/// The structure is built from an [`IndexedSeq`] with output `usize`, such
+/// as an [`EliasFano`], a slice wrapped in a [`SliceSeq`] or a
+/// [`BitFieldVec`] wrapped in a [`BitFieldSeq`]; the sequence is not needed
+/// afterwards. It stores the balanced-parentheses representation of a tree
+/// with a node for each element of the sequence, in which the parent of an
+/// element is the closest preceding element that is smaller than or equal to
+/// it (or greater than or equal to it, if `MAX` is true).
+///
+/// The type parameter `R` is the ranking and selection structure built on
+/// the 2*n* parentheses, which must implement [`RankUnchecked`] and
+/// [`SelectUnchecked`] and give access to the underlying words. By default,
+/// it is a [`SelectAdapt`] over a [`Rank9`]; a different structure can be
+/// chosen using [`new_with`](Rmq::new_with).
+///
+/// Besides the parentheses and the ranking and selection structure, the
+/// position of the minimum excess in each block of 512 parentheses, together
+/// with a sparse table over the blocks of each superblock of 16384
+/// parentheses, uses 32 bits per block, and a sparse table over superblocks
+/// uses a number of bits per superblock that is the square of the logarithm
+/// of the number of superblocks. In practice, these tables use between 6.25%
+/// and 8% of the space of the parentheses for sequences of less than a
+/// billion elements, that is, less than 0.16*n* bits.
+///
+/// Queries perform two selections, a bounded number of ranking operations
+/// and at most two byte-by-byte scans of a block, and return the leftmost
+/// position of the minimum (or maximum) in case of ties.
+///
+/// You should usually use the [`RangeMin`] and [`RangeMax`] type aliases.
+///
+/// # Examples
+///
+/// ```rust
+/// use sux::prelude::*;
+///
+/// let values = vec![5, 1, 4, 1, 5, 9, 2, 6, 5, 3];
+/// let seq = SliceSeq::new(&values);
+///
+/// let rmq = RangeMin::new(&seq);
+/// assert_eq!(rmq.argmin(0..10), 1);
+/// assert_eq!(rmq.argmin(2..6), 3);
+/// assert_eq!(rmq.argmin(4..6), 4);
+///
+/// let rmq = RangeMax::new(&seq);
+/// assert_eq!(rmq.argmax(0..10), 5);
+/// assert_eq!(rmq.argmax(6..10), 7);
+/// ```
+#[derive(Epserde, Debug, Clone, MemDbg, MemSize)]
+pub struct Rmq<const MAX: bool = false, R = SelectAdapt<Rank9>, B = Box<[u32]>, S = BitFieldVec> {
+    /// The balanced-parentheses representation of the Cartesian tree.
+    bits: R,
+    /// For each block, the offset in the block of the rightmost minimum
+    /// excess in the lower [`LOG2_BLOCK_BITS`] bits, followed by the offset
+    /// in the superblock of the block containing the rightmost minimum in the
+    /// next 2<sup>*k*</sup> blocks of the same superblock, for *k* from one to
+    /// [`LOG2_SUPER_BLOCKS`] − 1, using [`LOG2_SUPER_BLOCKS`] bits each.
+    blocks: B,
+    /// For *k* ≥ 1, the superblock containing the rightmost minimum excess in
+    /// the next 2<sup>*k*</sup> superblocks, in a table for each *k*.
+    supers: S,
+}
+
+impl<const MAX: bool> Rmq<MAX> {
+    /// Creates a new structure answering queries on the given sequence,
+    /// using the default ranking and selection structure.
+    pub fn new<S: IndexedSeq<Input = usize, Output = usize> + ?Sized>(seq: &S) -> Self {
+        Self::new_with(seq, |bits| SelectAdapt::new(Rank9::new(bits), 3))
+    }
+}
+
+impl<const MAX: bool, R> Rmq<MAX, R> {
+    /// Creates a new structure answering queries on the given sequence,
+    /// building the ranking and selection structure on the balanced
+    /// parentheses using `build`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sux::prelude::*;
+    ///
+    /// let values = vec![3, 1, 4, 1, 5, 9, 2, 6];
+    /// let rmq = RangeMin::new_with(&SliceSeq::new(&values), |bits| {
+    ///     SelectSmall::<2, 9, _>::new(RankSmall::<2, 9>::new(bits))
+    /// });
+    /// assert_eq!(rmq.argmin(4..8), 6);
+    /// ```
+    pub fn new_with<S: IndexedSeq<Input = usize, Output = usize> + ?Sized>(
+        seq: &S,
+        build: impl FnOnce(BitVec) -> R,
+    ) -> Self {
+        let len = seq.len();
+        let mut bits = BitVec::with_capacity(2 * len);
+        let mut stack: Vec<usize> = vec![];
+        for i in 0..len {
+            let value = seq.get(i);
+            // Elements that are not ancestors of i are closed
+            while let Some(&top) = stack.last() {
+                if (MAX && top >= value) || (!MAX && top <= value) {
+                    break;
+                }
+                stack.pop();
+                bits.push(false);
+            }
+            stack.push(value);
+            bits.push(true);
+        }
+        for _ in 0..stack.len() {
+            bits.push(false);
+        }
+
+        // The rightmost minimum excess in each block
+        let num_bits = bits.len();
+        let num_blocks = num_bits.div_ceil(BLOCK_BITS);
+        let mut blocks = vec![0_u32; num_blocks];
+        let mut mins = Vec::with_capacity(num_blocks);
+        let mut excess = 0;
+        for (block, offset) in blocks.iter_mut().enumerate() {
+            let start = block * BLOCK_BITS;
+            let mut min = usize::MAX;
+            for pos in start..(start + BLOCK_BITS).min(num_bits) {
+                if excess <= min {
+                    min = excess;
+                    *offset = (pos - start) as u32;
+                }
+                if bits.get(pos) {
+                    excess += 1;
+                } else {
+                    excess -= 1;
+                }
+            }
+            mins.push(min);
+        }
+
+        // Sparse tables over the blocks of each superblock
+        let mut best: Vec<usize> = (0..num_blocks).collect();
+        for k in 1..LOG2_SUPER_BLOCKS {
+            let half = 1 << (k - 1);
+            for block in 0..num_blocks {
+                let next = block + half;
+                if next < num_blocks
+                    && next / SUPER_BLOCKS == block / SUPER_BLOCKS
+                    && mins[best[next]] <= mins[best[block]]
+                {
+                    best[block] = best[next];
+                }
+                blocks[block] |= ((best[block] % SUPER_BLOCKS) as u32)
+                    << (LOG2_BLOCK_BITS + LOG2_SUPER_BLOCKS * (k - 1));
+            }
+        }
+
+        // Sparse table over superblocks
+        let num_supers = num_blocks.div_ceil(SUPER_BLOCKS);
+        let super_mins: Vec<usize> = (0..num_supers)
+            .map(|sup| {
+                let (first, second) = (sup * SUPER_BLOCKS, sup * SUPER_BLOCKS + SUPER_BLOCKS / 2);
+                if second < num_blocks && mins[best[second]] <= mins[best[first]] {
+                    mins[best[second]]
+                } else {
+                    mins[best[first]]
+                }
+            })
+            .collect();
+        let num_levels = if num_supers > 1 {
+            num_supers.ilog2() as usize
+        } else {
+            0
+        };
+        let bit_width = (usize::BITS - num_supers.saturating_sub(1).leading_zeros()) as usize;
+        let mut supers = BitFieldVec::new(bit_width, num_levels * num_supers);
+        let mut best: Vec<usize> = (0..num_supers).collect();
+        for k in 1..=num_levels {
+            let half = 1 << (k - 1);
+            for sup in 0..num_supers {
+                let next = sup + half;
+                if next < num_supers && super_mins[best[next]] <= super_mins[best[sup]] {
+                    best[sup] = best[next];
+                }
+                supers.set((k - 1) * num_supers + sup, best[sup]);
+            }
+        }
+
+        Self {
+            bits: build(bits),
+            blocks: blocks.into_boxed_slice(),
+            supers,
+        }
+    }
+}
+
+impl<const MAX: bool, R, B, S> Rmq<MAX, R, B, S> {
+    /// Returns the ranking and selection structure on the balanced
+    /// parentheses.
+    pub fn into_inner(self) -> R {
+        self.bits
+    }
+}
+
+impl<
+        const MAX: bool,
+        R: AsRef<[usize]> + BitLength + RankUnchecked + SelectUnchecked,
+        B: AsRef<[u32]>,
+        S: BitFieldSlice<usize>,
+    > Rmq<MAX, R, B, S>
+{
+    /// Returns the length of the sequence.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.bits.len() / 2
+    }
+
+    /// Returns whether the sequence is empty.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the excess before the parenthesis at `pos`.
+    #[inline(always)]
+    fn excess(&self, pos: usize) -> usize {
+        2 * unsafe { self.bits.rank_unchecked(pos) } - pos
+    }
+
+    /// Returns the position of the rightmost minimum excess between two
+    /// positions, the second of which is the rightmost minimum of a range
+    /// following that of the first one.
+    #[inline(always)]
+    fn rightmost(&self, left: usize, right: usize) -> usize {
+        if self.excess(right) <= self.excess(left) {
+            right
+        } else {
+            left
+        }
+    }
+
+    /// Returns the position of the rightmost minimum excess at positions
+    /// from `start` to `end`, included, scanning the parentheses.
+    fn scan(&self, start: usize, end: usize) -> usize {
+        let words = self.bits.as_ref();
+        let mut excess = self.excess(start) as isize;
+        let (mut min, mut min_pos) = (excess, start);
+        let mut pos = start;
+        while pos < end {
+            let word = words[pos / usize::BITS as usize] >> (pos % usize::BITS as usize);
+            if pos.is_multiple_of(8) && pos + 8 <= end {
+                let [delta, byte_min, _] = BYTE_EXCESS[word & 0xFF];
+                if excess + byte_min as isize > min {
+                    // No candidate in this byte
+                    excess += delta as isize;
+                    pos += 8;
+                    continue;
+                }
+            }
+            excess += if word & 1 != 0 { 1 } else { -1 };
+            pos += 1;
+            if excess <= min {
+                min = excess;
+                min_pos = pos;
+            }
+        }
+        min_pos
+    }
+
+    /// Returns the position of the rightmost minimum excess in `block`.
+    #[inline(always)]
+    fn block_min(&self, block: usize) -> usize {
+        block * BLOCK_BITS + (self.blocks.as_ref()[block] as usize & (BLOCK_BITS - 1))
+    }
+
+    /// Returns the block containing the rightmost minimum excess in the 2<sup>*k*</sup>
+    /// blocks starting from `block` (or in the rest of its superblock).
+    #[inline(always)]
+    fn best_block(&self, block: usize, k: usize) -> usize {
+        let shift = LOG2_BLOCK_BITS + LOG2_SUPER_BLOCKS * (k - 1);
+        (block & !(SUPER_BLOCKS - 1))
+            + ((self.blocks.as_ref()[block] as usize >> shift) & (SUPER_BLOCKS - 1))
+    }
+
+    /// Returns the position of the rightmost minimum excess in the blocks
+    /// from `first` to `last`, included, which must belong to the same
+    /// superblock.
+    fn super_scan(&self, first: usize, last: usize) -> usize {
+        debug_assert_eq!(first / SUPER_BLOCKS, last / SUPER_BLOCKS);
+        if first == last {
+            return self.block_min(first);
+        }
+        let k = (last - first + 1).ilog2() as usize;
+        let (left, right) = if k == LOG2_SUPER_BLOCKS {
+            // The whole superblock
+            (
+                self.best_block(first, k - 1),
+                self.best_block(first + SUPER_BLOCKS / 2, k - 1),
+            )
+        } else {
+            (
+                self.best_block(first, k),
+                self.best_block(last + 1 - (1 << k), k),
+            )
+        };
+        self.rightmost(self.block_min(left), self.block_min(right))
+    }
+
+    /// Returns the position of the rightmost minimum excess in `sup`.
+    #[inline(always)]
+    fn super_min(&self, sup: usize) -> usize {
+        let num_blocks = self.blocks.as_ref().len();
+        let first = sup * SUPER_BLOCKS;
+        self.super_scan(first, (first + SUPER_BLOCKS - 1).min(num_blocks - 1))
+    }
+
+    /// Returns the position of the rightmost minimum excess in the
+    /// superblocks from `first` to `last`, included.
+    fn supers_min(&self, first: usize, last: usize) -> usize {
+        if first == last {
+            return self.super_min(first);
+        }
+        let num_supers = self.blocks.as_ref().len().div_ceil(SUPER_BLOCKS);
+        let k = (last - first + 1).ilog2() as usize;
+        let offset = (k - 1) * num_supers;
+        let (left, right) = unsafe {
+            (
+                self.supers.get_unchecked(offset + first),
+                self.supers.get_unchecked(offset + last + 1 - (1 << k)),
+            )
+        };
+        self.rightmost(self.super_min(left), self.super_min(right))
+    }
+
+    /// Returns the position of the rightmost minimum excess in the blocks
+    /// from `first` to `last`, included.
+    fn blocks_min(&self, first: usize, last: usize) -> usize {
+        let (first_super, last_super) = (first / SUPER_BLOCKS, last / SUPER_BLOCKS);
+        if first_super == last_super {
+            return self.super_scan(first, last);
+        }
+        let mut min = self.super_scan(first, (first_super + 1) * SUPER_BLOCKS - 1);
+        if first_super + 1 < last_super {
+            min = self.rightmost(min, self.supers_min(first_super + 1, last_super - 1));
+        }
+        self.rightmost(min, self.super_scan(last_super * SUPER_BLOCKS, last))
+    }
+
+    /// Returns the position of the rightmost minimum excess at positions
+    /// from `start` to `end`, included.
+    fn min_pos(&self, start: usize, end: usize) -> usize {
+        let (first, last) = (start / BLOCK_BITS, end / BLOCK_BITS);
+        if first == last {
+            return self.scan(start, end);
+        }
+        let mut min = self.scan(start, (first + 1) * BLOCK_BITS - 1);
+        if first + 1 < last {
+            min = self.rightmost(min, self.blocks_min(first + 1, last - 1));
+        }
+        self.rightmost(min, self.scan(last * BLOCK_BITS, end))
+    }
+
+    /// Returns the leftmost position of the minimum (or maximum, if `MAX` is
+    /// true) in the given nonempty range.
+    fn query(&self, range: Range<usize>) -> usize {
+        assert!(!range.is_empty(), "Empty range {:?}", range);
+        if range.end > self.len() {
+            panic!("Range out of bounds: {} > {}", range.end, self.len());
+        }
+        let (l, r) = (range.start, range.end - 1);
+        if l == r {
+            return l;
+        }
+
+        let (pos_l, pos_r) =
+            unsafe { (self.bits.select_unchecked(l), self.bits.select_unchecked(r)) };
+        let pos = self.min_pos(pos_l + 1, pos_r);
+        let rank = unsafe { self.bits.rank_unchecked(pos) };
+        if 2 * rank - pos > 2 * l - pos_l {
+            // l is an ancestor of r
+            return l;
+        }
+        // The answer is the outermost node enclosing r and following l,
+        // whose open parenthesis is the last position of minimum excess
+        rank
+    }
+}
+
+impl<
+        R: AsRef<[usize]> + BitLength + RankUnchecked + SelectUnchecked,
+        B: AsRef<[u32]>,
+        S: BitFieldSlice<usize>,
+    > Rmq<false, R, B, S>
+{
+    /// Returns the leftmost position of the minimum in the given range.
+    ///
+    /// # Panics
+    ///
+    /// If `range` is empty or out of bounds.
+    #[inline]
+    pub fn argmin(&self, range: Range<usize>) -> usize {
+        self.query(range)
+    }
+}
+
+impl<
+        R: AsRef<[usize]> + BitLength + RankUnchecked + SelectUnchecked,
+        B: AsRef<[u32]>,
+        S: BitFieldSlice<usize>,
+    > Rmq<true, R, B, S>
+{
+    /// Returns the leftmost position of the maximum in the given range.
+    ///
+    /// # Panics
+    ///
+    /// If `range` is empty or out of bounds.
+    #[inline]
+    pub fn argmax(&self, range: Range<usize>) -> usize {
+        self.query(range)
+    }
+}
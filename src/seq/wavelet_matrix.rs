@@ -0,0 +1,435 @@
+/*
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! An implementation of wavelet matrices.
+//!
+//! A wavelet matrix represents a sequence of *n* integers of *w* bits using
+//! *nw* bits plus the space of a ranking and selection structure, and it makes
+//! it possible to access an element, to count or locate the occurrences of a
+//! value, and to answer order statistics on ranges of positions in time
+//! proportional to *w*.
+//!
+//! The structure has been described by Francisco Claude, Gonzalo Navarro, and
+//! Alberto Ordóñez in “[The wavelet
+//! matrix](https://doi.org/10.1016/j.is.2014.06.002)”, *Information Systems*,
+//! 47:15–32, Elsevier, 2015.
+
+use crate::prelude::*;
+use epserde::*;
+use mem_dbg::*;
+use std::collections::BinaryHeap;
+use std::ops::{Deref, Index, Range};
+
+/// The default ranking and selection structure used by a [`WaveletMatrix`].
+pub type DefaultRankSel = SelectZeroSmall<2, 9, SelectSmall<2, 9, RankSmall<2, 9>>>;
+
+/// A wavelet matrix.
+///
+/// Each of the *w* levels of the matrix is a bit vector of length *n*
+/// containing a bit of each element, from the most significant to the least
+/// significant one; between levels, elements are stably partitioned by the
+/// bit of the level, so that elements with a zero bit come first. Each level
+/// is stored in its own [`BitVec`], on which a ranking and selection
+/// structure is built.
+///
+/// The type parameter `L` is the sequence of the ranking and selection
+/// structures of the levels, which must dereference to a slice of structures
+/// implementing [`Rank`], [`SelectUnchecked`] and [`SelectZeroUnchecked`].
+/// By default, it is a boxed slice of [`RankSmall`] structures with
+/// [`SelectSmall`] and [`SelectZeroSmall`] on top, which use about 20% of
+/// additional space; a different structure can be chosen using
+/// [`new_with`](WaveletMatrix::new_with).
+///
+/// The structure implements [`IndexedSeq`], and provides
+/// [`rank`](WaveletMatrix::rank) and [`select`](WaveletMatrix::select) for
+/// every value, as well as [`quantile`](WaveletMatrix::quantile),
+/// [`range_count`](WaveletMatrix::range_count) and
+/// [`top_k`](WaveletMatrix::top_k) on ranges of positions. All operations
+/// perform a constant number of rank or select operations per level.
+///
+/// # Examples
+///
+/// ```rust
+/// use sux::prelude::*;
+///
+/// let values = bit_field_vec![3; 5, 1, 4, 1, 5, 2, 6, 5, 3];
+/// let wm = WaveletMatrix::new(&values);
+///
+/// assert_eq!(wm.get(2), 4);
+/// // Occurrences of 5 before position 7
+/// assert_eq!(wm.rank(5, 7), 2);
+/// // Position of the third occurrence of 5
+/// assert_eq!(wm.select(5, 2), Some(7));
+/// // Smallest and median value in positions 2..7
+/// assert_eq!(wm.quantile(2..7, 0), Some(1));
+/// assert_eq!(wm.quantile(2..7, 2), Some(4));
+/// // Number of values in 2..6 in the whole sequence
+/// assert_eq!(wm.range_count(0..9, 2..6), 6);
+/// // Most frequent value
+/// assert_eq!(wm.top_k(0..9, 1), vec![(5, 3)]);
+/// ```
+#[derive(Epserde, Debug, Clone, MemDbg, MemSize)]
+pub struct WaveletMatrix<L = Box<[DefaultRankSel]>, O = Box<[usize]>> {
+    /// The ranking and selection structures on the levels.
+    levels: L,
+    /// The number of zeros of each level.
+    zeros: O,
+    /// The number of elements.
+    len: usize,
+    /// The number of levels.
+    bit_width: usize,
+}
+
+impl WaveletMatrix {
+    /// Creates a new wavelet matrix containing the given values, using the
+    /// [default ranking and selection structure](DefaultRankSel).
+    ///
+    /// The number of levels is the bit width of `values`.
+    pub fn new(values: &impl BitFieldSlice<usize>) -> Self {
+        Self::new_with(values, |bits| {
+            SelectZeroSmall::<2, 9, _>::new(SelectSmall::<2, 9, _>::new(RankSmall::<2, 9>::new(
+                bits,
+            )))
+        })
+    }
+}
+
+impl<R> WaveletMatrix<Box<[R]>, Box<[usize]>> {
+    /// Creates a new wavelet matrix containing the given values, building the
+    /// ranking and selection structure on each level using `build`.
+    ///
+    /// The number of levels is the bit width of `values`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sux::prelude::*;
+    ///
+    /// let values = bit_field_vec![3; 5, 1, 4, 1, 5, 2, 6, 5, 3];
+    /// let wm = WaveletMatrix::new_with(&values, |bits| {
+    ///     SelectZeroAdapt::new(SelectAdapt::new(Rank9::new(bits), 3), 3)
+    /// });
+    /// assert_eq!(wm.rank(1, 9), 2);
+    /// ```
+    pub fn new_with(
+        values: &impl BitFieldSlice<usize>,
+        mut build: impl FnMut(BitVec) -> R,
+    ) -> Self {
+        let len = values.len();
+        let bit_width = values.bit_width();
+
+        let mut levels = Vec::with_capacity(bit_width);
+        let mut level_zeros = Vec::with_capacity(bit_width);
+        let mut cur = BitFieldVec::<usize>::new(bit_width, len);
+        let mut next = BitFieldVec::<usize>::new(bit_width, len);
+        for i in 0..len {
+            cur.set(i, values.get(i));
+        }
+
+        for level in 0..bit_width {
+            let shift = bit_width - 1 - level;
+            let mut bits = BitVec::new(len);
+            let zeros = (0..len).filter(|&i| (cur.get(i) >> shift) & 1 == 0).count();
+
+            // Stable partition by the bit of the level
+            let (mut z, mut o) = (0, zeros);
+            for i in 0..len {
+                let value = cur.get(i);
+                if (value >> shift) & 1 == 0 {
+                    next.set(z, value);
+                    z += 1;
+                } else {
+                    bits.set(i, true);
+                    next.set(o, value);
+                    o += 1;
+                }
+            }
+            levels.push(build(bits));
+            level_zeros.push(zeros);
+            std::mem::swap(&mut cur, &mut next);
+        }
+
+        Self {
+            levels: levels.into_boxed_slice(),
+            zeros: level_zeros.into_boxed_slice(),
+            len,
+            bit_width,
+        }
+    }
+}
+
+impl<L, O> WaveletMatrix<L, O> {
+    /// Returns the number of levels, that is, the bit width of the values.
+    #[inline(always)]
+    pub fn bit_width(&self) -> usize {
+        self.bit_width
+    }
+
+    /// Returns the number of elements.
+    ///
+    /// This method is equivalent to [`IndexedSeq::len`], but it is provided
+    /// to reduce ambiguity in method resolution.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the ranking and selection structures on the levels.
+    pub fn into_inner(self) -> L {
+        self.levels
+    }
+
+    /// Returns whether `value` can be represented using the bit width of the
+    /// matrix.
+    #[inline(always)]
+    fn fits(&self, value: usize) -> bool {
+        self.bit_width >= usize::BITS as usize || value >> self.bit_width == 0
+    }
+}
+
+impl<
+        R: Rank + SelectUnchecked + SelectZeroUnchecked + Index<usize, Output = bool>,
+        L: Deref<Target = [R]>,
+        O: AsRef<[usize]>,
+    > WaveletMatrix<L, O>
+{
+    /// Returns the number of ones before position `pos` of the given level.
+    #[inline(always)]
+    fn rank_ones(&self, level: usize, pos: usize) -> usize {
+        self.levels[level].rank(pos)
+    }
+
+    /// Returns the number of zeros of the given level.
+    #[inline(always)]
+    fn zeros(&self, level: usize) -> usize {
+        self.zeros.as_ref()[level]
+    }
+
+    /// Maps a position on a level to the corresponding position on the next
+    /// level, given the bit of the element at that position.
+    #[inline(always)]
+    fn descend(&self, level: usize, pos: usize, bit: bool) -> usize {
+        let ones = self.rank_ones(level, pos);
+        if bit {
+            self.zeros(level) + ones
+        } else {
+            pos - ones
+        }
+    }
+
+    /// Returns the number of occurrences of `value` before position `pos`.
+    ///
+    /// If `pos` is greater than the number of elements, the number of
+    /// occurrences of `value` is returned.
+    pub fn rank(&self, value: usize, pos: usize) -> usize {
+        if !self.fits(value) {
+            return 0;
+        }
+        let (start, end) = self.value_range(value, pos.min(self.len));
+        end - start
+    }
+
+    /// Returns the range of positions of the last level corresponding to
+    /// occurrences of `value` before `pos`.
+    #[inline(always)]
+    fn value_range(&self, value: usize, pos: usize) -> (usize, usize) {
+        let (mut start, mut end) = (0, pos);
+        for level in 0..self.bit_width {
+            let bit = (value >> (self.bit_width - 1 - level)) & 1 != 0;
+            start = self.descend(level, start, bit);
+            end = self.descend(level, end, bit);
+        }
+        (start, end)
+    }
+
+    /// Returns the position of the occurrence of given rank of `value`, or
+    /// `None` if there is no such occurrence.
+    pub fn select(&self, value: usize, rank: usize) -> Option<usize> {
+        if !self.fits(value) {
+            return None;
+        }
+        let (start, end) = self.value_range(value, self.len);
+        if rank >= end - start {
+            return None;
+        }
+
+        let mut pos = start + rank;
+        for level in (0..self.bit_width).rev() {
+            let bits = &self.levels[level];
+            // SAFETY: pos is the position of an element of the level with the
+            // given bit, so the ranks we select are within bounds
+            pos = unsafe {
+                if (value >> (self.bit_width - 1 - level)) & 1 != 0 {
+                    bits.select_unchecked(pos - self.zeros(level))
+                } else {
+                    bits.select_zero_unchecked(pos)
+                }
+            };
+        }
+        Some(pos)
+    }
+
+    /// Returns the value of given rank (starting from zero) in the sorted
+    /// sequence of the values in the given range of positions, or `None` if
+    /// `rank` is greater than or equal to the length of the range.
+    ///
+    /// For example, `quantile(range, 0)` is the minimum value in `range`.
+    ///
+    /// # Panics
+    ///
+    /// If the range is out of bounds.
+    pub fn quantile(&self, range: Range<usize>, mut rank: usize) -> Option<usize> {
+        self.check_range(&range);
+        let (mut start, mut end) = (range.start, range.end);
+        if rank >= end - start {
+            return None;
+        }
+
+        let mut value = 0;
+        for level in 0..self.bit_width {
+            let start_ones = self.rank_ones(level, start);
+            let end_ones = self.rank_ones(level, end);
+            let zeros = (end - start) - (end_ones - start_ones);
+            value <<= 1;
+            if rank < zeros {
+                start -= start_ones;
+                end -= end_ones;
+            } else {
+                rank -= zeros;
+                value |= 1;
+                start = self.zeros(level) + start_ones;
+                end = self.zeros(level) + end_ones;
+            }
+        }
+        Some(value)
+    }
+
+    /// Returns the number of elements in the given range of positions whose
+    /// value is in the given range of values.
+    ///
+    /// # Panics
+    ///
+    /// If the range of positions is out of bounds.
+    pub fn range_count(&self, range: Range<usize>, values: Range<usize>) -> usize {
+        self.check_range(&range);
+        if values.start >= values.end {
+            return 0;
+        }
+        self.count_less(&range, values.end) - self.count_less(&range, values.start)
+    }
+
+    /// Returns the number of elements in the given range of positions whose
+    /// value is smaller than `value`.
+    fn count_less(&self, range: &Range<usize>, value: usize) -> usize {
+        if !self.fits(value) {
+            return range.end - range.start;
+        }
+        let (mut start, mut end) = (range.start, range.end);
+        let mut count = 0;
+        for level in 0..self.bit_width {
+            let start_ones = self.rank_ones(level, start);
+            let end_ones = self.rank_ones(level, end);
+            if (value >> (self.bit_width - 1 - level)) & 1 != 0 {
+                // Elements with a zero bit are smaller
+                count += (end - start) - (end_ones - start_ones);
+                start = self.zeros(level) + start_ones;
+                end = self.zeros(level) + end_ones;
+            } else {
+                start -= start_ones;
+                end -= end_ones;
+            }
+        }
+        count
+    }
+
+    /// Returns the `k` most frequent values in the given range of positions,
+    /// with their frequencies, in order of decreasing frequency.
+    ///
+    /// Ties are broken arbitrarily. If the range contains fewer than `k`
+    /// distinct values, all distinct values are returned.
+    ///
+    /// # Panics
+    ///
+    /// If the range is out of bounds.
+    pub fn top_k(&self, range: Range<usize>, k: usize) -> Vec<(usize, usize)> {
+        self.check_range(&range);
+        let mut result = Vec::with_capacity(k);
+        // Subtrees are visited by decreasing size, so leaves are reached by
+        // decreasing frequency
+        let mut queue = BinaryHeap::new();
+        if range.start < range.end {
+            queue.push((range.end - range.start, 0, range.start, 0));
+        }
+
+        while let Some((size, level, start, value)) = queue.pop() {
+            if result.len() == k {
+                break;
+            }
+            if level == self.bit_width {
+                result.push((value, size));
+                continue;
+            }
+            let end = start + size;
+            let start_ones = self.rank_ones(level, start);
+            let end_ones = self.rank_ones(level, end);
+            let ones = end_ones - start_ones;
+            if size > ones {
+                queue.push((size - ones, level + 1, start - start_ones, value << 1));
+            }
+            if ones > 0 {
+                queue.push((
+                    ones,
+                    level + 1,
+                    self.zeros(level) + start_ones,
+                    (value << 1) | 1,
+                ));
+            }
+        }
+
+        result
+    }
+
+    #[inline(always)]
+    fn check_range(&self, range: &Range<usize>) {
+        assert!(
+            range.start <= range.end,
+            "Invalid range: {} > {}",
+            range.start,
+            range.end
+        );
+        if range.end > self.len {
+            panic!("Range out of bounds: {} > {}", range.end, self.len);
+        }
+    }
+}
+
+impl<L, O> Types for WaveletMatrix<L, O> {
+    type Input = usize;
+    type Output = usize;
+}
+
+impl<
+        R: Rank + SelectUnchecked + SelectZeroUnchecked + Index<usize, Output = bool>,
+        L: Deref<Target = [R]>,
+        O: AsRef<[usize]>,
+    > IndexedSeq for WaveletMatrix<L, O>
+{
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    unsafe fn get_unchecked(&self, index: usize) -> usize {
+        let mut pos = index;
+        let mut value = 0;
+        for level in 0..self.bit_width {
+            let bit = self.levels[level][pos];
+            value = (value << 1) | bit as usize;
+            pos = self.descend(level, pos, bit);
+        }
+        value
+    }
+}
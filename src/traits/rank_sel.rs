@@ -14,6 +14,7 @@
 
 use crate::ambassador_impl_AsRef;
 use crate::ambassador_impl_Index;
+use crate::traits::{Verify, VerifyError, VerifyMode};
 use ambassador::{delegatable_trait, Delegate};
 use epserde::Epserde;
 use impl_tools::autoimpl;
@@ -31,6 +32,29 @@ pub trait BitLength {
     fn len(&self) -> usize;
 }
 
+/// Word-based access to a bit vector.
+///
+/// This trait provides access to the words of a bit vector whose words might
+/// not be stored contiguously in memory, as it happens, for example, in
+/// [`Rank9Interleaved`](crate::rank_sel::Rank9Interleaved). Structures that
+/// need to scan the underlying bit vector during construction, such as
+/// [`SelectAdapt`](crate::rank_sel::SelectAdapt), use this trait in place of
+/// `AsRef<[usize]>`.
+#[autoimpl(for<T: trait + ?Sized> &T, &mut T, Box<T>)]
+#[delegatable_trait]
+pub trait BitWords {
+    /// Returns the number of words of the bit vector.
+    fn num_words(&self) -> usize;
+
+    /// Returns the word of given index.
+    ///
+    /// # Panics
+    ///
+    /// May panic if `index` is greater than or equal to
+    /// [`num_words`](BitWords::num_words).
+    fn word(&self, index: usize) -> usize;
+}
+
 /// Potentially expensive bit-counting methods.
 ///
 /// The methods in this trait compute the number of ones or zeros
@@ -96,6 +120,23 @@ pub trait Rank: BitLength + NumBits + RankUnchecked {
             unsafe { self.rank_unchecked(pos) }
         }
     }
+
+    /// Computes the ranks of a batch of positions, storing them in `ranks`.
+    ///
+    /// The semantics is the same of [`rank`](Rank::rank), but implementations
+    /// can exploit the independence of the queries, for example by
+    /// prefetching the data needed by the next queries. The default
+    /// implementation calls [`rank`](Rank::rank) on each position.
+    ///
+    /// # Panics
+    ///
+    /// If `pos` and `ranks` have different lengths.
+    fn rank_batch(&self, pos: &[usize], ranks: &mut [usize]) {
+        assert_eq!(pos.len(), ranks.len());
+        for (&pos, rank) in pos.iter().zip(ranks) {
+            *rank = self.rank(pos);
+        }
+    }
 }
 
 #[autoimpl(for<T: trait + ?Sized> &T, &mut T, Box<T>)]
@@ -171,6 +212,26 @@ pub trait SelectUnchecked {
     /// `rank` must be between zero (included) and the number of ones in the
     /// underlying bit vector (excluded).
     unsafe fn select_unchecked(&self, rank: usize) -> usize;
+
+    /// Computes the positions of the ones of a batch of ranks, storing them in
+    /// `pos`.
+    ///
+    /// The default implementation calls
+    /// [`select_unchecked`](SelectUnchecked::select_unchecked) on each rank.
+    ///
+    /// # Safety
+    /// All ranks must be between zero (included) and the number of ones in
+    /// the underlying bit vector (excluded).
+    ///
+    /// # Panics
+    ///
+    /// If `ranks` and `pos` have different lengths.
+    unsafe fn select_unchecked_batch(&self, ranks: &[usize], pos: &mut [usize]) {
+        assert_eq!(ranks.len(), pos.len());
+        for (&rank, pos) in ranks.iter().zip(pos) {
+            *pos = self.select_unchecked(rank);
+        }
+    }
 }
 
 /// Selection over a bit vector.
@@ -186,6 +247,24 @@ pub trait Select: SelectUnchecked + NumBits {
             Some(unsafe { self.select_unchecked(rank) })
         }
     }
+
+    /// Computes the positions of the ones of a batch of ranks, storing them in
+    /// `pos`.
+    ///
+    /// The semantics is the same of [`select`](Select::select), but
+    /// implementations can exploit the independence of the queries, for
+    /// example by prefetching the data needed by the next queries. The default
+    /// implementation calls [`select`](Select::select) on each rank.
+    ///
+    /// # Panics
+    ///
+    /// If `ranks` and `pos` have different lengths.
+    fn select_batch(&self, ranks: &[usize], pos: &mut [Option<usize>]) {
+        assert_eq!(ranks.len(), pos.len());
+        for (&rank, pos) in ranks.iter().zip(pos) {
+            *pos = self.select(rank);
+        }
+    }
 }
 
 /// Selection zeros over a bit vector without bound checks.
@@ -198,6 +277,27 @@ pub trait SelectZeroUnchecked {
     /// `rank` must be between zero (included) and the number of zeros in the
     /// underlying bit vector (excluded).
     unsafe fn select_zero_unchecked(&self, rank: usize) -> usize;
+
+    /// Computes the positions of the zeros of a batch of ranks, storing them
+    /// in `pos`.
+    ///
+    /// The default implementation calls
+    /// [`select_zero_unchecked`](SelectZeroUnchecked::select_zero_unchecked)
+    /// on each rank.
+    ///
+    /// # Safety
+    /// All ranks must be between zero (included) and the number of zeros in
+    /// the underlying bit vector (excluded).
+    ///
+    /// # Panics
+    ///
+    /// If `ranks` and `pos` have different lengths.
+    unsafe fn select_zero_unchecked_batch(&self, ranks: &[usize], pos: &mut [usize]) {
+        assert_eq!(ranks.len(), pos.len());
+        for (&rank, pos) in ranks.iter().zip(pos) {
+            *pos = self.select_zero_unchecked(rank);
+        }
+    }
 }
 
 /// Selection zeros over a bit vector.
@@ -213,6 +313,25 @@ pub trait SelectZero: SelectZeroUnchecked + NumBits {
             Some(unsafe { self.select_zero_unchecked(rank) })
         }
     }
+
+    /// Computes the positions of the zeros of a batch of ranks, storing them
+    /// in `pos`.
+    ///
+    /// The semantics is the same of [`select_zero`](SelectZero::select_zero),
+    /// but implementations can exploit the independence of the queries, for
+    /// example by prefetching the data needed by the next queries. The default
+    /// implementation calls [`select_zero`](SelectZero::select_zero) on each
+    /// rank.
+    ///
+    /// # Panics
+    ///
+    /// If `ranks` and `pos` have different lengths.
+    fn select_zero_batch(&self, ranks: &[usize], pos: &mut [Option<usize>]) {
+        assert_eq!(ranks.len(), pos.len());
+        for (&rank, pos) in ranks.iter().zip(pos) {
+            *pos = self.select_zero(rank);
+        }
+    }
 }
 
 /// Selection over a bit vector, with a hint.
@@ -256,6 +375,124 @@ pub trait SelectZeroHinted {
     unsafe fn select_zero_hinted(&self, rank: usize, hint_pos: usize, hint_rank: usize) -> usize;
 }
 
+/// Finding the next one at or after a position.
+///
+/// The default implementation uses [`Rank`] and [`Select`]; implementations
+/// with access to the underlying bit vector can scan the word containing the
+/// position first.
+#[autoimpl(for<T: trait + ?Sized> &T, &mut T, Box<T>)]
+#[delegatable_trait]
+pub trait NextOne: Rank + Select {
+    /// Returns the position of the first one at or after `pos`, or `None` if
+    /// no such one exists.
+    fn next_one(&self, pos: usize) -> Option<usize> {
+        self.select(self.rank(pos))
+    }
+}
+
+/// Finding the last one before a position.
+///
+/// The default implementation uses [`Rank`] and [`Select`]; implementations
+/// with access to the underlying bit vector can scan the word containing the
+/// position first.
+#[autoimpl(for<T: trait + ?Sized> &T, &mut T, Box<T>)]
+#[delegatable_trait]
+pub trait PrevOne: Rank + Select {
+    /// Returns the position of the last one before `pos`, or `None` if no
+    /// such one exists.
+    fn prev_one(&self, pos: usize) -> Option<usize> {
+        match self.rank(pos) {
+            0 => None,
+            rank => Some(unsafe { self.select_unchecked(rank - 1) }),
+        }
+    }
+}
+
+/// Finding the next zero at or after a position.
+///
+/// Note that albeit the bit vector is virtually zero-extended, only zeros
+/// in the [underlying bit vector](BitLength::len) are returned.
+#[autoimpl(for<T: trait + ?Sized> &T, &mut T, Box<T>)]
+#[delegatable_trait]
+pub trait NextZero: RankZero + SelectZero {
+    /// Returns the position of the first zero at or after `pos`, or `None` if
+    /// no such zero exists.
+    fn next_zero(&self, pos: usize) -> Option<usize> {
+        if pos >= self.len() {
+            None
+        } else {
+            self.select_zero(self.rank_zero(pos))
+        }
+    }
+}
+
+/// Finding the last zero before a position.
+///
+/// Note that albeit the bit vector is virtually zero-extended, only zeros
+/// in the [underlying bit vector](BitLength::len) are returned.
+#[autoimpl(for<T: trait + ?Sized> &T, &mut T, Box<T>)]
+#[delegatable_trait]
+pub trait PrevZero: RankZero + SelectZero {
+    /// Returns the position of the last zero before `pos`, or `None` if no
+    /// such zero exists.
+    fn prev_zero(&self, pos: usize) -> Option<usize> {
+        match self.rank_zero(pos.min(self.len())) {
+            0 => None,
+            rank => Some(unsafe { self.select_zero_unchecked(rank - 1) }),
+        }
+    }
+}
+
+/// An object-safe facade for ranking structures.
+///
+/// This trait collects [`Rank`], [`RankZero`], access to the bits of the
+/// underlying bit vector through [`Index`], and [`MemSize`]. It is
+/// automatically implemented for all types implementing such traits, so
+/// different ranking structures, such as [`Rank9`](crate::rank_sel::Rank9) or
+/// [`RankSmall`](crate::rank_sel::RankSmall), can be stored as `Box<dyn
+/// DynRank>`.
+pub trait DynRank: Rank + RankZero + Index<usize, Output = bool> + MemSize {}
+
+impl<T: Rank + RankZero + Index<usize, Output = bool> + MemSize + ?Sized> DynRank for T {}
+
+/// An object-safe facade for structures supporting both ranking and selection.
+///
+/// This trait extends [`DynRank`] with [`Select`] and [`SelectZero`]. It is
+/// automatically implemented for all types implementing such traits, such as
+/// a [`Select9`](crate::rank_sel::Select9) over a
+/// [`SelectZero9`](crate::rank_sel::SelectZero9), or a
+/// [`SelectZeroAdapt`](crate::rank_sel::SelectZeroAdapt) over a
+/// [`SelectAdapt`](crate::rank_sel::SelectAdapt) over a
+/// [`Rank9`](crate::rank_sel::Rank9), so structures with different
+/// space/time tradeoffs can be stored in a `Vec<Box<dyn DynRankSelect>>`.
+///
+/// The function [`auto`](crate::rank_sel::auto) chooses automatically a
+/// structure depending on the density and the distribution of the ones.
+///
+/// # Examples
+///
+/// ```rust
+/// use sux::bit_vec;
+/// use sux::rank_sel::{Rank9, Select9, SelectAdapt, SelectZero9, SelectZeroAdapt};
+/// use sux::traits::DynRankSelect;
+///
+/// let bits = bit_vec![0, 1, 0, 1, 1, 0, 1, 0];
+/// let structs: Vec<Box<dyn DynRankSelect>> = vec![
+///     Box::new(Select9::new(SelectZero9::new(Rank9::new(bits.clone())))),
+///     Box::new(SelectZeroAdapt::new(SelectAdapt::new(Rank9::new(bits), 3), 3)),
+/// ];
+///
+/// for s in &structs {
+///     assert_eq!(s.rank(4), 2);
+///     assert_eq!(s.select(2), Some(4));
+///     assert_eq!(s.select_zero(2), Some(5));
+///     assert!(s[6]);
+/// }
+/// ```
+pub trait DynRankSelect: DynRank + Select + SelectZero {}
+
+impl<T: DynRank + Select + SelectZero + ?Sized> DynRankSelect for T {}
+
 /// A thin wrapper implementing [`NumBits`] by caching the result of
 /// [`BitCount::count_ones`].
 ///
@@ -267,6 +504,7 @@ pub trait SelectZeroHinted {
 #[delegate(AsRef<[usize]>, target = "bits")]
 #[delegate(Index<usize>, target = "bits")]
 #[delegate(crate::traits::rank_sel::BitLength, target = "bits")]
+#[delegate(crate::traits::rank_sel::BitWords, target = "bits")]
 #[delegate(crate::traits::rank_sel::Rank, target = "bits")]
 #[delegate(crate::traits::rank_sel::RankHinted<64>, target = "bits")]
 #[delegate(crate::traits::rank_sel::RankUnchecked, target = "bits")]
@@ -277,6 +515,10 @@ pub trait SelectZeroHinted {
 #[delegate(crate::traits::rank_sel::SelectZero, target = "bits")]
 #[delegate(crate::traits::rank_sel::SelectZeroHinted, target = "bits")]
 #[delegate(crate::traits::rank_sel::SelectZeroUnchecked, target = "bits")]
+#[delegate(crate::traits::rank_sel::NextOne, target = "bits")]
+#[delegate(crate::traits::rank_sel::PrevOne, target = "bits")]
+#[delegate(crate::traits::rank_sel::NextZero, target = "bits")]
+#[delegate(crate::traits::rank_sel::PrevZero, target = "bits")]
 pub struct AddNumBits<B> {
     bits: B,
     number_of_ones: usize,
@@ -329,6 +571,25 @@ impl<B: BitLength> BitCount for AddNumBits<B> {
     }
 }
 
+impl<B: BitCount + Verify> Verify for AddNumBits<B> {
+    fn verify(&self, mode: VerifyMode) -> Result<(), VerifyError> {
+        self.bits.verify(mode)?;
+        let expected = match mode {
+            VerifyMode::Full => self.bits.count_ones(),
+            // We can just check that the number of ones is not too large
+            VerifyMode::Sampled { .. } => self.number_of_ones.min(self.bits.len()),
+        };
+        if self.number_of_ones != expected {
+            return Err(VerifyError::Param {
+                what: "number of ones",
+                expected,
+                found: self.number_of_ones,
+            });
+        }
+        Ok(())
+    }
+}
+
 impl<B: BitCount> From<B> for AddNumBits<B> {
     fn from(bits: B) -> Self {
         let number_of_ones = bits.count_ones();
@@ -0,0 +1,116 @@
+/*
+ *
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Consistency checks for structures loaded from untrusted sources.
+//!
+//! Structures deserialized with [ε-serde](epserde) (in particular, when
+//! memory-mapped) are not checked in any way: a truncated or corrupted file
+//! might lead to wrong results or, since most methods use unchecked accesses,
+//! to undefined behavior. The [`Verify`] trait makes it possible to recompute
+//! counters and to check inventories against the underlying bits, either
+//! [fully](VerifyMode::Full) or [by sampling](VerifyMode::Sampled).
+//!
+//! ```rust
+//! use sux::prelude::*;
+//! use sux::traits::{Verify, VerifyMode};
+//!
+//! let bits: BitVec = (0..10_000).map(|i| i % 3 == 0).collect();
+//! let rank9 = Rank9::new(bits);
+//! assert!(rank9.verify(VerifyMode::Full).is_ok());
+//! assert!(rank9
+//!     .verify(VerifyMode::Sampled {
+//!         samples: 10,
+//!         seed: 0
+//!     })
+//!     .is_ok());
+//! ```
+
+use impl_tools::autoimpl;
+use itertools::Either;
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+/// The extent of the checks performed by [`Verify::verify`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VerifyMode {
+    /// Check every counter and every inventory entry.
+    ///
+    /// The cost is linear in the size of the structure.
+    Full,
+    /// Check only `samples` counters and inventory entries chosen by a
+    /// pseudorandom number generator initialized with `seed`.
+    ///
+    /// Sizes and parameters are always checked in full, and each sampled entry
+    /// is checked against the preceding one. Linear-time checks that do not
+    /// involve counters or inventories are skipped.
+    Sampled { samples: usize, seed: u64 },
+}
+
+impl VerifyMode {
+    /// Returns the indices in `0..n` that should be checked in this mode.
+    ///
+    /// In [full](VerifyMode::Full) mode, indices are returned in increasing
+    /// order.
+    pub(crate) fn indices(&self, n: usize) -> impl Iterator<Item = usize> {
+        match *self {
+            VerifyMode::Full => Either::Left(0..n),
+            VerifyMode::Sampled { samples, seed } => {
+                let mut rng = SmallRng::seed_from_u64(seed);
+                let samples = if n == 0 { 0 } else { samples };
+                Either::Right((0..samples).map(move |_| rng.random_range(0..n)))
+            }
+        }
+    }
+}
+
+/// The first inconsistency found by [`Verify::verify`].
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum VerifyError {
+    #[error("Wrong {what}: expected {expected}, found {found}")]
+    /// A length or a parameter is not consistent with the rest of the
+    /// structure. For lengths of backends, `expected` is a lower bound.
+    Param {
+        what: &'static str,
+        expected: usize,
+        found: usize,
+    },
+    #[error("Wrong {what} at index {index}: expected {expected}, found {found}")]
+    /// An element of an array is not consistent with the underlying data. For
+    /// values that are not determined by the underlying data, `expected` is a
+    /// bound.
+    Value {
+        what: &'static str,
+        index: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+/// Checks the internal consistency of a structure.
+///
+/// Implementations check first their components (e.g., the underlying bit
+/// vector of a ranking structure) and then their own data, returning the
+/// first inconsistency found. Implementations never panic and never cause
+/// undefined behavior, even if the structure is corrupted.
+#[autoimpl(for<T: trait + ?Sized> &T, &mut T, Box<T>)]
+pub trait Verify {
+    /// Checks the structure with the given [mode](VerifyMode).
+    fn verify(&self, mode: VerifyMode) -> Result<(), VerifyError>;
+}
+
+/// Slices of words carry no redundant information, so they are always
+/// consistent.
+impl<W> Verify for [W] {
+    fn verify(&self, _mode: VerifyMode) -> Result<(), VerifyError> {
+        Ok(())
+    }
+}
+
+impl<W> Verify for Vec<W> {
+    fn verify(&self, _mode: VerifyMode) -> Result<(), VerifyError> {
+        Ok(())
+    }
+}
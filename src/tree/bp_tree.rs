@@ -0,0 +1,874 @@
+/*
+ *
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Ordinal trees represented by balanced parentheses.
+//!
+//! A tree with *n* nodes is represented by a sequence of 2*n* parentheses
+//! obtained by a depth-first visit, writing an open parenthesis when a node is
+//! entered and a close parenthesis when it is left. Navigation is reduced to
+//! searches for a given *excess*, that is, the difference between the number
+//! of open and close parentheses in a prefix of the sequence; such searches
+//! are performed using a *range min-max tree*, a complete binary tree over
+//! blocks of parentheses storing the minimum and maximum excess in each
+//! subtree.
+//!
+//! The range min-max tree has been described by Kunihiko Sadakane and Gonzalo
+//! Navarro in “[Fully-Functional Succinct
+//! Trees](https://doi.org/10.1137/1.9781611973075.13)”, *Proceedings of the
+//! Twenty-First Annual ACM-SIAM Symposium on Discrete Algorithms*, pages
+//! 134–149, SIAM, 2010.
+
+use crate::prelude::*;
+use crate::traits::bit_field_slice::panic_if_out_of_bounds;
+use epserde::*;
+use mem_dbg::*;
+
+/// The number of parentheses in a block of the range min-max tree.
+const BLOCK_BITS: usize = 1024;
+
+/// For each byte, the excess after its eight bits (least significant bit
+/// first), and the minimum and maximum excess reached after each of them.
+pub(crate) const BYTE_EXCESS: [[i8; 3]; 256] = {
+    let mut table = [[0; 3]; 256];
+    let mut byte = 0;
+    while byte < 256 {
+        let (mut excess, mut min, mut max) = (0, i8::MAX, i8::MIN);
+        let mut i = 0;
+        while i < 8 {
+            excess += if (byte >> i) & 1 != 0 { 1 } else { -1 };
+            if excess < min {
+                min = excess;
+            }
+            if excess > max {
+                max = excess;
+            }
+            i += 1;
+        }
+        table[byte] = [excess, min, max];
+        byte += 1;
+    }
+    table
+};
+
+/// A succinct ordinal tree represented by balanced parentheses.
+///
+/// Each node is identified by the position of its open parenthesis in the
+/// sequence of parentheses, where ones are open parentheses and zeros are
+/// close parentheses; the root, if present, is at position zero. Nodes can be
+/// converted to and from their preorder rank using
+/// [`preorder_rank`](BpTree::preorder_rank) and
+/// [`preorder_select`](BpTree::preorder_select).
+///
+/// The type parameter `R` is the ranking and selection structure built on the
+/// parentheses, which must implement [`Rank`] and [`SelectUnchecked`] and give
+/// access to the underlying words. By default, it is a [`SelectAdapt`] over a
+/// [`Rank9`]; a different structure can be chosen using
+/// [`new_with`](BpTree::new_with).
+///
+/// Besides the 2*n* bits of the parentheses and the ranking and selection
+/// structure, the range min-max tree uses about four words every 1024
+/// parentheses.
+/// Operations scan at most two blocks and visit a logarithmic number of nodes
+/// of the range min-max tree.
+///
+/// Trees can be built from a [sequence of parentheses](BpTree::new), from an
+/// [array of parents](BpTree::from_parents), from a [depth-first
+/// visit](BpTree::from_dfs) of an arbitrary tree, or incrementally using a
+/// [`BpTreeBuilder`].
+///
+/// # Examples
+///
+/// ```rust
+/// use sux::prelude::*;
+///
+/// // Node 0 is the root, with children 1 and 3; node 2 is a child of 1
+/// let tree = BpTree::from_parents(&[usize::MAX, 0, 1, 0]);
+/// assert_eq!(tree.num_nodes(), 4);
+///
+/// // Nodes are positions of open parentheses: (()())
+/// let root = 0;
+/// let first = tree.first_child(root).unwrap();
+/// let second = tree.next_sibling(first).unwrap();
+/// assert_eq!(tree.parent(second), Some(root));
+/// assert_eq!(tree.subtree_size(first), 2);
+/// assert_eq!(tree.depth(tree.first_child(first).unwrap()), 2);
+/// assert_eq!(tree.preorder_rank(second), 3);
+/// assert_eq!(tree.preorder_select(3), second);
+/// assert_eq!(tree.lca(tree.first_child(first).unwrap(), second), root);
+/// ```
+#[derive(Epserde, Debug, Clone, MemDbg, MemSize)]
+pub struct BpTree<R = SelectAdapt<Rank9>, M = Box<[usize]>> {
+    /// The parentheses.
+    bits: R,
+    /// The minimum excess in each node of the range min-max tree, in heap
+    /// order starting from index one.
+    mins: M,
+    /// The maximum excess in each node of the range min-max tree, in heap
+    /// order starting from index one.
+    maxs: M,
+    /// The number of leaves of the range min-max tree (a power of two).
+    num_leaves: usize,
+}
+
+impl BpTree {
+    /// Creates a new tree from a sequence of balanced parentheses, using the
+    /// default ranking and selection structure.
+    ///
+    /// # Panics
+    ///
+    /// If the parentheses are not balanced.
+    pub fn new(bits: BitVec) -> Self {
+        Self::new_with(bits, |bits| SelectAdapt::new(Rank9::new(bits), 3))
+    }
+
+    /// Creates a new tree from an array of parents, using the default ranking
+    /// and selection structure.
+    ///
+    /// The root must be the only node whose parent is [`usize::MAX`]. The
+    /// children of a node are visited in increasing order, so the node of
+    /// preorder rank *k* of the tree is the *k*-th node visited by a
+    /// depth-first visit of the parent array with this order.
+    ///
+    /// # Panics
+    ///
+    /// If the parent array does not describe a tree.
+    pub fn from_parents(parents: &[usize]) -> Self {
+        let n = parents.len();
+        if n == 0 {
+            return Self::new(BitVec::new(0));
+        }
+
+        // Children of each node, in increasing order
+        let mut offsets = vec![0; n + 1];
+        let mut root = None;
+        for (node, &parent) in parents.iter().enumerate() {
+            if parent == usize::MAX {
+                assert!(
+                    root.is_none(),
+                    "More than one root: {} and {}",
+                    root.unwrap(),
+                    node
+                );
+                root = Some(node);
+            } else {
+                panic_if_out_of_bounds!(parent, n);
+                offsets[parent + 1] += 1;
+            }
+        }
+        let root = root.expect("No root");
+        for i in 0..n {
+            offsets[i + 1] += offsets[i];
+        }
+        let mut children = vec![0; n - 1];
+        let mut next = offsets.clone();
+        for (node, &parent) in parents.iter().enumerate() {
+            if parent != usize::MAX {
+                children[next[parent]] = node;
+                next[parent] += 1;
+            }
+        }
+
+        let tree = Self::from_dfs(root, |&node| {
+            children[offsets[node]..offsets[node + 1]].iter().copied()
+        });
+        assert_eq!(
+            tree.num_nodes(),
+            n,
+            "The parent array contains cycles unreachable from the root"
+        );
+        tree
+    }
+
+    /// Creates a new tree by a depth-first visit of an arbitrary tree, using
+    /// the default ranking and selection structure.
+    ///
+    /// The visit starts from `root`, and `children` must return the children
+    /// of a node in the desired order. The visit is iterative, so there is no
+    /// limit to the depth of the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sux::prelude::*;
+    ///
+    /// // A complete binary tree of height 3 in heap order
+    /// let tree = BpTree::from_dfs(1_usize, |&node| {
+    ///     (2 * node..2 * node + 2).filter(|&child| child < 16)
+    /// });
+    /// assert_eq!(tree.num_nodes(), 15);
+    /// assert_eq!(tree.subtree_size(tree.first_child(0).unwrap()), 7);
+    /// ```
+    pub fn from_dfs<N, I: IntoIterator<Item = N>>(
+        root: N,
+        mut children: impl FnMut(&N) -> I,
+    ) -> Self {
+        let mut builder = BpTreeBuilder::new();
+        builder.open();
+        let mut stack = vec![children(&root).into_iter()];
+        while let Some(iter) = stack.last_mut() {
+            match iter.next() {
+                Some(child) => {
+                    builder.open();
+                    let iter = children(&child).into_iter();
+                    stack.push(iter);
+                }
+                None => {
+                    builder.close();
+                    stack.pop();
+                }
+            }
+        }
+        builder.build()
+    }
+}
+
+impl<R> BpTree<R, Box<[usize]>> {
+    /// Creates a new tree from a sequence of balanced parentheses, building
+    /// the ranking and selection structure using `build`.
+    ///
+    /// # Panics
+    ///
+    /// If the parentheses are not balanced.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sux::prelude::*;
+    ///
+    /// let tree = BpTree::new_with(bit_vec![1, 1, 0, 1, 0, 0], |bits| {
+    ///     SelectSmall::<2, 9, _>::new(RankSmall::<2, 9>::new(bits))
+    /// });
+    /// assert_eq!(tree.find_close(1), 2);
+    /// ```
+    pub fn new_with(bits: BitVec, build: impl FnOnce(BitVec) -> R) -> Self {
+        let len = bits.len();
+        let num_blocks = len.div_ceil(BLOCK_BITS);
+        let num_leaves = num_blocks.next_power_of_two();
+        let mut mins = vec![usize::MAX; 2 * num_leaves];
+        let mut maxs = vec![0; 2 * num_leaves];
+
+        let words: &[usize] = bits.as_ref();
+        let mut excess = 0_isize;
+        for block in 0..num_blocks {
+            let (mut min, mut max) = (isize::MAX, isize::MIN);
+            let end = ((block + 1) * BLOCK_BITS).min(len);
+            let mut k = block * BLOCK_BITS;
+            while k < end {
+                if k + 8 <= end {
+                    // Blocks are word aligned, so bytes are byte aligned
+                    let [delta, byte_min, byte_max] =
+                        BYTE_EXCESS[(words[k / usize::BITS as usize] >> (k % 64)) & 0xFF];
+                    min = min.min(excess + byte_min as isize);
+                    max = max.max(excess + byte_max as isize);
+                    excess += delta as isize;
+                    k += 8;
+                } else {
+                    excess += if bits.get(k) { 1 } else { -1 };
+                    min = min.min(excess);
+                    max = max.max(excess);
+                    k += 1;
+                }
+            }
+            assert!(min >= 0, "The parentheses are not balanced");
+            mins[num_leaves + block] = min as usize;
+            maxs[num_leaves + block] = max as usize;
+        }
+        assert!(excess == 0, "The parentheses are not balanced");
+
+        for node in (1..num_leaves).rev() {
+            mins[node] = mins[2 * node].min(mins[2 * node + 1]);
+            maxs[node] = maxs[2 * node].max(maxs[2 * node + 1]);
+        }
+
+        Self {
+            bits: build(bits),
+            mins: mins.into_boxed_slice(),
+            maxs: maxs.into_boxed_slice(),
+            num_leaves,
+        }
+    }
+}
+
+impl<R, M> BpTree<R, M> {
+    /// Returns the ranking and selection structure on the parentheses.
+    pub fn into_inner(self) -> R {
+        self.bits
+    }
+
+    /// Returns whether a search for `target` has been successful at `excess`.
+    #[inline(always)]
+    fn reached<const UP: bool>(excess: isize, target: isize) -> bool {
+        if UP {
+            excess >= target
+        } else {
+            excess <= target
+        }
+    }
+}
+
+impl<R: AsRef<[usize]> + BitLength + Rank + SelectUnchecked, M: AsRef<[usize]>> BpTree<R, M> {
+    /// Returns the number of nodes.
+    #[inline(always)]
+    pub fn num_nodes(&self) -> usize {
+        self.bits.len() / 2
+    }
+
+    /// Returns the number of parentheses, that is, twice the number of
+    /// nodes.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.bits.len()
+    }
+
+    /// Returns whether the tree has no nodes.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.bits.len() == 0
+    }
+
+    /// Returns whether the parenthesis at position `pos` is open.
+    #[inline(always)]
+    fn bit(&self, pos: usize) -> bool {
+        (self.bits.as_ref()[pos / usize::BITS as usize] >> (pos % usize::BITS as usize)) & 1 != 0
+    }
+
+    /// Returns the byte starting at position `pos`, which must be a multiple
+    /// of eight.
+    #[inline(always)]
+    fn byte(&self, pos: usize) -> usize {
+        (self.bits.as_ref()[pos / usize::BITS as usize] >> (pos % usize::BITS as usize)) & 0xFF
+    }
+
+    /// Returns whether the node of the range min-max tree of given index
+    /// contains an excess reaching `target`.
+    #[inline(always)]
+    fn node_reaches<const UP: bool>(&self, node: usize, target: usize) -> bool {
+        if UP {
+            self.maxs.as_ref()[node] >= target
+        } else {
+            self.mins.as_ref()[node] <= target
+        }
+    }
+
+    /// Returns the excess at position `pos`, that is, the number of open
+    /// parentheses minus the number of close parentheses before `pos`.
+    ///
+    /// # Panics
+    ///
+    /// If `pos` is greater than the [number of parentheses](BpTree::len).
+    #[inline]
+    pub fn excess(&self, pos: usize) -> usize {
+        if pos > self.len() {
+            panic!("Position out of bounds: {} > {}", pos, self.len());
+        }
+        2 * self.bits.rank(pos) - pos
+    }
+
+    /// Scans forward from `pos`, with excess `excess`, up to `end`, returning
+    /// the first position at which the excess reaches `target`, or the excess
+    /// at `end`.
+    fn fwd_scan<const UP: bool>(
+        &self,
+        mut pos: usize,
+        end: usize,
+        mut excess: isize,
+        target: isize,
+    ) -> Result<usize, isize> {
+        while pos < end {
+            if pos.is_multiple_of(8) && pos + 8 <= end {
+                let [delta, min, max] = BYTE_EXCESS[self.byte(pos)];
+                let extreme = if UP { max } else { min };
+                if !Self::reached::<UP>(excess + extreme as isize, target) {
+                    excess += delta as isize;
+                    pos += 8;
+                    continue;
+                }
+            }
+            excess += if self.bit(pos) { 1 } else { -1 };
+            pos += 1;
+            if Self::reached::<UP>(excess, target) {
+                return Ok(pos);
+            }
+        }
+        Err(excess)
+    }
+
+    /// Scans backward from `pos`, with excess `excess`, down to `start`,
+    /// returning the last position at which the excess reaches `target`, or
+    /// the excess at `start`.
+    fn bwd_scan<const UP: bool>(
+        &self,
+        mut pos: usize,
+        start: usize,
+        mut excess: isize,
+        target: isize,
+    ) -> Result<usize, isize> {
+        while pos > start {
+            if pos.is_multiple_of(8) && pos >= start + 8 {
+                let [delta, min, max] = BYTE_EXCESS[self.byte(pos - 8)];
+                let extreme = if UP { max } else { min };
+                let base = excess - delta as isize;
+                if !Self::reached::<UP>(base + extreme as isize, target)
+                    && !Self::reached::<UP>(base, target)
+                {
+                    excess = base;
+                    pos -= 8;
+                    continue;
+                }
+            }
+            pos -= 1;
+            excess -= if self.bit(pos) { 1 } else { -1 };
+            if Self::reached::<UP>(excess, target) {
+                return Ok(pos);
+            }
+        }
+        Err(excess)
+    }
+
+    /// Returns the smallest position greater than or equal to `pos` at which
+    /// the excess is `target`, or `None` if there is no such position.
+    ///
+    /// # Panics
+    ///
+    /// If `pos` is greater than the [number of parentheses](BpTree::len).
+    pub fn fwd_search(&self, pos: usize, target: usize) -> Option<usize> {
+        let excess = self.excess(pos);
+        if excess == target {
+            Some(pos)
+        } else if target > excess {
+            self.fwd::<true>(pos, excess, target)
+        } else {
+            self.fwd::<false>(pos, excess, target)
+        }
+    }
+
+    fn fwd<const UP: bool>(&self, pos: usize, excess: usize, target: usize) -> Option<usize> {
+        let len = self.len();
+        // The block containing the excess after the parenthesis at pos
+        let block = pos / BLOCK_BITS;
+        let end = ((block + 1) * BLOCK_BITS).min(len);
+        if let Ok(pos) = self.fwd_scan::<UP>(pos, end, excess as isize, target as isize) {
+            return Some(pos);
+        }
+        if end == len {
+            return None;
+        }
+
+        // Climb until a right sibling reaches the target
+        let mut node = self.num_leaves + block;
+        loop {
+            if node == 1 {
+                return None;
+            }
+            if node.is_multiple_of(2) && self.node_reaches::<UP>(node + 1, target) {
+                node += 1;
+                break;
+            }
+            node /= 2;
+        }
+        // Descend to the leftmost leaf reaching the target
+        while node < self.num_leaves {
+            node *= 2;
+            if !self.node_reaches::<UP>(node, target) {
+                node += 1;
+            }
+        }
+
+        let start = (node - self.num_leaves) * BLOCK_BITS;
+        self.fwd_scan::<UP>(
+            start,
+            (start + BLOCK_BITS).min(len),
+            self.excess(start) as isize,
+            target as isize,
+        )
+        .ok()
+    }
+
+    /// Returns the largest position smaller than or equal to `pos` at which
+    /// the excess is `target`, or `None` if there is no such position.
+    ///
+    /// # Panics
+    ///
+    /// If `pos` is greater than the [number of parentheses](BpTree::len).
+    pub fn bwd_search(&self, pos: usize, target: usize) -> Option<usize> {
+        let excess = self.excess(pos);
+        if excess == target {
+            Some(pos)
+        } else if target > excess {
+            self.bwd::<true>(pos, excess, target)
+        } else {
+            self.bwd::<false>(pos, excess, target)
+        }
+    }
+
+    fn bwd<const UP: bool>(&self, pos: usize, excess: usize, target: usize) -> Option<usize> {
+        if pos == 0 {
+            return None;
+        }
+        // The block containing the excess at pos
+        let block = (pos - 1) / BLOCK_BITS;
+        let start = block * BLOCK_BITS;
+        if let Ok(pos) = self.bwd_scan::<UP>(pos, start, excess as isize, target as isize) {
+            return Some(pos);
+        }
+
+        // Climb until a left sibling reaches the target
+        let mut node = self.num_leaves + block;
+        loop {
+            if node == 1 {
+                // The excess at position zero is zero, which is not covered
+                // by the range min-max tree
+                return if UP { None } else { Some(0) };
+            }
+            if node % 2 == 1 && self.node_reaches::<UP>(node - 1, target) {
+                node -= 1;
+                break;
+            }
+            node /= 2;
+        }
+        // Descend to the rightmost leaf reaching the target
+        while node < self.num_leaves {
+            node = 2 * node + 1;
+            if !self.node_reaches::<UP>(node, target) {
+                node -= 1;
+            }
+        }
+
+        let start = (node - self.num_leaves) * BLOCK_BITS;
+        let end = (start + BLOCK_BITS).min(self.len());
+        let excess = self.excess(end);
+        if excess == target {
+            return Some(end);
+        }
+        self.bwd_scan::<UP>(end, start, excess as isize, target as isize)
+            .ok()
+    }
+
+    /// Returns the minimum excess after the parentheses from `pos` to `end`,
+    /// excluded, given the excess at `pos`.
+    fn scan_min(&self, mut pos: usize, end: usize, excess: usize) -> usize {
+        let mut excess = excess as isize;
+        let mut min = isize::MAX;
+        while pos < end {
+            if pos.is_multiple_of(8) && pos + 8 <= end {
+                let [delta, byte_min, _] = BYTE_EXCESS[self.byte(pos)];
+                min = min.min(excess + byte_min as isize);
+                excess += delta as isize;
+                pos += 8;
+            } else {
+                excess += if self.bit(pos) { 1 } else { -1 };
+                min = min.min(excess);
+                pos += 1;
+            }
+        }
+        min as usize
+    }
+
+    /// Returns the minimum excess at positions in (`start`..`end`].
+    fn min_excess(&self, start: usize, end: usize) -> usize {
+        debug_assert!(start < end);
+        let first_block = start / BLOCK_BITS;
+        let last_block = (end - 1) / BLOCK_BITS;
+        if first_block == last_block {
+            return self.scan_min(start, end, self.excess(start));
+        }
+
+        let last_start = last_block * BLOCK_BITS;
+        let mut min = self
+            .scan_min(start, (first_block + 1) * BLOCK_BITS, self.excess(start))
+            .min(self.scan_min(last_start, end, self.excess(last_start)));
+
+        // Full blocks in between
+        let mins = self.mins.as_ref();
+        let (mut l, mut r) = (
+            self.num_leaves + first_block + 1,
+            self.num_leaves + last_block,
+        );
+        while l < r {
+            if l % 2 == 1 {
+                min = min.min(mins[l]);
+                l += 1;
+            }
+            if r % 2 == 1 {
+                r -= 1;
+                min = min.min(mins[r]);
+            }
+            l /= 2;
+            r /= 2;
+        }
+        min
+    }
+
+    /// Returns the position of the close parenthesis matching the open
+    /// parenthesis at position `pos`.
+    ///
+    /// If the parenthesis at position `pos` is not open, the result is
+    /// unspecified.
+    ///
+    /// # Panics
+    ///
+    /// If `pos` is out of bounds, or if no matching parenthesis is found,
+    /// which can happen only if the parenthesis at position `pos` is not
+    /// open. In debug mode, the latter condition is always checked.
+    pub fn find_close(&self, pos: usize) -> usize {
+        panic_if_out_of_bounds!(pos, self.len());
+        debug_assert!(
+            self.bit(pos),
+            "The parenthesis at position {pos} is not open"
+        );
+        let excess = self.excess(pos);
+        self.fwd::<false>(pos + 1, excess + 1, excess)
+            .expect("No matching close parenthesis")
+            - 1
+    }
+
+    /// Returns the position of the open parenthesis matching the close
+    /// parenthesis at position `pos`.
+    ///
+    /// If the parenthesis at position `pos` is not closed, the result is
+    /// unspecified.
+    ///
+    /// # Panics
+    ///
+    /// If `pos` is out of bounds, or if no matching parenthesis is found,
+    /// which can happen only if the parenthesis at position `pos` is not
+    /// closed. In debug mode, the latter condition is always checked.
+    pub fn find_open(&self, pos: usize) -> usize {
+        panic_if_out_of_bounds!(pos, self.len());
+        debug_assert!(
+            !self.bit(pos),
+            "The parenthesis at position {pos} is not closed"
+        );
+        let excess = self.excess(pos + 1);
+        self.bwd::<false>(pos, excess + 1, excess)
+            .expect("No matching open parenthesis")
+    }
+
+    /// Returns the position of the open parenthesis of the closest pair
+    /// enclosing the open parenthesis at position `pos`, or `None` if there
+    /// is no such pair.
+    ///
+    /// If the parenthesis at position `pos` is not open, the result is
+    /// unspecified.
+    ///
+    /// # Panics
+    ///
+    /// If `pos` is out of bounds.
+    pub fn enclose(&self, pos: usize) -> Option<usize> {
+        panic_if_out_of_bounds!(pos, self.len());
+        let excess = self.excess(pos);
+        if excess == 0 {
+            return None;
+        }
+        self.bwd::<false>(pos, excess, excess - 1)
+    }
+
+    /// Returns the parent of a node, or `None` if the node is the root.
+    ///
+    /// This method is equivalent to [`enclose`](BpTree::enclose).
+    #[inline(always)]
+    pub fn parent(&self, node: usize) -> Option<usize> {
+        self.enclose(node)
+    }
+
+    /// Returns whether a node is a leaf.
+    ///
+    /// # Panics
+    ///
+    /// If `node` is out of bounds.
+    #[inline]
+    pub fn is_leaf(&self, node: usize) -> bool {
+        panic_if_out_of_bounds!(node, self.len());
+        !self.bit(node + 1)
+    }
+
+    /// Returns the first child of a node, or `None` if the node is a leaf.
+    ///
+    /// # Panics
+    ///
+    /// If `node` is out of bounds.
+    #[inline]
+    pub fn first_child(&self, node: usize) -> Option<usize> {
+        if self.is_leaf(node) {
+            None
+        } else {
+            Some(node + 1)
+        }
+    }
+
+    /// Returns the next sibling of a node, or `None` if the node is the last
+    /// child of its parent.
+    ///
+    /// # Panics
+    ///
+    /// If `node` is out of bounds.
+    pub fn next_sibling(&self, node: usize) -> Option<usize> {
+        let next = self.find_close(node) + 1;
+        if next < self.len() && self.bit(next) {
+            Some(next)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the number of nodes in the subtree rooted at a node, including
+    /// the node itself.
+    ///
+    /// # Panics
+    ///
+    /// If `node` is out of bounds.
+    #[inline]
+    pub fn subtree_size(&self, node: usize) -> usize {
+        (self.find_close(node) - node).div_ceil(2)
+    }
+
+    /// Returns the depth of a node; the root has depth zero.
+    ///
+    /// # Panics
+    ///
+    /// If `node` is out of bounds.
+    #[inline]
+    pub fn depth(&self, node: usize) -> usize {
+        panic_if_out_of_bounds!(node, self.len());
+        self.excess(node)
+    }
+
+    /// Returns the lowest common ancestor of two nodes.
+    ///
+    /// # Panics
+    ///
+    /// If `u` or `v` are out of bounds.
+    pub fn lca(&self, u: usize, v: usize) -> usize {
+        let (u, v) = if u <= v { (u, v) } else { (v, u) };
+        panic_if_out_of_bounds!(v, self.len());
+        if u == v || self.find_close(u) > v {
+            return u;
+        }
+        // The leftmost minimum of the excess between the two nodes is at the
+        // beginning of a child of the lowest common ancestor
+        let min = self.min_excess(u, v + 1);
+        let child = self
+            .fwd_search(u + 1, min)
+            .expect("The parentheses are not balanced");
+        self.enclose(child)
+            .expect("The parentheses are not balanced")
+    }
+
+    /// Returns the preorder rank of a node, that is, the number of nodes
+    /// preceding it in a depth-first visit.
+    ///
+    /// # Panics
+    ///
+    /// If `node` is out of bounds.
+    #[inline]
+    pub fn preorder_rank(&self, node: usize) -> usize {
+        panic_if_out_of_bounds!(node, self.len());
+        self.bits.rank(node)
+    }
+
+    /// Returns the node of given preorder rank.
+    ///
+    /// # Panics
+    ///
+    /// If `rank` is greater than or equal to the number of nodes.
+    #[inline]
+    pub fn preorder_select(&self, rank: usize) -> usize {
+        panic_if_out_of_bounds!(rank, self.num_nodes());
+        // SAFETY: there are exactly num_nodes() open parentheses
+        unsafe { self.bits.select_unchecked(rank) }
+    }
+}
+
+/// An incremental builder for [`BpTree`].
+///
+/// The builder must be fed the events of a depth-first visit of a tree, or of
+/// a sequence of trees, calling [`open`](BpTreeBuilder::open) when a node is
+/// entered and [`close`](BpTreeBuilder::close) when it is left.
+///
+/// # Examples
+///
+/// ```rust
+/// use sux::prelude::*;
+///
+/// let mut builder = BpTreeBuilder::new();
+/// builder.open(); // Root
+/// builder.open(); // First child
+/// builder.close();
+/// builder.open(); // Second child
+/// builder.close();
+/// builder.close();
+/// let tree = builder.build();
+/// assert_eq!(tree.next_sibling(1), Some(3));
+/// ```
+#[derive(Debug, Clone, MemDbg, MemSize)]
+pub struct BpTreeBuilder {
+    bits: BitVec,
+    excess: usize,
+}
+
+impl Default for BpTreeBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BpTreeBuilder {
+    /// Creates a new empty builder.
+    pub fn new() -> Self {
+        Self {
+            bits: BitVec::new(0),
+            excess: 0,
+        }
+    }
+
+    /// Enters a new node.
+    #[inline]
+    pub fn open(&mut self) {
+        self.bits.push(true);
+        self.excess += 1;
+    }
+
+    /// Leaves the current node.
+    ///
+    /// # Panics
+    ///
+    /// If no node has been entered and not left.
+    #[inline]
+    pub fn close(&mut self) {
+        assert!(self.excess > 0, "No node to close");
+        self.bits.push(false);
+        self.excess -= 1;
+    }
+
+    /// Builds the tree, using the default ranking and selection structure.
+    ///
+    /// # Panics
+    ///
+    /// If some node has been entered and not left.
+    pub fn build(self) -> BpTree {
+        BpTree::new(self.into_bits())
+    }
+
+    /// Builds the tree, building the ranking and selection structure using
+    /// `build`.
+    ///
+    /// # Panics
+    ///
+    /// If some node has been entered and not left.
+    pub fn build_with<R>(self, build: impl FnOnce(BitVec) -> R) -> BpTree<R> {
+        BpTree::new_with(self.into_bits(), build)
+    }
+
+    fn into_bits(self) -> BitVec {
+        assert!(
+            self.excess == 0,
+            "{} nodes have not been closed",
+            self.excess
+        );
+        self.bits
+    }
+}
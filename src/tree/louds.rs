@@ -0,0 +1,329 @@
+/*
+ *
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Ordinal trees represented by level-order unary degree sequences.
+//!
+//! A tree with *n* nodes is represented by a bit vector of 2*n* + 1 bits
+//! obtained by a breadth-first visit, writing for each node its degree in
+//! unary (that is, as many ones as children, followed by a zero), preceded
+//! by `10`, which represents a virtual parent of the root. Nodes are
+//! numbered in breadth-first order, and navigation is reduced to selection
+//! on the bit vector.
+//!
+//! The representation has been introduced by Guy Jacobson in
+//! “[Space-efficient static trees and
+//! graphs](https://doi.org/10.1109/SFCS.1989.63533)”, *30th Annual Symposium
+//! on Foundations of Computer Science*, pages 549–554, IEEE, 1989.
+
+use crate::prelude::*;
+use crate::traits::bit_field_slice::panic_if_out_of_bounds;
+use epserde::*;
+use mem_dbg::*;
+use std::ops::Range;
+
+/// A succinct ordinal tree represented by its level-order unary degree
+/// sequence (LOUDS).
+///
+/// Nodes are identified by their index in breadth-first order, so the root
+/// is node zero and the children of a node have consecutive indices. Each
+/// node but the root has a label, that is, the label of the edge from its
+/// parent, which is stored at the index of the node minus one in a
+/// [`BitFieldSlice`] of type `L`; by default, a [`BitFieldVec`].
+/// Labels make it possible to use the tree as the skeleton of a trie by
+/// [`child_with_label`](Louds::child_with_label).
+///
+/// The type parameter `R` is the selection structure built on the degree
+/// sequence, which must implement [`SelectUnchecked`] and
+/// [`SelectZeroUnchecked`]. By default, it is a [`SelectZeroAdapt`] over a
+/// [`Select9`]; a different structure can be chosen using
+/// [`new_with`](Louds::new_with).
+///
+/// [`degree`](Louds::degree), [`children`](Louds::children) and
+/// [`child`](Louds::child) perform two selections on zeros, whereas
+/// [`parent`](Louds::parent) performs a selection on ones, and
+/// [`child_rank`](Louds::child_rank) one selection of each kind.
+///
+/// # Examples
+///
+/// ```rust
+/// use sux::prelude::*;
+///
+/// // The root has children 1, 2 and 3; node 1 has children 4 and 5
+/// let labels = bit_field_vec![4; 1, 2, 3, 7, 8];
+/// let louds = Louds::with_labels([3, 2, 0, 0, 0, 0], labels);
+///
+/// assert_eq!(louds.num_nodes(), 6);
+/// assert_eq!(louds.degree(0), 3);
+/// assert_eq!(louds.children(1), 4..6);
+/// assert_eq!(louds.child(0, 2), 3);
+/// assert_eq!(louds.parent(5), Some(1));
+/// assert_eq!(louds.child_rank(5), Some(1));
+/// assert_eq!(louds.label(2), Some(2));
+/// assert_eq!(louds.child_with_label(1, 8), Some(5));
+/// ```
+#[derive(Epserde, Debug, Clone, MemDbg, MemSize)]
+pub struct Louds<R = SelectZeroAdapt<Select9>, L = BitFieldVec> {
+    /// The degree sequence.
+    bits: R,
+    /// The labels of the nodes, starting from node one.
+    labels: L,
+}
+
+impl Louds {
+    /// Creates a new tree from the degrees of its nodes in breadth-first
+    /// order, using the default selection structure.
+    ///
+    /// Labels have width zero, so they are all zero and use no space.
+    ///
+    /// # Panics
+    ///
+    /// If the degrees do not describe a tree.
+    pub fn new(degrees: impl IntoIterator<Item = usize>) -> Self {
+        let bits = Self::degree_seq(degrees);
+        let num_nodes = bits.len() / 2;
+        Self::from_parts(
+            bits,
+            BitFieldVec::new(0, num_nodes.saturating_sub(1)),
+            |bits| SelectZeroAdapt::new(Select9::new(Rank9::new(bits)), 3),
+        )
+    }
+
+    /// Creates a new tree from the degrees of its nodes in breadth-first
+    /// order and the labels of the nodes from node one onwards, using the
+    /// default selection structure.
+    ///
+    /// # Panics
+    ///
+    /// If the degrees do not describe a tree, or if the number of labels is
+    /// not the number of nodes minus one.
+    pub fn with_labels(degrees: impl IntoIterator<Item = usize>, labels: BitFieldVec) -> Self {
+        Self::new_with(degrees, labels, |bits| {
+            SelectZeroAdapt::new(Select9::new(Rank9::new(bits)), 3)
+        })
+    }
+}
+
+impl<R, L: BitFieldSliceCore<usize>> Louds<R, L> {
+    /// Creates a new tree from the degrees of its nodes in breadth-first
+    /// order and the labels of the nodes from node one onwards, building the
+    /// selection structure using `build`.
+    ///
+    /// # Panics
+    ///
+    /// If the degrees do not describe a tree, or if the number of labels is
+    /// not the number of nodes minus one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sux::prelude::*;
+    ///
+    /// let louds = Louds::new_with([2, 0, 0], bit_field_vec![1; 0, 1], |bits| {
+    ///     SelectZeroSmall::<2, 9, _>::new(SelectSmall::<2, 9, _>::new(RankSmall::<2, 9>::new(bits)))
+    /// });
+    /// assert_eq!(louds.child_with_label(0, 1), Some(2));
+    /// ```
+    pub fn new_with(
+        degrees: impl IntoIterator<Item = usize>,
+        labels: L,
+        build: impl FnOnce(BitVec) -> R,
+    ) -> Self {
+        Self::from_parts(Self::degree_seq(degrees), labels, build)
+    }
+
+    fn from_parts(bits: BitVec, labels: L, build: impl FnOnce(BitVec) -> R) -> Self {
+        let num_nodes = bits.len() / 2;
+        assert_eq!(
+            labels.len(),
+            num_nodes.saturating_sub(1),
+            "The number of labels must be the number of nodes minus one"
+        );
+        Self {
+            bits: build(bits),
+            labels,
+        }
+    }
+
+    /// Returns the degree sequence described by the given degrees.
+    fn degree_seq(degrees: impl IntoIterator<Item = usize>) -> BitVec {
+        let mut bits = BitVec::new(0);
+        // The number of nodes that have a parent, plus the root
+        let mut reachable = 1;
+        let mut num_nodes = 0;
+        for degree in degrees {
+            if num_nodes == 0 {
+                bits.push(true);
+                bits.push(false);
+            }
+            assert!(
+                num_nodes < reachable,
+                "Node {} has no parent: the degrees do not describe a tree",
+                num_nodes
+            );
+            for _ in 0..degree {
+                bits.push(true);
+            }
+            bits.push(false);
+            reachable += degree;
+            num_nodes += 1;
+        }
+        assert!(
+            num_nodes == 0 || num_nodes == reachable,
+            "The degrees describe {} nodes, but only {} degrees were provided",
+            reachable,
+            num_nodes
+        );
+        bits
+    }
+}
+
+impl<R, L> Louds<R, L> {
+    /// Returns the selection structure on the degree sequence and the
+    /// labels.
+    pub fn into_inner(self) -> (R, L) {
+        (self.bits, self.labels)
+    }
+
+    /// Returns the labels of the nodes, starting from node one.
+    pub fn labels(&self) -> &L {
+        &self.labels
+    }
+}
+
+impl<R: BitLength + SelectUnchecked + SelectZeroUnchecked, L: BitFieldSlice<usize>> Louds<R, L> {
+    /// Returns the number of nodes.
+    #[inline(always)]
+    pub fn num_nodes(&self) -> usize {
+        self.bits.len() / 2
+    }
+
+    /// Returns whether the tree has no nodes.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.bits.len() == 0
+    }
+
+    /// Returns the children of a node, which are consecutive in
+    /// breadth-first order.
+    ///
+    /// # Panics
+    ///
+    /// If `node` is out of bounds.
+    #[inline]
+    pub fn children(&self, node: usize) -> Range<usize> {
+        panic_if_out_of_bounds!(node, self.num_nodes());
+        // SAFETY: there are num_nodes() + 1 zeros
+        let (start, end) = unsafe {
+            (
+                self.bits.select_zero_unchecked(node),
+                self.bits.select_zero_unchecked(node + 1),
+            )
+        };
+        // The ones before the first child of node are preceded by node + 1
+        // zeros, and the first one is the virtual edge to the root
+        start - node..end - node - 1
+    }
+
+    /// Returns the degree of a node, that is, its number of children.
+    ///
+    /// # Panics
+    ///
+    /// If `node` is out of bounds.
+    #[inline]
+    pub fn degree(&self, node: usize) -> usize {
+        self.children(node).len()
+    }
+
+    /// Returns whether a node is a leaf.
+    ///
+    /// # Panics
+    ///
+    /// If `node` is out of bounds.
+    #[inline]
+    pub fn is_leaf(&self, node: usize) -> bool {
+        self.children(node).is_empty()
+    }
+
+    /// Returns the child of given index of a node.
+    ///
+    /// # Panics
+    ///
+    /// If `node` is out of bounds, or if `index` is greater than or equal to
+    /// the degree of `node`.
+    #[inline]
+    pub fn child(&self, node: usize, index: usize) -> usize {
+        let children = self.children(node);
+        panic_if_out_of_bounds!(index, children.len());
+        children.start + index
+    }
+
+    /// Returns the parent of a node, or `None` if the node is the root.
+    ///
+    /// # Panics
+    ///
+    /// If `node` is out of bounds.
+    #[inline]
+    pub fn parent(&self, node: usize) -> Option<usize> {
+        panic_if_out_of_bounds!(node, self.num_nodes());
+        if node == 0 {
+            return None;
+        }
+        // SAFETY: there are num_nodes() ones
+        let pos = unsafe { self.bits.select_unchecked(node) };
+        // The parent is the number of zeros before pos, minus one
+        Some(pos - node - 1)
+    }
+
+    /// Returns the index of a node among the children of its parent, or
+    /// `None` if the node is the root.
+    ///
+    /// # Panics
+    ///
+    /// If `node` is out of bounds.
+    #[inline]
+    pub fn child_rank(&self, node: usize) -> Option<usize> {
+        panic_if_out_of_bounds!(node, self.num_nodes());
+        if node == 0 {
+            return None;
+        }
+        // SAFETY: there are num_nodes() ones and num_nodes() + 1 zeros
+        unsafe {
+            let pos = self.bits.select_unchecked(node);
+            let parent = pos - node - 1;
+            Some(pos - self.bits.select_zero_unchecked(parent) - 1)
+        }
+    }
+
+    /// Returns the label of a node, or `None` if the node is the root.
+    ///
+    /// # Panics
+    ///
+    /// If `node` is out of bounds.
+    #[inline]
+    pub fn label(&self, node: usize) -> Option<usize> {
+        panic_if_out_of_bounds!(node, self.num_nodes());
+        if node == 0 {
+            None
+        } else {
+            Some(self.labels.get(node - 1))
+        }
+    }
+
+    /// Returns the first child of a node with the given label, or `None` if
+    /// there is no such child.
+    ///
+    /// The children are scanned linearly, so this method takes time
+    /// proportional to the degree of `node`.
+    ///
+    /// # Panics
+    ///
+    /// If `node` is out of bounds.
+    pub fn child_with_label(&self, node: usize, label: usize) -> Option<usize> {
+        self.children(node)
+            .find(|&child| self.labels.get(child - 1) == label)
+    }
+}
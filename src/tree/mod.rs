@@ -0,0 +1,13 @@
+/*
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Succinct representations of ordinal trees.
+
+pub mod bp_tree;
+pub use bp_tree::{BpTree, BpTreeBuilder};
+
+pub mod louds;
+pub use louds::Louds;
@@ -0,0 +1,99 @@
+/*
+ *
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::{ensure, Context, Result};
+use mmap_rs::{Mmap, MmapFlags, MmapOptions};
+use std::{marker::PhantomData, path::Path};
+
+/// A read-only memory-mapped file viewed as a slice of `W`.
+///
+/// This structure makes it possible to use as a backend data that only exists
+/// on disk in raw form, such as the words of a bit vector: for example,
+/// [`BitVec::from_raw_parts`](crate::bits::BitVec::from_raw_parts) can turn
+/// a memory-mapped file of words into a bit vector, which can then be used
+/// with the structures built by the
+/// [external-memory constructors](crate::rank_sel::stream_rank9).
+///
+/// The file is interpreted in native byte order, and its length must be a
+/// multiple of the size of `W`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use sux::utils::MmapHelper;
+/// # use mmap_rs::MmapFlags;
+/// # use std::io::Write;
+/// # fn main() -> anyhow::Result<()> {
+/// let mut file = tempfile::NamedTempFile::new()?;
+/// for word in [1_usize, 2, 3] {
+///     file.write_all(&word.to_ne_bytes())?;
+/// }
+/// file.flush()?;
+///
+/// let words = MmapHelper::<usize>::mmap(file.path(), MmapFlags::empty())?;
+/// assert_eq!(words.as_ref(), &[1, 2, 3]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct MmapHelper<W> {
+    mmap: Mmap,
+    len: usize,
+    _marker: PhantomData<W>,
+}
+
+impl<W> MmapHelper<W> {
+    /// Memory-maps a file with the given [flags](MmapFlags).
+    ///
+    /// Since memory mappings cannot be empty, an empty file is represented by
+    /// an anonymous mapping of one page.
+    pub fn mmap(path: impl AsRef<Path>, flags: MmapFlags) -> Result<Self> {
+        let path = path.as_ref();
+        let file_len = path
+            .metadata()
+            .with_context(|| format!("Cannot stat {}", path.display()))?
+            .len() as usize;
+        ensure!(
+            file_len.is_multiple_of(size_of::<W>()),
+            "The length of {} ({} bytes) is not a multiple of {}",
+            path.display(),
+            file_len,
+            size_of::<W>()
+        );
+        let file =
+            std::fs::File::open(path).with_context(|| format!("Cannot open {}", path.display()))?;
+
+        let mmap = if file_len == 0 {
+            MmapOptions::new(MmapOptions::page_size())?
+                .with_flags(flags)
+                .map()?
+        } else {
+            // SAFETY: the file is opened read-only, and the mapping is never
+            // written to
+            unsafe {
+                MmapOptions::new(file_len)?
+                    .with_flags(flags)
+                    .with_file(&file, 0)
+                    .map()?
+            }
+        };
+
+        Ok(Self {
+            mmap,
+            len: file_len / size_of::<W>(),
+            _marker: PhantomData,
+        })
+    }
+}
+
+impl<W> AsRef<[W]> for MmapHelper<W> {
+    #[inline(always)]
+    fn as_ref(&self) -> &[W] {
+        // SAFETY: mappings are page-aligned, and we checked the length
+        unsafe { std::slice::from_raw_parts(self.mmap.as_ptr() as *const W, self.len) }
+    }
+}
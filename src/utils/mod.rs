@@ -19,6 +19,11 @@ pub use fair_chunks::FairChunks;
 pub mod mod2_sys;
 pub use mod2_sys::*;
 
+pub mod word_ops;
+
+pub mod mmap_helper;
+pub use mmap_helper::*;
+
 /// Transmutes a vector of one type into a vector of another type.
 ///
 /// [It is not safe to transmute a
@@ -46,3 +51,52 @@ pub unsafe fn transmute_boxed_slice<S, D>(b: Box<[S]>) -> Box<[D]> {
     let mut b = std::mem::ManuallyDrop::new(b);
     Box::from_raw(b.as_mut() as *mut [S] as *mut [D])
 }
+
+/// Prefetches the cache line containing the element of given index of a
+/// slice.
+///
+/// The index is not checked, as prefetching never faults; on architectures
+/// for which no prefetch instruction is available this function does nothing.
+#[inline(always)]
+pub fn prefetch_index<T>(data: &[T], index: usize) {
+    let ptr = data.as_ptr().wrapping_add(index);
+    #[cfg(target_arch = "x86_64")]
+    unsafe {
+        core::arch::x86_64::_mm_prefetch(ptr as *const i8, core::arch::x86_64::_MM_HINT_T0)
+    };
+    #[cfg(target_arch = "aarch64")]
+    unsafe {
+        core::arch::asm!("prfm pldl1keep, [{}]", in(reg) ptr, options(nostack, readonly))
+    };
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    let _ = ptr;
+}
+
+/// The number of queries ahead for which batched queries issue prefetches.
+pub(crate) const PREFETCH_DISTANCE: usize = 8;
+
+/// Answers a batch of independent queries, calling `prefetch` on the
+/// query [`PREFETCH_DISTANCE`] positions ahead before answering the current
+/// one with `query`.
+///
+/// # Panics
+///
+/// If `queries` and `results` have different lengths.
+#[inline(always)]
+pub(crate) fn prefetched_batch<T>(
+    queries: &[usize],
+    results: &mut [T],
+    prefetch: impl Fn(usize),
+    query: impl Fn(usize) -> T,
+) {
+    assert_eq!(queries.len(), results.len());
+    for &q in queries.iter().take(PREFETCH_DISTANCE) {
+        prefetch(q);
+    }
+    for (i, (&q, result)) in queries.iter().zip(results).enumerate() {
+        if let Some(&next) = queries.get(i + PREFETCH_DISTANCE) {
+            prefetch(next);
+        }
+        *result = query(q);
+    }
+}
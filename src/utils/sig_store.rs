@@ -71,6 +71,24 @@ pub trait Sig: ZeroCopy + PartialEq + Eq {
     /// This method is used to build filters. It is masked to
     /// obtain a value to associate with a key.
     fn sig_u64(&self) -> u64;
+
+    /// Extract a 128-bit signature.
+    ///
+    /// This method is used to build filters using more than 64 bits per key.
+    /// The lower 64 bits are equal to [`sig_u64`](Sig::sig_u64), so filters
+    /// using at most 64 bits can use either method.
+    ///
+    /// The default implementation computes the upper 64 bits by hashing
+    /// [`sig_u64`](Sig::sig_u64) with a different seed. Since the upper 64
+    /// bits are then a function of the lower 64 bits, filters using more than
+    /// 64 bits will not have a lower false-positive rate: signatures with
+    /// more than 64 bits should override this method.
+    #[inline(always)]
+    fn sig_u128(&self) -> u128 {
+        let low = self.sig_u64();
+        let high = xxh3::xxh3_64_with_seed(&low.to_ne_bytes(), 1);
+        ((high as u128) << 64) | low as u128
+    }
 }
 
 impl Sig for [u64; 2] {
@@ -84,6 +102,15 @@ impl Sig for [u64; 2] {
     fn sig_u64(&self) -> u64 {
         xxh3::xxh3_64_with_seed(&(self[0] ^ self[1]).to_ne_bytes(), 0)
     }
+
+    #[inline(always)]
+    fn sig_u128(&self) -> u128 {
+        let high = xxh3::xxh3_64_with_seed(
+            &(((self[0] as u128) << 64) | self[1] as u128).to_ne_bytes(),
+            0,
+        );
+        ((high as u128) << 64) | self.sig_u64() as u128
+    }
 }
 
 impl Sig for [u64;1] {
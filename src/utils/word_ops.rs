@@ -0,0 +1,162 @@
+/*
+ *
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Selection in a word and population counts using CPU-specific
+//! instructions.
+//!
+//! On x86-64, [`select_in_word`] uses BMI2 (`pdep` and `tzcnt`) if available
+//! at runtime, and [broadword code](select_in_word_broadword) otherwise. If
+//! the crate is compiled with the `bmi2` target feature enabled (e.g., with
+//! `-C target-feature=+bmi2`, or with `-C target-cpu=native` on a machine
+//! supporting it), the runtime check is omitted. Note that on AMD processors
+//! preceding the Zen 3 microarchitecture `pdep` is microcoded and much slower
+//! than broadword code. The `bench_select_in_word` example compares the
+//! implementations on the current machine.
+//!
+//! [`count_ones`] uses AVX-512 VPOPCNTDQ, if available at runtime, and
+//! [`usize::count_ones`] otherwise. Features are detected once by the
+//! standard library, which caches the result.
+//!
+//! The CPU-specific implementations and the fallbacks are public, so that
+//! they can be tested and benchmarked against each other.
+
+/// For each byte and each rank, the position of the one of given rank in the
+/// byte, or 8 if there is no such one. The index is `rank << 8 | byte`.
+const SELECT_IN_BYTE: [u8; 2048] = {
+    let mut table = [8; 2048];
+    let mut byte = 0;
+    while byte < 256 {
+        let mut rank = 0;
+        let mut pos = 0;
+        while pos < 8 {
+            if byte & (1 << pos) != 0 {
+                table[rank << 8 | byte] = pos as u8;
+                rank += 1;
+            }
+            pos += 1;
+        }
+        byte += 1;
+    }
+    table
+};
+
+/// Returns the position of the one of given rank in a word.
+///
+/// The result is unspecified if `rank` is greater than or equal to the
+/// number of ones in `word`.
+///
+/// This function uses BMI2 instructions if they are available, and
+/// [broadword code](select_in_word_broadword) otherwise.
+#[inline(always)]
+pub fn select_in_word(word: usize, rank: usize) -> usize {
+    debug_assert!(rank < word.count_ones() as usize);
+    #[cfg(all(target_arch = "x86_64", target_feature = "bmi2"))]
+    {
+        // SAFETY: the crate is compiled for CPUs supporting BMI2
+        unsafe { select_in_word_bmi2(word, rank) }
+    }
+    #[cfg(not(all(target_arch = "x86_64", target_feature = "bmi2")))]
+    {
+        #[cfg(target_arch = "x86_64")]
+        if std::arch::is_x86_feature_detected!("bmi2") {
+            // SAFETY: we checked that the CPU supports BMI2
+            return unsafe { select_in_word_bmi2(word, rank) };
+        }
+        select_in_word_broadword(word, rank)
+    }
+}
+
+/// Returns the position of the one of given rank in a word using broadword
+/// code.
+///
+/// The result is unspecified if `rank` is greater than or equal to the
+/// number of ones in `word`.
+///
+/// See Sebastiano Vigna, “[Broadword Implementation of Rank/Select
+/// Queries](https://doi.org/10.1007/978-3-540-68552-4_12)”, _Proc. of the 7th
+/// International Workshop on Experimental Algorithms_, LNCS 5038, pages
+/// 154−168, Springer, 2008. As suggested by Giuseppe Ottaviano, the byte
+/// containing the one is located using a population count.
+#[inline(always)]
+pub fn select_in_word_broadword(word: usize, rank: usize) -> usize {
+    const ONES_STEP_4: u64 = 0x1111111111111111;
+    const ONES_STEP_8: u64 = 0x0101010101010101;
+    const LAMBDAS_STEP_8: u64 = 0x80 * ONES_STEP_8;
+
+    let word = word as u64;
+    let mut s = word;
+    s -= (s & (0xA * ONES_STEP_4)) >> 1;
+    s = (s & (0x3 * ONES_STEP_4)) + ((s >> 2) & (0x3 * ONES_STEP_4));
+    s = (s + (s >> 4)) & (0xF * ONES_STEP_8);
+    let byte_sums = s.wrapping_mul(ONES_STEP_8);
+
+    let rank_step_8 = rank as u64 * ONES_STEP_8;
+    let geq_rank_step_8 = ((rank_step_8 | LAMBDAS_STEP_8) - byte_sums) & LAMBDAS_STEP_8;
+    let place = geq_rank_step_8.count_ones() as usize * 8;
+    let byte_rank = rank as u64 - (((byte_sums << 8) >> place) & 0xFF);
+    let index = ((word >> place) & 0xFF) | (byte_rank << 8);
+    place + SELECT_IN_BYTE[index as usize] as usize
+}
+
+/// Returns the position of the one of given rank in a word using the BMI2
+/// instruction `pdep`.
+///
+/// See Prashant Pandey, Michael A. Bender, and Rob Johnson, “[A Fast x86
+/// Implementation of Select](https://arxiv.org/abs/1706.00990)”, 2017.
+///
+/// # Safety
+///
+/// The CPU must support BMI2.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "bmi2")]
+#[inline]
+pub unsafe fn select_in_word_bmi2(word: usize, rank: usize) -> usize {
+    core::arch::x86_64::_pdep_u64(1 << rank, word as u64).trailing_zeros() as usize
+}
+
+/// Returns the number of ones in a slice of words.
+///
+/// This function uses AVX-512 VPOPCNTDQ instructions if they are available,
+/// and [`usize::count_ones`] otherwise.
+#[inline]
+pub fn count_ones(words: &[usize]) -> usize {
+    #[cfg(target_arch = "x86_64")]
+    if std::arch::is_x86_feature_detected!("avx512f")
+        && std::arch::is_x86_feature_detected!("avx512vpopcntdq")
+    {
+        // SAFETY: we checked that the CPU supports AVX-512 VPOPCNTDQ
+        return unsafe { count_ones_avx512(words) };
+    }
+    count_ones_scalar(words)
+}
+
+/// Returns the number of ones in a slice of words using
+/// [`usize::count_ones`].
+#[inline]
+pub fn count_ones_scalar(words: &[usize]) -> usize {
+    words.iter().map(|word| word.count_ones() as usize).sum()
+}
+
+/// Returns the number of ones in a slice of words using AVX-512 VPOPCNTDQ
+/// instructions, which count the ones of eight words at a time.
+///
+/// # Safety
+///
+/// The CPU must support AVX-512F and AVX-512 VPOPCNTDQ.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f,avx512vpopcntdq")]
+pub unsafe fn count_ones_avx512(words: &[usize]) -> usize {
+    use core::arch::x86_64::*;
+
+    let mut chunks = words.chunks_exact(8);
+    let mut sums = _mm512_setzero_si512();
+    for chunk in &mut chunks {
+        let block = _mm512_loadu_si512(chunk.as_ptr().cast());
+        sums = _mm512_add_epi64(sums, _mm512_popcnt_epi64(block));
+    }
+    _mm512_reduce_add_epi64(sums) as usize + count_ones_scalar(chunks.remainder())
+}
@@ -12,6 +12,7 @@ use common_traits::CastableFrom;
 use common_traits::CastableInto;
 use common_traits::IntoAtomic;
 use core::sync::atomic::Ordering;
+use epserde::prelude::*;
 use rand::rngs::SmallRng;
 use rand::seq::SliceRandom;
 use rand::Rng;
@@ -44,6 +45,7 @@ fn test_bit_field_vec_apply() {
     test_bit_field_vec_apply_param::<u16>();
     test_bit_field_vec_apply_param::<u32>();
     test_bit_field_vec_apply_param::<u64>();
+    test_bit_field_vec_apply_param::<u128>();
 }
 
 fn test_bit_field_vec_apply_param<W: Word + CastableInto<u64> + CastableFrom<u64>>() {
@@ -577,3 +579,91 @@ fn test_slice_atomic() {
     atomic_slice_eq(b.as_slice(), v.as_slice());
     assert_eq!(b.get_atomic(21, Ordering::Relaxed), 4);
 }
+
+#[test]
+fn test_u128() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let n = 100;
+    for bit_width in 0..=u128::BITS as usize {
+        let mask = if bit_width == 0 {
+            0
+        } else {
+            u128::MAX >> (u128::BITS as usize - bit_width)
+        };
+        let values = (0..n)
+            .map(|_| rng.random::<u128>() & mask)
+            .collect::<Vec<_>>();
+
+        let mut v = BitFieldVec::<u128>::new(bit_width, n);
+        assert_eq!(v.mask(), mask);
+        for (i, &value) in values.iter().enumerate() {
+            v.set(i, value);
+        }
+        for (i, &value) in values.iter().enumerate() {
+            assert_eq!(v.get(i), value);
+        }
+        assert_eq!(v.iter().collect::<Vec<_>>(), values);
+
+        for from in 0..=v.len() {
+            let mut iter = v.into_unchecked_iter_from(from);
+            for &value in &values[from..] {
+                assert_eq!(unsafe { iter.next_unchecked() }, value);
+            }
+            let mut iter = v.into_rev_unchecked_iter_from(from);
+            for &value in values[..from].iter().rev() {
+                assert_eq!(unsafe { iter.next_unchecked() }, value);
+            }
+        }
+
+        let mut w = BitFieldVec::<u128>::with_capacity(bit_width, n);
+        w.extend(values.iter().copied());
+        assert_eq!(v, w);
+        for &value in values.iter().rev() {
+            assert_eq!(w.pop(), Some(value));
+        }
+
+        for (from, to, len) in [(3, 7, 50), (7, 3, 50), (5, 5, 90), (0, 1, 1), (40, 0, 60)] {
+            let mut w = BitFieldVec::<u128>::new(bit_width, n);
+            w.apply_in_place(|_| mask);
+            v.copy(from, &mut w, to, len);
+            let len = len.min(n - from).min(n - to);
+            for i in 0..n {
+                if (to..to + len).contains(&i) {
+                    assert_eq!(w.get(i), values[from + i - to]);
+                } else {
+                    assert_eq!(w.get(i), mask);
+                }
+            }
+        }
+
+        let mut i = 0;
+        v.apply_in_place(|x| {
+            assert_eq!(x, values[i]);
+            i += 1;
+            !x & mask
+        });
+        for (i, &value) in values.iter().enumerate() {
+            assert_eq!(v.get(i), !value & mask);
+        }
+
+        let s = BitFieldVec::<u128>::from_slice(&values).unwrap();
+        assert!(s.bit_width() <= bit_width.max(1));
+        for (i, &value) in values.iter().enumerate() {
+            assert_eq!(s.get(i), value);
+        }
+
+        let mut cursor = <AlignedCursor<maligned::A16>>::new();
+        s.serialize(&mut cursor).unwrap();
+        cursor.set_position(0);
+        let d = <BitFieldVec<u128>>::deserialize_eps(cursor.as_bytes()).unwrap();
+        assert_eq!(d.bit_width(), s.bit_width());
+        for (i, &value) in values.iter().enumerate() {
+            assert_eq!(d.get(i), value);
+        }
+
+        let b: BitFieldVec<u128, Box<[u128]>> = s.clone().into();
+        let (bits, w, l) = b.into_raw_parts();
+        let r = unsafe { BitFieldVec::<u128, &[u128]>::from_raw_parts(bits.as_ref(), w, l) };
+        assert_eq!(s, r);
+    }
+}
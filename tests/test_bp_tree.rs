@@ -0,0 +1,277 @@
+/*
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use epserde::prelude::*;
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand::SeedableRng;
+use sux::prelude::*;
+
+/// A pointer-based tree, with nodes numbered in preorder.
+struct Tree {
+    parent: Vec<Option<usize>>,
+    children: Vec<Vec<usize>>,
+    depth: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl Tree {
+    /// Builds a tree from a parent array, renumbering nodes in preorder
+    /// (children are visited in increasing order).
+    fn new(parents: &[usize]) -> Self {
+        let n = parents.len();
+        let mut children = vec![vec![]; n];
+        let mut root = 0;
+        for (node, &parent) in parents.iter().enumerate() {
+            if parent == usize::MAX {
+                root = node;
+            } else {
+                children[parent].push(node);
+            }
+        }
+
+        let mut preorder = vec![0; n];
+        let mut order = vec![];
+        let mut stack = vec![root];
+        while let Some(node) = stack.pop() {
+            preorder[node] = order.len();
+            order.push(node);
+            stack.extend(children[node].iter().rev());
+        }
+
+        let mut tree = Tree {
+            parent: vec![None; n],
+            children: vec![vec![]; n],
+            depth: vec![0; n],
+            size: vec![1; n],
+        };
+        for &node in &order {
+            let p = preorder[node];
+            tree.children[p] = children[node].iter().map(|&c| preorder[c]).collect();
+            for &c in &tree.children[p].clone() {
+                tree.parent[c] = Some(p);
+                tree.depth[c] = tree.depth[p] + 1;
+            }
+        }
+        for node in (1..n).rev() {
+            let parent = tree.parent[node].unwrap();
+            tree.size[parent] += tree.size[node];
+        }
+        tree
+    }
+
+    fn lca(&self, mut u: usize, mut v: usize) -> usize {
+        while self.depth[u] > self.depth[v] {
+            u = self.parent[u].unwrap();
+        }
+        while self.depth[v] > self.depth[u] {
+            v = self.parent[v].unwrap();
+        }
+        while u != v {
+            u = self.parent[u].unwrap();
+            v = self.parent[v].unwrap();
+        }
+        u
+    }
+}
+
+fn check<R: AsRef<[usize]> + BitLength + Rank + SelectUnchecked, M: AsRef<[usize]>>(
+    bp: &BpTree<R, M>,
+    tree: &Tree,
+    rng: &mut SmallRng,
+) {
+    let n = tree.parent.len();
+    assert_eq!(bp.num_nodes(), n);
+    let nodes: Vec<usize> = (0..n).map(|k| bp.preorder_select(k)).collect();
+    let rank = |node: usize| bp.preorder_rank(node);
+
+    for (k, &node) in nodes.iter().enumerate() {
+        assert_eq!(rank(node), k);
+        assert_eq!(bp.depth(node), tree.depth[k], "node {}", k);
+        assert_eq!(bp.subtree_size(node), tree.size[k], "node {}", k);
+        let close = bp.find_close(node);
+        assert_eq!(close, node + 2 * tree.size[k] - 1);
+        assert_eq!(bp.find_open(close), node);
+        assert_eq!(bp.parent(node).map(rank), tree.parent[k], "node {}", k);
+        assert_eq!(bp.enclose(node), bp.parent(node));
+        assert_eq!(bp.is_leaf(node), tree.children[k].is_empty());
+        assert_eq!(
+            bp.first_child(node).map(rank),
+            tree.children[k].first().copied()
+        );
+        let next = tree.parent[k].and_then(|p| {
+            let siblings = &tree.children[p];
+            let i = siblings.iter().position(|&c| c == k).unwrap();
+            siblings[i + 1..].first().copied()
+        });
+        assert_eq!(bp.next_sibling(node).map(rank), next, "node {}", k);
+    }
+
+    for _ in 0..n.min(10_000) {
+        let (u, v) = (rng.random_range(0..n), rng.random_range(0..n));
+        assert_eq!(rank(bp.lca(nodes[u], nodes[v])), tree.lca(u, v));
+    }
+
+    // Searches against the naive excess
+    let len = bp.len();
+    let mut excess = vec![0; len + 1];
+    let mut open = vec![false; len];
+    for &node in &nodes {
+        open[node] = true;
+    }
+    for pos in 0..len {
+        excess[pos + 1] = if open[pos] {
+            excess[pos] + 1
+        } else {
+            excess[pos] - 1
+        };
+    }
+    for _ in 0..n.min(1000) {
+        let pos = rng.random_range(0..=len);
+        let target = rng.random_range(0..=excess[pos] + 3);
+        assert_eq!(bp.excess(pos), excess[pos]);
+        assert_eq!(
+            bp.fwd_search(pos, target),
+            (pos..=len).find(|&p| excess[p] == target)
+        );
+        assert_eq!(
+            bp.bwd_search(pos, target),
+            (0..=pos).rev().find(|&p| excess[p] == target)
+        );
+    }
+}
+
+fn random_parents(rng: &mut SmallRng, n: usize, window: usize) -> Vec<usize> {
+    (0..n)
+        .map(|i| {
+            if i == 0 {
+                usize::MAX
+            } else {
+                rng.random_range(i.saturating_sub(window)..i)
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn test_bp_tree() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for n in [1, 2, 10, 100, 1000, 10_000, 100_000] {
+        // Bushy, deep and very deep trees
+        for window in [n, 10, 2] {
+            let parents = random_parents(&mut rng, n, window);
+            let tree = Tree::new(&parents);
+            let bp = BpTree::from_parents(&parents);
+            check(&bp, &tree, &mut rng);
+        }
+    }
+}
+
+#[test]
+fn test_shapes() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let n: usize = 5000;
+    // A path
+    let parents: Vec<usize> = (0..n).map(|i| i.wrapping_sub(1)).collect();
+    check(
+        &BpTree::from_parents(&parents),
+        &Tree::new(&parents),
+        &mut rng,
+    );
+    // A star
+    let parents: Vec<usize> = (0..n)
+        .map(|i| if i == 0 { usize::MAX } else { 0 })
+        .collect();
+    check(
+        &BpTree::from_parents(&parents),
+        &Tree::new(&parents),
+        &mut rng,
+    );
+    // A caterpillar
+    let parents: Vec<usize> = (0..n)
+        .map(|i| if i == 0 { usize::MAX } else { (i - 1) & !1 })
+        .collect();
+    check(
+        &BpTree::from_parents(&parents),
+        &Tree::new(&parents),
+        &mut rng,
+    );
+}
+
+#[test]
+fn test_from_dfs() {
+    // A complete ternary tree of height 8 in heap order
+    let n = (3_usize.pow(9) - 1) / 2;
+    let bp = BpTree::from_dfs(0_usize, |&node| {
+        (3 * node + 1..3 * node + 4).filter(|&c| c < n)
+    });
+    let parents: Vec<usize> = (0..n)
+        .map(|i| if i == 0 { usize::MAX } else { (i - 1) / 3 })
+        .collect();
+    let mut rng = SmallRng::seed_from_u64(0);
+    check(&bp, &Tree::new(&parents), &mut rng);
+
+    let mut builder = BpTreeBuilder::new();
+    builder.open();
+    builder.close();
+    let bp = builder.build_with(|bits| SelectSmall::<2, 9, _>::new(RankSmall::<2, 9>::new(bits)));
+    assert_eq!(bp.num_nodes(), 1);
+    assert_eq!(bp.parent(0), None);
+    assert_eq!(bp.first_child(0), None);
+    assert_eq!(bp.next_sibling(0), None);
+
+    assert!(BpTree::from_parents(&[]).is_empty());
+}
+
+#[test]
+#[should_panic]
+fn test_unbalanced() {
+    BpTree::new(bit_vec![1, 0, 0, 1]);
+}
+
+#[test]
+#[should_panic]
+fn test_unclosed() {
+    let mut builder = BpTreeBuilder::new();
+    builder.open();
+    builder.build();
+}
+
+#[test]
+#[should_panic]
+fn test_two_roots() {
+    BpTree::from_parents(&[usize::MAX, 0, usize::MAX]);
+}
+
+#[test]
+#[should_panic]
+fn test_find_close_on_close() {
+    BpTree::new(bit_vec![1, 0]).find_close(1);
+}
+
+#[test]
+#[should_panic]
+fn test_find_open_on_open() {
+    BpTree::new(bit_vec![1, 0]).find_open(0);
+}
+
+#[test]
+fn test_epserde() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let parents = random_parents(&mut rng, 50_000, 10);
+    let tree = Tree::new(&parents);
+    let bp = BpTree::from_parents(&parents);
+
+    let mut cursor = <AlignedCursor<maligned::A16>>::new();
+    bp.serialize(&mut cursor)?;
+    cursor.set_position(0);
+    let full = <BpTree>::deserialize_full(&mut cursor)?;
+    check(&full, &tree, &mut rng);
+    let eps = <BpTree>::deserialize_eps(cursor.as_bytes())?;
+    check(&eps, &tree, &mut rng);
+    Ok(())
+}
@@ -9,11 +9,14 @@
 use std::iter::zip;
 
 use anyhow::Result;
+use common_traits::{CastableFrom, CastableInto};
 use epserde::prelude::*;
 use rand::rngs::SmallRng;
 use rand::Rng;
 use rand::SeedableRng;
 use sux::prelude::*;
+use sux::traits::bit_field_slice::Word;
+use sux::utils::{FromIntoIterator, LineLender, ZstdLineLender};
 
 #[test]
 #[cfg(feature = "rayon")]
@@ -177,6 +180,42 @@ fn test_elias_fano() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_get_batch() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n, u) in [
+        (0, 10),
+        (1000, 1000),
+        (100_000, 1 << 20),
+        (100_000, 1 << 40),
+    ] {
+        let mut values = (0..n).map(|_| rng.random_range(0..u)).collect::<Vec<_>>();
+        values.sort();
+        let mut efb = EliasFanoBuilder::new(n, u);
+        efb.extend(values.iter().copied());
+        let ef = efb.build_with_seq();
+
+        let indices = (0..n.min(10_000))
+            .map(|_| rng.random_range(0..n))
+            .collect::<Vec<_>>();
+        let mut batch = vec![0; indices.len()];
+        ef.get_batch(&indices, &mut batch);
+        for (&i, &v) in indices.iter().zip(&batch) {
+            assert_eq!(v, values[i]);
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_get_batch_out_of_bounds() {
+    let mut efb = EliasFanoBuilder::new(2, 10);
+    efb.extend([1, 2]);
+    let ef = efb.build_with_seq();
+    let mut batch = [0; 2];
+    ef.get_batch(&[0, 2], &mut batch);
+}
+
 #[test]
 #[should_panic]
 fn test_too_many_values() {
@@ -240,6 +279,7 @@ fn test_epserde() -> Result<()> {
         println!("{}", schema.to_csv());
 
         let c = <EliasFano<
+            usize,
             SelectAdaptConst<BitVec<Box<[usize]>>, Box<[usize]>>,
             BitFieldVec<usize, Box<[usize]>>,
         >>::mmap(&tmp_file, epserde::deser::Flags::empty())?;
@@ -281,3 +321,359 @@ fn test_convenience_methods() {
         assert_eq!(ef.succ(i * 10).unwrap(), (i, i * 10));
     }
 }
+
+fn check_word<W: Word + CastableFrom<usize> + CastableInto<usize>>(values: &[W], u: W) {
+    let mut efb = EliasFanoBuilder::<W>::new_generic(values.len(), u);
+    efb.extend(values.iter().copied());
+    let ef = efb.build_with_seq_and_dict();
+    ef.verify(VerifyMode::Full).unwrap();
+    assert_eq!(ef.len(), values.len());
+    assert_eq!(ef.upper_bound(), u);
+    assert_eq!(ef.iter().collect::<Vec<_>>(), values);
+    let from = values.len() / 2;
+    assert_eq!(ef.iter_from(from).collect::<Vec<_>>(), values[from..]);
+
+    for (i, &v) in values.iter().enumerate() {
+        assert_eq!(ef.get(i), v);
+        assert_eq!(values[ef.index_of(v).unwrap()], v);
+        assert_eq!(ef.succ(v).map(|(_, s)| s), Some(v));
+        assert_eq!(ef.pred(v).map(|(_, p)| p), Some(v));
+    }
+    for w in values.windows(2) {
+        if w[0] < w[1] {
+            assert_eq!(ef.succ_strict(w[0]).map(|(_, s)| s), Some(w[1]));
+            assert_eq!(ef.pred_strict(w[1]).map(|(_, p)| p), Some(w[0]));
+        }
+    }
+    if let Some(&last) = values.last() {
+        assert_eq!(ef.pred(W::MAX).map(|(_, p)| p), Some(last));
+        if last < W::MAX {
+            assert_eq!(ef.succ(last + W::ONE), None);
+        }
+    }
+
+    let ef: EliasFano<W> = values.into();
+    assert_eq!(ef.iter().collect::<Vec<_>>(), values);
+}
+
+#[test]
+fn test_word_types() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for n in [0, 1, 10, 1000, 10_000] {
+        let mut values = (0..n).map(|_| rng.random::<u32>()).collect::<Vec<_>>();
+        values.sort();
+        check_word(&values, u32::MAX);
+        let mut values = (0..n)
+            .map(|_| rng.random_range(0..n as u32 / 4 + 1))
+            .collect::<Vec<_>>();
+        values.sort();
+        check_word(&values, n as u32);
+
+        let mut values = (0..n).map(|_| rng.random::<u64>()).collect::<Vec<_>>();
+        values.sort();
+        check_word(&values, u64::MAX);
+
+        let mut values = (0..n).map(|_| rng.random::<u128>()).collect::<Vec<_>>();
+        values.sort();
+        check_word(&values, u128::MAX);
+        let mut values = (0..n)
+            .map(|_| rng.random_range(0..1_u128 << 80))
+            .collect::<Vec<_>>();
+        values.sort();
+        check_word(&values, 1 << 80);
+    }
+    check_word(&[0, 0, 1, u128::MAX - 1, u128::MAX, u128::MAX], u128::MAX);
+    check_word(&[0_u16, 1, 1000, u16::MAX], u16::MAX);
+}
+
+#[test]
+fn test_word_types_epserde() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut values = (0..1000).map(|_| rng.random::<u32>()).collect::<Vec<_>>();
+    values.sort();
+    let mut efb = EliasFanoBuilder::<u32>::new_generic(values.len(), u32::MAX);
+    efb.extend(values.iter().copied());
+    let ef = efb.build_with_seq_and_dict();
+
+    let mut cursor = <AlignedCursor<maligned::A16>>::new();
+    ef.serialize(&mut cursor)?;
+    cursor.set_position(0);
+    let eps = <sux::dict::elias_fano::EfSeqDict<u32>>::deserialize_eps(cursor.as_bytes())?;
+    for (i, &v) in values.iter().enumerate() {
+        assert_eq!(eps.get(i), v);
+        assert_eq!(eps.succ(v), ef.succ(v));
+    }
+
+    let mut values = (0..1000).map(|_| rng.random::<u128>()).collect::<Vec<_>>();
+    values.sort();
+    let ef: EliasFano<u128> = values.clone().into();
+    let mut cursor = <AlignedCursor<maligned::A16>>::new();
+    ef.serialize(&mut cursor)?;
+    cursor.set_position(0);
+    let full = <EliasFano<u128>>::deserialize_full(&mut cursor)?;
+    assert_eq!(full.iter().collect::<Vec<_>>(), values);
+    Ok(())
+}
+
+#[test]
+fn test_word_types_concurrent() {
+    let values = [1_u32, 5, 5, 1 << 20, u32::MAX];
+    let efcb = EliasFanoConcurrentBuilder::<u32>::new_generic(values.len(), u32::MAX);
+    for (i, &v) in values.iter().enumerate() {
+        unsafe { efcb.set(i, v) };
+    }
+    let ef = efcb.build_with_seq();
+    for (i, &v) in values.iter().enumerate() {
+        assert_eq!(ef.get(i), v);
+    }
+}
+
+#[test]
+fn test_from_lender() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n, u) in [(0, 0), (1, 10), (1000, 1000), (1000, 1_000_000)] {
+        let mut values = (0..n)
+            .map(|_| rng.random_range(0..=u))
+            .collect::<Vec<usize>>();
+        values.sort();
+        let efb = EliasFanoBuilder::from_lender(FromIntoIterator::from(values.clone()))?;
+        let ef = efb.build_with_seq_and_dict();
+        ef.verify(VerifyMode::Full)?;
+        assert_eq!(ef.len(), n);
+        assert_eq!(ef.upper_bound(), values.last().copied().unwrap_or(0));
+        assert_eq!(ef.iter().collect::<Vec<_>>(), values);
+
+        // Zstd-compressed lines
+        let text = values
+            .iter()
+            .map(|v| format!("{}\n", v))
+            .collect::<String>();
+        let compressed = zstd::encode_all(text.as_bytes(), 0)?;
+        let lender = ZstdLineLender::new(std::io::Cursor::new(compressed))?;
+        let ef = EliasFanoBuilder::<u64>::from_line_lender(lender)?.build_with_seq();
+        assert_eq!(
+            ef.iter().collect::<Vec<_>>(),
+            values.iter().map(|&v| v as u64).collect::<Vec<_>>()
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_from_lender_errors() {
+    assert!(matches!(
+        EliasFanoBuilder::from_lender(FromIntoIterator::from(vec![0_usize, 5, 3, 10])),
+        Err(EfLenderError::NotMonotone {
+            index: 2,
+            value: 3,
+            prev: 5
+        })
+    ));
+    let lines = std::io::Cursor::new("1\n 2 \nthree\n");
+    assert!(matches!(
+        EliasFanoBuilder::<u32>::from_line_lender(LineLender::new(lines)),
+        Err(EfLenderError::Parse { index: 2, .. })
+    ));
+    let lines = std::io::Cursor::new("1\n2\n256\n");
+    assert!(matches!(
+        EliasFanoBuilder::<u8>::from_line_lender(LineLender::new(lines)),
+        Err(EfLenderError::Parse { index: 2, .. })
+    ));
+}
+
+#[test]
+fn test_stream_builder() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n, u) in [
+        (0, 0),
+        (0, 1000),
+        (1, 0),
+        (10, 10),
+        (1000, 100),
+        (100_000, 1_000),
+        (100_000, 100_000),
+        (100_000, 100_000_000),
+        (10_000, usize::MAX),
+    ] {
+        let mut values = (0..n).map(|_| rng.random_range(0..=u)).collect::<Vec<_>>();
+        values.sort();
+        for buffer_size in [1, 100, 1 << 20] {
+            // Also check a sequence shorter than declared
+            for len in [n, n / 2] {
+                let mut efb = EliasFanoBuilder::new(n, u);
+                let mut efsb = EliasFanoStreamBuilder::new(n, u, buffer_size)?;
+                for &value in &values[..len] {
+                    efb.push(value);
+                    efsb.push(value)?;
+                }
+                let mut expected = vec![];
+                efb.build_with_seq_and_dict().serialize(&mut expected)?;
+                let mut streamed = vec![];
+                efsb.build_with_seq_and_dict(&mut streamed)?;
+                assert_eq!(streamed, expected, "n = {n}, u = {u}, len = {len}");
+            }
+        }
+    }
+
+    let dir = tempfile::TempDir::new()?;
+    let path = dir.path().join("ef");
+    let mut efsb = EliasFanoStreamBuilder::new_in(1000, 10_000, 64, dir.path())?;
+    for i in 0..1000 {
+        efsb.push(i * 10)?;
+    }
+    efsb.build_with_seq_and_dict(std::fs::File::create(&path)?)?;
+    let ef = <sux::dict::elias_fano::EfSeqDict>::mmap(&path, Flags::empty())?;
+    assert_eq!(ef.len(), 1000);
+    for i in 0..1000 {
+        assert_eq!(ef.get(i), i * 10);
+        assert_eq!(ef.succ(i * 10 - i.min(9)), Some((i, i * 10)));
+    }
+
+    // Values of other types
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n, u) in [(0, 0), (1000, 1000), (10_000, u32::MAX)] {
+        let mut values = (0..n).map(|_| rng.random_range(0..=u)).collect::<Vec<_>>();
+        values.sort();
+        let mut efb = EliasFanoBuilder::<u32>::new_generic(n, u);
+        let mut efsb = EliasFanoStreamBuilder::<u32>::new_generic(n, u, 256)?;
+        for &value in &values {
+            efb.push(value);
+            efsb.push(value)?;
+        }
+        let mut expected = vec![];
+        efb.build_with_seq_and_dict().serialize(&mut expected)?;
+        let mut streamed = vec![];
+        efsb.build_with_seq_and_dict(&mut streamed)?;
+        assert_eq!(streamed, expected, "n = {n}, u = {u}");
+    }
+    Ok(())
+}
+
+#[test]
+fn test_cursor() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n, u) in [
+        (0, 0),
+        (1, 0),
+        (10, 10),
+        (1000, 100),
+        (10_000, 10_000),
+        (10_000, 100_000_000),
+    ] {
+        let mut values = (0..n).map(|_| rng.random_range(0..=u)).collect::<Vec<_>>();
+        values.sort();
+        let mut efb = EliasFanoBuilder::new(n, u);
+        efb.extend(values.iter().copied());
+        let ef = efb.build_with_seq_and_dict();
+
+        // Forward and backward
+        let mut cursor = ef.cursor();
+        assert_eq!(cursor.index(), 0);
+        assert_eq!(cursor.value(), values.first().copied());
+        for (i, &value) in values.iter().enumerate().skip(1) {
+            assert_eq!(cursor.next(), Some(value));
+            assert_eq!(cursor.index(), i);
+        }
+        assert_eq!(cursor.next(), None);
+        assert_eq!(cursor.next(), None);
+        assert_eq!(cursor.index(), n);
+        assert_eq!(cursor.value(), None);
+        for (i, &value) in values.iter().enumerate().rev() {
+            assert_eq!(cursor.prev(), Some(value));
+            assert_eq!(cursor.index(), i);
+        }
+        assert_eq!(cursor.prev(), None);
+        assert_eq!(cursor.index(), 0);
+
+        for i in 0..=n {
+            let cursor = ef.cursor_from(i);
+            assert_eq!(cursor.index(), i);
+            assert_eq!(cursor.value(), values[i..].first().copied());
+        }
+
+        // Skips of increasing length
+        for max_step in [1, 10, u / 100 + 1, u / 10 + 1, u + 2] {
+            let mut cursor = ef.cursor();
+            let mut bound = 0;
+            loop {
+                let index =
+                    cursor.index() + values[cursor.index()..].partition_point(|&v| v < bound);
+                assert_eq!(cursor.skip_to(bound), values[index..].first().copied());
+                assert_eq!(cursor.index(), index);
+                if index == n {
+                    break;
+                }
+                bound += rng.random_range(1..=max_step);
+            }
+            assert_eq!(cursor.skip_to(0), None);
+            // Moving back after skipping past the end
+            if n > 0 {
+                assert_eq!(cursor.prev(), Some(values[n - 1]));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_cursor_dict() {
+    // skip_to needs just a zero-selection structure
+    let values = (0..1000_u32).map(|x| x * x).collect::<Vec<_>>();
+    let mut efb = EliasFanoBuilder::new_generic(values.len(), 999 * 999);
+    efb.extend(values.iter().copied());
+    let ef = efb.build_with_dict();
+    let mut cursor = ef.cursor();
+    assert_eq!(cursor.skip_to(10), Some(16));
+    assert_eq!(cursor.index(), 4);
+    assert_eq!(cursor.skip_to(250_000), Some(250_000));
+    assert_eq!(cursor.index(), 500);
+    assert_eq!(cursor.skip_to(250_001), Some(251_001));
+    assert_eq!(cursor.prev(), Some(250_000));
+    assert_eq!(cursor.skip_to(999 * 999), Some(999 * 999));
+    assert_eq!(cursor.next(), None);
+    assert_eq!(cursor.index(), 1000);
+}
+
+#[test]
+fn test_fewer_values() -> Result<()> {
+    // A builder sized with an upper bound on the number of values
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n, u) in [(10, 10), (1000, 10_000), (100_000, 1 << 30)] {
+        let mut values = (0..n).map(|_| rng.random_range(0..=u)).collect::<Vec<_>>();
+        values.sort();
+        for len in [0, 1, n / 3, n - 1, n] {
+            let values = &values[..len];
+            let mut efb = EliasFanoBuilder::new(n, u);
+            efb.extend(values.iter().copied());
+            let ef = efb.clone().build_with_seq_and_dict();
+            ef.verify(VerifyMode::Full)?;
+            assert_eq!(ef.len(), len);
+            assert_eq!(ef.upper_bound(), u);
+            assert_eq!(ef.iter().collect::<Vec<_>>(), values);
+            for (i, &v) in values.iter().enumerate() {
+                assert_eq!(ef.get(i), v);
+                assert_eq!(ef.succ(v).unwrap().1, v);
+                assert_eq!(ef.pred(v).unwrap().1, v);
+            }
+            assert_eq!(ef.pred(usize::MAX).map(|(_, v)| v), values.last().copied());
+            assert_eq!(efb.clone().build().len(), len);
+            assert_eq!(efb.clone().build_with_seq().len(), len);
+            assert_eq!(efb.build_with_dict().len(), len);
+        }
+    }
+
+    // The number of lower bits is computed using the declared number of
+    // values, so the upper bits are longer than necessary
+    let mut efb = EliasFanoBuilder::new(1000, 10_000);
+    efb.extend([5, 10, 1000, 10_000]);
+    let sized = efb.build();
+    let mut efb = EliasFanoBuilder::new(4, 10_000);
+    efb.extend([5, 10, 1000, 10_000]);
+    let exact = efb.build();
+    assert_eq!(
+        sized.iter().collect::<Vec<_>>(),
+        exact.iter().collect::<Vec<_>>()
+    );
+    use mem_dbg::{MemSize, SizeFlags};
+    assert!(sized.mem_size(SizeFlags::default()) > exact.mem_size(SizeFlags::default()));
+    Ok(())
+}
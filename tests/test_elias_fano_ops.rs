@@ -0,0 +1,176 @@
+/*
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand::SeedableRng;
+use std::collections::BTreeSet;
+use sux::dict::elias_fano::{EfDict, EfSeqDict};
+use sux::prelude::*;
+
+fn random_list(rng: &mut SmallRng, n: usize, u: usize) -> Vec<usize> {
+    let mut values: Vec<usize> = (0..n).map(|_| rng.random_range(0..=u)).collect();
+    values.sort();
+    values
+}
+
+fn build_dict(values: &[usize], u: usize) -> EfDict {
+    let mut efb = EliasFanoBuilder::new(values.len(), u);
+    efb.extend(values.iter().copied());
+    efb.build_with_dict()
+}
+
+fn build_seq_dict(values: &[usize], u: usize) -> EfSeqDict {
+    let mut efb = EliasFanoBuilder::new(values.len(), u);
+    efb.extend(values.iter().copied());
+    efb.build_with_seq_and_dict()
+}
+
+#[test]
+fn test_intersection_union() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for k in 1..6 {
+        for (n, u) in [
+            (0, 10),
+            (10, 10),
+            (100, 1000),
+            (1000, 2000),
+            (10_000, 1_000_000),
+        ] {
+            let lists: Vec<Vec<usize>> = (0..k)
+                .map(|i| random_list(&mut rng, n * (i + 1), u))
+                .collect();
+            let sets: Vec<BTreeSet<usize>> =
+                lists.iter().map(|l| l.iter().copied().collect()).collect();
+            let efs: Vec<EfDict> = lists.iter().map(|l| build_dict(l, u)).collect();
+
+            let expected: Vec<usize> = sets[0]
+                .iter()
+                .copied()
+                .filter(|v| sets.iter().all(|s| s.contains(v)))
+                .collect();
+            assert_eq!(EfIntersection::new(&efs).collect::<Vec<_>>(), expected);
+            let ef = EfIntersection::new(&efs)
+                .into_builder()
+                .build_with_seq_and_dict();
+            assert_eq!(ef.len(), expected.len());
+            assert_eq!(ef.iter().collect::<Vec<_>>(), expected);
+            for (i, &v) in expected.iter().enumerate() {
+                assert_eq!(ef.get(i), v);
+                assert_eq!(ef.index_of(v), Some(i));
+            }
+
+            let expected: Vec<usize> = sets
+                .iter()
+                .flatten()
+                .copied()
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect();
+            assert_eq!(EfUnion::new(&efs).collect::<Vec<_>>(), expected);
+            let ef = EfUnion::new(&efs).into_builder().build_with_seq();
+            assert_eq!(ef.len(), expected.len());
+            for (i, &v) in expected.iter().enumerate() {
+                assert_eq!(ef.get(i), v);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_difference() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n, m, u) in [
+        (0, 0, 10),
+        (10, 0, 10),
+        (0, 10, 10),
+        (100, 100, 200),
+        (1000, 10, 100_000),
+        (10, 1000, 100_000),
+        (10_000, 10_000, 15_000),
+    ] {
+        let a = random_list(&mut rng, n, u);
+        let b = random_list(&mut rng, m, u / 2);
+        let set_b: BTreeSet<usize> = b.iter().copied().collect();
+        let expected: Vec<usize> = a
+            .iter()
+            .copied()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .filter(|v| !set_b.contains(v))
+            .collect();
+
+        // The minuend needs no selection structure
+        let mut efb = EliasFanoBuilder::new(n, u);
+        efb.extend(a.iter().copied());
+        let ef_a = efb.build();
+        let ef_b = build_seq_dict(&b, u / 2);
+        assert_eq!(
+            EfDifference::new(&ef_a, &ef_b).collect::<Vec<_>>(),
+            expected
+        );
+
+        let ef_a = build_seq_dict(&a, u);
+        let ef_b = build_dict(&b, u / 2);
+        assert_eq!(
+            EfDifference::new(&ef_a, &ef_b).collect::<Vec<_>>(),
+            expected
+        );
+        let ef = EfDifference::new(&ef_a, &ef_b)
+            .into_builder()
+            .build_with_seq();
+        assert_eq!(ef.len(), expected.len());
+        for (i, &v) in expected.iter().enumerate() {
+            assert_eq!(ef.get(i), v);
+        }
+    }
+}
+
+#[test]
+fn test_empty() {
+    let efs: Vec<EfDict> = vec![];
+    assert_eq!(EfIntersection::new(&efs).next(), None);
+    assert_eq!(EfUnion::new(&efs).next(), None);
+    assert_eq!(EfIntersection::new(&efs).into_builder().build().len(), 0);
+
+    let ef = build_dict(&[0, 1, usize::MAX], usize::MAX);
+    let empty = build_dict(&[], 0);
+    assert_eq!(EfIntersection::new([&ef, &empty]).next(), None);
+    assert_eq!(
+        EfUnion::new([&ef, &empty]).collect::<Vec<_>>(),
+        vec![0, 1, usize::MAX]
+    );
+    assert_eq!(
+        EfIntersection::new([&ef, &ef]).collect::<Vec<_>>(),
+        vec![0, 1, usize::MAX]
+    );
+    assert_eq!(EfDifference::new(&ef, &ef).next(), None);
+}
+
+#[test]
+fn test_mixed() {
+    // Sequences with different selection structures
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n, u) in [(0, 10), (100, 1000), (10_000, 1_000_000)] {
+        let a = random_list(&mut rng, n, u);
+        let b = random_list(&mut rng, 2 * n, u);
+        let set_a: BTreeSet<usize> = a.iter().copied().collect();
+        let set_b: BTreeSet<usize> = b.iter().copied().collect();
+        let ef_a = build_dict(&a, u);
+        let ef_b = build_seq_dict(&b, u);
+
+        let expected: Vec<usize> = set_a.intersection(&set_b).copied().collect();
+        assert_eq!(
+            EfIntersection::new([&ef_a as &dyn EfSuccOperand, &ef_b]).collect::<Vec<_>>(),
+            expected
+        );
+        let expected: Vec<usize> = set_a.union(&set_b).copied().collect();
+        let ef = EfUnion::new([&ef_b as &dyn EfOperand, &ef_a])
+            .into_builder()
+            .build_with_seq();
+        assert_eq!(ef.iter().collect::<Vec<_>>(), expected);
+    }
+}
@@ -0,0 +1,128 @@
+/*
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use dsi_progress_logger::no_logging;
+use epserde::prelude::*;
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand::SeedableRng;
+use std::io::Write;
+use sux::prelude::*;
+
+fn naive_locate(text: &[u8], pattern: &[u8]) -> Vec<usize> {
+    (0..=text.len().saturating_sub(pattern.len()))
+        .filter(|&i| text[i..].starts_with(pattern))
+        .collect()
+}
+
+fn random_text(rng: &mut SmallRng, len: usize, sigma: u8) -> Vec<u8> {
+    (0..len)
+        .map(|_| b'a' + rng.random_range(0..sigma))
+        .collect()
+}
+
+fn check<
+    R: Rank + SelectUnchecked + SelectZeroUnchecked + std::ops::Index<usize, Output = bool>,
+    L: std::ops::Deref<Target = [R]>,
+    P: AsRef<[usize]>,
+    S: Rank + std::ops::Index<usize, Output = bool>,
+    V: BitFieldSlice<usize>,
+    O: AsRef<[usize]>,
+>(
+    fm: &FmIndex<WaveletMatrix<L, P>, S, V, O>,
+    text: &[u8],
+    rng: &mut SmallRng,
+) {
+    assert_eq!(fm.len(), text.len());
+    assert_eq!(fm.extract(0..text.len()), text);
+    for _ in 0..200 {
+        // Patterns from the text, and random patterns
+        let len = rng.random_range(1..8);
+        let pattern = if !text.is_empty() && rng.random_bool(0.5) {
+            let start = rng.random_range(0..text.len());
+            text[start..(start + len).min(text.len())].to_vec()
+        } else {
+            random_text(rng, len, 4)
+        };
+        let expected = naive_locate(text, &pattern);
+        assert_eq!(fm.count(&pattern), expected.len());
+        let mut positions = fm.locate(&pattern).collect::<Vec<_>>();
+        positions.sort();
+        assert_eq!(positions, expected);
+
+        if !text.is_empty() {
+            let start = rng.random_range(0..text.len());
+            let end = rng.random_range(start..=text.len().min(start + 100));
+            assert_eq!(fm.extract(start..end), &text[start..end]);
+        }
+    }
+}
+
+#[test]
+fn test_fm_index() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 2, 10, 100, 1000, 10_000] {
+        for sigma in [1, 2, 4, 26] {
+            let text = random_text(&mut rng, len, sigma);
+            for sample_rate in [1, 3, 32] {
+                let fm = FmIndex::new(&text, sample_rate, no_logging![]);
+                check(&fm, &text, &mut rng);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_all_bytes() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let text: Vec<u8> = (0..20_000).map(|_| rng.random()).collect();
+    let fm = FmIndex::new(&text, 16, no_logging![]);
+    check(&fm, &text, &mut rng);
+    assert_eq!(fm.count(&[]), text.len() + 1);
+    for byte in [0, 1, 255] {
+        assert_eq!(
+            fm.count(&[byte]),
+            text.iter().filter(|&&b| b == byte).count()
+        );
+    }
+}
+
+#[test]
+fn test_from_file() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let text = random_text(&mut rng, 50_000, 8);
+    let mut file = tempfile::NamedTempFile::new()?;
+    file.write_all(&text)?;
+    file.flush()?;
+    let fm = FmIndex::from_file(file.path(), 8, no_logging![])?;
+    check(&fm, &text, &mut rng);
+    assert!(FmIndex::from_file("/nonexistent/file", 8, no_logging![]).is_err());
+    Ok(())
+}
+
+#[test]
+#[should_panic]
+fn test_extract_out_of_bounds() {
+    let fm = FmIndex::new(b"abracadabra", 4, no_logging![]);
+    fm.extract(5..12);
+}
+
+#[test]
+fn test_epserde() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let text = random_text(&mut rng, 50_000, 16);
+    let fm = FmIndex::new(&text, 8, no_logging![]);
+
+    let mut cursor = <AlignedCursor<maligned::A16>>::new();
+    fm.serialize(&mut cursor)?;
+    cursor.set_position(0);
+    let full = <FmIndex>::deserialize_full(&mut cursor)?;
+    check(&full, &text, &mut rng);
+    let eps = <FmIndex>::deserialize_eps(cursor.as_bytes())?;
+    check(&eps, &text, &mut rng);
+    Ok(())
+}
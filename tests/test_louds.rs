@@ -0,0 +1,146 @@
+/*
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use epserde::prelude::*;
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand::SeedableRng;
+use sux::prelude::*;
+
+/// Returns the degrees in breadth-first order and the children of each node
+/// of a random tree with `n` nodes.
+fn random_tree(rng: &mut SmallRng, n: usize, max_degree: usize) -> (Vec<usize>, Vec<Vec<usize>>) {
+    let mut degrees = vec![];
+    let mut children = vec![];
+    let mut next = 1;
+    for node in 0..n {
+        // Make sure the tree does not end before n nodes
+        let min = if next == node + 1 && next < n { 1 } else { 0 };
+        let degree = rng.random_range(min..=max_degree).min(n - next);
+        degrees.push(degree);
+        children.push((next..next + degree).collect());
+        next += degree;
+    }
+    (degrees, children)
+}
+
+fn check<R: BitLength + SelectUnchecked + SelectZeroUnchecked, L: BitFieldSlice<usize>>(
+    louds: &Louds<R, L>,
+    children: &[Vec<usize>],
+    labels: &[usize],
+) {
+    let n = children.len();
+    assert_eq!(louds.num_nodes(), n);
+    let mut parent = vec![None; n];
+    let mut child_rank = vec![None; n];
+    for (node, c) in children.iter().enumerate() {
+        for (i, &child) in c.iter().enumerate() {
+            parent[child] = Some(node);
+            child_rank[child] = Some(i);
+        }
+    }
+
+    for node in 0..n {
+        assert_eq!(louds.degree(node), children[node].len());
+        assert_eq!(louds.is_leaf(node), children[node].is_empty());
+        assert_eq!(louds.children(node).collect::<Vec<_>>(), children[node]);
+        for (i, &child) in children[node].iter().enumerate() {
+            assert_eq!(louds.child(node, i), child);
+        }
+        assert_eq!(louds.parent(node), parent[node]);
+        assert_eq!(louds.child_rank(node), child_rank[node]);
+        assert_eq!(louds.label(node), node.checked_sub(1).map(|i| labels[i]));
+        for &child in &children[node] {
+            let first = children[node]
+                .iter()
+                .copied()
+                .find(|&c| labels[c - 1] == labels[child - 1]);
+            assert_eq!(louds.child_with_label(node, labels[child - 1]), first);
+        }
+    }
+}
+
+#[test]
+fn test_louds() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for n in [1, 2, 10, 100, 1000, 10_000, 100_000] {
+        for max_degree in [1, 2, 5, 100] {
+            let (degrees, children) = random_tree(&mut rng, n, max_degree);
+            let labels: Vec<usize> = (1..n).map(|_| rng.random_range(0..26)).collect();
+            let mut bfv = BitFieldVec::new(5, 0);
+            labels.iter().for_each(|&l| bfv.push(l));
+            let louds = Louds::with_labels(degrees.iter().copied(), bfv);
+            check(&louds, &children, &labels);
+
+            let louds = Louds::new(degrees.iter().copied());
+            check(&louds, &children, &vec![0; n - 1]);
+        }
+    }
+}
+
+#[test]
+fn test_new_with() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let (degrees, children) = random_tree(&mut rng, 10_000, 4);
+    let labels: Vec<usize> = (1..10_000).collect();
+    let louds = Louds::new_with(degrees, labels.clone(), |bits| {
+        SelectZeroSmall::<2, 9, _>::new(SelectSmall::<2, 9, _>::new(RankSmall::<2, 9>::new(bits)))
+    });
+    check(&louds, &children, &labels);
+}
+
+#[test]
+fn test_empty() {
+    let louds = Louds::new([]);
+    assert!(louds.is_empty());
+    assert_eq!(louds.num_nodes(), 0);
+}
+
+#[test]
+#[should_panic]
+fn test_forest() {
+    Louds::new([1, 0, 0]);
+}
+
+#[test]
+#[should_panic]
+fn test_missing_nodes() {
+    Louds::new([2, 0]);
+}
+
+#[test]
+#[should_panic]
+fn test_wrong_labels() {
+    Louds::with_labels([2, 0, 0], bit_field_vec![1; 0]);
+}
+
+#[test]
+#[should_panic]
+fn test_child_out_of_bounds() {
+    let louds = Louds::new([2, 0, 0]);
+    louds.child(0, 2);
+}
+
+#[test]
+fn test_epserde() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let n = 50_000;
+    let (degrees, children) = random_tree(&mut rng, n, 8);
+    let labels: Vec<usize> = (1..n).map(|_| rng.random_range(0..256)).collect();
+    let mut bfv = BitFieldVec::new(8, 0);
+    labels.iter().for_each(|&l| bfv.push(l));
+    let louds = Louds::with_labels(degrees, bfv);
+
+    let mut cursor = <AlignedCursor<maligned::A16>>::new();
+    louds.serialize(&mut cursor)?;
+    cursor.set_position(0);
+    let full = <Louds>::deserialize_full(&mut cursor)?;
+    check(&full, &children, &labels);
+    let eps = <Louds>::deserialize_eps(cursor.as_bytes())?;
+    check(&eps, &children, &labels);
+    Ok(())
+}
@@ -0,0 +1,165 @@
+/*
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use epserde::prelude::*;
+use mem_dbg::*;
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand::SeedableRng;
+use sux::prelude::*;
+
+/// Returns a sequence alternating runs, dense and sparse regions, with
+/// occasional repeated values.
+fn clustered(rng: &mut SmallRng, n: usize) -> Vec<usize> {
+    let mut values = Vec::with_capacity(n);
+    let mut value = 0;
+    while values.len() < n {
+        let len = rng.random_range(1..500).min(n - values.len());
+        let gap = match rng.random_range(0..4) {
+            0 => 1,
+            1 => 3,
+            2 => 1000,
+            _ => 0,
+        };
+        value += rng.random_range(0..1_000_000);
+        for _ in 0..len {
+            values.push(value);
+            value += if gap == 0 {
+                rng.random_range(0..2)
+            } else {
+                rng.random_range(1..=gap)
+            };
+        }
+    }
+    values
+}
+
+fn check<S>(pef: &S, values: &[usize], rng: &mut SmallRng)
+where
+    S: IndexedSeq<Input = usize, Output = usize> + IndexedDict + Succ + Pred,
+    for<'a> &'a S: IntoIteratorFrom<Item = usize>,
+{
+    assert_eq!(pef.len(), values.len());
+    for (i, &v) in values.iter().enumerate() {
+        assert_eq!(pef.get(i), v);
+    }
+    assert_eq!(pef.into_iter_from(0).collect::<Vec<_>>(), values);
+    for _ in 0..100 {
+        let from = rng.random_range(0..=values.len());
+        assert_eq!(pef.into_iter_from(from).collect::<Vec<_>>(), values[from..]);
+    }
+
+    let mut queries = values.to_vec();
+    queries.extend(values.iter().map(|v| v.saturating_sub(1)));
+    queries.extend(values.iter().map(|v| v.saturating_add(1)));
+    queries.extend([0, usize::MAX]);
+    let max = values.last().copied().unwrap_or(0).saturating_add(10);
+    queries.extend((0..1000).map(|_| rng.random_range(0..=max)));
+
+    for &q in &queries {
+        match values.binary_search(&q) {
+            Ok(_) => assert_eq!(values[pef.index_of(q).unwrap()], q),
+            Err(_) => assert_eq!(pef.index_of(q), None),
+        }
+
+        let succ = values[values.partition_point(|&v| v < q)..]
+            .first()
+            .copied();
+        assert_eq!(
+            pef.succ(q).map(|(i, v)| (values[i], v)),
+            succ.map(|v| (v, v))
+        );
+        let succ_strict = values[values.partition_point(|&v| v <= q)..]
+            .first()
+            .copied();
+        assert_eq!(
+            pef.succ_strict(q).map(|(i, v)| (values[i], v)),
+            succ_strict.map(|v| (v, v))
+        );
+        let pred = values[..values.partition_point(|&v| v <= q)]
+            .last()
+            .copied();
+        assert_eq!(
+            pef.pred(q).map(|(i, v)| (values[i], v)),
+            pred.map(|v| (v, v))
+        );
+        let pred_strict = values[..values.partition_point(|&v| v < q)].last().copied();
+        assert_eq!(
+            pef.pred_strict(q).map(|(i, v)| (values[i], v)),
+            pred_strict.map(|v| (v, v))
+        );
+    }
+}
+
+#[test]
+fn test_partitioned_elias_fano() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for n in [0, 1, 2, 10, 100, 1000, 10_000] {
+        // Clustered, uniform, dense with repetitions, and consecutive values
+        let mut uniform: Vec<usize> = (0..n).map(|_| rng.random_range(0..n * 100)).collect();
+        uniform.sort();
+        let mut dense: Vec<usize> = (0..n).map(|_| rng.random_range(0..n / 2 + 1)).collect();
+        dense.sort();
+        let run: Vec<usize> = (1000..1000 + n).collect();
+        for values in [clustered(&mut rng, n), uniform, dense, run] {
+            let pef: PartitionedEliasFano = values.clone().into();
+            check(&pef, &values, &mut rng);
+        }
+    }
+}
+
+#[test]
+fn test_large_values() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let values = vec![0, 1, 2, 1 << 40, usize::MAX - 2, usize::MAX - 1, usize::MAX];
+    let pef: PartitionedEliasFano = values.clone().into();
+    check(&pef, &values, &mut rng);
+    let values = vec![usize::MAX; 10];
+    let pef: PartitionedEliasFano = values.clone().into();
+    check(&pef, &values, &mut rng);
+}
+
+#[test]
+fn test_space() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let values = clustered(&mut rng, 1_000_000);
+    let pef: PartitionedEliasFano = values.clone().into();
+    let ef: EliasFano = values.clone().into();
+    let pef_size = pef.mem_size(SizeFlags::default());
+    let ef_size = ef.mem_size(SizeFlags::default());
+    assert!(
+        pef_size * 3 < ef_size * 2,
+        "{} bytes vs. {} bytes",
+        pef_size,
+        ef_size
+    );
+    assert!(pef.num_chunks() > 1);
+}
+
+#[test]
+#[should_panic]
+fn test_not_monotone() {
+    let mut pefb = PartitionedEliasFanoBuilder::new();
+    pefb.extend([0, 10, 5]);
+}
+
+#[test]
+fn test_epserde() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let values = clustered(&mut rng, 100_000);
+    let pef: PartitionedEliasFano = values.clone().into();
+
+    let mut cursor = <AlignedCursor<maligned::A16>>::new();
+    pef.serialize(&mut cursor)?;
+    cursor.set_position(0);
+    let full = <PartitionedEliasFano>::deserialize_full(&mut cursor)?;
+    check(&full, &values, &mut rng);
+    let eps = <PartitionedEliasFano>::deserialize_eps(cursor.as_bytes())?;
+    check(&eps, &values, &mut rng);
+    assert_eq!(eps.num_chunks(), pef.num_chunks());
+    Ok(())
+}
@@ -0,0 +1,113 @@
+/*
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use epserde::prelude::*;
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use sux::prelude::*;
+
+fn check<V: BitFieldSlice<usize>, R: Rank + std::ops::Index<usize, Output = bool>>(
+    perm: &Permutation<V, R>,
+    values: &[usize],
+) {
+    assert_eq!(perm.len(), values.len());
+    for (i, &v) in values.iter().enumerate() {
+        assert_eq!(perm.apply(i), v);
+        assert_eq!(perm.inverse(v), i);
+    }
+}
+
+#[test]
+fn test_permutation() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for n in [0, 1, 2, 10, 100, 1000, 100_000] {
+        let mut values: Vec<usize> = (0..n).collect();
+        values.shuffle(&mut rng);
+        for step in [1, 2, 3, 16, 1000] {
+            check(&Permutation::new(values.iter().copied(), step)?, &values);
+        }
+        check(&Permutation::new(values.clone(), 8)?, &values);
+    }
+    // Iterators without an exact size
+    let values = (0..2000).filter(|i| i % 2 == 0).map(|i| 999 - i / 2);
+    let expected = values.clone().collect::<Vec<_>>();
+    check(&Permutation::new(values, 3)?, &expected);
+    // Increasing values, so that the bit width is increased several times
+    let values = (0..2000).filter(|i| i % 2 == 0).map(|i| i / 2);
+    let expected = values.clone().collect::<Vec<_>>();
+    let perm = Permutation::new(values, 3)?;
+    check(&perm, &expected);
+    assert_eq!(perm.into_inner().0.bit_width(), 10);
+    let values = (0..10).chain(11..20).flat_map(|i| [2 * i, 2 * i + 1]);
+    assert!(Permutation::new(values, 3).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_cycles() -> Result<()> {
+    // A single long cycle
+    let n = 10_000;
+    let values: Vec<usize> = (0..n).map(|i| (i + 1) % n).collect();
+    for step in [1, 7, 64, n - 1, n, n + 1] {
+        check(&Permutation::new(values.clone(), step)?, &values);
+    }
+    // Identity, and many short cycles
+    let values: Vec<usize> = (0..n).collect();
+    check(&Permutation::new(values.clone(), 4)?, &values);
+    let values: Vec<usize> = (0..n).map(|i| i ^ 1).collect();
+    check(&Permutation::new(values.clone(), 1)?, &values);
+    // Cycles of increasing length
+    let mut values = vec![];
+    let mut start = 0;
+    for len in 1..100 {
+        values.extend((0..len).map(|i| start + (i + 1) % len));
+        start += len;
+    }
+    check(&Permutation::new(values.clone(), 5)?, &values);
+    Ok(())
+}
+
+#[test]
+fn test_errors() {
+    assert_eq!(
+        Permutation::new(vec![0, 3, 1], 2).unwrap_err(),
+        PermutationError::OutOfRange {
+            index: 1,
+            value: 3,
+            len: 3
+        }
+    );
+    assert_eq!(
+        Permutation::new(vec![2, 0, 2], 2).unwrap_err(),
+        PermutationError::Duplicate { index: 2, value: 2 }
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_zero_step() {
+    let _ = Permutation::new(vec![0], 0);
+}
+
+#[test]
+fn test_epserde() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut values: Vec<usize> = (0..100_000).collect();
+    values.shuffle(&mut rng);
+    let perm = Permutation::new(values.clone(), 16)?;
+
+    let mut cursor = <AlignedCursor<maligned::A16>>::new();
+    perm.serialize(&mut cursor)?;
+    cursor.set_position(0);
+    let full = <Permutation>::deserialize_full(&mut cursor)?;
+    check(&full, &values);
+    let eps = <Permutation>::deserialize_eps(cursor.as_bytes())?;
+    check(&eps, &values);
+    assert_eq!(eps.step(), 16);
+    Ok(())
+}
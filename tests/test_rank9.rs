@@ -82,3 +82,76 @@ fn test_empty() {
     let inner = rank9.into_inner();
     assert_eq!(inner.len(), 0);
 }
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_par_new() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let lens = (0..1000)
+        .step_by(7)
+        .chain((10_000..100_000).step_by(10_000))
+        .chain([10_000_000]);
+    for density in [0.001, 0.5, 0.999] {
+        for len in lens.clone() {
+            let bits = (0..len)
+                .map(|_| rng.random_bool(density))
+                .collect::<BitVec>();
+            let rank9 = Rank9::new(bits.clone());
+            let par_rank9 = Rank9::par_new(bits);
+            assert_eq!(format!("{:?}", rank9), format!("{:?}", par_rank9));
+        }
+    }
+}
+
+#[test]
+fn test_set() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [1, 63, 64, 65, 511, 512, 513, 10_000] {
+        for density in [0.001, 0.5, 0.999] {
+            let mut bits = (0..len)
+                .map(|_| rng.random_bool(density))
+                .collect::<BitVec>();
+            let mut rank9 = Rank9::new(bits.clone());
+            for _ in 0..100 {
+                let pos = rng.random_range(0..len);
+                let value = rng.random_bool(0.5);
+                bits.set(pos, value);
+                rank9.set(pos, value);
+                assert_eq!(
+                    format!("{:?}", rank9),
+                    format!("{:?}", Rank9::new(bits.clone()))
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_set_lazy() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [1, 64, 513, 10_000] {
+        let mut bits = (0..len).map(|_| rng.random_bool(0.5)).collect::<BitVec>();
+        let mut rank9 = Rank9::new(bits.clone());
+        for _ in 0..10 {
+            let mut first_block = usize::MAX;
+            for _ in 0..20 {
+                let pos = rng.random_range(0..len);
+                let value = rng.random_bool(0.5);
+                bits.set(pos, value);
+                unsafe { rank9.set_lazy(pos, value) };
+                first_block = first_block.min(rank9.block_of(pos));
+            }
+            rank9.recompute_from(first_block);
+            assert_eq!(
+                format!("{:?}", rank9),
+                format!("{:?}", Rank9::new(bits.clone()))
+            );
+            let mut rank = 0;
+            for (pos, bit) in bits.iter().enumerate() {
+                assert_eq!(rank9.rank(pos), rank);
+                rank += bit as usize;
+            }
+            assert_eq!(rank9.rank(len), rank);
+        }
+    }
+}
@@ -0,0 +1,137 @@
+/*
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use epserde::prelude::*;
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+use sux::prelude::*;
+
+#[test]
+fn test() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let lens = (0..1000)
+        .chain((10_000..100_000).step_by(1000))
+        .chain((100_000..1_000_000).step_by(100_000));
+    for density in [0.1, 0.5, 0.9] {
+        for len in lens.clone() {
+            let bits = (0..len)
+                .map(|_| rng.random_bool(density))
+                .collect::<BitVec>();
+            let rank9 = Rank9::new(bits.clone());
+            let rank9_inter = Rank9Interleaved::new(bits.clone());
+
+            assert_eq!(rank9_inter.len(), len);
+            assert_eq!(rank9_inter.num_ones(), rank9.num_ones());
+            assert_eq!(rank9_inter.count_ones(), bits.count_ones());
+            for i in 0..len {
+                assert_eq!(rank9_inter.rank(i), rank9.rank(i));
+                assert_eq!(rank9_inter.rank_zero(i), rank9.rank_zero(i));
+                assert_eq!(rank9_inter[i], bits[i]);
+            }
+            assert_eq!(unsafe { rank9_inter.rank_unchecked(len) }, rank9.num_ones());
+            assert_eq!(rank9_inter.rank(len + 1), rank9.num_ones());
+            assert_eq!(rank9_inter.to_bit_vec(), bits);
+        }
+    }
+}
+
+#[test]
+fn test_select() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let lens = (1..100)
+        .step_by(10)
+        .chain([512, 1024, 4096])
+        .chain((100_000..1_000_000).step_by(300_000));
+    for density in [0.01, 0.5, 0.99] {
+        for len in lens.clone() {
+            let bits = (0..len)
+                .map(|_| rng.random_bool(density))
+                .collect::<BitVec>();
+            let sel =
+                SelectZeroAdapt::new(SelectAdapt::new(Rank9Interleaved::new(bits.clone()), 3), 3);
+
+            let mut ones = vec![];
+            let mut zeros = vec![];
+            for i in 0..len {
+                if bits[i] {
+                    ones.push(i);
+                } else {
+                    zeros.push(i);
+                }
+            }
+
+            for (i, &p) in ones.iter().enumerate() {
+                assert_eq!(sel.select(i), Some(p));
+                assert_eq!(sel.rank(p), i);
+            }
+            assert_eq!(sel.select(ones.len()), None);
+            for (i, &p) in zeros.iter().enumerate() {
+                assert_eq!(sel.select_zero(i), Some(p));
+                assert_eq!(sel.rank_zero(p), i);
+            }
+            assert_eq!(sel.select_zero(zeros.len()), None);
+        }
+    }
+}
+
+#[test]
+fn test_select_sparse() {
+    // Exercises the skipping of whole blocks in the hinted selections
+    let len = 1 << 20;
+    let mut bits = BitVec::new(len);
+    for i in (0..len).step_by(10_000) {
+        bits.set(i, true);
+    }
+    let sel = SelectAdapt::<_, _>::with_inv(Rank9Interleaved::new(bits.clone()), 10, 0);
+    for (i, p) in (0..len).step_by(10_000).enumerate() {
+        assert_eq!(sel.select(i), Some(p));
+    }
+
+    bits.flip();
+    let sel = SelectZeroAdapt::<_, _>::with_inv(Rank9Interleaved::new(bits), 10, 0);
+    for (i, p) in (0..len).step_by(10_000).enumerate() {
+        assert_eq!(sel.select_zero(i), Some(p));
+    }
+}
+
+#[test]
+fn test_words() {
+    let bits = bit_vec![1, 0, 1, 1, 0, 1, 0, 1];
+    let rank9 = Rank9Interleaved::new(bits.clone());
+    assert_eq!(rank9.num_words(), 8);
+    assert_eq!(rank9.word(0), bits.word(0));
+    for i in 1..8 {
+        assert_eq!(rank9.word(i), 0);
+    }
+}
+
+#[test]
+fn test_empty() {
+    let rank9 = Rank9Interleaved::new(BitVec::new(0));
+    assert_eq!(rank9.len(), 0);
+    assert_eq!(rank9.num_ones(), 0);
+    assert_eq!(rank9.rank(0), 0);
+    assert_eq!(unsafe { rank9.rank_unchecked(0) }, 0);
+    let sel = SelectAdapt::new(rank9, 3);
+    assert_eq!(sel.select(0), None);
+}
+
+#[test]
+fn test_epserde() -> anyhow::Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let bits = (0..100_000)
+        .map(|_| rng.random_bool(0.5))
+        .collect::<BitVec>();
+    let rank9 = Rank9Interleaved::new(bits);
+
+    let mut cursor = <AlignedCursor<maligned::A16>>::new();
+    rank9.serialize(&mut cursor)?;
+    cursor.set_position(0);
+    let des = <Rank9Interleaved>::deserialize_eps(cursor.as_bytes())?;
+    for i in 0..=rank9.len() {
+        assert_eq!(des.rank(i), rank9.rank(i));
+    }
+    Ok(())
+}
@@ -5,6 +5,8 @@
  * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
  */
 
+use mem_dbg::{MemSize, SizeFlags};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
 use sux::prelude::*;
 
 #[test]
@@ -32,3 +34,319 @@ fn test_rank_sel_add_num_bits() {
     assert_eq!(b, bits);
     assert_eq!(c, 5);
 }
+
+fn check_rank_batch(rank: impl Rank, rng: &mut SmallRng) {
+    let pos = (0..10_000)
+        .map(|_| rng.random_range(0..rank.len() + 10))
+        .collect::<Vec<_>>();
+    let mut ranks = vec![0; pos.len()];
+    rank.rank_batch(&pos, &mut ranks);
+    for (&p, &r) in pos.iter().zip(&ranks) {
+        assert_eq!(r, rank.rank(p));
+    }
+}
+
+fn check_select_batch(select: impl Select, rng: &mut SmallRng) {
+    let ranks = (0..10_000)
+        .map(|_| rng.random_range(0..select.num_ones() + 10))
+        .collect::<Vec<_>>();
+    let mut pos = vec![None; ranks.len()];
+    select.select_batch(&ranks, &mut pos);
+    for (&r, &p) in ranks.iter().zip(&pos) {
+        assert_eq!(p, select.select(r));
+    }
+
+    let ranks = ranks
+        .into_iter()
+        .filter(|&r| r < select.num_ones())
+        .collect::<Vec<_>>();
+    let mut pos = vec![0; ranks.len()];
+    unsafe { select.select_unchecked_batch(&ranks, &mut pos) };
+    for (&r, &p) in ranks.iter().zip(&pos) {
+        assert_eq!(Some(p), select.select(r));
+    }
+}
+
+fn check_select_zero_batch(select: impl SelectZero, rng: &mut SmallRng) {
+    let ranks = (0..10_000)
+        .map(|_| rng.random_range(0..select.num_zeros() + 10))
+        .collect::<Vec<_>>();
+    let mut pos = vec![None; ranks.len()];
+    select.select_zero_batch(&ranks, &mut pos);
+    for (&r, &p) in ranks.iter().zip(&pos) {
+        assert_eq!(p, select.select_zero(r));
+    }
+
+    let ranks = ranks
+        .into_iter()
+        .filter(|&r| r < select.num_zeros())
+        .collect::<Vec<_>>();
+    let mut pos = vec![0; ranks.len()];
+    unsafe { select.select_zero_unchecked_batch(&ranks, &mut pos) };
+    for (&r, &p) in ranks.iter().zip(&pos) {
+        assert_eq!(Some(p), select.select_zero(r));
+    }
+}
+
+#[test]
+fn test_rank_sel_batch() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [1, 100, 10_000, 1_000_000] {
+        for density in [0.01, 0.5, 0.99] {
+            let bits = (0..len)
+                .map(|_| rng.random_bool(density))
+                .collect::<BitVec>();
+            let num_bits: AddNumBits<_> = bits.clone().into();
+
+            check_rank_batch(Rank9::new(bits.clone()), &mut rng);
+            check_rank_batch(rank_small![0; bits.clone()], &mut rng);
+            check_rank_batch(rank_small![1; bits.clone()], &mut rng);
+            check_rank_batch(rank_small![4; bits.clone()], &mut rng);
+
+            check_select_batch(Select9::new(Rank9::new(bits.clone())), &mut rng);
+            check_select_batch(SelectAdapt::new(num_bits.clone(), 3), &mut rng);
+            check_select_batch(SelectAdaptConst::<_, _>::new(num_bits.clone()), &mut rng);
+            check_select_batch(
+                SelectSmall::<2, 9, _>::new(rank_small![0; bits.clone()]),
+                &mut rng,
+            );
+
+            check_select_zero_batch(SelectZero9::new(Rank9::new(bits.clone())), &mut rng);
+            check_select_zero_batch(SelectZeroAdapt::new(num_bits.clone(), 3), &mut rng);
+            check_select_zero_batch(
+                SelectZeroAdaptConst::<_, _>::new(num_bits.clone()),
+                &mut rng,
+            );
+            check_select_zero_batch(
+                SelectZeroSmall::<2, 9, _>::new(rank_small![0; bits.clone()]),
+                &mut rng,
+            );
+
+            // Batched queries are forwarded
+            check_rank_batch(SelectAdapt::new(Rank9::new(bits.clone()), 3), &mut rng);
+            check_select_zero_batch(
+                SelectZeroAdapt::new(SelectAdapt::new(Rank9::new(bits), 3), 3),
+                &mut rng,
+            );
+        }
+    }
+}
+
+fn check_next_prev_one(bits: &BitVec, sel: impl NextOne + PrevOne) {
+    let len = bits.len();
+    let mut next = None;
+    for pos in (0..len + 2).rev() {
+        if pos < len && bits[pos] {
+            next = Some(pos);
+        }
+        assert_eq!(sel.next_one(pos), next, "next_one({})", pos);
+    }
+    let mut prev = None;
+    for pos in 0..len + 2 {
+        assert_eq!(sel.prev_one(pos), prev, "prev_one({})", pos);
+        if pos < len && bits[pos] {
+            prev = Some(pos);
+        }
+    }
+}
+
+fn check_next_prev_zero(bits: &BitVec, sel: impl NextZero + PrevZero) {
+    let len = bits.len();
+    let mut next = None;
+    for pos in (0..len + 2).rev() {
+        if pos < len && !bits[pos] {
+            next = Some(pos);
+        }
+        assert_eq!(sel.next_zero(pos), next, "next_zero({})", pos);
+    }
+    let mut prev = None;
+    for pos in 0..len + 2 {
+        assert_eq!(sel.prev_zero(pos), prev, "prev_zero({})", pos);
+        if pos < len && !bits[pos] {
+            prev = Some(pos);
+        }
+    }
+}
+
+#[test]
+fn test_rank_sel_next_prev() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 63, 64, 65, 1000, 100_000] {
+        for density in [0.001, 0.5, 0.999] {
+            let bits = (0..len)
+                .map(|_| rng.random_bool(density))
+                .collect::<BitVec>();
+
+            check_next_prev_one(&bits, Select9::new(Rank9::new(bits.clone())));
+            check_next_prev_one(&bits, SelectAdapt::new(Rank9::new(bits.clone()), 3));
+            check_next_prev_one(
+                &bits,
+                SelectAdaptConst::<_, _>::new(Rank9::new(bits.clone())),
+            );
+            check_next_prev_one(
+                &bits,
+                SelectSmall::<2, 9, _>::new(rank_small![0; bits.clone()]),
+            );
+            check_next_prev_one(
+                &bits,
+                SelectSmall::<3, 13, _>::new(rank_small![4; bits.clone()]),
+            );
+
+            check_next_prev_zero(&bits, SelectZero9::new(Rank9::new(bits.clone())));
+            check_next_prev_zero(&bits, SelectZeroAdapt::new(Rank9::new(bits.clone()), 3));
+            check_next_prev_zero(
+                &bits,
+                SelectZeroAdaptConst::<_, _>::new(Rank9::new(bits.clone())),
+            );
+            check_next_prev_zero(
+                &bits,
+                SelectZeroSmall::<2, 9, _>::new(rank_small![0; bits.clone()]),
+            );
+
+            // Both kinds of queries are forwarded
+            let sel = SelectZero9::new(Select9::new(Rank9::new(bits.clone())));
+            check_next_prev_one(&bits, &sel);
+            check_next_prev_zero(&bits, &sel);
+            let sel = SelectZeroAdapt::new(SelectAdapt::new(Rank9::new(bits.clone()), 3), 3);
+            check_next_prev_one(&bits, &sel);
+            check_next_prev_zero(&bits, &sel);
+            let sel = SelectZeroSmall::<1, 11, _>::new(SelectSmall::<1, 11, _>::new(
+                rank_small![3; bits.clone()],
+            ));
+            check_next_prev_one(&bits, &sel);
+            check_next_prev_zero(&bits, &sel);
+        }
+    }
+}
+
+fn check_dyn_rank_select(bits: &BitVec, rank_sel: &dyn DynRankSelect) {
+    assert_eq!(rank_sel.len(), bits.len());
+    let mut ones = 0;
+    for i in 0..bits.len() {
+        assert_eq!(rank_sel[i], bits[i]);
+        assert_eq!(rank_sel.rank(i), ones);
+        assert_eq!(rank_sel.rank_zero(i), i - ones);
+        if bits[i] {
+            assert_eq!(rank_sel.select(ones), Some(i));
+            ones += 1;
+        } else {
+            assert_eq!(rank_sel.select_zero(i - ones), Some(i));
+        }
+    }
+    assert_eq!(rank_sel.num_ones(), ones);
+    assert_eq!(rank_sel.rank(bits.len()), ones);
+    assert_eq!(rank_sel.select(ones), None);
+    assert_eq!(rank_sel.select_zero(bits.len() - ones), None);
+}
+
+#[test]
+fn test_rank_sel_dyn() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let bits = (0..100_000)
+        .map(|_| rng.random_bool(0.3))
+        .collect::<BitVec>();
+
+    let ranks: Vec<Box<dyn DynRank>> = vec![
+        Box::new(Rank9::new(bits.clone())),
+        Box::new(rank_small![0; bits.clone()]),
+        Box::new(rank_small![4; bits.clone()]),
+    ];
+    for rank in &ranks {
+        let mut ones = 0;
+        for i in 0..bits.len() {
+            assert_eq!(rank[i], bits[i]);
+            assert_eq!(rank.rank(i), ones);
+            ones += bits[i] as usize;
+        }
+        assert!(rank.mem_size(SizeFlags::default()) * 8 > bits.len());
+    }
+
+    let rank_sels: Vec<Box<dyn DynRankSelect>> = vec![
+        Box::new(Select9::new(SelectZero9::new(Rank9::new(bits.clone())))),
+        Box::new(SelectZeroAdapt::new(
+            SelectAdapt::new(Rank9::new(bits.clone()), 3),
+            3,
+        )),
+        Box::new(SelectZeroAdaptConst::<_, _>::new(
+            SelectAdaptConst::<_, _>::new(Rank9::new(bits.clone())),
+        )),
+        Box::new(SelectZeroSmall::<2, 9, _>::new(
+            SelectSmall::<2, 9, _>::new(rank_small![0; bits.clone()]),
+        )),
+    ];
+    for rank_sel in &rank_sels {
+        check_dyn_rank_select(&bits, rank_sel.as_ref());
+    }
+}
+
+#[test]
+fn test_rank_sel_auto() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let len = 1 << 20;
+    for preference in [AutoPreference::Space, AutoPreference::Speed] {
+        let (rank_sel, report) = auto(BitVec::new(0), preference);
+        assert_eq!(rank_sel.len(), 0);
+        assert_eq!(rank_sel.select(0), None);
+        assert_eq!(rank_sel.select_zero(0), None);
+        assert!(report.uniform);
+
+        for density in [0.0, 0.0001, 0.001, 0.1, 0.5, 0.9, 0.999, 0.9999, 1.0] {
+            for uniform in [true, false] {
+                // In the non-uniform case, the first half is much sparser or
+                // denser than the second half
+                let (density0, density1) = if uniform {
+                    (density, density)
+                } else if density <= 0.5 {
+                    (density * 0.01, density * 1.99)
+                } else {
+                    (1.0 - (1.0 - density) * 1.99, 1.0 - (1.0 - density) * 0.01)
+                };
+                let bits = (0..len)
+                    .map(|i| rng.random_bool(if i < len / 2 { density0 } else { density1 }))
+                    .collect::<BitVec>();
+
+                let (rank_sel, report) = auto(bits.clone(), preference);
+                check_dyn_rank_select(&bits, rank_sel.as_ref());
+
+                assert_eq!(report.density, rank_sel.num_ones() as f64 / len as f64);
+                // With too few rare bits the distribution cannot be estimated
+                if (0.001..=0.999).contains(&density) {
+                    assert_eq!(report.uniform, uniform, "{density} {uniform}");
+                }
+                assert_eq!(
+                    report.overhead,
+                    ((rank_sel.as_ref().mem_size(SizeFlags::default()) * 8 - len) * 100) as f64
+                        / len as f64
+                );
+                assert_eq!(
+                    report.to_string(),
+                    format!("Memory cost of {}: {}%", report.structure, report.overhead)
+                );
+
+                match preference {
+                    AutoPreference::Space => assert!(report.overhead < 5.0, "{report}"),
+                    AutoPreference::Speed => assert!(report.overhead < 100.0, "{report}"),
+                }
+
+                let expected = match (preference, density, uniform) {
+                    (AutoPreference::Speed, 0.001, true) => "SelectZeroAdaptConst<Select9<Rank9>>",
+                    (AutoPreference::Speed, 0.999, true) => "SelectAdaptConst<SelectZero9<Rank9>>",
+                    (AutoPreference::Speed, 0.001 | 0.5 | 0.999, _) => {
+                        "SelectZeroAdaptConst<SelectAdaptConst<Rank9>>"
+                    }
+                    (AutoPreference::Space, 0.0001, _) => {
+                        "SelectAdapt<SelectZeroSmall<3, 13, RankSmall<3, 13>>>"
+                    }
+                    (AutoPreference::Space, 0.9999, _) => {
+                        "SelectZeroAdapt<SelectSmall<3, 13, RankSmall<3, 13>>>"
+                    }
+                    (AutoPreference::Space, 0.001 | 0.5 | 0.999, _) => {
+                        "SelectZeroSmall<3, 13, SelectSmall<3, 13, RankSmall<3, 13>>>"
+                    }
+                    _ => continue,
+                };
+                assert_eq!(report.structure, expected);
+            }
+        }
+    }
+}
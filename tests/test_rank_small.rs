@@ -113,3 +113,101 @@ fn test_rank_small_large() {
         assert_eq!(rank_small.rank(i), i.div_ceil(5));
     }
 }
+
+#[cfg(feature = "rayon")]
+macro_rules! test_par_new {
+    ($n: tt; $w: tt) => {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let lens = (0..1000)
+            .step_by(7)
+            .chain((10_000..100_000).step_by(10_000))
+            .chain([10_000_000]);
+        for density in [0.001, 0.5, 0.999] {
+            for len in lens.clone() {
+                let bits = (0..len)
+                    .map(|_| rng.random_bool(density))
+                    .collect::<BitVec>();
+                let rank_small = RankSmall::<$n, $w>::new(bits.clone());
+                let par_rank_small = RankSmall::<$n, $w>::par_new(bits);
+                assert_eq!(format!("{:?}", rank_small), format!("{:?}", par_rank_small));
+            }
+        }
+    };
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_par_new() {
+    test_par_new![2; 9];
+    test_par_new![1; 9];
+    test_par_new![1; 10];
+    test_par_new![1; 11];
+    test_par_new![3; 13];
+}
+
+macro_rules! test_set {
+    ($n: tt; $w: tt) => {
+        let mut rng = SmallRng::seed_from_u64(0);
+        for len in [1, 63, 64, 65, 2047, 2048, 2049, 10_000, 100_000] {
+            for density in [0.001, 0.5, 0.999] {
+                let mut bits = (0..len)
+                    .map(|_| rng.random_bool(density))
+                    .collect::<BitVec>();
+                let mut rank_small = RankSmall::<$n, $w>::new(bits.clone());
+                for _ in 0..50 {
+                    let pos = rng.random_range(0..len);
+                    let value = rng.random_bool(0.5);
+                    bits.set(pos, value);
+                    rank_small.set(pos, value);
+                    assert_eq!(
+                        format!("{:?}", rank_small),
+                        format!("{:?}", RankSmall::<$n, $w>::new(bits.clone()))
+                    );
+                }
+
+                let mut first_block = usize::MAX;
+                for _ in 0..50 {
+                    let pos = rng.random_range(0..len);
+                    let value = rng.random_bool(0.5);
+                    bits.set(pos, value);
+                    unsafe { rank_small.set_lazy(pos, value) };
+                    first_block = first_block.min(rank_small.block_of(pos));
+                }
+                rank_small.recompute_from(first_block);
+                assert_eq!(
+                    format!("{:?}", rank_small),
+                    format!("{:?}", RankSmall::<$n, $w>::new(bits.clone()))
+                );
+                assert_eq!(rank_small.num_ones(), bits.count_ones());
+            }
+        }
+    };
+}
+
+#[test]
+fn test_set() {
+    test_set![2; 9];
+    test_set![1; 9];
+    test_set![1; 10];
+    test_set![1; 11];
+    test_set![3; 13];
+}
+
+#[cfg(feature = "slow_tests")]
+#[test]
+fn test_set_large() {
+    // Updates crossing the boundary of an upper counter
+    let mut bits = BitVec::new((1 << 32) + 100000);
+    for i in (0..bits.len()).step_by(5) {
+        bits.set(i, true);
+    }
+    let mut rank_small = RankSmall::<2, 9>::new(bits.clone());
+    rank_small.set(1, true);
+    rank_small.set(0, false);
+    rank_small.set(2, true);
+    assert_eq!(rank_small.rank(bits.len()), bits.len().div_ceil(5) + 1);
+    unsafe { rank_small.set_lazy(3, true) };
+    rank_small.recompute_from(0);
+    assert_eq!(rank_small.rank(bits.len()), bits.len().div_ceil(5) + 2);
+    assert_eq!(rank_small.num_ones(), bits.len().div_ceil(5) + 2);
+}
@@ -0,0 +1,155 @@
+/*
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use epserde::prelude::*;
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand::SeedableRng;
+use sux::prelude::*;
+
+fn naive_argmin(values: &[usize], range: std::ops::Range<usize>) -> usize {
+    let min = *values[range.clone()].iter().min().unwrap();
+    range.start + values[range].iter().position(|&v| v == min).unwrap()
+}
+
+fn naive_argmax(values: &[usize], range: std::ops::Range<usize>) -> usize {
+    let max = *values[range.clone()].iter().max().unwrap();
+    range.start + values[range].iter().position(|&v| v == max).unwrap()
+}
+
+fn random_range(rng: &mut SmallRng, len: usize) -> std::ops::Range<usize> {
+    let start = rng.random_range(0..len);
+    // Favor short ranges, which are more difficult
+    let max_len = if rng.random_bool(0.5) { 100 } else { len };
+    let end = rng.random_range(start + 1..=(start + max_len).min(len));
+    start..end
+}
+
+#[test]
+fn test_rmq() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [1, 2, 10, 100, 1000, 10_000, 100_000] {
+        // Few distinct values (many ties), many distinct values, and
+        // monotone sequences
+        for max in [2, 100, usize::MAX] {
+            let mut values: Vec<usize> = (0..len).map(|_| rng.random_range(0..max)).collect();
+            for sorted in [false, true] {
+                if sorted {
+                    values.sort();
+                }
+                let seq = SliceSeq::new(&values);
+                let min = RangeMin::new(&seq);
+                let max = RangeMax::new(&seq);
+                assert_eq!(min.len(), len);
+                assert_eq!(min.argmin(0..len), naive_argmin(&values, 0..len));
+                assert_eq!(max.argmax(0..len), naive_argmax(&values, 0..len));
+                for _ in 0..len.min(10_000) {
+                    let range = random_range(&mut rng, len);
+                    assert_eq!(
+                        min.argmin(range.clone()),
+                        naive_argmin(&values, range.clone()),
+                        "{:?}",
+                        range
+                    );
+                    assert_eq!(
+                        max.argmax(range.clone()),
+                        naive_argmax(&values, range.clone()),
+                        "{:?}",
+                        range
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_long() {
+    // Several levels of the sparse table over superblocks
+    let mut rng = SmallRng::seed_from_u64(0);
+    let len = 1_000_000;
+    for max in [2, 1000, usize::MAX] {
+        let values: Vec<usize> = (0..len).map(|_| rng.random_range(0..max)).collect();
+        let seq = SliceSeq::new(&values);
+        let min = RangeMin::new(&seq);
+        let max = RangeMax::new(&seq);
+        for _ in 0..300 {
+            let start = rng.random_range(0..len);
+            let end = rng.random_range(start + 1..=len);
+            assert_eq!(min.argmin(start..end), naive_argmin(&values, start..end));
+            assert_eq!(max.argmax(start..end), naive_argmax(&values, start..end));
+        }
+    }
+}
+
+#[test]
+fn test_seqs() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let len = 10_000;
+
+    let mut bfv = BitFieldVec::<usize>::new(10, len);
+    for i in 0..len {
+        bfv.set(i, rng.random_range(0..1 << 10));
+    }
+    let values: Vec<usize> = (0..len).map(|i| bfv.get(i)).collect();
+    let min = RangeMin::new(&BitFieldSeq::new(&bfv));
+    let max = RangeMax::new(&BitFieldSeq::new(&bfv));
+    for _ in 0..1000 {
+        let range = random_range(&mut rng, len);
+        assert_eq!(
+            min.argmin(range.clone()),
+            naive_argmin(&values, range.clone())
+        );
+        assert_eq!(max.argmax(range.clone()), naive_argmax(&values, range));
+    }
+
+    let mut values: Vec<usize> = (0..len).map(|_| rng.random_range(0..1000)).collect();
+    values.sort();
+    let mut efb = EliasFanoBuilder::new(len, 1000);
+    values.iter().for_each(|&v| efb.push(v));
+    let ef = efb.build_with_seq();
+    let max = RangeMax::new(&ef);
+    for _ in 0..1000 {
+        let range = random_range(&mut rng, len);
+        assert_eq!(max.argmax(range.clone()), naive_argmax(&values, range));
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_empty_range() {
+    let values = vec![1, 2, 3];
+    RangeMin::new(&SliceSeq::new(&values)).argmin(1..1);
+}
+
+#[test]
+#[should_panic]
+fn test_out_of_bounds() {
+    let values = vec![1, 2, 3];
+    RangeMin::new(&SliceSeq::new(&values)).argmin(1..4);
+}
+
+#[test]
+fn test_epserde() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let len = 50_000;
+    let values: Vec<usize> = (0..len).map(|_| rng.random_range(0..1000)).collect();
+    let rmq = RangeMin::new(&SliceSeq::new(&values));
+
+    let mut cursor = <AlignedCursor<maligned::A16>>::new();
+    rmq.serialize(&mut cursor)?;
+    cursor.set_position(0);
+    let full = <RangeMin>::deserialize_full(&mut cursor)?;
+    let eps = <RangeMin>::deserialize_eps(cursor.as_bytes())?;
+    for _ in 0..1000 {
+        let range = random_range(&mut rng, len);
+        let expected = naive_argmin(&values, range.clone());
+        assert_eq!(full.argmin(range.clone()), expected);
+        assert_eq!(eps.argmin(range), expected);
+    }
+    Ok(())
+}
@@ -210,3 +210,45 @@ fn test_rank() {
         assert_eq!(select9.rank(len + 1), select9.count_ones());
     }
 }
+
+#[test]
+fn test_sparse_odd_len() {
+    // The number of words is not a multiple of four
+    let len = 40_000;
+    let bits = (0..len).map(|i| i % 10_000 == 0).collect::<BitVec>();
+    let select9 = Select9::new(Rank9::new(bits));
+    for i in 0..4 {
+        assert_eq!(select9.select(i), Some(i * 10_000));
+    }
+    assert_eq!(select9.select(4), None);
+}
+
+#[test]
+fn test_sparse() {
+    // Inventories spanning more than 2^17 bits store absolute positions
+    let mut rng = SmallRng::seed_from_u64(0);
+    let len = 1 << 22;
+    let bits = (0..len).map(|_| rng.random_bool(0.001)).collect::<BitVec>();
+    let select9 = Select9::new(Rank9::new(bits.clone()));
+    for (i, p) in bits.iter_ones().enumerate() {
+        assert_eq!(select9.select(i), Some(p));
+    }
+    assert_eq!(select9.select(bits.count_ones()), None);
+}
+
+#[test]
+fn test_very_sparse() {
+    // Inventories spanning more than 2^20 bits store explicitly the position
+    // of every one; we need more than one inventory to check the offset
+    let mut rng = SmallRng::seed_from_u64(0);
+    let len = 1 << 25;
+    let bits = (0..len)
+        .map(|_| rng.random_bool(0.0001))
+        .collect::<BitVec>();
+    let select9 = Select9::new(Rank9::new(bits.clone()));
+    assert!(bits.count_ones() > 2 * 512);
+    for (i, p) in bits.iter_ones().enumerate() {
+        assert_eq!(select9.select(i), Some(p));
+    }
+    assert_eq!(select9.select(bits.count_ones()), None);
+}
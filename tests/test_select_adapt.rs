@@ -301,3 +301,34 @@ fn test_sub32s_last_small() {
         assert_eq!(simple.select(ones + 1), None);
     }
 }
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_par_new() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let lens = (1..1000)
+        .step_by(7)
+        .chain((100_000..1_000_000).step_by(300_000))
+        .chain([10_000_000]);
+    for density in [0.0001, 0.01, 0.5, 0.99] {
+        for len in lens.clone() {
+            let bits: AddNumBits<_> = (0..len)
+                .map(|_| rng.random_bool(density))
+                .collect::<BitVec>()
+                .into();
+            for max_log2_u64_per_subinv in [0, 3] {
+                let select = SelectAdapt::new(bits.clone(), max_log2_u64_per_subinv);
+                let par_select = SelectAdapt::par_new(bits.clone(), max_log2_u64_per_subinv);
+                assert_eq!(format!("{:?}", select), format!("{:?}", par_select));
+            }
+            // Exercises the 32-bit and 64-bit subinventories
+            for max_log2_u64_per_subinv in [0, 3, 16] {
+                let select =
+                    SelectAdapt::<_, _>::with_inv(bits.clone(), 13, max_log2_u64_per_subinv);
+                let par_select =
+                    SelectAdapt::<_, _>::par_with_inv(bits.clone(), 13, max_log2_u64_per_subinv);
+                assert_eq!(format!("{:?}", select), format!("{:?}", par_select));
+            }
+        }
+    }
+}
@@ -295,3 +295,30 @@ fn test_sub32s_last_small() {
         assert_eq!(simple.select(ones + 1), None);
     }
 }
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_par_new() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let lens = (1..1000)
+        .step_by(7)
+        .chain((100_000..1_000_000).step_by(300_000))
+        .chain([10_000_000]);
+    for density in [0.0001, 0.01, 0.5, 0.99] {
+        for len in lens.clone() {
+            let bits: AddNumBits<_> = (0..len)
+                .map(|_| rng.random_bool(density))
+                .collect::<BitVec>()
+                .into();
+            let select = SelectAdaptConst::<_, _, INV, SUB>::new(bits.clone());
+            let par_select = SelectAdaptConst::<_, _, INV, SUB>::par_new(bits.clone());
+            assert_eq!(format!("{:?}", select), format!("{:?}", par_select));
+            let select = SelectAdaptConst::<_, _, 13, 0>::new(bits.clone());
+            let par_select = SelectAdaptConst::<_, _, 13, 0>::par_new(bits.clone());
+            assert_eq!(format!("{:?}", select), format!("{:?}", par_select));
+            let select = SelectAdaptConst::<_, _, 8, 4>::new(Rank9::new(bits.clone()));
+            let par_select = SelectAdaptConst::<_, _, 8, 4>::par_new(Rank9::par_new(bits));
+            assert_eq!(format!("{:?}", select), format!("{:?}", par_select));
+        }
+    }
+}
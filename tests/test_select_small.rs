@@ -280,6 +280,30 @@ macro_rules! test_large {
     };
 }
 
+#[test]
+fn test_epserde() -> anyhow::Result<()> {
+    use epserde::prelude::*;
+    // Selection works also on ε-deserialized structures, whose inventories
+    // are slices
+    let mut rng = SmallRng::seed_from_u64(0);
+    let bits = (0..100_000)
+        .map(|_| rng.random_bool(0.5))
+        .collect::<BitVec>();
+    let sel = SelectSmall::<2, 9, _>::new(RankSmall::<2, 9, _>::new(bits.clone()));
+    let mut cursor = <AlignedCursor<maligned::A16>>::new();
+    sel.serialize(&mut cursor)?;
+    let eps = <SelectSmall<2, 9, RankSmall<2, 9>>>::deserialize_eps(cursor.as_bytes())?;
+    let mut rank = 0;
+    for i in 0..bits.len() {
+        if bits[i] {
+            assert_eq!(eps.select(rank), Some(i));
+            rank += 1;
+        }
+    }
+    assert_eq!(eps.select(rank), None);
+    Ok(())
+}
+
 #[cfg(feature = "slow_tests")]
 #[test]
 fn test_large0() {
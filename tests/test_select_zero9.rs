@@ -0,0 +1,274 @@
+/*
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+use sux::prelude::*;
+
+#[test]
+fn test() {
+    let mut rng = rand::rngs::SmallRng::seed_from_u64(0);
+    let density = 0.5;
+    for len in (1..1000).chain((1000..10000).step_by(100)) {
+        let bits = (0..len)
+            .map(|_| rng.random_bool(density))
+            .collect::<BitVec>();
+        let select_zero9 = SelectZero9::new(Rank9::new(bits.clone()));
+
+        let zeros = bits.count_zeros();
+        let mut pos = Vec::with_capacity(zeros);
+        for i in 0..len {
+            if !bits[i] {
+                pos.push(i);
+            }
+        }
+
+        for (i, &p) in pos.iter().enumerate() {
+            assert_eq!(select_zero9.select_zero(i), Some(p));
+        }
+        assert_eq!(select_zero9.select_zero(zeros + 1), None);
+    }
+}
+
+#[test]
+fn test_into_inner() {
+    let bits = BitVec::new(0);
+    let select = SelectZero9::new(Rank9::new(bits));
+
+    let inner = select.into_inner();
+    assert_eq!(inner.len(), 0);
+    let inner = inner.into_inner();
+    assert_eq!(inner.len(), 0);
+}
+
+#[test]
+fn test_mult_usize() {
+    let mut rng = rand::rngs::SmallRng::seed_from_u64(0);
+    let density = 0.5;
+    for len in (1 << 10..1 << 15).step_by(usize::BITS as _) {
+        let bits = (0..len)
+            .map(|_| rng.random_bool(density))
+            .collect::<BitVec>();
+        let select_zero9 = SelectZero9::new(Rank9::new(bits.clone()));
+
+        let zeros = bits.count_zeros();
+        let mut pos = Vec::with_capacity(zeros);
+        for i in 0..len {
+            if !bits[i] {
+                pos.push(i);
+            }
+        }
+
+        for (i, &p) in pos.iter().enumerate() {
+            assert_eq!(select_zero9.select_zero(i), Some(p));
+        }
+        assert_eq!(select_zero9.select_zero(zeros + 1), None);
+    }
+}
+
+#[test]
+fn test_empty() {
+    let bits = BitVec::new(0);
+    let select_zero9 = SelectZero9::new(Rank9::new(bits.clone()));
+    assert_eq!(select_zero9.count_zeros(), 0);
+    assert_eq!(select_zero9.len(), 0);
+    assert_eq!(select_zero9.select_zero(0), None);
+}
+
+#[test]
+fn test_zeros() {
+    let len = 300_000;
+    let bits = (0..len).map(|_| false).collect::<BitVec>();
+    let select_zero9 = SelectZero9::new(Rank9::new(bits));
+    assert_eq!(select_zero9.count_zeros(), len);
+    assert_eq!(select_zero9.len(), len);
+    for i in 0..len {
+        assert_eq!(select_zero9.select_zero(i), Some(i));
+    }
+}
+
+#[test]
+fn test_ones() {
+    let len = 300_000;
+    let bits = (0..len).map(|_| true).collect::<BitVec>();
+    let select_zero9 = SelectZero9::new(Rank9::new(bits));
+    assert_eq!(select_zero9.count_zeros(), 0);
+    assert_eq!(select_zero9.len(), len);
+    assert_eq!(select_zero9.select_zero(0), None);
+}
+
+#[test]
+fn test_few_zeros() {
+    let lens = [1 << 18, 1 << 19, 1 << 20, (1 << 20) + 100];
+    for len in lens {
+        for num_zeros in [1, 2, 4, 8, 16, 32, 64, 128, 256] {
+            let bits = (0..len)
+                .map(|i| i % (len / num_zeros) != 0 || i / (len / num_zeros) >= num_zeros)
+                .collect::<BitVec>();
+            let select_zero9 = SelectZero9::new(Rank9::new(bits));
+            assert_eq!(select_zero9.count_zeros(), num_zeros);
+            assert_eq!(select_zero9.len(), len);
+            for i in 0..num_zeros {
+                assert_eq!(select_zero9.select_zero(i), Some(i * (len / num_zeros)));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_non_uniform() {
+    let lens = [1 << 18, 1 << 19, 1 << 20, 1 << 25];
+
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in lens {
+        for density in [0.5] {
+            let density0 = density * 0.01;
+            let density1 = density * 0.99;
+
+            let len1;
+            let len2;
+            if len % 2 != 0 {
+                len1 = len / 2 + 1;
+                len2 = len / 2;
+            } else {
+                len1 = len / 2;
+                len2 = len / 2;
+            }
+
+            let first_half = loop {
+                let b = (0..len1)
+                    .map(|_| !rng.random_bool(density0))
+                    .collect::<BitVec>();
+                if b.count_zeros() > 0 {
+                    break b;
+                }
+            };
+            let num_zeros_first_half = first_half.count_zeros();
+            let second_half = (0..len2)
+                .map(|_| !rng.random_bool(density1))
+                .collect::<BitVec>();
+            let num_zeros_second_half = second_half.count_zeros();
+
+            assert!(num_zeros_first_half > 0);
+            assert!(num_zeros_second_half > 0);
+
+            let bits = first_half
+                .into_iter()
+                .chain(&second_half)
+                .collect::<BitVec>();
+
+            assert_eq!(
+                num_zeros_first_half + num_zeros_second_half,
+                bits.count_zeros()
+            );
+
+            assert_eq!(bits.len(), len as usize);
+
+            let zeros = bits.count_zeros();
+            let mut pos = Vec::with_capacity(zeros);
+            for i in 0..(len as usize) {
+                if !bits[i] {
+                    pos.push(i);
+                }
+            }
+
+            let select_zero9 = SelectZero9::new(Rank9::new(bits));
+
+            for (i, &p) in pos.iter().enumerate() {
+                assert_eq!(select_zero9.select_zero(i), Some(p));
+            }
+            assert_eq!(select_zero9.select_zero(zeros + 1), None);
+        }
+    }
+}
+
+#[test]
+fn test_rank() {
+    let mut rng = rand::rngs::SmallRng::seed_from_u64(0);
+    let density = 0.5;
+    for len in (10_000..100_000).step_by(1000) {
+        let bits = (0..len)
+            .map(|_| rng.random_bool(density))
+            .collect::<BitVec>();
+        let select_zero9 = SelectZero9::new(Rank9::new(bits.clone()));
+
+        let mut ranks = Vec::with_capacity(len);
+        let mut r = 0;
+        for bit in bits.into_iter() {
+            ranks.push(r);
+            if bit {
+                r += 1;
+            }
+        }
+
+        for (i, &r) in ranks.iter().enumerate() {
+            assert_eq!(select_zero9.rank(i), r);
+        }
+        assert_eq!(select_zero9.rank(len + 1), select_zero9.count_ones());
+    }
+}
+
+#[test]
+fn test_composition() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for density in [0.01, 0.5, 0.99] {
+        for len in (1..1000).chain((10_000..100_000).step_by(10_000)) {
+            let bits = (0..len)
+                .map(|_| rng.random_bool(density))
+                .collect::<BitVec>();
+            let sel0 = SelectZero9::new(Select9::new(Rank9::new(bits.clone())));
+            let sel1 = Select9::new(SelectZero9::new(Rank9::new(bits.clone())));
+
+            let (mut ones, mut zeros) = (0, 0);
+            for i in 0..len {
+                assert_eq!(sel0.rank(i), ones);
+                assert_eq!(sel1.rank(i), ones);
+                if bits[i] {
+                    assert_eq!(sel0.select(ones), Some(i));
+                    assert_eq!(sel1.select(ones), Some(i));
+                    ones += 1;
+                } else {
+                    assert_eq!(sel0.select_zero(zeros), Some(i));
+                    assert_eq!(sel1.select_zero(zeros), Some(i));
+                    zeros += 1;
+                }
+            }
+            assert_eq!(sel0.select(ones), None);
+            assert_eq!(sel1.select(ones), None);
+            assert_eq!(sel0.select_zero(zeros), None);
+            assert_eq!(sel1.select_zero(zeros), None);
+        }
+    }
+}
+
+#[test]
+fn test_sparse() {
+    // Inventories spanning more than 2^17 bits store absolute positions
+    let mut rng = SmallRng::seed_from_u64(0);
+    let len = 1 << 22;
+    let bits = (0..len).map(|_| rng.random_bool(0.999)).collect::<BitVec>();
+    let select_zero9 = SelectZero9::new(Rank9::new(bits.clone()));
+    for (i, p) in bits.iter_zeros().enumerate() {
+        assert_eq!(select_zero9.select_zero(i), Some(p));
+    }
+    assert_eq!(select_zero9.select_zero(bits.count_zeros()), None);
+}
+
+#[test]
+fn test_very_sparse() {
+    // Inventories spanning more than 2^20 bits store explicitly the position
+    // of every zero; we need more than one inventory to check the offset
+    let mut rng = SmallRng::seed_from_u64(0);
+    let len = 1 << 25;
+    let bits = (0..len)
+        .map(|_| rng.random_bool(0.9999))
+        .collect::<BitVec>();
+    let select_zero9 = SelectZero9::new(Rank9::new(bits.clone()));
+    assert!(bits.count_zeros() > 2 * 512);
+    for (i, p) in bits.iter_zeros().enumerate() {
+        assert_eq!(select_zero9.select_zero(i), Some(p));
+    }
+    assert_eq!(select_zero9.select_zero(bits.count_zeros()), None);
+}
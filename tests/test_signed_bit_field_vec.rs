@@ -0,0 +1,108 @@
+/*
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use epserde::prelude::*;
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand::SeedableRng;
+use sux::prelude::*;
+
+#[test]
+fn test_zig_zag() {
+    for (value, encoded) in [(0, 0), (-1, 1), (1, 2), (-2, 3), (2, 4)] {
+        assert_eq!(zig_zag_encode::<u64>(value), encoded);
+        assert_eq!(zig_zag_decode::<u64>(encoded), value);
+    }
+    assert_eq!(zig_zag_encode::<u64>(i64::MAX), u64::MAX - 1);
+    assert_eq!(zig_zag_encode::<u64>(i64::MIN), u64::MAX);
+    assert_eq!(zig_zag_decode::<u64>(u64::MAX - 1), i64::MAX);
+    assert_eq!(zig_zag_decode::<u64>(u64::MAX), i64::MIN);
+    assert_eq!(zig_zag_encode::<u32>(i32::MIN), u32::MAX);
+    assert_eq!(zig_zag_decode::<u32>(u32::MAX), i32::MIN);
+}
+
+#[test]
+fn test_signed_bit_field_vec() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let n = 1000;
+    for bit_width in 1..=64 {
+        let min = i64::MIN >> (64 - bit_width);
+        let max = i64::MAX >> (64 - bit_width);
+        let values = (0..n)
+            .map(|_| rng.random_range(min..=max))
+            .collect::<Vec<_>>();
+
+        let mut v = SignedBitFieldVec::<u64>::new(bit_width, n);
+        for (i, &value) in values.iter().enumerate() {
+            v.set(i, value);
+        }
+        assert_eq!(v.len(), n);
+        assert_eq!(v.bit_width(), bit_width);
+        for (i, &value) in values.iter().enumerate() {
+            assert_eq!(v.get(i), value);
+        }
+        assert_eq!(v.iter().collect::<Vec<_>>(), values);
+        for from in [0, 1, n / 2, n] {
+            let mut iter = v.iter_from(from);
+            assert_eq!(iter.len(), n - from);
+            for &value in &values[from..] {
+                assert_eq!(iter.next(), Some(value));
+            }
+            assert_eq!(iter.next(), None);
+        }
+
+        let mut w = SignedBitFieldVec::<u64>::with_capacity(bit_width, n);
+        w.extend(values.iter().copied());
+        assert_eq!(v, w);
+        for &value in values.iter().rev() {
+            assert_eq!(w.pop(), Some(value));
+        }
+        assert!(w.is_empty());
+
+        let s = SignedBitFieldVec::<u64>::from_slice(&values);
+        assert!(s.bit_width() <= bit_width);
+        assert_eq!(s.iter().collect::<Vec<_>>(), values);
+
+        let b: SignedBitFieldVec<u64, Box<[u64]>> = s.into();
+        let mut cursor = <AlignedCursor<maligned::A16>>::new();
+        b.serialize(&mut cursor).unwrap();
+        cursor.set_position(0);
+        let d = <SignedBitFieldVec<u64, Box<[u64]>>>::deserialize_eps(cursor.as_bytes()).unwrap();
+        assert_eq!(d.iter().collect::<Vec<_>>(), values);
+    }
+}
+
+#[test]
+fn test_signed_bit_field_vec_i32() {
+    let values = [-3, 0, 2, -1, 1];
+    let v = SignedBitFieldVec::<u32>::from_slice(&values);
+    assert_eq!(v.bit_width(), 3);
+    assert_eq!(v.iter().collect::<Vec<i32>>(), values);
+    assert_eq!(v.as_bit_field_vec().get(0), 5);
+
+    let v = SignedBitFieldVec::<u32>::from_slice(&[i32::MIN, i32::MAX]);
+    assert_eq!(v.bit_width(), 32);
+    assert_eq!(v.get(0), i32::MIN);
+    assert_eq!(v.get(1), i32::MAX);
+
+    let v = SignedBitFieldVec::<u32>::from_slice(&[0, 0]);
+    assert_eq!(v.bit_width(), 0);
+    assert_eq!(v.get(1), 0);
+}
+
+#[test]
+#[should_panic]
+fn test_signed_bit_field_vec_too_large() {
+    let mut v = SignedBitFieldVec::<u64>::new(4, 1);
+    v.set(0, 8);
+}
+
+#[test]
+#[should_panic]
+fn test_signed_bit_field_vec_too_small() {
+    let mut v = SignedBitFieldVec::<u64>::with_capacity(4, 1);
+    v.push(-9);
+}
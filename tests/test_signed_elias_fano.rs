@@ -0,0 +1,112 @@
+/*
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use epserde::prelude::*;
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand::SeedableRng;
+use sux::dict::elias_fano::EfSeqDict;
+use sux::dict::{SignedEliasFano, SignedEliasFanoBuilder};
+use sux::prelude::*;
+
+#[test]
+fn test_signed_elias_fano() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n, lower, upper) in [
+        (10, -1000, 1000),
+        (100, -1000, -10),
+        (100, -100, 0),
+        (1000, -50, 50),
+        (1000, 10, 20),
+        (10, isize::MIN, isize::MAX),
+    ] {
+        let mut values = (0..n)
+            .map(|_| rng.random_range(lower as i64..=upper as i64) as isize)
+            .collect::<Vec<_>>();
+        values.sort();
+
+        let mut efb = SignedEliasFanoBuilder::new(n, lower, upper);
+        efb.extend(values.iter().copied());
+        let ef = efb.build_with_seq_and_dict();
+
+        assert_eq!(ef.len(), n);
+        assert_eq!(ef.offset(), lower);
+        for (i, &v) in values.iter().enumerate() {
+            assert_eq!(ef.get(i), v);
+        }
+        assert_eq!(ef.iter().collect::<Vec<_>>(), values);
+        for from in [0, 1, n / 2, n] {
+            assert_eq!(ef.iter_from(from).collect::<Vec<_>>(), values[from..]);
+        }
+
+        let mut queries = values.clone();
+        queries.extend(values.iter().map(|v| v.saturating_sub(1)));
+        queries.extend(values.iter().map(|v| v.saturating_add(1)));
+        queries.extend([lower, upper, isize::MIN, isize::MAX]);
+        queries.extend((0..1000).map(|_| rng.random_range(lower as i64..=upper as i64) as isize));
+
+        for &q in &queries {
+            match values.binary_search(&q) {
+                Ok(_) => assert_eq!(values[ef.index_of(q).unwrap()], q),
+                Err(_) => assert_eq!(ef.index_of(q), None),
+            }
+
+            let succ = values.iter().copied().find(|&v| v >= q);
+            assert_eq!(
+                ef.succ(q).map(|(i, v)| (values[i], v)),
+                succ.map(|v| (v, v))
+            );
+            let succ_strict = values.iter().copied().find(|&v| v > q);
+            assert_eq!(
+                ef.succ_strict(q).map(|(i, v)| (values[i], v)),
+                succ_strict.map(|v| (v, v))
+            );
+            let pred = values.iter().copied().rev().find(|&v| v <= q);
+            assert_eq!(
+                ef.pred(q).map(|(i, v)| (values[i], v)),
+                pred.map(|v| (v, v))
+            );
+            let pred_strict = values.iter().copied().rev().find(|&v| v < q);
+            assert_eq!(
+                ef.pred_strict(q).map(|(i, v)| (values[i], v)),
+                pred_strict.map(|v| (v, v))
+            );
+        }
+
+        let mut cursor = <AlignedCursor<maligned::A16>>::new();
+        ef.serialize(&mut cursor)?;
+        cursor.set_position(0);
+        let ef = <SignedEliasFano<EfSeqDict>>::deserialize_eps(cursor.as_bytes())?;
+        assert_eq!(ef.iter().collect::<Vec<_>>(), values);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_signed_elias_fano_from_slice() {
+    let values = vec![-5, -5, 0, 7];
+    let ef: SignedEliasFano = values.clone().into();
+    assert_eq!(ef.offset(), -5);
+    assert_eq!(ef.iter().collect::<Vec<_>>(), values);
+
+    let ef: SignedEliasFano = Vec::<isize>::new().into();
+    assert_eq!(ef.len(), 0);
+    assert_eq!(ef.iter().next(), None);
+}
+
+#[test]
+#[should_panic]
+fn test_signed_elias_fano_not_monotone() {
+    let _: SignedEliasFano = vec![0, -1].into();
+}
+
+#[test]
+#[should_panic]
+fn test_signed_elias_fano_too_small() {
+    let mut efb = SignedEliasFanoBuilder::new(1, -10, 10);
+    efb.push(-11);
+}
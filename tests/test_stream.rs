@@ -0,0 +1,204 @@
+/*
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use dsi_progress_logger::no_logging;
+use epserde::prelude::*;
+use mmap_rs::MmapFlags;
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand::SeedableRng;
+use std::fs::File;
+use std::io::{BufReader, Write};
+use sux::prelude::*;
+use sux::rank_sel::{stream_rank9, stream_rank9_select_adapt_const, stream_select_adapt_const};
+use sux::traits::{Verify, VerifyMode};
+use sux::utils::MmapHelper;
+
+fn random_bits(rng: &mut SmallRng, len: usize, density: f64) -> BitVec {
+    (0..len).map(|_| rng.random_bool(density)).collect()
+}
+
+fn raw(bits: &BitVec) -> &[u8] {
+    bytemuck::cast_slice(bits.as_ref())
+}
+
+fn check<const L: usize, const S: usize>(bits: BitVec) -> Result<()> {
+    let len = bits.len();
+
+    let mut counters = <AlignedCursor<maligned::A16>>::new();
+    let mut inventory = <AlignedCursor<maligned::A16>>::new();
+    let num_ones = stream_rank9_select_adapt_const::<L, S>(
+        raw(&bits),
+        len,
+        &mut counters,
+        &mut inventory,
+        no_logging![],
+    )?;
+    assert_eq!(num_ones, bits.count_ones());
+
+    let rank9 = Rank9::new(bits.clone());
+    let streamed = <Rank9<(), Box<[BlockCounters]>>>::deserialize_eps(counters.as_bytes())?;
+    let streamed = unsafe { streamed.map(|_| bits.clone()) };
+    assert_eq!(
+        format!("{:?}", streamed.counts()),
+        format!("{:?}", rank9.counts())
+    );
+
+    let select = SelectAdaptConst::<_, _, L, S>::new(AddNumBits::from(bits.clone()));
+    let streamed =
+        <SelectAdaptConst<(), Box<[usize]>, L, S>>::deserialize_eps(inventory.as_bytes())?;
+    let streamed = unsafe { streamed.map(|_| AddNumBits::from(bits.clone())) };
+    assert!(streamed.verify(VerifyMode::Full).is_ok());
+    for rank in 0..num_ones {
+        assert_eq!(streamed.select(rank), select.select(rank));
+    }
+    assert_eq!(streamed.select(num_ones), None);
+
+    // The streamed slices have the same layout of boxed slices: if ε-serde
+    // changes the layout of slices, the streaming code must be updated
+    let counts = <Rank9<(), Box<[BlockCounters]>>>::deserialize_full(&mut counters.as_bytes())?;
+    let mut reserialized = <AlignedCursor<maligned::A16>>::new();
+    counts.serialize(&mut reserialized)?;
+    assert_eq!(reserialized.as_bytes(), counters.as_bytes());
+    let inv =
+        <SelectAdaptConst<(), Box<[usize]>, L, S>>::deserialize_full(&mut inventory.as_bytes())?;
+    let mut reserialized = <AlignedCursor<maligned::A16>>::new();
+    inv.serialize(&mut reserialized)?;
+    assert_eq!(reserialized.as_bytes(), inventory.as_bytes());
+
+    // The separate functions produce the same output
+    let mut counters_only = <AlignedCursor<maligned::A16>>::new();
+    stream_rank9(raw(&bits), len, &mut counters_only, no_logging![])?;
+    assert_eq!(counters_only.as_bytes(), counters.as_bytes());
+    let mut inventory_only = <AlignedCursor<maligned::A16>>::new();
+    stream_select_adapt_const::<L, S>(raw(&bits), len, &mut inventory_only, no_logging![])?;
+    assert_eq!(inventory_only.as_bytes(), inventory.as_bytes());
+    Ok(())
+}
+
+#[test]
+fn test_stream() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 63, 64, 65, 511, 512, 513, 10_000, 1 << 20] {
+        for density in [0.0, 0.001, 0.1, 0.5, 0.9, 1.0] {
+            let bits = random_bits(&mut rng, len, density);
+            check::<12, 3>(bits.clone())?;
+            check::<8, 2>(bits.clone())?;
+            check::<10, 0>(bits)?;
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_stream_sparse() -> Result<()> {
+    // Inventory entries spanning more than 2^16 bits, and thus using 32-bit
+    // subinventories, the spill, and the positions of the ones
+    let mut rng = SmallRng::seed_from_u64(0);
+    let len = 1 << 24;
+    for density in [0.0001, 0.001] {
+        let bits = random_bits(&mut rng, len, density);
+        check::<12, 3>(bits.clone())?;
+        check::<8, 1>(bits)?;
+    }
+
+    // Dense and sparse regions
+    let bits: BitVec = (0..len)
+        .map(|i| {
+            if (i >> 20) % 2 == 0 {
+                rng.random_bool(0.5)
+            } else {
+                rng.random_bool(0.00001)
+            }
+        })
+        .collect();
+    check::<12, 3>(bits.clone())?;
+    check::<8, 1>(bits)?;
+    Ok(())
+}
+
+#[test]
+fn test_garbage() -> Result<()> {
+    // Bits beyond the length are ignored
+    let mut rng = SmallRng::seed_from_u64(0);
+    let bits = random_bits(&mut rng, 1000, 0.5);
+    let mut words = bits.as_ref().to_vec();
+    *words.last_mut().unwrap() |= usize::MAX << (1000 % usize::BITS as usize);
+    let mut counters = vec![];
+    let num_ones = stream_rank9(
+        bytemuck::cast_slice::<_, u8>(&words),
+        1000,
+        &mut counters,
+        no_logging![],
+    )?;
+    assert_eq!(num_ones, bits.count_ones());
+    Ok(())
+}
+
+#[test]
+fn test_short_input() {
+    let bits: BitVec = (0..1000).map(|i| i % 2 == 0).collect();
+    let result = stream_rank9(raw(&bits), 2000, vec![], no_logging![]);
+    assert!(result.is_err());
+    let result = stream_select_adapt_const::<12, 3>(raw(&bits), 2000, vec![], no_logging![]);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_mmap() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let len = 1_000_000;
+    let bits = random_bits(&mut rng, len, 0.3);
+
+    let dir = tempfile::TempDir::new()?;
+    let bits_path = dir.path().join("bits");
+    let counters_path = dir.path().join("counters");
+    let inventory_path = dir.path().join("inventory");
+    File::create(&bits_path)?.write_all(raw(&bits))?;
+
+    stream_rank9_select_adapt_const::<12, 3>(
+        BufReader::new(File::open(&bits_path)?),
+        len,
+        File::create(&counters_path)?,
+        File::create(&inventory_path)?,
+        no_logging![],
+    )?;
+
+    let mmap_bits = MmapHelper::<usize>::mmap(&bits_path, MmapFlags::empty())?;
+    let mmap_bits = unsafe { BitVec::from_raw_parts(mmap_bits, len) };
+    let counters = <Rank9<(), Box<[BlockCounters]>>>::mmap(&counters_path, Flags::empty())?;
+    let inventory =
+        <SelectAdaptConst<(), Box<[usize]>, 12, 3>>::mmap(&inventory_path, Flags::empty())?;
+    let rank9 = unsafe { counters.clone().map(|_| mmap_bits) };
+    let rank9_sel = unsafe { inventory.clone().map(|_| rank9) };
+    assert!(rank9_sel.verify(VerifyMode::Full).is_ok());
+
+    let expected = SelectAdaptConst::<_, _>::new(Rank9::new(bits));
+    for pos in (0..=len).step_by(97) {
+        assert_eq!(rank9_sel.rank(pos), expected.rank(pos));
+    }
+    for rank in 0..expected.num_ones() {
+        assert_eq!(rank9_sel.select(rank), expected.select(rank));
+    }
+
+    // Empty bit vectors
+    File::create(&bits_path)?;
+    stream_rank9_select_adapt_const::<12, 3>(
+        BufReader::new(File::open(&bits_path)?),
+        0,
+        File::create(&counters_path)?,
+        File::create(&inventory_path)?,
+        no_logging![],
+    )?;
+    let mmap_bits = MmapHelper::<usize>::mmap(&bits_path, MmapFlags::empty())?;
+    let mmap_bits = unsafe { BitVec::from_raw_parts(mmap_bits, 0) };
+    let counters = <Rank9<(), Box<[BlockCounters]>>>::mmap(&counters_path, Flags::empty())?;
+    let rank9 = unsafe { counters.clone().map(|_| mmap_bits) };
+    assert_eq!(rank9.rank(0), 0);
+    assert_eq!(rank9.num_ones(), 0);
+    Ok(())
+}
@@ -0,0 +1,96 @@
+/*
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand::SeedableRng;
+use sux::prelude::*;
+
+fn check<const WIDTH: usize>(symbols: &[usize]) {
+    let sigma = 1 << WIDTH;
+    let mut bfv = BitFieldVec::<usize>::new(WIDTH, 0);
+    symbols.iter().for_each(|&s| bfv.push(s));
+    let symbol_rank = SymbolRank::<WIDTH>::new(bfv);
+    assert!(symbol_rank.verify(VerifyMode::Full).is_ok());
+    assert_eq!(symbol_rank.len(), symbols.len());
+
+    let mut ranks = vec![0; sigma];
+    let mut positions = vec![vec![]; sigma];
+    for (pos, &symbol) in symbols.iter().enumerate() {
+        assert_eq!(symbol_rank.get(pos), symbol);
+        for (c, &rank) in ranks.iter().enumerate() {
+            assert_eq!(symbol_rank.rank(c, pos), rank, "symbol {c}, pos {pos}");
+        }
+        ranks[symbol] += 1;
+        positions[symbol].push(pos);
+    }
+
+    for c in 0..sigma {
+        assert_eq!(symbol_rank.rank(c, symbols.len()), ranks[c]);
+        assert_eq!(symbol_rank.rank(c, symbols.len() + 1), ranks[c]);
+        assert_eq!(symbol_rank.count(c), ranks[c]);
+        for (rank, &pos) in positions[c].iter().enumerate() {
+            assert_eq!(
+                symbol_rank.select(c, rank),
+                Some(pos),
+                "symbol {c}, rank {rank}"
+            );
+        }
+        assert_eq!(symbol_rank.select(c, ranks[c]), None);
+    }
+}
+
+#[test]
+fn test_symbol_rank() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 31, 32, 33, 63, 64, 65, 511, 512, 513, 1000, 10_000] {
+        let symbols = (0..len).map(|_| rng.random_range(0..4)).collect::<Vec<_>>();
+        check::<2>(&symbols);
+        let symbols = (0..len)
+            .map(|_| rng.random_range(0..16))
+            .collect::<Vec<_>>();
+        check::<4>(&symbols);
+    }
+}
+
+#[test]
+fn test_skewed() {
+    // Long runs of a single symbol, and symbols missing entirely
+    let mut rng = SmallRng::seed_from_u64(0);
+    let symbols = (0..100_000)
+        .map(|i| {
+            if (i / 5000) % 2 == 0 {
+                0
+            } else {
+                rng.random_range(2..4)
+            }
+        })
+        .collect::<Vec<_>>();
+    check::<2>(&symbols);
+    let symbols = (0..100_000)
+        .map(|_| if rng.random_bool(0.99) { 15 } else { 7 })
+        .collect::<Vec<_>>();
+    check::<4>(&symbols);
+}
+
+#[test]
+fn test_corrupted() {
+    let mut bfv = BitFieldVec::<usize>::new(2, 0);
+    (0..2000).for_each(|i| bfv.push(i % 4));
+    let symbol_rank = SymbolRank::<2>::new(bfv.clone());
+    assert!(symbol_rank.verify(VerifyMode::Full).is_ok());
+
+    // Counters computed on different symbols
+    bfv.set(1500, 1);
+    let corrupted = unsafe { symbol_rank.map(|_| bfv.as_slice().to_vec()) };
+    assert!(corrupted.verify(VerifyMode::Full).is_err());
+}
+
+#[test]
+#[should_panic]
+fn test_wrong_width() {
+    SymbolRank::<2>::new(BitFieldVec::<usize>::new(4, 10));
+}
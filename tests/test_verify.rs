@@ -0,0 +1,286 @@
+/*
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use dsi_progress_logger::no_logging;
+use epserde::prelude::*;
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand::SeedableRng;
+use sux::func::{VBuilder, VFunc};
+use sux::prelude::*;
+use sux::traits::{Verify, VerifyError, VerifyMode};
+use sux::utils::FromIntoIterator;
+
+const SAMPLED: VerifyMode = VerifyMode::Sampled {
+    samples: 100,
+    seed: 0,
+};
+
+fn random_bits(rng: &mut SmallRng, len: usize, density: f64) -> BitVec {
+    (0..len).map(|_| rng.random_bool(density)).collect()
+}
+
+fn flip(mut bits: BitVec, pos: usize) -> BitVec {
+    let bit = bits.get(pos);
+    bits.set(pos, !bit);
+    bits
+}
+
+fn assert_ok(s: &impl Verify) {
+    assert_eq!(s.verify(VerifyMode::Full), Ok(()));
+    assert_eq!(s.verify(SAMPLED), Ok(()));
+}
+
+#[test]
+fn test_rank9() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 64, 511, 512, 513, 10_000, 1 << 20] {
+        for density in [0.01, 0.5, 0.99] {
+            let rank9 = Rank9::new(random_bits(&mut rng, len, density));
+            assert_ok(&rank9);
+            if len < 1024 {
+                continue;
+            }
+
+            // Make the counters inconsistent with the bits
+            let pos = rng.random_range(0..len);
+            let block = pos / 512;
+            let rank9 = unsafe { rank9.map(|bits| flip(bits, pos)) };
+            match rank9.verify(VerifyMode::Full) {
+                Err(VerifyError::Value { what, index, .. }) => {
+                    assert!(what.contains("counter"));
+                    assert!(index == block || index == block + 1);
+                }
+                result => panic!("Unexpected result {:?}", result),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_select_adapt() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 100, 10_000, 1 << 20] {
+        for density in [0.001, 0.1, 0.5, 0.9, 0.999] {
+            let bits = random_bits(&mut rng, len, density);
+            // Flipping the first one (zero) always changes the first
+            // inventory entry (or the number of entries). Flipping a random
+            // bit might not be detected; this went unnoticed because map()
+            // on the const structures used to reset the parameters to their
+            // defaults, making verification always fail.
+            let one = (0..len).find(|&i| bits[i]).unwrap_or(0);
+            let zero = (0..len).find(|&i| !bits[i]).unwrap_or(0);
+
+            let select = SelectAdapt::new(bits.clone(), 3);
+            assert_ok(&select);
+            if len >= 10_000 {
+                let select = unsafe { select.map(|bits| flip(bits, one)) };
+                assert!(select.verify(VerifyMode::Full).is_err());
+            }
+
+            let select = SelectZeroAdapt::new(bits.clone(), 3);
+            assert_ok(&select);
+            if len >= 10_000 {
+                let select = unsafe { select.map(|bits| flip(bits, zero)) };
+                assert!(select.verify(VerifyMode::Full).is_err());
+            }
+
+            let select = SelectAdaptConst::<_, _, 8, 2>::new(bits.clone());
+            assert_ok(&select);
+            if len >= 10_000 {
+                // map() preserves the parameters
+                let select: SelectAdaptConst<_, _, 8, 2> =
+                    unsafe { select.map(|bits| flip(bits, one)) };
+                assert!(select.verify(VerifyMode::Full).is_err());
+            }
+
+            let select = SelectZeroAdaptConst::<_, _, 8, 2>::new(bits.clone());
+            assert_ok(&select);
+            if len >= 10_000 {
+                // map() preserves the parameters
+                let select: SelectZeroAdaptConst<_, _, 8, 2> =
+                    unsafe { select.map(|bits| flip(bits, zero)) };
+                assert!(select.verify(VerifyMode::Full).is_err());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_select_adapt_sparse() {
+    // Spans larger than 2^16 bits use 32-bit subinventories and the spill
+    let mut rng = SmallRng::seed_from_u64(0);
+    let len = 1 << 24;
+    let bits = random_bits(&mut rng, len, 0.0001);
+    let select = SelectAdapt::with_inv(bits.clone(), 8, 2);
+    assert_ok(&select);
+    let select = SelectAdaptConst::<_, _, 8, 1>::new(bits.clone());
+    assert_ok(&select);
+
+    let bits = flip(bits, len / 2);
+    let select = unsafe { select.map(|_| bits) };
+    assert!(select.verify(VerifyMode::Full).is_err());
+}
+
+#[test]
+fn test_select_adapt_sampled_length() {
+    // Sampled verification deduces the number of entries from the length of
+    // the inventory, and checks that the last entry covers the last ones
+    let len = 1 << 16;
+    let bits = (0..len).map(|i| i % 64 == 0).collect::<BitVec>();
+    let select = SelectAdapt::with_inv(bits, 8, 2);
+    assert_ok(&select);
+
+    // Ones beyond the last entry
+    let select = unsafe {
+        select.map(|mut bits| {
+            (len / 2..len).for_each(|i| bits.set(i, true));
+            bits
+        })
+    };
+    assert!(matches!(
+        select.verify(SAMPLED),
+        Err(VerifyError::Param {
+            what: "inventory length",
+            ..
+        })
+    ));
+    assert!(select.verify(VerifyMode::Full).is_err());
+}
+
+#[test]
+fn test_stacked() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let bits = random_bits(&mut rng, 100_000, 0.5);
+    let rank_sel = SelectZeroAdaptConst::<_, _>::new(SelectAdaptConst::<_, _>::new(Rank9::new(
+        AddNumBits::from(bits),
+    )));
+    assert_ok(&rank_sel);
+}
+
+#[test]
+fn test_add_num_bits() {
+    let bits = bit_vec![1, 0, 1, 1, 0];
+    let (bits, _) = AddNumBits::from(bits).into_raw_parts();
+    let wrong = unsafe { AddNumBits::from_raw_parts(bits.clone(), 4) };
+    assert_eq!(
+        wrong.verify(VerifyMode::Full),
+        Err(VerifyError::Param {
+            what: "number of ones",
+            expected: 3,
+            found: 4,
+        })
+    );
+    let too_large = unsafe { AddNumBits::from_raw_parts(bits, 6) };
+    assert!(too_large.verify(SAMPLED).is_err());
+}
+
+#[test]
+fn test_elias_fano() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n, u) in [(0, 0), (1, 0), (100, 1000), (1000, 100), (10_000, 1 << 30)] {
+        let mut values = (0..n).map(|_| rng.random_range(0..=u)).collect::<Vec<_>>();
+        values.sort();
+        let mut efb = EliasFanoBuilder::new(n, u);
+        efb.extend(values.iter().copied());
+        let ef = efb.build();
+        assert_ok(&ef);
+        let ef = unsafe { ef.map_high_bits(SelectAdaptConst::<_, _>::new) };
+        assert_ok(&ef);
+        let ef = unsafe { ef.map_high_bits(SelectZeroAdaptConst::<_, _>::new) };
+        assert_ok(&ef);
+    }
+
+    let mut efb = EliasFanoBuilder::new(4, 10);
+    efb.extend([5, 5, 5, 5]);
+    let ef = efb.build();
+    let ef = unsafe {
+        ef.map_low_bits(|mut low_bits| {
+            low_bits.set(1, 0);
+            low_bits
+        })
+    };
+    assert_eq!(
+        ef.verify(VerifyMode::Full),
+        Err(VerifyError::Value {
+            what: "value",
+            index: 1,
+            expected: 5,
+            found: 4,
+        })
+    );
+    // Sampled verification does not check values
+    assert_eq!(ef.verify(SAMPLED), Ok(()));
+
+    let ef = unsafe { ef.map_low_bits(|_| BitFieldVec::<usize>::new(2, 4)) };
+    assert!(matches!(
+        ef.verify(SAMPLED),
+        Err(VerifyError::Param {
+            what: "bit width of the lower bits",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_epserde() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let bits = random_bits(&mut rng, 100_000, 0.5);
+    let word = bits.as_ref()[bits.as_ref().len() / 2].to_ne_bytes();
+    let select = SelectAdaptConst::<_, _>::new(Rank9::new(bits));
+
+    let mut cursor = <AlignedCursor<maligned::A16>>::new();
+    select.serialize(&mut cursor)?;
+    let select = <SelectAdaptConst<Rank9>>::deserialize_eps(cursor.as_bytes())?;
+    assert_ok(&select);
+
+    // Corrupt a word in the middle of the bit vector
+    let bytes = cursor.as_bytes_mut();
+    let offset = bytes
+        .windows(word.len())
+        .position(|w| w == word)
+        .expect("word not found");
+    bytes[offset] ^= 1;
+    let select = <SelectAdaptConst<Rank9>>::deserialize_eps(cursor.as_bytes())?;
+    assert!(select.verify(VerifyMode::Full).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_vfunc() -> Result<()> {
+    for n in [0, 10, 1000, 100_000] {
+        let func = VBuilder::<usize, usize, BitFieldVec<usize>, [u64; 2], false>::default()
+            .try_build_func(
+                FromIntoIterator::from(0..n),
+                FromIntoIterator::from(0_usize..),
+                no_logging![],
+            )?;
+        assert_ok(&func);
+
+        let mut cursor = <AlignedCursor<maligned::A16>>::new();
+        func.serialize(&mut cursor)?;
+        let func = VFunc::<usize, usize, BitFieldVec<usize>, [u64; 2], false>::deserialize_eps(
+            cursor.as_bytes(),
+        )?;
+        assert_ok(&func);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_display() {
+    let error = VerifyError::Value {
+        what: "absolute counter",
+        index: 3,
+        expected: 10,
+        found: 11,
+    };
+    assert_eq!(
+        error.to_string(),
+        "Wrong absolute counter at index 3: expected 10, found 11"
+    );
+}
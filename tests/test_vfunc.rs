@@ -179,3 +179,91 @@ fn test_dup_key() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_vfunc_u128() -> Result<()> {
+    let _ = env_logger::builder()
+        .is_test(true)
+        .filter_level(log::LevelFilter::Info)
+        .try_init();
+
+    let mut pl = ProgressLogger::default();
+
+    for n in [0, 200_000, 1_000_000] {
+        dbg!(n);
+        let func = VBuilder::<_, _, BitFieldVec<u128>, [u64; 2], false>::default()
+            .log2_buckets(4)
+            .try_build_func(
+                FromIntoIterator::from(0..n),
+                FromIntoIterator::from((0_u128..).map(|i| i << 64 | i)),
+                &mut pl,
+            )?;
+        let mut cursor = <AlignedCursor<maligned::A16>>::new();
+        func.serialize(&mut cursor)?;
+        cursor.set_position(0);
+        let func =
+            VFunc::<_, _, BitFieldVec<u128>, [u64; 2], false>::deserialize_eps(cursor.as_bytes())?;
+        pl.start("Querying...");
+        for i in 0..n {
+            assert_eq!((i as u128) << 64 | i as u128, func.get(&i));
+        }
+        pl.done_with_count(n);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_vfilter_u128() -> Result<()> {
+    let _ = env_logger::builder()
+        .is_test(true)
+        .filter_level(log::LevelFilter::Info)
+        .try_init();
+
+    let mut pl = ProgressLogger::default();
+
+    for n in [0, 200_000, 1_000_000] {
+        dbg!(n);
+        let filter = VBuilder::<_, _, BitFieldVec<u128>, [u64; 2], false, ()>::default()
+            .log2_buckets(4)
+            .seed(1)
+            .try_build_filter(FromIntoIterator::from(0..n), 100, &mut pl)?;
+        let mut cursor = <AlignedCursor<maligned::A16>>::new();
+        filter.serialize(&mut cursor)?;
+        cursor.set_position(0);
+        let filter =
+            VFilter::<u128, VFunc<_, _, BitFieldVec<u128>, [u64; 2], false>>::deserialize_eps(
+                cursor.as_bytes(),
+            )?;
+        pl.start("Querying (positive)...");
+        let mut high = 0;
+        for i in 0..n {
+            assert!(filter.contains(&i), "Contains failed for {}", i);
+            assert!(filter.get(&i) < 1 << 100);
+            high |= filter.get(&i) >> 64;
+        }
+        pl.done_with_count(n);
+        // Fingerprints must use more than 64 bits
+        assert!(n == 0 || high == (1 << 36) - 1);
+
+        pl.start("Querying (negative)...");
+        for i in 0..n {
+            assert!(!filter.contains(&(i + n)));
+        }
+        pl.done_with_count(n);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_sig_u128() {
+    use sux::utils::Sig;
+    // The lower 64 bits are the 64-bit signature, both for overridden and
+    // for default implementations
+    for sig in [[0_u64, 1], [u64::MAX, 42], [1 << 63, 1 << 63]] {
+        assert_eq!(sig.sig_u128() as u64, sig.sig_u64());
+        let sig = [sig[0]];
+        assert_eq!(sig.sig_u128() as u64, sig.sig_u64());
+    }
+}
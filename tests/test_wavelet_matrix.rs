@@ -0,0 +1,149 @@
+/*
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use epserde::prelude::*;
+use mem_dbg::*;
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand::SeedableRng;
+use std::collections::HashMap;
+use sux::prelude::*;
+
+fn random_values(rng: &mut SmallRng, len: usize, bit_width: usize) -> BitFieldVec {
+    let mut values = BitFieldVec::<usize>::new(bit_width, 0);
+    let max = if bit_width == 0 { 1 } else { 1 << bit_width };
+    for _ in 0..len {
+        // Skew the distribution so that some values are frequent
+        let value = if rng.random_bool(0.3) {
+            max / 3
+        } else {
+            rng.random_range(0..max)
+        };
+        values.push(value);
+    }
+    values
+}
+
+fn check<
+    R: Rank + SelectUnchecked + SelectZeroUnchecked + std::ops::Index<usize, Output = bool>,
+    L: std::ops::Deref<Target = [R]>,
+    O: AsRef<[usize]>,
+>(
+    wm: &WaveletMatrix<L, O>,
+    values: &[usize],
+    rng: &mut SmallRng,
+) {
+    let len = values.len();
+    assert_eq!(wm.len(), len);
+    let max = values.iter().copied().max().unwrap_or(0);
+
+    let mut ranks = HashMap::<usize, usize>::new();
+    for (pos, &value) in values.iter().enumerate() {
+        assert_eq!(wm.get(pos), value);
+        let rank = ranks.entry(value).or_default();
+        assert_eq!(wm.rank(value, pos), *rank);
+        assert_eq!(wm.select(value, *rank), Some(pos));
+        *rank += 1;
+    }
+    for (&value, &count) in &ranks {
+        assert_eq!(wm.rank(value, len), count);
+        assert_eq!(wm.rank(value, len + 1), count);
+        assert_eq!(wm.select(value, count), None);
+    }
+    assert_eq!(
+        wm.rank(max + 1, len),
+        ranks.get(&(max + 1)).copied().unwrap_or(0)
+    );
+    assert_eq!(wm.rank(usize::MAX, len), 0);
+    assert_eq!(wm.select(usize::MAX, 0), None);
+
+    for _ in 0..200 {
+        let start = rng.random_range(0..=len);
+        let end = rng.random_range(start..=len);
+        let mut sorted = values[start..end].to_vec();
+        sorted.sort();
+
+        for (rank, &value) in sorted.iter().enumerate() {
+            assert_eq!(wm.quantile(start..end, rank), Some(value));
+        }
+        assert_eq!(wm.quantile(start..end, sorted.len()), None);
+
+        let lo = rng.random_range(0..=max + 1);
+        let hi = rng.random_range(lo..=max + 2);
+        let expected = sorted.iter().filter(|&&v| lo <= v && v < hi).count();
+        assert_eq!(wm.range_count(start..end, lo..hi), expected);
+        assert_eq!(wm.range_count(start..end, 0..usize::MAX), end - start);
+
+        let mut freqs = HashMap::<usize, usize>::new();
+        sorted
+            .iter()
+            .for_each(|&v| *freqs.entry(v).or_default() += 1);
+        let mut expected = freqs.values().copied().collect::<Vec<_>>();
+        expected.sort_by(|a, b| b.cmp(a));
+        for k in [0, 1, 3, 100] {
+            let top = wm.top_k(start..end, k);
+            assert_eq!(
+                top.iter().map(|&(_, f)| f).collect::<Vec<_>>(),
+                expected[..k.min(expected.len())]
+            );
+            for (value, freq) in top {
+                assert_eq!(freqs[&value], freq);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_wavelet_matrix() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in [0, 1, 3, 8, 13] {
+        for len in [0, 1, 10, 100, 1000, 10_000] {
+            let values = random_values(&mut rng, len, bit_width);
+            let wm = WaveletMatrix::new(&values);
+            check(&wm, &values.iter().collect::<Vec<_>>(), &mut rng);
+        }
+    }
+}
+
+#[test]
+fn test_new_with() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let values = random_values(&mut rng, 10_000, 10);
+    let wm = WaveletMatrix::new_with(&values, |bits| {
+        SelectZeroAdapt::new(SelectAdapt::new(Rank9::new(bits), 3), 3)
+    });
+    check(&wm, &values.iter().collect::<Vec<_>>(), &mut rng);
+    // One structure per level
+    assert_eq!(wm.into_inner().len(), 10);
+}
+
+#[test]
+#[should_panic]
+fn test_out_of_bounds() {
+    let wm = WaveletMatrix::new(&bit_field_vec![3; 1, 2, 3]);
+    wm.quantile(1..4, 0);
+}
+
+#[test]
+fn test_epserde() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let values = random_values(&mut rng, 10_000, 12);
+    let wm = WaveletMatrix::new(&values);
+    // The levels use 12 bits per element, plus the rank/select structure
+    let size = wm.mem_size(SizeFlags::default());
+    assert!(size > 10_000 * 12 / 8 && size < 10_000 * 12 / 8 * 3 / 2);
+
+    let mut cursor = <AlignedCursor<maligned::A16>>::new();
+    wm.serialize(&mut cursor)?;
+    cursor.set_position(0);
+    let full = <WaveletMatrix>::deserialize_full(&mut cursor)?;
+    let eps = <WaveletMatrix>::deserialize_eps(cursor.as_bytes())?;
+    let values = values.iter().collect::<Vec<_>>();
+    check(&full, &values, &mut rng);
+    check(&eps, &values, &mut rng);
+    Ok(())
+}
@@ -0,0 +1,75 @@
+/*
+ * SPDX-FileCopyrightText: 2025 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use common_traits::SelectInWord;
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand::SeedableRng;
+use sux::utils::word_ops::*;
+
+fn random_words(rng: &mut SmallRng) -> impl Iterator<Item = usize> + '_ {
+    // Words of different densities, plus some corner cases
+    [0, 1, usize::MAX, 1 << 63, 0x8000_0000_0000_0001, 0xFF00]
+        .into_iter()
+        .chain((0..10_000).map(|i| {
+            let word = rng.random::<u64>() as usize;
+            match i % 4 {
+                0 => word,
+                1 => word & rng.random::<u64>() as usize & rng.random::<u64>() as usize,
+                2 => word | rng.random::<u64>() as usize | rng.random::<u64>() as usize,
+                _ => word & (usize::MAX >> rng.random_range(0..64)),
+            }
+        }))
+}
+
+#[test]
+fn test_select_in_word() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for word in random_words(&mut rng) {
+        let mut expected = 0;
+        for rank in 0..word.count_ones() as usize {
+            while word & (1 << expected) == 0 {
+                expected += 1;
+            }
+            assert_eq!(
+                select_in_word_broadword(word, rank),
+                expected,
+                "{word:x} {rank}"
+            );
+            assert_eq!(select_in_word(word, rank), expected, "{word:x} {rank}");
+            assert_eq!(word.select_in_word(rank), expected, "{word:x} {rank}");
+            #[cfg(target_arch = "x86_64")]
+            if std::arch::is_x86_feature_detected!("bmi2") {
+                assert_eq!(
+                    unsafe { select_in_word_bmi2(word, rank) },
+                    expected,
+                    "{word:x} {rank}"
+                );
+            }
+            expected += 1;
+        }
+    }
+}
+
+#[test]
+fn test_count_ones() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let words = random_words(&mut rng).collect::<Vec<_>>();
+    for len in (0..100).chain([1000, words.len() - 7]) {
+        for start in [0, 1, 7] {
+            let words = &words[start..start + len];
+            let expected = words.iter().map(|w| w.count_ones() as usize).sum::<usize>();
+            assert_eq!(count_ones_scalar(words), expected);
+            assert_eq!(count_ones(words), expected);
+            #[cfg(target_arch = "x86_64")]
+            if std::arch::is_x86_feature_detected!("avx512f")
+                && std::arch::is_x86_feature_detected!("avx512vpopcntdq")
+            {
+                assert_eq!(unsafe { count_ones_avx512(words) }, expected);
+            }
+        }
+    }
+}